@@ -0,0 +1,2 @@
+-- Optional per-repo base branch used as the PR base and worktree fork point
+ALTER TABLE project_repos ADD COLUMN base_branch TEXT;
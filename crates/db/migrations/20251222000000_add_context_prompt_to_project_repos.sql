@@ -0,0 +1,2 @@
+-- Per-repo context prepended to agent prompts for tasks touching the repo
+ALTER TABLE project_repos ADD COLUMN context_prompt TEXT;
@@ -0,0 +1,2 @@
+-- Debugging switch: skip all automatic cleanup scripts for the project's repos
+ALTER TABLE projects ADD COLUMN disable_cleanup INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- Repos holding vendored or generated artifacts can opt out of pull requests
+ALTER TABLE project_repos ADD COLUMN auto_open_pr INTEGER NOT NULL DEFAULT 1;
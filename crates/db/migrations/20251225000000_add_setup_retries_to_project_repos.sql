@@ -0,0 +1,2 @@
+-- Number of times to re-run a failing setup script before giving up
+ALTER TABLE project_repos ADD COLUMN setup_retries INTEGER DEFAULT 0;
@@ -0,0 +1,2 @@
+-- Optimistic locking: bumped on every update so stale writes can be rejected
+ALTER TABLE project_repos ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
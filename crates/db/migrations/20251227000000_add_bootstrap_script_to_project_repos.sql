@@ -0,0 +1,3 @@
+-- One-time script run in the repository the first time a workspace starts after it is added
+ALTER TABLE project_repos ADD COLUMN bootstrap_script TEXT;
+ALTER TABLE project_repos ADD COLUMN bootstrapped_at TEXT;
@@ -0,0 +1,2 @@
+-- Repo-relative path to a KEY=value file merged into the environment of executor runs
+ALTER TABLE project_repos ADD COLUMN env_file TEXT;
@@ -0,0 +1,2 @@
+-- JSON array of path globs; when set, the setup script only runs if changed files match one
+ALTER TABLE project_repos ADD COLUMN setup_paths TEXT;
@@ -0,0 +1,8 @@
+-- Last successful setup per reused worktree, so setup can be skipped when its inputs are unchanged
+CREATE TABLE IF NOT EXISTS worktree_setup_fingerprints (
+    repo_id       BLOB NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+    worktree_path TEXT NOT NULL,
+    fingerprint   TEXT NOT NULL,
+    recorded_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (repo_id, worktree_path)
+);
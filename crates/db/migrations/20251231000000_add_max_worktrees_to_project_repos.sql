@@ -0,0 +1,2 @@
+-- Upper bound on worktrees that may exist at once for the repo; NULL means unlimited
+ALTER TABLE project_repos ADD COLUMN max_worktrees INTEGER;
@@ -0,0 +1,2 @@
+-- JSON array of repo ids in the same project whose setup runs first (and cleanup runs after)
+ALTER TABLE project_repos ADD COLUMN setup_depends_on TEXT;
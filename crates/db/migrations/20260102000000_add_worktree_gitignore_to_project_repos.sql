@@ -0,0 +1,2 @@
+-- Extra ignore patterns written to each worktree's info/exclude, never to the tracked .gitignore
+ALTER TABLE project_repos ADD COLUMN worktree_gitignore TEXT;
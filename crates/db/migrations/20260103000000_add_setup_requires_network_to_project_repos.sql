@@ -0,0 +1,2 @@
+-- Setup scripts that need network access are skipped, with a warning, when running offline
+ALTER TABLE project_repos ADD COLUMN setup_requires_network INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- JSON object of env vars injected into setup and cleanup scripts, e.g. DATABASE_URL
+ALTER TABLE project_repos ADD COLUMN env_vars TEXT;
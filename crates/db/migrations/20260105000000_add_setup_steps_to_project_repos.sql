@@ -0,0 +1,2 @@
+-- Ordered, named setup steps as a JSON array of {name, command}; NULL falls back to setup_script
+ALTER TABLE project_repos ADD COLUMN setup_steps TEXT;
@@ -0,0 +1,2 @@
+-- Removing a repo from a project soft-deletes it so its settings survive re-adding
+ALTER TABLE project_repos ADD COLUMN deleted_at TEXT;
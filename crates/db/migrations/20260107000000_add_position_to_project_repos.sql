@@ -0,0 +1,2 @@
+-- User-controlled repo order within a project; ties fall back to display_name
+ALTER TABLE project_repos ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
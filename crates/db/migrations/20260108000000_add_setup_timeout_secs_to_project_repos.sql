@@ -0,0 +1,2 @@
+-- Seconds before a hung setup script is killed; NULL uses the default of 600
+ALTER TABLE project_repos ADD COLUMN setup_timeout_secs INTEGER;
@@ -0,0 +1,2 @@
+-- Per-repo long-running dev server command, run alongside the project's dev_script
+ALTER TABLE project_repos ADD COLUMN dev_server_script TEXT;
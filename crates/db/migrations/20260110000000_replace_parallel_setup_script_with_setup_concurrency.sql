@@ -0,0 +1,5 @@
+-- Cap on setup scripts running at once across a project's repos: 0 = unlimited, 1 = serial,
+-- N = at most N. Parallel repos become unlimited and the rest stay serial.
+ALTER TABLE project_repos ADD COLUMN setup_concurrency INTEGER NOT NULL DEFAULT 1;
+UPDATE project_repos SET setup_concurrency = CASE WHEN parallel_setup_script THEN 0 ELSE 1 END;
+ALTER TABLE project_repos DROP COLUMN parallel_setup_script;
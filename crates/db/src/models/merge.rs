@@ -49,6 +49,32 @@ pub struct PullRequestInfo {
     pub status: MergeStatus,
     pub merged_at: Option<chrono::DateTime<chrono::Utc>>,
     pub merge_commit_sha: Option<String>,
+    /// Draft state as last reported by the host; not stored
+    #[serde(default)]
+    pub is_draft: bool,
+    /// Host of the PR, derived from its URL; not stored or sent to clients
+    #[serde(skip)]
+    #[ts(skip)]
+    pub provider: PrProvider,
+}
+
+/// Which hosting provider a pull (or merge) request belongs to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrProvider {
+    #[default]
+    GitHub,
+    GitLab,
+}
+
+impl PrProvider {
+    /// GitLab merge request URLs, including self-hosted ones, contain `/-/merge_requests/`
+    pub fn from_url(url: &str) -> Self {
+        if url.contains("/-/merge_requests/") {
+            PrProvider::GitLab
+        } else {
+            PrProvider::GitHub
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -308,6 +334,7 @@ impl From<MergeRow> for DirectMerge {
 
 impl From<MergeRow> for PrMerge {
     fn from(row: MergeRow) -> Self {
+        let url = row.pr_url.expect("pr merge must have pr_url");
         PrMerge {
             id: row.id,
             workspace_id: row.workspace_id,
@@ -315,10 +342,12 @@ impl From<MergeRow> for PrMerge {
             target_branch_name: row.target_branch_name,
             pr_info: PullRequestInfo {
                 number: row.pr_number.expect("pr merge must have pr_number"),
-                url: row.pr_url.expect("pr merge must have pr_url"),
+                provider: PrProvider::from_url(&url),
+                url,
                 status: row.pr_status.expect("pr merge must have status"),
                 merged_at: row.pr_merged_at,
                 merge_commit_sha: row.pr_merge_commit_sha,
+                is_draft: false,
             },
             created_at: row.created_at,
         }
@@ -24,6 +24,7 @@ pub struct Project {
     pub dev_script: Option<String>,
     pub dev_script_working_dir: Option<String>,
     pub default_agent_working_dir: Option<String>,
+    pub disable_cleanup: bool,
     pub remote_project_id: Option<Uuid>,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
@@ -43,6 +44,7 @@ pub struct UpdateProject {
     pub dev_script: Option<String>,
     pub dev_script_working_dir: Option<String>,
     pub default_agent_working_dir: Option<String>,
+    pub disable_cleanup: Option<bool>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -74,6 +76,7 @@ impl Project {
                       dev_script,
                       dev_script_working_dir,
                       default_agent_working_dir,
+                      disable_cleanup as "disable_cleanup!: bool",
                       remote_project_id as "remote_project_id: Uuid",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
@@ -91,6 +94,7 @@ impl Project {
             r#"
             SELECT p.id as "id!: Uuid", p.name, p.dev_script, p.dev_script_working_dir,
                    p.default_agent_working_dir,
+                   p.disable_cleanup as "disable_cleanup!: bool",
                    p.remote_project_id as "remote_project_id: Uuid",
                    p.created_at as "created_at!: DateTime<Utc>", p.updated_at as "updated_at!: DateTime<Utc>"
             FROM projects p
@@ -116,6 +120,7 @@ impl Project {
                       dev_script,
                       dev_script_working_dir,
                       default_agent_working_dir,
+                      disable_cleanup as "disable_cleanup!: bool",
                       remote_project_id as "remote_project_id: Uuid",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
@@ -135,6 +140,7 @@ impl Project {
                       dev_script,
                       dev_script_working_dir,
                       default_agent_working_dir,
+                      disable_cleanup as "disable_cleanup!: bool",
                       remote_project_id as "remote_project_id: Uuid",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
@@ -157,6 +163,7 @@ impl Project {
                       dev_script,
                       dev_script_working_dir,
                       default_agent_working_dir,
+                      disable_cleanup as "disable_cleanup!: bool",
                       remote_project_id as "remote_project_id: Uuid",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
@@ -187,6 +194,7 @@ impl Project {
                           dev_script,
                           dev_script_working_dir,
                           default_agent_working_dir,
+                          disable_cleanup as "disable_cleanup!: bool",
                           remote_project_id as "remote_project_id: Uuid",
                           created_at as "created_at!: DateTime<Utc>",
                           updated_at as "updated_at!: DateTime<Utc>""#,
@@ -210,17 +218,20 @@ impl Project {
         let dev_script = payload.dev_script.clone();
         let dev_script_working_dir = payload.dev_script_working_dir.clone();
         let default_agent_working_dir = payload.default_agent_working_dir.clone();
+        let disable_cleanup = payload.disable_cleanup.unwrap_or(existing.disable_cleanup);
 
         sqlx::query_as!(
             Project,
             r#"UPDATE projects
-               SET name = $2, dev_script = $3, dev_script_working_dir = $4, default_agent_working_dir = $5
+               SET name = $2, dev_script = $3, dev_script_working_dir = $4, default_agent_working_dir = $5,
+                   disable_cleanup = $6
                WHERE id = $1
                RETURNING id as "id!: Uuid",
                          name,
                          dev_script,
                          dev_script_working_dir,
                          default_agent_working_dir,
+                         disable_cleanup as "disable_cleanup!: bool",
                          remote_project_id as "remote_project_id: Uuid",
                          created_at as "created_at!: DateTime<Utc>",
                          updated_at as "updated_at!: DateTime<Utc>""#,
@@ -229,6 +240,7 @@ impl Project {
             dev_script,
             dev_script_working_dir,
             default_agent_working_dir,
+            disable_cleanup,
         )
         .fetch_one(pool)
         .await
@@ -1,10 +1,15 @@
-use std::path::Path;
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Component, Path, PathBuf},
+};
 
 use chrono::{DateTime, Utc};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
 use thiserror::Error;
 use ts_rs::TS;
+use utils::git::is_valid_branch_name;
 use uuid::Uuid;
 
 use super::repo::Repo;
@@ -17,9 +22,41 @@ pub enum ProjectRepoError {
     NotFound,
     #[error("Repository already exists in this project")]
     AlreadyExists,
+    #[error("Invalid base branch name: {0}")]
+    InvalidBaseBranch(String),
+    #[error("Setup retries must not be negative, got {0}")]
+    InvalidSetupRetries(i64),
+    #[error("Env file must be a path inside the repository: {0}")]
+    InvalidEnvFile(String),
+    #[error("Setup paths must be a JSON array of globs: {0}")]
+    InvalidSetupPaths(String),
+    #[error("Setup timeout must be a positive number of seconds, got {0}")]
+    InvalidSetupTimeout(i64),
+    #[error("Max worktrees must be at least 1, got {0}")]
+    InvalidMaxWorktrees(i64),
+    #[error("Setup concurrency must not be negative, got {0}")]
+    InvalidSetupConcurrency(i64),
+    #[error("Repository already has {limit} active worktrees, the configured maximum")]
+    WorktreeLimitReached { limit: i64 },
+    #[error("Setup dependencies must be a JSON array of repository ids: {0}")]
+    InvalidSetupDependsOn(String),
+    #[error("Env vars must be a JSON object of string values: {0}")]
+    InvalidEnvVars(String),
+    #[error("Setup steps must be a JSON array of {{name, command}} objects: {0}")]
+    InvalidSetupSteps(String),
+    #[error("Copy files must be globs relative to the repository: {0}")]
+    InvalidCopyFiles(String),
+    #[error("Reorder must list every repository of the project exactly once")]
+    InvalidOrder,
+    #[error("Repository is also used by other projects: {0:?}")]
+    SharedAcrossProjects(Vec<Uuid>),
+    #[error("Setup dependencies form a cycle between repositories {0:?}")]
+    DependencyCycle(Vec<Uuid>),
+    #[error("Repository settings were modified since they were loaded")]
+    Conflict,
 }
 
-#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Default, FromRow, Serialize, Deserialize, TS)]
 pub struct ProjectRepo {
     pub id: Uuid,
     pub project_id: Uuid,
@@ -27,11 +64,53 @@ pub struct ProjectRepo {
     pub setup_script: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
-    pub parallel_setup_script: bool,
+    /// How many setup scripts of the project's repos may run at once: 0 for no limit, 1 to
+    /// run them one after another. The strictest limit among the repos being set up applies.
+    #[ts(type = "number")]
+    pub setup_concurrency: i64,
+    pub base_branch: Option<String>,
+    pub context_prompt: Option<String>,
+    pub auto_open_pr: bool,
+    #[ts(type = "number | null")]
+    pub setup_retries: Option<i64>,
+    #[ts(type = "number")]
+    pub version: i64,
+    pub bootstrap_script: Option<String>,
+    pub bootstrapped_at: Option<DateTime<Utc>>,
+    /// Repo-relative path of a `.env` file merged into the `ExecutionEnv` of this repo's
+    /// setup and cleanup scripts only
+    pub env_file: Option<String>,
+    pub setup_paths: Option<String>,
+    #[ts(type = "number | null")]
+    pub max_worktrees: Option<i64>,
+    pub setup_depends_on: Option<String>,
+    pub worktree_gitignore: Option<String>,
+    pub setup_requires_network: bool,
+    /// JSON object of variables injected into the `ExecutionEnv` of this repo's setup and
+    /// cleanup scripts only, overriding the env file
+    pub env_vars: Option<String>,
+    /// JSON array of `{ name, command }` objects run in order; takes precedence over
+    /// `setup_script`
+    pub setup_steps: Option<String>,
+    /// Set when the repo is removed from the project; the row is kept so re-adding the repo
+    /// restores its settings
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Display and setup order within the project, see [`ProjectRepo::reorder`]
+    #[ts(type = "number")]
+    pub position: i64,
+    /// Seconds a setup script may run before its process group is killed, defaulting to
+    /// [`DEFAULT_SETUP_TIMEOUT_SECS`]. The limit covers all retries. Parallel setups each
+    /// run as their own process with their own timer, so one repo timing out never holds
+    /// up the others; in a sequential chain a timeout fails the chain like any other error.
+    #[ts(type = "number | null")]
+    pub setup_timeout_secs: Option<i64>,
+    /// Long-running command such as `npm run dev`, started in the repo's worktree alongside
+    /// the project's dev server and kept alive rather than expected to exit
+    pub dev_server_script: Option<String>,
 }
 
 /// ProjectRepo with the associated repo name (for script execution in worktrees)
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, Default, FromRow)]
 pub struct ProjectRepoWithName {
     pub id: Uuid,
     pub project_id: Uuid,
@@ -40,7 +119,85 @@ pub struct ProjectRepoWithName {
     pub setup_script: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
-    pub parallel_setup_script: bool,
+    pub setup_concurrency: i64,
+    pub base_branch: Option<String>,
+    pub context_prompt: Option<String>,
+    pub auto_open_pr: bool,
+    pub setup_retries: Option<i64>,
+    pub version: i64,
+    pub bootstrap_script: Option<String>,
+    pub bootstrapped_at: Option<DateTime<Utc>>,
+    pub env_file: Option<String>,
+    pub setup_paths: Option<String>,
+    pub max_worktrees: Option<i64>,
+    pub setup_depends_on: Option<String>,
+    pub worktree_gitignore: Option<String>,
+    pub setup_requires_network: bool,
+    pub env_vars: Option<String>,
+    pub setup_steps: Option<String>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub position: i64,
+    pub setup_timeout_secs: Option<i64>,
+    pub dev_server_script: Option<String>,
+}
+
+/// Setup timeout used when a repo doesn't configure `setup_timeout_secs`
+pub const DEFAULT_SETUP_TIMEOUT_SECS: u64 = 600;
+
+impl ProjectRepoWithName {
+    /// The configured setup timeout, or [`DEFAULT_SETUP_TIMEOUT_SECS`]
+    pub fn setup_timeout_secs(&self) -> u64 {
+        self.setup_timeout_secs
+            .and_then(|secs| u64::try_from(secs).ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_SETUP_TIMEOUT_SECS)
+    }
+
+    /// Whether the setup script should run given the task's changed files
+    pub fn setup_required_for(&self, changed: &[String]) -> bool {
+        setup_required_for(self.setup_paths.as_deref(), changed)
+    }
+
+    /// The configured env vars, sorted by name. Stored JSON that no longer parses is an
+    /// error rather than an empty set, so scripts never run without their secrets unnoticed.
+    pub fn env_vars(&self) -> Result<Vec<(String, String)>, ProjectRepoError> {
+        match self.env_vars.as_deref() {
+            Some(raw) => parse_env_vars(raw).map_err(ProjectRepoError::InvalidEnvVars),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// The repo's dev server command, unless blank. The runner starts it in the worktree
+    /// under `repo_name` and leaves it running for the life of the task.
+    pub fn dev_server_script(&self) -> Option<&str> {
+        self.dev_server_script
+            .as_deref()
+            .filter(|script| !script.trim().is_empty())
+    }
+
+    /// Whether the repo has any setup to run
+    pub fn has_setup(&self) -> bool {
+        self.setup_script.is_some() || self.setup_steps.is_some()
+    }
+
+    /// Ordered setup steps, see [`setup_steps`]
+    pub fn setup_steps(&self) -> Result<Vec<SetupStep>, ProjectRepoError> {
+        setup_steps(self.setup_steps.as_deref(), self.setup_script.as_deref())
+    }
+
+    /// Fill in the placeholders of one of this repo's scripts, see [`render_script`]
+    pub fn render_script(&self, template: &str, ctx: &ScriptContext) -> String {
+        render_script(template, self.project_id, ctx)
+    }
+}
+
+/// One command of a repo's setup, reported by name when it fails
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct SetupStep {
+    /// `None` for a plain `setup_script` run as a single step
+    #[serde(default)]
+    pub name: Option<String>,
+    pub command: String,
 }
 
 #[derive(Debug, Clone, Deserialize, TS)]
@@ -49,16 +206,438 @@ pub struct CreateProjectRepo {
     pub git_repo_path: String,
 }
 
-#[derive(Debug, Clone, Deserialize, TS)]
+#[derive(Debug, Clone, Default, Deserialize, TS)]
 #[ts(export)]
 pub struct UpdateProjectRepo {
     pub setup_script: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
-    pub parallel_setup_script: Option<bool>,
+    /// Also accepts the old `parallel_setup_script` flag: `true` means no limit, `false` serial
+    #[serde(
+        default,
+        alias = "parallel_setup_script",
+        deserialize_with = "setup_concurrency_compat"
+    )]
+    #[ts(type = "number | null")]
+    pub setup_concurrency: Option<i64>,
+    pub base_branch: Option<String>,
+    pub context_prompt: Option<String>,
+    pub auto_open_pr: Option<bool>,
+    #[ts(type = "number | null")]
+    pub setup_retries: Option<i64>,
+    /// Version the client loaded; the update is rejected if the row changed since
+    #[ts(type = "number | null")]
+    pub version: Option<i64>,
+    pub bootstrap_script: Option<String>,
+    pub env_file: Option<String>,
+    pub setup_paths: Option<String>,
+    #[ts(type = "number | null")]
+    pub max_worktrees: Option<i64>,
+    pub setup_depends_on: Option<String>,
+    pub worktree_gitignore: Option<String>,
+    pub setup_requires_network: Option<bool>,
+    pub env_vars: Option<String>,
+    pub setup_steps: Option<String>,
+    #[ts(type = "number | null")]
+    pub setup_timeout_secs: Option<i64>,
+    pub dev_server_script: Option<String>,
+}
+
+/// A `setup_concurrency` value, or the `parallel_setup_script` bool older clients still send
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SetupConcurrencyInput {
+    Limit(i64),
+    Parallel(bool),
+}
+
+fn setup_concurrency_compat<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(
+        Option::<SetupConcurrencyInput>::deserialize(deserializer)?.map(|input| match input {
+            SetupConcurrencyInput::Limit(limit) => limit,
+            SetupConcurrencyInput::Parallel(true) => 0,
+            SetupConcurrencyInput::Parallel(false) => 1,
+        }),
+    )
+}
+
+/// How many of the given repos' setup scripts may run at once, `None` meaning no limit. The
+/// strictest non-zero `setup_concurrency` wins, so one serial repo keeps the whole set serial.
+pub fn effective_setup_concurrency(limits: impl IntoIterator<Item = i64>) -> Option<usize> {
+    limits
+        .into_iter()
+        .filter(|limit| *limit > 0)
+        .min()
+        .map(|limit| limit as usize)
+}
+
+/// Whether `path` stays inside the repository: relative and without `..` components.
+fn is_repo_relative(path: &str) -> bool {
+    let path = Path::new(path);
+    path.is_relative()
+        && path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Entries of a `copy_files` value, separated by commas or newlines
+pub fn copy_file_patterns(copy_files: &str) -> impl Iterator<Item = &str> {
+    copy_files
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+/// Check a single `copy_files` entry, returning it with forward slashes
+fn parse_copy_file_pattern(pattern: &str) -> Result<String, String> {
+    let pattern = pattern.replace('\\', "/");
+    if pattern.starts_with('/') || Path::new(&pattern).is_absolute() {
+        return Err(format!("{pattern} is an absolute path"));
+    }
+    if pattern.split('/').any(|c| c == "..") {
+        return Err(format!("{pattern} points outside the repository"));
+    }
+    Glob::new(&pattern).map_err(|e| format!("{pattern}: {e}"))?;
+    Ok(pattern)
+}
+
+/// Check a `copy_files` entry, returning its source and destination with forward slashes.
+/// `src -> dest` copies a single file or directory to another path, otherwise the
+/// destination is the source itself.
+pub fn parse_copy_file_entry(entry: &str) -> Result<(String, String), String> {
+    let Some((src, dest)) = entry.split_once("->") else {
+        let src = parse_copy_file_pattern(entry)?;
+        return Ok((src.clone(), src));
+    };
+    let src = parse_copy_file_pattern(src.trim())?;
+    let dest = dest.trim().replace('\\', "/");
+    if src.contains(['*', '?', '[', '{']) {
+        return Err(format!(
+            "{src}: only a single file or directory can be renamed"
+        ));
+    }
+    if dest.is_empty() {
+        return Err(format!("{src}: missing destination after ->"));
+    }
+    if dest.starts_with('/') || !is_repo_relative(&dest) {
+        return Err(format!("{dest} points outside the worktree"));
+    }
+    Ok((src, dest))
+}
+
+/// Parse `KEY=value` lines of an env file, skipping blanks and `#` comments.
+/// An optional `export ` prefix and matching surrounding quotes are stripped.
+pub fn parse_env_file(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() {
+                return None;
+            }
+            let value = value.trim();
+            let value = ['"', '\'']
+                .iter()
+                .find_map(|q| value.strip_prefix(*q)?.strip_suffix(*q))
+                .unwrap_or(value);
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Parse a `setup_steps` JSON array, rejecting steps without a command
+fn parse_setup_steps(raw: &str) -> Result<Vec<SetupStep>, String> {
+    let steps: Vec<SetupStep> = serde_json::from_str(raw).map_err(|e| e.to_string())?;
+    if let Some(index) = steps.iter().position(|s| s.command.trim().is_empty()) {
+        return Err(format!("step {} has an empty command", index + 1));
+    }
+    Ok(steps)
+}
+
+/// Setup steps in run order: the parsed `setup_steps`, or else `setup_script` as a single
+/// anonymous step. Stored steps that don't parse are an error, never silently skipped.
+pub fn setup_steps(
+    setup_steps: Option<&str>,
+    setup_script: Option<&str>,
+) -> Result<Vec<SetupStep>, ProjectRepoError> {
+    match (setup_steps, setup_script) {
+        (Some(raw), _) => parse_setup_steps(raw).map_err(ProjectRepoError::InvalidSetupSteps),
+        (None, Some(script)) => Ok(vec![SetupStep {
+            name: None,
+            command: script.to_string(),
+        }]),
+        (None, None) => Ok(Vec::new()),
+    }
+}
+
+/// Where a setup or cleanup script runs, for the placeholders of [`render_script`]
+#[derive(Debug, Clone)]
+pub struct ScriptContext {
+    pub worktree_path: String,
+    pub repo_name: String,
+    pub branch_name: String,
+}
+
+/// Substitute `${WORKTREE_PATH}`, `${REPO_NAME}`, `${BRANCH_NAME}` and `${PROJECT_ID}` in a
+/// script. Any other `${...}` is left as written, so shell variables still expand, and
+/// `$${...}` emits a literal `${...}`.
+pub fn render_script(template: &str, project_id: Uuid, ctx: &ScriptContext) -> String {
+    let lookup = |name: &str| match name {
+        "WORKTREE_PATH" => Some(ctx.worktree_path.clone()),
+        "REPO_NAME" => Some(ctx.repo_name.clone()),
+        "BRANCH_NAME" => Some(ctx.branch_name.clone()),
+        "PROJECT_ID" => Some(project_id.to_string()),
+        _ => None,
+    };
+
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if let Some(escaped) = tail.strip_prefix("$${") {
+            out.push_str("${");
+            rest = escaped;
+        } else if let Some(inner) = tail.strip_prefix("${")
+            && let Some(end) = inner.find('}')
+            && let Some(value) = lookup(&inner[..end])
+        {
+            out.push_str(&value);
+            rest = &inner[end + 1..];
+        } else {
+            out.push('$');
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Parse an `env_vars` JSON object of string values
+fn parse_env_vars(raw: &str) -> Result<Vec<(String, String)>, String> {
+    let vars: BTreeMap<String, String> = serde_json::from_str(raw).map_err(|e| e.to_string())?;
+    if vars.keys().any(|key| key.trim().is_empty()) {
+        return Err("variable names must not be empty".to_string());
+    }
+    Ok(vars.into_iter().collect())
+}
+
+/// Compile a `setup_paths` JSON array of globs into a matcher
+fn parse_setup_paths(raw: &str) -> Result<GlobSet, String> {
+    let patterns: Vec<String> = serde_json::from_str(raw).map_err(|e| e.to_string())?;
+    let mut builder = GlobSetBuilder::new();
+    for pattern in &patterns {
+        builder.add(Glob::new(pattern).map_err(|e| e.to_string())?);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// Whether a setup script gated by `setup_paths` should run for `changed` files. Unset or
+/// unparseable globs always require setup.
+pub fn setup_required_for(setup_paths: Option<&str>, changed: &[String]) -> bool {
+    let Some(raw) = setup_paths else {
+        return true;
+    };
+    match parse_setup_paths(raw) {
+        Ok(globs) => changed.iter().any(|path| globs.is_match(path)),
+        Err(e) => {
+            tracing::warn!("Ignoring invalid setup_paths {raw}: {e}");
+            true
+        }
+    }
+}
+
+/// Parse a `setup_depends_on` JSON array of repo ids
+fn parse_setup_depends_on(raw: &str) -> Result<Vec<Uuid>, String> {
+    serde_json::from_str(raw).map_err(|e| e.to_string())
+}
+
+/// Repo ids whose setup must finish before this repo's. Unset or unparseable means none.
+pub fn setup_dependencies(setup_depends_on: Option<&str>) -> Vec<Uuid> {
+    let Some(raw) = setup_depends_on else {
+        return Vec::new();
+    };
+    parse_setup_depends_on(raw).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring invalid setup_depends_on {raw}: {e}");
+        Vec::new()
+    })
+}
+
+/// Group `(repo_id, dependencies)` into batches where every repo only depends on repos in
+/// earlier batches. Dependencies on repos not listed are ignored; batches keep input order.
+pub fn setup_batches(repos: &[(Uuid, Vec<Uuid>)]) -> Result<Vec<Vec<Uuid>>, ProjectRepoError> {
+    let known: HashSet<Uuid> = repos.iter().map(|(id, _)| *id).collect();
+    let mut placed: HashSet<Uuid> = HashSet::new();
+    let mut remaining: Vec<&(Uuid, Vec<Uuid>)> = repos.iter().collect();
+    let mut batches = Vec::new();
+
+    while !remaining.is_empty() {
+        let (ready, blocked): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|(_, deps)| {
+            deps.iter()
+                .all(|dep| !known.contains(dep) || placed.contains(dep))
+        });
+        if ready.is_empty() {
+            return Err(ProjectRepoError::DependencyCycle(
+                blocked.iter().map(|(id, _)| *id).collect(),
+            ));
+        }
+        let batch: Vec<Uuid> = ready.iter().map(|(id, _)| *id).collect();
+        placed.extend(&batch);
+        batches.push(batch);
+        remaining = blocked;
+    }
+    Ok(batches)
+}
+
+/// Setup batches in reverse, so a repo is torn down before the repos it depends on
+pub fn cleanup_batches(repos: &[(Uuid, Vec<Uuid>)]) -> Result<Vec<Vec<Uuid>>, ProjectRepoError> {
+    let mut batches = setup_batches(repos)?;
+    batches.reverse();
+    Ok(batches)
 }
 
 impl ProjectRepo {
+    fn dependency_graph(repos: &[Self]) -> Vec<(Uuid, Vec<Uuid>)> {
+        repos
+            .iter()
+            .map(|r| (r.repo_id, setup_dependencies(r.setup_depends_on.as_deref())))
+            .collect()
+    }
+
+    /// Repo ids of the project grouped into setup batches, dependencies first
+    pub async fn setup_order(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Vec<Uuid>>, ProjectRepoError> {
+        let repos = Self::find_by_project_id(pool, project_id).await?;
+        setup_batches(&Self::dependency_graph(&repos))
+    }
+
+    /// Whether the setup script should run given the task's changed files
+    pub fn setup_required_for(&self, changed: &[String]) -> bool {
+        setup_required_for(self.setup_paths.as_deref(), changed)
+    }
+
+    /// Ordered setup steps, see [`setup_steps`]
+    pub fn setup_steps(&self) -> Result<Vec<SetupStep>, ProjectRepoError> {
+        setup_steps(self.setup_steps.as_deref(), self.setup_script.as_deref())
+    }
+
+    /// Validate `copy_files` entries as repository-relative globs, returning them normalized
+    /// so callers can preview what will be copied
+    pub fn validate_copy_files(patterns: &str) -> Result<Vec<String>, ProjectRepoError> {
+        copy_file_patterns(patterns)
+            .map(|entry| {
+                parse_copy_file_entry(entry).map(|(src, dest)| {
+                    if src == dest {
+                        src
+                    } else {
+                        format!("{src} -> {dest}")
+                    }
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map_err(ProjectRepoError::InvalidCopyFiles)
+    }
+
+    /// Source and worktree destination of each `copy_files` entry, see
+    /// [`parse_copy_file_entry`]
+    pub fn copy_file_mappings(&self) -> Result<Vec<(PathBuf, PathBuf)>, ProjectRepoError> {
+        copy_file_patterns(self.copy_files.as_deref().unwrap_or_default())
+            .map(|entry| parse_copy_file_entry(entry).map(|(src, dest)| (src.into(), dest.into())))
+            .collect::<Result<_, _>>()
+            .map_err(ProjectRepoError::InvalidCopyFiles)
+    }
+
+    /// Fill in the placeholders of one of this repo's scripts, see [`render_script`]
+    pub fn render_script(&self, template: &str, ctx: &ScriptContext) -> String {
+        render_script(template, self.project_id, ctx)
+    }
+
+    /// The configured `base_branch`, or else the repo's default branch as reported by
+    /// `default_branch` (e.g. the hosting provider's default, for repos on `develop` or
+    /// `trunk`)
+    pub async fn resolve_base_branch<F, Fut, E>(&self, default_branch: F) -> Result<String, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<String, E>>,
+    {
+        match &self.base_branch {
+            Some(branch) => Ok(branch.clone()),
+            None => default_branch().await,
+        }
+    }
+
+    /// Whether another worktree may be created when `current_count` already exist
+    pub fn can_create_worktree(&self, current_count: i64) -> bool {
+        self.max_worktrees.is_none_or(|max| current_count < max)
+    }
+
+    /// Whether the repo has a bootstrap script that has not run successfully yet
+    pub fn needs_bootstrap(&self) -> bool {
+        self.bootstrapped_at.is_none()
+            && self
+                .bootstrap_script
+                .as_deref()
+                .is_some_and(|script| !script.trim().is_empty())
+    }
+
+    pub async fn mark_bootstrapped(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE project_repos SET bootstrapped_at = datetime('now', 'subsec') WHERE id = $1",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record that setup succeeded in `worktree_path` for inputs hashing to `fingerprint`
+    pub async fn record_setup_success(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        worktree_path: &str,
+        fingerprint: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO worktree_setup_fingerprints (repo_id, worktree_path, fingerprint)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(repo_id, worktree_path) DO UPDATE
+               SET fingerprint = excluded.fingerprint,
+                   recorded_at = datetime('now', 'subsec')"#,
+            repo_id,
+            worktree_path,
+            fingerprint
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Fingerprint of the last successful setup in `worktree_path`, if any
+    pub async fn last_setup_fingerprint(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT fingerprint
+               FROM worktree_setup_fingerprints
+               WHERE repo_id = $1 AND worktree_path = $2"#,
+            repo_id,
+            worktree_path
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
     pub async fn find_by_project_id(
         pool: &SqlitePool,
         project_id: Uuid,
@@ -71,9 +650,29 @@ impl ProjectRepo {
                       setup_script,
                       cleanup_script,
                       copy_files,
-                      parallel_setup_script as "parallel_setup_script!: bool"
+                      setup_concurrency as "setup_concurrency!: i64",
+                      base_branch,
+                      context_prompt,
+                      auto_open_pr as "auto_open_pr!: bool",
+                      setup_retries,
+                      version as "version!: i64",
+                      bootstrap_script,
+                      bootstrapped_at as "bootstrapped_at: DateTime<Utc>",
+                      env_file,
+                      setup_paths,
+                      max_worktrees,
+                      setup_depends_on,
+                      worktree_gitignore,
+                      setup_requires_network as "setup_requires_network!: bool",
+                      env_vars,
+                      setup_steps,
+                      deleted_at as "deleted_at: DateTime<Utc>",
+                      position as "position!: i64",
+                      setup_timeout_secs,
+                      dev_server_script
                FROM project_repos
-               WHERE project_id = $1"#,
+               WHERE project_id = $1 AND deleted_at IS NULL
+               ORDER BY position ASC"#,
             project_id
         )
         .fetch_all(pool)
@@ -92,9 +691,28 @@ impl ProjectRepo {
                       setup_script,
                       cleanup_script,
                       copy_files,
-                      parallel_setup_script as "parallel_setup_script!: bool"
+                      setup_concurrency as "setup_concurrency!: i64",
+                      base_branch,
+                      context_prompt,
+                      auto_open_pr as "auto_open_pr!: bool",
+                      setup_retries,
+                      version as "version!: i64",
+                      bootstrap_script,
+                      bootstrapped_at as "bootstrapped_at: DateTime<Utc>",
+                      env_file,
+                      setup_paths,
+                      max_worktrees,
+                      setup_depends_on,
+                      worktree_gitignore,
+                      setup_requires_network as "setup_requires_network!: bool",
+                      env_vars,
+                      setup_steps,
+                      deleted_at as "deleted_at: DateTime<Utc>",
+                      position as "position!: i64",
+                      setup_timeout_secs,
+                      dev_server_script
                FROM project_repos
-               WHERE repo_id = $1"#,
+               WHERE repo_id = $1 AND deleted_at IS NULL"#,
             repo_id
         )
         .fetch_all(pool)
@@ -114,11 +732,30 @@ impl ProjectRepo {
                       pr.setup_script,
                       pr.cleanup_script,
                       pr.copy_files,
-                      pr.parallel_setup_script as "parallel_setup_script!: bool"
+                      pr.setup_concurrency as "setup_concurrency!: i64",
+                      pr.base_branch,
+                      pr.context_prompt,
+                      pr.auto_open_pr as "auto_open_pr!: bool",
+                      pr.setup_retries,
+                      pr.version as "version!: i64",
+                      pr.bootstrap_script,
+                      pr.bootstrapped_at as "bootstrapped_at: DateTime<Utc>",
+                      pr.env_file,
+                      pr.setup_paths,
+                      pr.max_worktrees,
+                      pr.setup_depends_on,
+                      pr.worktree_gitignore,
+                      pr.setup_requires_network as "setup_requires_network!: bool",
+                      pr.env_vars,
+                      pr.setup_steps,
+                      pr.deleted_at as "deleted_at: DateTime<Utc>",
+                      pr.position as "position!: i64",
+                      pr.setup_timeout_secs,
+                      pr.dev_server_script
                FROM project_repos pr
                JOIN repos r ON r.id = pr.repo_id
-               WHERE pr.project_id = $1
-               ORDER BY r.display_name ASC"#,
+               WHERE pr.project_id = $1 AND pr.deleted_at IS NULL
+               ORDER BY pr.position ASC, r.display_name ASC"#,
             project_id
         )
         .fetch_all(pool)
@@ -139,8 +776,8 @@ impl ProjectRepo {
                       r.updated_at as "updated_at!: DateTime<Utc>"
                FROM repos r
                JOIN project_repos pr ON r.id = pr.repo_id
-               WHERE pr.project_id = $1
-               ORDER BY r.display_name ASC"#,
+               WHERE pr.project_id = $1 AND pr.deleted_at IS NULL
+               ORDER BY pr.position ASC, r.display_name ASC"#,
             project_id
         )
         .fetch_all(pool)
@@ -160,9 +797,28 @@ impl ProjectRepo {
                       setup_script,
                       cleanup_script,
                       copy_files,
-                      parallel_setup_script as "parallel_setup_script!: bool"
+                      setup_concurrency as "setup_concurrency!: i64",
+                      base_branch,
+                      context_prompt,
+                      auto_open_pr as "auto_open_pr!: bool",
+                      setup_retries,
+                      version as "version!: i64",
+                      bootstrap_script,
+                      bootstrapped_at as "bootstrapped_at: DateTime<Utc>",
+                      env_file,
+                      setup_paths,
+                      max_worktrees,
+                      setup_depends_on,
+                      worktree_gitignore,
+                      setup_requires_network as "setup_requires_network!: bool",
+                      env_vars,
+                      setup_steps,
+                      deleted_at as "deleted_at: DateTime<Utc>",
+                      position as "position!: i64",
+                      setup_timeout_secs,
+                      dev_server_script
                FROM project_repos
-               WHERE project_id = $1 AND repo_id = $2"#,
+               WHERE project_id = $1 AND repo_id = $2 AND deleted_at IS NULL"#,
             project_id,
             repo_id
         )
@@ -170,12 +826,52 @@ impl ProjectRepo {
         .await
     }
 
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectRepo,
+            r#"SELECT id as "id!: Uuid",
+                      project_id as "project_id!: Uuid",
+                      repo_id as "repo_id!: Uuid",
+                      setup_script,
+                      cleanup_script,
+                      copy_files,
+                      setup_concurrency as "setup_concurrency!: i64",
+                      base_branch,
+                      context_prompt,
+                      auto_open_pr as "auto_open_pr!: bool",
+                      setup_retries,
+                      version as "version!: i64",
+                      bootstrap_script,
+                      bootstrapped_at as "bootstrapped_at: DateTime<Utc>",
+                      env_file,
+                      setup_paths,
+                      max_worktrees,
+                      setup_depends_on,
+                      worktree_gitignore,
+                      setup_requires_network as "setup_requires_network!: bool",
+                      env_vars,
+                      setup_steps,
+                      deleted_at as "deleted_at: DateTime<Utc>",
+                      position as "position!: i64",
+                      setup_timeout_secs,
+                      dev_server_script
+               FROM project_repos
+               WHERE id = $1 AND deleted_at IS NULL"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Add the repo at `repo_path` to the project. The second value is a non-fatal
+    /// [`ProjectRepoError::SharedAcrossProjects`] warning when other projects use the same
+    /// repo, since cleaning up its worktrees in one project affects the others.
     pub async fn add_repo_to_project(
         pool: &SqlitePool,
         project_id: Uuid,
         repo_path: &str,
         repo_name: &str,
-    ) -> Result<Repo, ProjectRepoError> {
+    ) -> Result<(Repo, Option<ProjectRepoError>), ProjectRepoError> {
         let repo = Repo::find_or_create(pool, Path::new(repo_path), repo_name).await?;
 
         if Self::find_by_project_and_repo(pool, project_id, repo.id)
@@ -185,27 +881,121 @@ impl ProjectRepo {
             return Err(ProjectRepoError::AlreadyExists);
         }
 
-        let id = Uuid::new_v4();
-        sqlx::query!(
-            r#"INSERT INTO project_repos (id, project_id, repo_id)
-               VALUES ($1, $2, $3)"#,
-            id,
-            project_id,
-            repo.id
-        )
-        .execute(pool)
-        .await?;
+        let restored = match Self::restore(pool, project_id, repo.id).await {
+            Ok(_) => true,
+            Err(ProjectRepoError::NotFound) => false,
+            Err(e) => return Err(e),
+        };
+        if !restored {
+            let id = Uuid::new_v4();
+            sqlx::query!(
+                r#"INSERT INTO project_repos (id, project_id, repo_id, position)
+                   VALUES ($1, $2, $3, (SELECT COALESCE(MAX(position) + 1, 0)
+                                        FROM project_repos
+                                        WHERE project_id = $2))"#,
+                id,
+                project_id,
+                repo.id
+            )
+            .execute(pool)
+            .await?;
+        }
+
+        let others: Vec<Uuid> = Self::find_projects_sharing_repo(pool, repo.id)
+            .await?
+            .into_iter()
+            .filter(|id| *id != project_id)
+            .collect();
+        let warning =
+            (!others.is_empty()).then_some(ProjectRepoError::SharedAcrossProjects(others));
+
+        Ok((repo, warning))
+    }
+
+    /// Add several repos in one transaction, returning those not already in the project.
+    /// Paths are expected to be validated git repositories already, see `ProjectService`.
+    pub async fn add_repos_to_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        repos: &[CreateProjectRepo],
+    ) -> Result<Vec<Repo>, ProjectRepoError> {
+        let mut tx = pool.begin().await?;
+        let mut added = Vec::new();
 
-        Ok(repo)
+        for create in repos {
+            let path = Path::new(&create.git_repo_path);
+            let repo = Repo::find_or_create(&mut *tx, path, &create.display_name).await?;
+
+            let deleted = sqlx::query_scalar!(
+                r#"SELECT deleted_at IS NOT NULL as "deleted!: bool"
+                   FROM project_repos
+                   WHERE project_id = $1 AND repo_id = $2"#,
+                project_id,
+                repo.id
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+            match deleted {
+                Some(false) => continue,
+                Some(true) => {
+                    sqlx::query!(
+                        r#"UPDATE project_repos
+                           SET deleted_at = NULL
+                           WHERE project_id = $1 AND repo_id = $2"#,
+                        project_id,
+                        repo.id
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                None => {
+                    let id = Uuid::new_v4();
+                    sqlx::query!(
+                        r#"INSERT INTO project_repos (id, project_id, repo_id, position)
+                           VALUES ($1, $2, $3, (SELECT COALESCE(MAX(position) + 1, 0)
+                                                FROM project_repos
+                                                WHERE project_id = $2))"#,
+                        id,
+                        project_id,
+                        repo.id
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+            added.push(repo);
+        }
+
+        tx.commit().await?;
+        Ok(added)
+    }
+
+    /// Projects that currently include the repo
+    pub async fn find_projects_sharing_repo(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT project_id as "project_id!: Uuid"
+               FROM project_repos
+               WHERE repo_id = $1 AND deleted_at IS NULL
+               ORDER BY project_id"#,
+            repo_id
+        )
+        .fetch_all(pool)
+        .await
     }
 
+    /// Soft-delete the repo from the project, keeping its settings for [`Self::restore`]
     pub async fn remove_repo_from_project(
         pool: &SqlitePool,
         project_id: Uuid,
         repo_id: Uuid,
     ) -> Result<(), ProjectRepoError> {
         let result = sqlx::query!(
-            "DELETE FROM project_repos WHERE project_id = $1 AND repo_id = $2",
+            r#"UPDATE project_repos
+               SET deleted_at = datetime('now', 'subsec')
+               WHERE project_id = $1 AND repo_id = $2 AND deleted_at IS NULL"#,
             project_id,
             repo_id
         )
@@ -219,6 +1009,91 @@ impl ProjectRepo {
         Ok(())
     }
 
+    /// Undo [`Self::remove_repo_from_project`], bringing back the repo with its settings
+    pub async fn restore(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        repo_id: Uuid,
+    ) -> Result<Self, ProjectRepoError> {
+        sqlx::query_as!(
+            ProjectRepo,
+            r#"UPDATE project_repos
+               SET deleted_at = NULL
+               WHERE project_id = $1 AND repo_id = $2 AND deleted_at IS NOT NULL
+               RETURNING id as "id!: Uuid",
+                         project_id as "project_id!: Uuid",
+                         repo_id as "repo_id!: Uuid",
+                         setup_script,
+                         cleanup_script,
+                         copy_files,
+                         setup_concurrency as "setup_concurrency!: i64",
+                         base_branch,
+                         context_prompt,
+                         auto_open_pr as "auto_open_pr!: bool",
+                         setup_retries,
+                         version as "version!: i64",
+                         bootstrap_script,
+                         bootstrapped_at as "bootstrapped_at: DateTime<Utc>",
+                         env_file,
+                         setup_paths,
+                         max_worktrees,
+                         setup_depends_on,
+                         worktree_gitignore,
+                         setup_requires_network as "setup_requires_network!: bool",
+                         env_vars,
+                         setup_steps,
+                         deleted_at as "deleted_at: DateTime<Utc>",
+                         position as "position!: i64",
+                         setup_timeout_secs,
+                         dev_server_script"#,
+            project_id,
+            repo_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or(ProjectRepoError::NotFound)
+    }
+
+    /// Set the project's repo order to `ordered_repo_ids`, which must list each of its repos
+    /// exactly once. Positions are rewritten in one transaction so they stay contiguous.
+    pub async fn reorder(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        ordered_repo_ids: &[Uuid],
+    ) -> Result<(), ProjectRepoError> {
+        let mut tx = pool.begin().await?;
+
+        let current: HashSet<Uuid> = sqlx::query_scalar!(
+            r#"SELECT repo_id as "repo_id!: Uuid"
+               FROM project_repos
+               WHERE project_id = $1 AND deleted_at IS NULL"#,
+            project_id
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .collect();
+        let requested: HashSet<Uuid> = ordered_repo_ids.iter().copied().collect();
+        if requested.len() != ordered_repo_ids.len() || requested != current {
+            return Err(ProjectRepoError::InvalidOrder);
+        }
+
+        for (position, repo_id) in ordered_repo_ids.iter().enumerate() {
+            let position = position as i64;
+            sqlx::query!(
+                "UPDATE project_repos SET position = $1 WHERE project_id = $2 AND repo_id = $3",
+                position,
+                project_id,
+                repo_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     pub async fn create(
         executor: impl sqlx::Executor<'_, Database = sqlx::Sqlite>,
         project_id: Uuid,
@@ -227,15 +1102,36 @@ impl ProjectRepo {
         let id = Uuid::new_v4();
         sqlx::query_as!(
             ProjectRepo,
-            r#"INSERT INTO project_repos (id, project_id, repo_id)
-               VALUES ($1, $2, $3)
+            r#"INSERT INTO project_repos (id, project_id, repo_id, position)
+               VALUES ($1, $2, $3, (SELECT COALESCE(MAX(position) + 1, 0)
+                                    FROM project_repos
+                                    WHERE project_id = $2))
                RETURNING id as "id!: Uuid",
                          project_id as "project_id!: Uuid",
                          repo_id as "repo_id!: Uuid",
                          setup_script,
                          cleanup_script,
                          copy_files,
-                         parallel_setup_script as "parallel_setup_script!: bool""#,
+                         setup_concurrency as "setup_concurrency!: i64",
+                         base_branch,
+                         context_prompt,
+                         auto_open_pr as "auto_open_pr!: bool",
+                         setup_retries,
+                         version as "version!: i64",
+                         bootstrap_script,
+                         bootstrapped_at as "bootstrapped_at: DateTime<Utc>",
+                         env_file,
+                         setup_paths,
+                         max_worktrees,
+                         setup_depends_on,
+                         worktree_gitignore,
+                         setup_requires_network as "setup_requires_network!: bool",
+                         env_vars,
+                         setup_steps,
+                         deleted_at as "deleted_at: DateTime<Utc>",
+                         position as "position!: i64",
+                         setup_timeout_secs,
+                         dev_server_script"#,
             id,
             project_id,
             repo_id
@@ -256,9 +1152,111 @@ impl ProjectRepo {
         let setup_script = payload.setup_script.clone();
         let cleanup_script = payload.cleanup_script.clone();
         let copy_files = payload.copy_files.clone();
-        let parallel_setup_script = payload
-            .parallel_setup_script
-            .unwrap_or(existing.parallel_setup_script);
+        if let Some(patterns) = &copy_files {
+            Self::validate_copy_files(patterns)?;
+        }
+        let setup_concurrency = payload
+            .setup_concurrency
+            .unwrap_or(existing.setup_concurrency);
+        if setup_concurrency < 0 {
+            return Err(ProjectRepoError::InvalidSetupConcurrency(setup_concurrency));
+        }
+        let base_branch = payload
+            .base_branch
+            .as_deref()
+            .map(str::trim)
+            .filter(|b| !b.is_empty())
+            .map(str::to_string);
+        if let Some(branch) = &base_branch
+            && !is_valid_branch_name(branch)
+        {
+            return Err(ProjectRepoError::InvalidBaseBranch(branch.clone()));
+        }
+        let context_prompt = payload.context_prompt.clone();
+        let dev_server_script = payload.dev_server_script.clone();
+        let auto_open_pr = payload.auto_open_pr.unwrap_or(existing.auto_open_pr);
+        let setup_requires_network = payload
+            .setup_requires_network
+            .unwrap_or(existing.setup_requires_network);
+        let bootstrap_script = payload.bootstrap_script.clone();
+        let setup_retries = payload.setup_retries;
+        if let Some(retries) = setup_retries
+            && retries < 0
+        {
+            return Err(ProjectRepoError::InvalidSetupRetries(retries));
+        }
+        let env_file = payload
+            .env_file
+            .as_deref()
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .map(str::to_string);
+        if let Some(file) = &env_file
+            && !is_repo_relative(file)
+        {
+            return Err(ProjectRepoError::InvalidEnvFile(file.clone()));
+        }
+        let setup_paths = payload
+            .setup_paths
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string);
+        if let Some(paths) = &setup_paths {
+            parse_setup_paths(paths).map_err(ProjectRepoError::InvalidSetupPaths)?;
+        }
+        let setup_depends_on = payload
+            .setup_depends_on
+            .as_deref()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(str::to_string);
+        if let Some(raw) = &setup_depends_on {
+            let deps =
+                parse_setup_depends_on(raw).map_err(ProjectRepoError::InvalidSetupDependsOn)?;
+            let mut graph =
+                Self::dependency_graph(&Self::find_by_project_id(pool, project_id).await?);
+            if let Some((_, existing_deps)) = graph.iter_mut().find(|(id, _)| *id == repo_id) {
+                *existing_deps = deps;
+            }
+            setup_batches(&graph)?;
+        }
+        let worktree_gitignore = payload
+            .worktree_gitignore
+            .as_deref()
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+            .map(str::to_string);
+        let env_vars = payload
+            .env_vars
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string);
+        if let Some(raw) = &env_vars {
+            parse_env_vars(raw).map_err(ProjectRepoError::InvalidEnvVars)?;
+        }
+        let setup_steps = payload
+            .setup_steps
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+        if let Some(raw) = &setup_steps {
+            parse_setup_steps(raw).map_err(ProjectRepoError::InvalidSetupSteps)?;
+        }
+        let setup_timeout_secs = payload.setup_timeout_secs;
+        if let Some(secs) = setup_timeout_secs
+            && secs <= 0
+        {
+            return Err(ProjectRepoError::InvalidSetupTimeout(secs));
+        }
+        let max_worktrees = payload.max_worktrees;
+        if let Some(max) = max_worktrees
+            && max < 1
+        {
+            return Err(ProjectRepoError::InvalidMaxWorktrees(max));
+        }
 
         sqlx::query_as!(
             ProjectRepo,
@@ -266,24 +1264,954 @@ impl ProjectRepo {
                SET setup_script = $1,
                    cleanup_script = $2,
                    copy_files = $3,
-                   parallel_setup_script = $4
-               WHERE project_id = $5 AND repo_id = $6
+                   setup_concurrency = $4,
+                   base_branch = $5,
+                   context_prompt = $6,
+                   auto_open_pr = $7,
+                   setup_retries = $8,
+                   bootstrapped_at = CASE WHEN bootstrap_script IS $9 THEN bootstrapped_at END,
+                   bootstrap_script = $9,
+                   env_file = $10,
+                   setup_paths = $11,
+                   max_worktrees = $12,
+                   setup_depends_on = $13,
+                   worktree_gitignore = $14,
+                   setup_requires_network = $15,
+                   env_vars = $16,
+                   setup_steps = $17,
+                   setup_timeout_secs = $18,
+                   dev_server_script = $19,
+                   version = version + 1
+               WHERE project_id = $20
+                 AND repo_id = $21
+                 AND deleted_at IS NULL
+                 AND ($22 IS NULL OR version = $22)
                RETURNING id as "id!: Uuid",
                          project_id as "project_id!: Uuid",
                          repo_id as "repo_id!: Uuid",
                          setup_script,
                          cleanup_script,
                          copy_files,
-                         parallel_setup_script as "parallel_setup_script!: bool""#,
+                         setup_concurrency as "setup_concurrency!: i64",
+                         base_branch,
+                         context_prompt,
+                         auto_open_pr as "auto_open_pr!: bool",
+                         setup_retries,
+                         version as "version!: i64",
+                         bootstrap_script,
+                         bootstrapped_at as "bootstrapped_at: DateTime<Utc>",
+                         env_file,
+                         setup_paths,
+                         max_worktrees,
+                         setup_depends_on,
+                         worktree_gitignore,
+                         setup_requires_network as "setup_requires_network!: bool",
+                         env_vars,
+                         setup_steps,
+                         deleted_at as "deleted_at: DateTime<Utc>",
+                         position as "position!: i64",
+                         setup_timeout_secs,
+                         dev_server_script"#,
             setup_script,
             cleanup_script,
             copy_files,
-            parallel_setup_script,
+            setup_concurrency,
+            base_branch,
+            context_prompt,
+            auto_open_pr,
+            setup_retries,
+            bootstrap_script,
+            env_file,
+            setup_paths,
+            max_worktrees,
+            setup_depends_on,
+            worktree_gitignore,
+            setup_requires_network,
+            env_vars,
+            setup_steps,
+            setup_timeout_secs,
+            dev_server_script,
             project_id,
-            repo_id
+            repo_id,
+            payload.version
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or(ProjectRepoError::Conflict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::models::project::{CreateProject, Project};
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    fn update(version: Option<i64>) -> UpdateProjectRepo {
+        UpdateProjectRepo {
+            setup_script: Some("npm ci".to_string()),
+            version,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn setup_concurrency_accepts_old_parallel_flag() {
+        let parse = |json: serde_json::Value| {
+            serde_json::from_value::<UpdateProjectRepo>(json)
+                .unwrap()
+                .setup_concurrency
+        };
+        assert_eq!(
+            parse(serde_json::json!({ "setup_concurrency": 3 })),
+            Some(3)
+        );
+        assert_eq!(
+            parse(serde_json::json!({ "parallel_setup_script": true })),
+            Some(0)
+        );
+        assert_eq!(
+            parse(serde_json::json!({ "parallel_setup_script": false })),
+            Some(1)
+        );
+        assert_eq!(parse(serde_json::json!({})), None);
+    }
+
+    #[test]
+    fn strictest_setup_concurrency_wins() {
+        assert_eq!(effective_setup_concurrency([0, 0]), None);
+        assert_eq!(effective_setup_concurrency([0, 3, 2]), Some(2));
+        assert_eq!(effective_setup_concurrency([4, 1]), Some(1));
+        assert_eq!(effective_setup_concurrency([]), None);
+    }
+
+    #[tokio::test]
+    async fn stale_update_is_rejected() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let (repo, _) = ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("lock").to_string_lossy(),
+            "repo",
         )
-        .fetch_one(pool)
         .await
-        .map_err(ProjectRepoError::from)
+        .unwrap();
+        let loaded = ProjectRepo::find_by_project_and_repo(&pool, project_id, repo.id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let saved = ProjectRepo::update(&pool, project_id, repo.id, &update(Some(loaded.version)))
+            .await
+            .unwrap();
+        assert_eq!(saved.version, loaded.version + 1);
+
+        let stale =
+            ProjectRepo::update(&pool, project_id, repo.id, &update(Some(loaded.version))).await;
+        assert!(matches!(stale, Err(ProjectRepoError::Conflict)));
+
+        let unversioned = ProjectRepo::update(&pool, project_id, repo.id, &update(None))
+            .await
+            .unwrap();
+        assert_eq!(unversioned.version, saved.version + 1);
+    }
+
+    async fn repo_names(pool: &SqlitePool, project_id: Uuid) -> Vec<String> {
+        ProjectRepo::find_by_project_id_with_names(pool, project_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.repo_name)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn reorder_sets_display_and_setup_order() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let mut ids = Vec::new();
+        for name in ["a", "b", "c"] {
+            let path = dir.path().join(name).to_string_lossy().into_owned();
+            let (repo, _) = ProjectRepo::add_repo_to_project(&pool, project_id, &path, name)
+                .await
+                .unwrap();
+            ids.push(repo.id);
+        }
+        assert_eq!(repo_names(&pool, project_id).await, ["a", "b", "c"]);
+
+        ProjectRepo::reorder(&pool, project_id, &[ids[2], ids[0], ids[1]])
+            .await
+            .unwrap();
+        assert_eq!(repo_names(&pool, project_id).await, ["c", "a", "b"]);
+        assert_eq!(
+            ProjectRepo::setup_order(&pool, project_id).await.unwrap(),
+            vec![vec![ids[2], ids[0], ids[1]]]
+        );
+
+        for bad in [vec![ids[0], ids[1]], vec![ids[0], ids[0], ids[1], ids[2]]] {
+            assert!(matches!(
+                ProjectRepo::reorder(&pool, project_id, &bad).await,
+                Err(ProjectRepoError::InvalidOrder)
+            ));
+        }
+        assert_eq!(repo_names(&pool, project_id).await, ["c", "a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn bulk_add_skips_repos_already_in_project() {
+        let pool = test_pool().await;
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let create = |name: &str| CreateProjectRepo {
+            display_name: name.to_string(),
+            git_repo_path: root.path().join(name).to_string_lossy().into_owned(),
+        };
+        let (api, web) = (create("api"), create("web"));
+
+        ProjectRepo::add_repos_to_project(&pool, project_id, std::slice::from_ref(&api))
+            .await
+            .unwrap();
+        let added = ProjectRepo::add_repos_to_project(&pool, project_id, &[api, web.clone(), web])
+            .await
+            .unwrap();
+        assert_eq!(
+            added.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(),
+            ["web"]
+        );
+        assert_eq!(repo_names(&pool, project_id).await, ["api", "web"]);
+    }
+
+    #[tokio::test]
+    async fn warns_when_repo_is_shared_across_projects() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        for (id, name) in [(first, "first"), (second, "second")] {
+            let project = CreateProject {
+                name: name.to_string(),
+                repositories: vec![],
+            };
+            Project::create(&pool, &project, id).await.unwrap();
+        }
+
+        let (repo, warning) = ProjectRepo::add_repo_to_project(
+            &pool,
+            first,
+            &dir.path().join("shared").to_string_lossy(),
+            "repo",
+        )
+        .await
+        .unwrap();
+        assert!(warning.is_none());
+
+        let (shared, warning) = ProjectRepo::add_repo_to_project(
+            &pool,
+            second,
+            &dir.path().join("shared").to_string_lossy(),
+            "other name",
+        )
+        .await
+        .unwrap();
+        assert_eq!(shared.id, repo.id);
+        assert!(matches!(
+            warning,
+            Some(ProjectRepoError::SharedAcrossProjects(ref projects)) if projects == &[first]
+        ));
+        let mut expected = vec![first, second];
+        expected.sort();
+        assert_eq!(
+            ProjectRepo::find_projects_sharing_repo(&pool, repo.id)
+                .await
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn removed_repos_keep_their_settings() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let (repo, _) = ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("soft").to_string_lossy(),
+            "repo",
+        )
+        .await
+        .unwrap();
+        ProjectRepo::update(&pool, project_id, repo.id, &update(None))
+            .await
+            .unwrap();
+
+        ProjectRepo::remove_repo_from_project(&pool, project_id, repo.id)
+            .await
+            .unwrap();
+        assert!(
+            ProjectRepo::find_by_project_and_repo(&pool, project_id, repo.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            ProjectRepo::find_by_project_id_with_names(&pool, project_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            ProjectRepo::remove_repo_from_project(&pool, project_id, repo.id).await,
+            Err(ProjectRepoError::NotFound)
+        ));
+
+        let restored = ProjectRepo::restore(&pool, project_id, repo.id)
+            .await
+            .unwrap();
+        assert!(restored.deleted_at.is_none());
+        assert_eq!(restored.setup_script.as_deref(), Some("npm ci"));
+        assert!(matches!(
+            ProjectRepo::restore(&pool, project_id, repo.id).await,
+            Err(ProjectRepoError::NotFound)
+        ));
+
+        ProjectRepo::remove_repo_from_project(&pool, project_id, repo.id)
+            .await
+            .unwrap();
+        ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("soft").to_string_lossy(),
+            "repo",
+        )
+        .await
+        .unwrap();
+        let readded = ProjectRepo::find_by_project_and_repo(&pool, project_id, repo.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(readded.setup_script.as_deref(), Some("npm ci"));
+    }
+
+    #[tokio::test]
+    async fn find_by_id_addresses_the_join_row() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let (repo, _) = ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("by-id").to_string_lossy(),
+            "repo",
+        )
+        .await
+        .unwrap();
+        let row = ProjectRepo::find_by_project_and_repo(&pool, project_id, repo.id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let found = ProjectRepo::find_by_id(&pool, row.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((found.project_id, found.repo_id), (project_id, repo.id));
+        assert!(
+            ProjectRepo::find_by_id(&pool, Uuid::new_v4())
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        ProjectRepo::remove_repo_from_project(&pool, project_id, repo.id)
+            .await
+            .unwrap();
+        assert!(
+            ProjectRepo::find_by_id(&pool, row.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn env_vars_are_validated_and_parsed() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let (repo, _) = ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("env").to_string_lossy(),
+            "repo",
+        )
+        .await
+        .unwrap();
+
+        let invalid = UpdateProjectRepo {
+            env_vars: Some(r#"["DATABASE_URL"]"#.to_string()),
+            ..update(None)
+        };
+        assert!(matches!(
+            ProjectRepo::update(&pool, project_id, repo.id, &invalid).await,
+            Err(ProjectRepoError::InvalidEnvVars(_))
+        ));
+
+        let valid = UpdateProjectRepo {
+            env_vars: Some(r#"{"DATABASE_URL": "postgres://db", "API_KEY": "secret"}"#.to_string()),
+            ..update(None)
+        };
+        ProjectRepo::update(&pool, project_id, repo.id, &valid)
+            .await
+            .unwrap();
+        let stored = ProjectRepo::find_by_project_id_with_names(&pool, project_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            stored[0].env_vars().unwrap(),
+            [
+                ("API_KEY".to_string(), "secret".to_string()),
+                ("DATABASE_URL".to_string(), "postgres://db".to_string()),
+            ]
+        );
+
+        sqlx::query("UPDATE project_repos SET env_vars = '{broken' WHERE repo_id = $1")
+            .bind(repo.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let stored = ProjectRepo::find_by_project_id_with_names(&pool, project_id)
+            .await
+            .unwrap();
+        assert!(matches!(
+            stored[0].env_vars(),
+            Err(ProjectRepoError::InvalidEnvVars(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn base_branch_override_wins_over_default() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let (repo, _) = ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("base").to_string_lossy(),
+            "repo",
+        )
+        .await
+        .unwrap();
+        let default = || async { Ok::<_, String>("trunk".to_string()) };
+
+        let unset = ProjectRepo::find_by_project_and_repo(&pool, project_id, repo.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(unset.resolve_base_branch(default).await.unwrap(), "trunk");
+
+        let with_override = UpdateProjectRepo {
+            base_branch: Some("develop".to_string()),
+            ..update(None)
+        };
+        let saved = ProjectRepo::update(&pool, project_id, repo.id, &with_override)
+            .await
+            .unwrap();
+        assert_eq!(saved.resolve_base_branch(default).await.unwrap(), "develop");
+    }
+
+    #[tokio::test]
+    async fn bootstrap_runs_once_until_script_changes() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let (repo, _) = ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("boot").to_string_lossy(),
+            "repo",
+        )
+        .await
+        .unwrap();
+
+        let with_bootstrap = |script: &str| UpdateProjectRepo {
+            bootstrap_script: Some(script.to_string()),
+            ..update(None)
+        };
+        let project_repo = ProjectRepo::update(
+            &pool,
+            project_id,
+            repo.id,
+            &with_bootstrap("git config x y"),
+        )
+        .await
+        .unwrap();
+        assert!(project_repo.needs_bootstrap());
+
+        ProjectRepo::mark_bootstrapped(&pool, project_repo.id)
+            .await
+            .unwrap();
+        let project_repo = ProjectRepo::update(
+            &pool,
+            project_id,
+            repo.id,
+            &with_bootstrap("git config x y"),
+        )
+        .await
+        .unwrap();
+        assert!(!project_repo.needs_bootstrap());
+
+        let project_repo = ProjectRepo::update(
+            &pool,
+            project_id,
+            repo.id,
+            &with_bootstrap("git config x z"),
+        )
+        .await
+        .unwrap();
+        assert!(project_repo.needs_bootstrap());
+    }
+
+    #[tokio::test]
+    async fn max_worktrees_limits_new_worktrees() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let (repo, _) = ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("limit").to_string_lossy(),
+            "repo",
+        )
+        .await
+        .unwrap();
+
+        let unlimited = ProjectRepo::find_by_project_and_repo(&pool, project_id, repo.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(unlimited.can_create_worktree(1_000));
+
+        let with_max = |max: i64| UpdateProjectRepo {
+            max_worktrees: Some(max),
+            ..update(None)
+        };
+        let limited = ProjectRepo::update(&pool, project_id, repo.id, &with_max(2))
+            .await
+            .unwrap();
+        assert_eq!(limited.max_worktrees, Some(2));
+        assert!(limited.can_create_worktree(1));
+        assert!(!limited.can_create_worktree(2));
+
+        let invalid = ProjectRepo::update(&pool, project_id, repo.id, &with_max(0)).await;
+        assert!(matches!(
+            invalid,
+            Err(ProjectRepoError::InvalidMaxWorktrees(0))
+        ));
+    }
+
+    #[tokio::test]
+    async fn setup_timeout_must_be_positive() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let (repo, _) = ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("timeout").to_string_lossy(),
+            "repo",
+        )
+        .await
+        .unwrap();
+        let with_timeout = |secs: i64| UpdateProjectRepo {
+            setup_timeout_secs: Some(secs),
+            ..update(None)
+        };
+
+        let saved = ProjectRepo::update(&pool, project_id, repo.id, &with_timeout(30))
+            .await
+            .unwrap();
+        assert_eq!(saved.setup_timeout_secs, Some(30));
+        for secs in [0, -5] {
+            assert!(matches!(
+                ProjectRepo::update(&pool, project_id, repo.id, &with_timeout(secs)).await,
+                Err(ProjectRepoError::InvalidSetupTimeout(_))
+            ));
+        }
+
+        let timeouts: Vec<_> = ProjectRepo::find_by_project_id_with_names(&pool, project_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(ProjectRepoWithName::setup_timeout_secs)
+            .collect();
+        assert_eq!(timeouts, [30]);
+        ProjectRepo::update(&pool, project_id, repo.id, &update(None))
+            .await
+            .unwrap();
+        let repos = ProjectRepo::find_by_project_id_with_names(&pool, project_id)
+            .await
+            .unwrap();
+        assert_eq!(repos[0].setup_timeout_secs(), DEFAULT_SETUP_TIMEOUT_SECS);
+    }
+
+    #[tokio::test]
+    async fn dev_server_script_is_saved_and_blank_is_ignored() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let (repo, _) = ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("dev").to_string_lossy(),
+            "repo",
+        )
+        .await
+        .unwrap();
+        let with_dev_server = |script: &str| UpdateProjectRepo {
+            dev_server_script: Some(script.to_string()),
+            ..update(None)
+        };
+
+        ProjectRepo::update(&pool, project_id, repo.id, &with_dev_server("npm run dev"))
+            .await
+            .unwrap();
+        let repos = ProjectRepo::find_by_project_id_with_names(&pool, project_id)
+            .await
+            .unwrap();
+        assert_eq!(repos[0].dev_server_script(), Some("npm run dev"));
+
+        ProjectRepo::update(&pool, project_id, repo.id, &with_dev_server("  "))
+            .await
+            .unwrap();
+        let repos = ProjectRepo::find_by_project_id_with_names(&pool, project_id)
+            .await
+            .unwrap();
+        assert_eq!(repos[0].dev_server_script(), None);
+    }
+
+    #[test]
+    fn cleanup_batches_reverse_setup_batches() {
+        let (db, api, web, docs) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        // web -> api -> db, docs stands alone, and a dependency outside the project is ignored
+        let graph = vec![
+            (web, vec![api]),
+            (api, vec![db, Uuid::new_v4()]),
+            (db, vec![]),
+            (docs, vec![]),
+        ];
+
+        let setup = setup_batches(&graph).unwrap();
+        assert_eq!(setup, vec![vec![db, docs], vec![api], vec![web]]);
+
+        let mut cleanup = cleanup_batches(&graph).unwrap();
+        assert_eq!(cleanup, vec![vec![web], vec![api], vec![db, docs]]);
+        cleanup.reverse();
+        assert_eq!(cleanup, setup);
+
+        let cyclic = vec![(db, vec![api]), (api, vec![db]), (docs, vec![])];
+        assert!(matches!(
+            cleanup_batches(&cyclic),
+            Err(ProjectRepoError::DependencyCycle(ids)) if ids == vec![db, api]
+        ));
+    }
+
+    #[tokio::test]
+    async fn cyclic_setup_dependencies_are_rejected() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let (first, _) = ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("dep-a").to_string_lossy(),
+            "a",
+        )
+        .await
+        .unwrap();
+        let (second, _) = ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("dep-b").to_string_lossy(),
+            "b",
+        )
+        .await
+        .unwrap();
+
+        let depends_on = |id: Uuid| UpdateProjectRepo {
+            setup_depends_on: Some(serde_json::json!([id]).to_string()),
+            ..update(None)
+        };
+        ProjectRepo::update(&pool, project_id, second.id, &depends_on(first.id))
+            .await
+            .unwrap();
+        assert_eq!(
+            ProjectRepo::setup_order(&pool, project_id).await.unwrap(),
+            vec![vec![first.id], vec![second.id]]
+        );
+
+        let cycle = ProjectRepo::update(&pool, project_id, first.id, &depends_on(second.id)).await;
+        assert!(matches!(cycle, Err(ProjectRepoError::DependencyCycle(_))));
+    }
+
+    #[tokio::test]
+    async fn setup_fingerprint_is_tracked_per_worktree() {
+        let pool = test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let project_id = Uuid::new_v4();
+        let project = CreateProject {
+            name: "project".to_string(),
+            repositories: vec![],
+        };
+        Project::create(&pool, &project, project_id).await.unwrap();
+        let (repo, _) = ProjectRepo::add_repo_to_project(
+            &pool,
+            project_id,
+            &dir.path().join("setup").to_string_lossy(),
+            "repo",
+        )
+        .await
+        .unwrap();
+        let worktree = "/tmp/worktrees/a/repo";
+
+        let miss = ProjectRepo::last_setup_fingerprint(&pool, repo.id, worktree)
+            .await
+            .unwrap();
+        assert_eq!(miss, None);
+
+        ProjectRepo::record_setup_success(&pool, repo.id, worktree, "abc")
+            .await
+            .unwrap();
+        let hit = ProjectRepo::last_setup_fingerprint(&pool, repo.id, worktree)
+            .await
+            .unwrap();
+        assert_eq!(hit.as_deref(), Some("abc"));
+
+        ProjectRepo::record_setup_success(&pool, repo.id, worktree, "def")
+            .await
+            .unwrap();
+        let updated = ProjectRepo::last_setup_fingerprint(&pool, repo.id, worktree)
+            .await
+            .unwrap();
+        assert_eq!(updated.as_deref(), Some("def"));
+
+        let other_worktree =
+            ProjectRepo::last_setup_fingerprint(&pool, repo.id, "/tmp/worktrees/b/repo")
+                .await
+                .unwrap();
+        assert_eq!(other_worktree, None);
+    }
+
+    #[test]
+    fn env_file_must_stay_in_repo() {
+        assert!(is_repo_relative(".env"));
+        assert!(is_repo_relative("config/dev.env"));
+        assert!(!is_repo_relative("/etc/environment"));
+        assert!(!is_repo_relative("../other/.env"));
+        assert!(!is_repo_relative("config/../../.env"));
+    }
+
+    #[test]
+    fn copy_files_must_be_repo_relative_globs() {
+        assert_eq!(
+            ProjectRepo::validate_copy_files(".env, config\\local.toml\n\nsecrets/*.pem").unwrap(),
+            vec![".env", "config/local.toml", "secrets/*.pem"]
+        );
+        assert_eq!(
+            ProjectRepo::validate_copy_files(".env.example -> .env\nconfig\\dev -> config\\local")
+                .unwrap(),
+            vec![".env.example -> .env", "config/dev -> config/local"]
+        );
+        assert_eq!(
+            parse_copy_file_entry(".env").unwrap(),
+            (".env".to_string(), ".env".to_string())
+        );
+        for bad in [
+            "/etc/hosts",
+            "../other/.env",
+            "config/../../.env",
+            "src/[a-",
+            ".env.example -> ../.env",
+            ".env.example -> /tmp/.env",
+            ".env.example ->",
+            "*.example -> .env",
+        ] {
+            assert!(matches!(
+                ProjectRepo::validate_copy_files(bad),
+                Err(ProjectRepoError::InvalidCopyFiles(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn parses_env_file_lines() {
+        let vars = parse_env_file(
+            "# comment\n\nFOO=bar\nexport BAZ=\"quoted value\"\nEMPTY=\nnot a pair\n=nokey\n",
+        );
+        assert_eq!(
+            vars,
+            vec![
+                ("FOO".to_string(), "bar".to_string()),
+                ("BAZ".to_string(), "quoted value".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_script_placeholders() {
+        let project_id = Uuid::nil();
+        let ctx = ScriptContext {
+            worktree_path: "/tmp/worktrees/vk-1/web".to_string(),
+            repo_name: "web".to_string(),
+            branch_name: "vk/1-fix".to_string(),
+        };
+        assert_eq!(
+            render_script(
+                "cd ${WORKTREE_PATH} && echo ${REPO_NAME}@${BRANCH_NAME} ${PROJECT_ID}",
+                project_id,
+                &ctx
+            ),
+            format!("cd /tmp/worktrees/vk-1/web && echo web@vk/1-fix {project_id}")
+        );
+        assert_eq!(
+            render_script("echo ${HOME} $PATH ${UNCLOSED", project_id, &ctx),
+            "echo ${HOME} $PATH ${UNCLOSED"
+        );
+        assert_eq!(
+            render_script("echo $${REPO_NAME} ${REPO_NAME} $$", project_id, &ctx),
+            "echo ${REPO_NAME} web $$"
+        );
+    }
+
+    #[test]
+    fn setup_script_is_a_single_anonymous_step() {
+        assert_eq!(
+            setup_steps(None, Some("npm ci")).unwrap(),
+            [SetupStep {
+                name: None,
+                command: "npm ci".to_string(),
+            }]
+        );
+        assert!(setup_steps(None, None).unwrap().is_empty());
+
+        let steps = setup_steps(
+            Some(r#"[{"name": "install", "command": "npm ci"}, {"name": "migrate", "command": "npm run db:migrate"}]"#),
+            Some("ignored"),
+        )
+        .unwrap();
+        let names: Vec<_> = steps.iter().map(|s| s.name.as_deref()).collect();
+        assert_eq!(names, [Some("install"), Some("migrate")]);
+
+        for invalid in [
+            r#"{"command": "npm ci"}"#,
+            r#"[{"name": "install", "command": " "}]"#,
+        ] {
+            assert!(matches!(
+                setup_steps(Some(invalid), None),
+                Err(ProjectRepoError::InvalidSetupSteps(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn setup_runs_only_for_matching_changes() {
+        let repo_with_paths = |paths: Option<&str>| ProjectRepo {
+            setup_script: Some("pnpm install".to_string()),
+            setup_paths: paths.map(str::to_string),
+            ..Default::default()
+        };
+        let changes = |paths: &[&str]| paths.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        let gated = repo_with_paths(Some(r#"["packages/api/**", "package.json"]"#));
+        assert!(gated.setup_required_for(&changes(&["packages/api/src/main.ts"])));
+        assert!(gated.setup_required_for(&changes(&["docs/intro.md", "package.json"])));
+        assert!(!gated.setup_required_for(&changes(&["docs/intro.md", "README.md"])));
+        assert!(!gated.setup_required_for(&[]));
+
+        let ungated = repo_with_paths(None);
+        assert!(ungated.setup_required_for(&changes(&["docs/intro.md"])));
+        assert!(ungated.setup_required_for(&[]));
+
+        let invalid = repo_with_paths(Some("not json"));
+        assert!(invalid.setup_required_for(&changes(&["docs/intro.md"])));
     }
 }
@@ -96,6 +96,22 @@ impl WorkspaceRepo {
         .await
     }
 
+    /// Number of workspaces that currently have a worktree checked out for the repo
+    pub async fn count_active_worktrees(
+        pool: &SqlitePool,
+        repo_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM workspace_repos wr
+               JOIN workspaces w ON w.id = wr.workspace_id
+               WHERE wr.repo_id = $1 AND w.container_ref IS NOT NULL"#,
+            repo_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
     pub async fn find_repos_for_workspace(
         pool: &SqlitePool,
         workspace_id: Uuid,
@@ -253,7 +269,8 @@ impl WorkspaceRepo {
                JOIN workspace_repos wr ON r.id = wr.repo_id
                JOIN workspaces w ON w.id = wr.workspace_id
                JOIN tasks t ON t.id = w.task_id
-               LEFT JOIN project_repos pr ON pr.project_id = t.project_id AND pr.repo_id = r.id
+               LEFT JOIN project_repos pr
+                 ON pr.project_id = t.project_id AND pr.repo_id = r.id AND pr.deleted_at IS NULL
                WHERE wr.workspace_id = $1"#,
             workspace_id
         )
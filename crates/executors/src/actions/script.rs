@@ -1,4 +1,4 @@
-use std::{path::Path, sync::Arc};
+use std::{path::Path, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use command_group::AsyncCommandGroup;
@@ -11,7 +11,7 @@ use crate::{
     actions::Executable,
     approvals::ExecutorApprovalService,
     env::ExecutionEnv,
-    executors::{ExecutorError, SpawnedChild},
+    executors::{ExecutorError, ExecutorExitResult, SpawnedChild},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -36,6 +36,10 @@ pub struct ScriptRequest {
     /// If None, uses the container_ref directory directly.
     #[serde(default)]
     pub working_dir: Option<String>,
+    /// Kill the script's process group if it is still running after this many seconds
+    #[serde(default)]
+    #[ts(type = "number | null")]
+    pub timeout_secs: Option<u64>,
 }
 
 #[async_trait]
@@ -68,6 +72,21 @@ impl Executable for ScriptRequest {
 
         let child = command.group_spawn()?;
 
-        Ok(child.into())
+        let mut spawned = SpawnedChild::from(child);
+        if let Some(secs) = self.timeout_secs {
+            let timeout = Duration::from_secs(secs);
+            let (mut tx, rx) = tokio::sync::oneshot::channel();
+            tokio::spawn(async move {
+                tokio::select! {
+                    _ = tokio::time::sleep(timeout) => {}
+                    // The script exited first and its monitor dropped the receiver
+                    _ = tx.closed() => return,
+                }
+                let _ = tx.send(ExecutorExitResult::TimedOut(timeout));
+            });
+            spawned.exit_signal = Some(rx);
+        }
+
+        Ok(spawned)
     }
 }
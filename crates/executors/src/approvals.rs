@@ -1,9 +1,18 @@
-use std::fmt;
+use std::{
+    fmt,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use async_trait::async_trait;
+use globset::{Glob, GlobBuilder, GlobMatcher};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+use ts_rs::TS;
 use workspace_utils::approvals::ApprovalStatus;
 
 /// Errors emitted by executor approval services.
@@ -15,6 +24,8 @@ pub enum ExecutorApprovalError {
     RequestFailed(String),
     #[error("executor approval service unavailable")]
     ServiceUnavailable,
+    #[error("invalid approval policy: {0}")]
+    InvalidPolicy(String),
 }
 
 impl ExecutorApprovalError {
@@ -23,6 +34,15 @@ impl ExecutorApprovalError {
     }
 }
 
+/// Decision applied to a tool approval request nobody answered in time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalTimeoutAction {
+    #[default]
+    Deny,
+    Approve,
+}
+
 /// Abstraction for executor approval backends.
 #[async_trait]
 pub trait ExecutorApprovalService: Send + Sync {
@@ -50,7 +70,400 @@ impl ExecutorApprovalService for NoopExecutorApprovalService {
     }
 }
 
+/// Pending requests buffered before `request_tool_approval` waits for the receiver
+const APPROVAL_CHANNEL_CAPACITY: usize = 32;
+
+/// A tool call waiting for a decision, delivered by [`ChannelApprovalService`].
+#[derive(Debug)]
+pub struct PendingApproval {
+    pub tool_name: String,
+    pub tool_input: Value,
+    pub tool_call_id: String,
+    responder: oneshot::Sender<ApprovalStatus>,
+}
+
+impl PendingApproval {
+    /// Send the decision back to the waiting executor. Returns false if it stopped waiting.
+    pub fn respond(self, status: ApprovalStatus) -> bool {
+        self.responder.send(status).is_ok()
+    }
+}
+
+/// Approval backend that posts each request onto an mpsc channel and waits for the answer,
+/// denying the tool call if none arrives within `timeout`.
+#[derive(Debug, Clone)]
+pub struct ChannelApprovalService {
+    requests: mpsc::Sender<PendingApproval>,
+    timeout: Duration,
+}
+
+impl ChannelApprovalService {
+    /// Create the service and the receiver that pending approvals are delivered to
+    pub fn new(timeout: Duration) -> (Self, mpsc::Receiver<PendingApproval>) {
+        let (requests, receiver) = mpsc::channel(APPROVAL_CHANNEL_CAPACITY);
+        (Self { requests, timeout }, receiver)
+    }
+}
+
+#[async_trait]
+impl ExecutorApprovalService for ChannelApprovalService {
+    async fn request_tool_approval(
+        &self,
+        tool_name: &str,
+        tool_input: Value,
+        tool_call_id: &str,
+    ) -> Result<ApprovalStatus, ExecutorApprovalError> {
+        let (responder, decision) = oneshot::channel();
+        let pending = PendingApproval {
+            tool_name: tool_name.to_string(),
+            tool_input,
+            tool_call_id: tool_call_id.to_string(),
+            responder,
+        };
+
+        let wait = async {
+            self.requests
+                .send(pending)
+                .await
+                .map_err(|_| ExecutorApprovalError::ServiceUnavailable)?;
+            decision
+                .await
+                .map_err(|_| ExecutorApprovalError::ServiceUnavailable)
+        };
+
+        match tokio::time::timeout(self.timeout, wait).await {
+            Ok(result) => result,
+            Err(_) => {
+                tracing::info!(
+                    "Approval for {tool_name} ({tool_call_id}) timed out after {:?}, denying",
+                    self.timeout
+                );
+                Ok(ApprovalStatus::Denied { reason: None })
+            }
+        }
+    }
+}
+
+/// Outcome of an [`ApprovalPolicy`] for a tool call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyDecision {
+    Allow,
+    /// Defer to the human-facing approval service
+    #[default]
+    Ask,
+    Deny,
+}
+
+/// One rule of an [`ApprovalPolicy`]; every condition that is set must hold for it to match
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct ApprovalRule {
+    /// Case-insensitive glob matched against the tool name, e.g. `read` or `{edit,write}`;
+    /// unset matches every tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    /// Glob every path the call touches must match, e.g. `src/**`. Paths inside the worktree
+    /// are matched relative to it, others as absolute paths. Calls touching no path never match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// `true` matches calls touching any path outside the worktree, `false` calls whose paths
+    /// all lie inside it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outside_worktree: Option<bool>,
+    pub decision: PolicyDecision,
+}
+
+/// Declarative approval rules evaluated in order; the first matching rule decides and calls
+/// no rule matches get `default`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct ApprovalPolicy {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<ApprovalRule>,
+    #[serde(default)]
+    pub default: PolicyDecision,
+}
+
+#[derive(Debug)]
+struct CompiledRule {
+    tool: Option<GlobMatcher>,
+    path: Option<GlobMatcher>,
+    outside_worktree: Option<bool>,
+    decision: PolicyDecision,
+}
+
+impl CompiledRule {
+    fn matches(&self, tool_name: &str, paths: &[PathBuf], worktree: &Path) -> bool {
+        if self
+            .tool
+            .as_ref()
+            .is_some_and(|tool| !tool.is_match(tool_name))
+        {
+            return false;
+        }
+        if let Some(path) = &self.path
+            && (paths.is_empty()
+                || !paths
+                    .iter()
+                    .all(|p| path.is_match(p.strip_prefix(worktree).unwrap_or(p.as_path()))))
+        {
+            return false;
+        }
+        match self.outside_worktree {
+            Some(true) => paths.iter().any(|p| !p.starts_with(worktree)),
+            Some(false) => !paths.is_empty() && paths.iter().all(|p| p.starts_with(worktree)),
+            None => true,
+        }
+    }
+}
+
+/// `path` resolved against `root` with `.` and `..` folded away, without touching the disk
+fn resolve_path(root: &Path, path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in root.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            component => resolved.push(component),
+        }
+    }
+    resolved
+}
+
+/// Paths a tool call touches: ACP `locations` plus the usual path arguments of the raw input
+fn tool_call_paths(tool_input: &Value) -> Vec<&str> {
+    const PATH_KEYS: [&str; 3] = ["path", "file_path", "filePath"];
+    let call = tool_input.get("tool_call").unwrap_or(tool_input);
+    let locations = call
+        .get("locations")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|location| location.get("path"));
+    let arguments = [Some(call), call.get("rawInput")]
+        .into_iter()
+        .flatten()
+        .flat_map(|input| PATH_KEYS.into_iter().filter_map(move |key| input.get(key)));
+    let mut paths: Vec<&str> = locations
+        .chain(arguments)
+        .filter_map(Value::as_str)
+        .collect();
+    paths.sort_unstable();
+    paths.dedup();
+    paths
+}
+
+/// Approval backend that applies an [`ApprovalPolicy`] and only asks `fallback`, normally the
+/// human-facing service, for calls the policy leaves at `ask`
+pub struct PolicyApprovalService {
+    rules: Vec<CompiledRule>,
+    default: PolicyDecision,
+    worktree: PathBuf,
+    fallback: Arc<dyn ExecutorApprovalService>,
+}
+
+impl PolicyApprovalService {
+    pub fn new(
+        policy: &ApprovalPolicy,
+        worktree: &Path,
+        fallback: Arc<dyn ExecutorApprovalService>,
+    ) -> Result<Self, ExecutorApprovalError> {
+        let invalid = |e: globset::Error| ExecutorApprovalError::InvalidPolicy(e.to_string());
+        let rules = policy
+            .rules
+            .iter()
+            .map(|rule| -> Result<CompiledRule, ExecutorApprovalError> {
+                Ok(CompiledRule {
+                    tool: rule
+                        .tool
+                        .as_deref()
+                        .map(|tool| {
+                            GlobBuilder::new(tool)
+                                .case_insensitive(true)
+                                .build()
+                                .map(|glob| glob.compile_matcher())
+                        })
+                        .transpose()
+                        .map_err(invalid)?,
+                    path: rule
+                        .path
+                        .as_deref()
+                        .map(|path| Glob::new(path).map(|glob| glob.compile_matcher()))
+                        .transpose()
+                        .map_err(invalid)?,
+                    outside_worktree: rule.outside_worktree,
+                    decision: rule.decision,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            rules,
+            default: policy.default,
+            worktree: resolve_path(Path::new("/"), worktree),
+            fallback,
+        })
+    }
+
+    /// The policy's decision for a tool call, before any fallback is asked
+    pub fn decide(&self, tool_name: &str, tool_input: &Value) -> PolicyDecision {
+        let paths: Vec<PathBuf> = tool_call_paths(tool_input)
+            .into_iter()
+            .map(|path| resolve_path(&self.worktree, Path::new(path)))
+            .collect();
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(tool_name, &paths, &self.worktree))
+            .map_or(self.default, |rule| rule.decision)
+    }
+}
+
+#[async_trait]
+impl ExecutorApprovalService for PolicyApprovalService {
+    async fn request_tool_approval(
+        &self,
+        tool_name: &str,
+        tool_input: Value,
+        tool_call_id: &str,
+    ) -> Result<ApprovalStatus, ExecutorApprovalError> {
+        match self.decide(tool_name, &tool_input) {
+            PolicyDecision::Allow => {
+                tracing::debug!("Approval policy allowed {tool_name} ({tool_call_id})");
+                Ok(ApprovalStatus::Approved)
+            }
+            PolicyDecision::Deny => {
+                tracing::debug!("Approval policy denied {tool_name} ({tool_call_id})");
+                Ok(ApprovalStatus::Denied {
+                    reason: Some(format!(
+                        "{tool_name} is not permitted by the approval policy"
+                    )),
+                })
+            }
+            PolicyDecision::Ask => {
+                self.fallback
+                    .request_tool_approval(tool_name, tool_input, tool_call_id)
+                    .await
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ToolCallMetadata {
     pub tool_call_id: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn channel_approval_resolves_with_decision() {
+        let (service, mut receiver) = ChannelApprovalService::new(Duration::from_secs(5));
+        tokio::spawn(async move {
+            let pending = receiver.recv().await.unwrap();
+            assert_eq!(pending.tool_name, "bash");
+            assert_eq!(pending.tool_call_id, "call-1");
+            assert!(pending.respond(ApprovalStatus::Approved));
+        });
+
+        let status = service
+            .request_tool_approval("bash", serde_json::json!({ "command": "ls" }), "call-1")
+            .await
+            .unwrap();
+        assert!(matches!(status, ApprovalStatus::Approved));
+    }
+
+    #[tokio::test]
+    async fn policy_rules_apply_in_order_before_asking() {
+        let policy: ApprovalPolicy = serde_json::from_value(serde_json::json!({
+            "rules": [
+                { "tool": "read", "decision": "allow" },
+                { "tool": "{edit,write}", "outside_worktree": true, "decision": "ask" },
+                { "tool": "edit", "path": ".git/**", "decision": "deny" },
+                { "tool": "{edit,write}", "decision": "allow" },
+            ],
+            "default": "deny",
+        }))
+        .unwrap();
+        let (human, mut receiver) = ChannelApprovalService::new(Duration::from_secs(5));
+        let service =
+            PolicyApprovalService::new(&policy, Path::new("/work/tree"), Arc::new(human)).unwrap();
+        let call =
+            |path: &str| serde_json::json!({ "tool_call": { "locations": [{ "path": path }] } });
+
+        assert_eq!(
+            service.decide("Read", &call("/etc/passwd")),
+            PolicyDecision::Allow
+        );
+        assert_eq!(
+            service.decide("edit", &call("src/main.rs")),
+            PolicyDecision::Allow
+        );
+        assert_eq!(
+            service.decide("edit", &call("../other/file")),
+            PolicyDecision::Ask
+        );
+        assert_eq!(
+            service.decide("edit", &call(".git/config")),
+            PolicyDecision::Deny
+        );
+        assert_eq!(
+            service.decide("write", &serde_json::json!({ "file_path": "/tmp/out" })),
+            PolicyDecision::Ask
+        );
+        assert_eq!(service.decide("bash", &Value::Null), PolicyDecision::Deny);
+
+        assert!(matches!(
+            service
+                .request_tool_approval("bash", Value::Null, "call-1")
+                .await
+                .unwrap(),
+            ApprovalStatus::Denied { reason: Some(_) }
+        ));
+        tokio::spawn(async move {
+            let pending = receiver.recv().await.unwrap();
+            assert_eq!(pending.tool_call_id, "call-2");
+            pending.respond(ApprovalStatus::Approved);
+        });
+        assert!(matches!(
+            service
+                .request_tool_approval("edit", call("/elsewhere/file"), "call-2")
+                .await
+                .unwrap(),
+            ApprovalStatus::Approved
+        ));
+
+        let invalid = ApprovalPolicy {
+            rules: vec![ApprovalRule {
+                path: Some("src/[a-".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(matches!(
+            PolicyApprovalService::new(
+                &invalid,
+                Path::new("/work/tree"),
+                Arc::new(NoopExecutorApprovalService)
+            ),
+            Err(ExecutorApprovalError::InvalidPolicy(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn channel_approval_denies_after_timeout() {
+        let (service, mut receiver) = ChannelApprovalService::new(Duration::from_millis(50));
+        // Hold the request without answering it
+        let held = tokio::spawn(async move { receiver.recv().await });
+
+        let status = service
+            .request_tool_approval("edit", Value::Null, "call-2")
+            .await
+            .unwrap();
+        assert!(matches!(status, ApprovalStatus::Denied { reason: None }));
+
+        let pending = held.await.unwrap().unwrap();
+        assert!(!pending.respond(ApprovalStatus::Approved));
+    }
+}
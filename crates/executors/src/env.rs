@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use tokio::process::Command;
 
 use crate::command::CmdOverrides;
@@ -8,12 +9,15 @@ use crate::command::CmdOverrides;
 #[derive(Debug, Clone, Default)]
 pub struct ExecutionEnv {
     pub vars: HashMap<String, String>,
+    /// `vars` is the complete environment; nothing is inherited from vibe-kanban's process
+    isolated: bool,
 }
 
 impl ExecutionEnv {
     pub fn new() -> Self {
         Self {
             vars: HashMap::new(),
+            isolated: false,
         }
     }
 
@@ -43,8 +47,32 @@ impl ExecutionEnv {
         }
     }
 
-    /// Apply all environment variables to a Command
+    /// Copy of the full environment the process would see, i.e. vibe-kanban's own variables
+    /// overlaid with these. Inherited variables are kept only if they match an `allow`
+    /// pattern and no `deny` pattern; the ones set here only have to avoid `deny`. Patterns
+    /// are globs such as `AWS_*`; an empty `allow` admits every name and `deny` always wins.
+    /// The result replaces the inherited environment when applied. Invalid patterns are
+    /// logged and match nothing.
+    pub fn filtered(&self, allow: &[String], deny: &[String]) -> ExecutionEnv {
+        let allow = (!allow.is_empty()).then(|| glob_set(allow));
+        let deny = glob_set(deny);
+        let vars = std::env::vars()
+            .filter(|(key, _)| allow.as_ref().is_none_or(|allow| allow.is_match(key)))
+            .chain(self.vars.clone())
+            .filter(|(key, _)| !deny.is_match(key))
+            .collect();
+        ExecutionEnv {
+            vars,
+            isolated: true,
+        }
+    }
+
+    /// Apply all environment variables to a Command. For a [`filtered`](Self::filtered) env
+    /// this first clears everything, including variables already set on `command`.
     pub fn apply_to_command(&self, command: &mut Command) {
+        if self.isolated {
+            command.env_clear();
+        }
         for (key, value) in &self.vars {
             command.env(key, value);
         }
@@ -55,10 +83,50 @@ impl ExecutionEnv {
     }
 }
 
+fn glob_set(patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        match Glob::new(pattern) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => tracing::warn!("Ignoring invalid env filter pattern `{pattern}`: {e}"),
+        }
+    }
+    builder.build().unwrap_or_else(|e| {
+        tracing::warn!("Failed to build env filter: {e}");
+        GlobSet::empty()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn filter_denylist_wins_over_allowlist() {
+        let mut env = ExecutionEnv::default();
+        env.insert("VK_TEST_FILTER_KEEP", "1");
+        env.insert("VK_TEST_FILTER_SECRET", "2");
+        env.insert("UNRELATED_TEST_FILTER", "3");
+
+        let filtered = env.filtered(&["VK_TEST_FILTER_*".to_string()], &["*_SECRET".to_string()]);
+        assert_eq!(filtered.vars.len(), 2);
+        assert_eq!(filtered.vars.get("VK_TEST_FILTER_KEEP").unwrap(), "1");
+        // The allowlist only narrows what is inherited, not what was set explicitly
+        assert!(filtered.contains_key("UNRELATED_TEST_FILTER"));
+        assert!(!filtered.contains_key("PATH"));
+
+        // No allowlist admits everything that is not denied, inherited variables included
+        let filtered = env.filtered(&[], &["VK_TEST_FILTER_*".to_string()]);
+        assert!(filtered.contains_key("UNRELATED_TEST_FILTER"));
+        assert!(!filtered.contains_key("VK_TEST_FILTER_KEEP"));
+        assert_eq!(
+            filtered.vars.get("PATH"),
+            std::env::var("PATH").ok().as_ref()
+        );
+    }
+
     #[test]
     fn profile_overrides_runtime_env() {
         let mut base = ExecutionEnv::default();
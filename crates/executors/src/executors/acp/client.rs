@@ -7,8 +7,8 @@ use tracing::{debug, warn};
 use workspace_utils::approvals::ApprovalStatus;
 
 use crate::{
-    approvals::{ExecutorApprovalError, ExecutorApprovalService},
-    executors::acp::{AcpEvent, ApprovalResponse},
+    approvals::{ApprovalTimeoutAction, ExecutorApprovalError, ExecutorApprovalService},
+    executors::acp::{AcpEvent, ApprovalResponse, ApprovalTimeout, TokenUsage},
 };
 
 /// ACP client that handles agent-client protocol communication
@@ -16,6 +16,7 @@ use crate::{
 pub struct AcpClient {
     event_tx: mpsc::UnboundedSender<AcpEvent>,
     approvals: Option<Arc<dyn ExecutorApprovalService>>,
+    approval_timeout: Option<ApprovalTimeout>,
     feedback_queue: Arc<Mutex<Vec<String>>>,
 }
 
@@ -28,10 +29,17 @@ impl AcpClient {
         Self {
             event_tx,
             approvals,
+            approval_timeout: None,
             feedback_queue: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    /// Resolve approval requests the approval service leaves unanswered for too long
+    pub fn with_approval_timeout(mut self, timeout: Option<ApprovalTimeout>) -> Self {
+        self.approval_timeout = timeout;
+        self
+    }
+
     pub fn record_user_prompt_event(&self, prompt: &str) {
         self.send_event(AcpEvent::User(prompt.to_string()));
     }
@@ -59,6 +67,43 @@ impl AcpClient {
     }
 }
 
+impl AcpClient {
+    /// Apply the timeout's action to an unanswered request and record the decision
+    fn resolve_timed_out_approval(
+        &self,
+        args: &acp::RequestPermissionRequest,
+        tool_call_id: String,
+        timeout: ApprovalTimeout,
+    ) -> acp::RequestPermissionResponse {
+        let chosen = args.options.iter().find(|o| match timeout.action {
+            ApprovalTimeoutAction::Approve => {
+                matches!(o.kind, acp::PermissionOptionKind::AllowOnce)
+            }
+            ApprovalTimeoutAction::Deny => matches!(o.kind, acp::PermissionOptionKind::RejectOnce),
+        });
+        let outcome = match chosen {
+            Some(opt) => acp::RequestPermissionOutcome::Selected(
+                acp::SelectedPermissionOutcome::new(opt.option_id.clone()),
+            ),
+            None => {
+                warn!("No permission option for timed out approval, cancelling");
+                acp::RequestPermissionOutcome::Cancelled
+            }
+        };
+        warn!(
+            "Approval for {tool_call_id} timed out after {}s, applying {:?}",
+            timeout.after.as_secs(),
+            timeout.action
+        );
+        self.send_event(AcpEvent::ApprovalTimedOut {
+            tool_call_id,
+            action: timeout.action,
+            after_secs: timeout.after.as_secs(),
+        });
+        acp::RequestPermissionResponse::new(outcome)
+    }
+}
+
 #[async_trait(?Send)]
 impl acp::Client for AcpClient {
     async fn request_permission(
@@ -94,7 +139,7 @@ impl acp::Client for AcpClient {
         }
 
         let tool_call_id = args.tool_call.tool_call_id.0.to_string();
-        let status = match self
+        let request = self
             .approvals
             .as_ref()
             .ok_or(ExecutorApprovalError::ServiceUnavailable)
@@ -103,9 +148,15 @@ impl acp::Client for AcpClient {
                 args.tool_call.fields.title.as_deref().unwrap_or("tool"),
                 serde_json::json!({ "tool_call": args.tool_call }),
                 &tool_call_id,
-            )
-            .await
-        {
+            );
+        let result = match self.approval_timeout {
+            Some(timeout) => match tokio::time::timeout(timeout.after, request).await {
+                Ok(result) => result,
+                Err(_) => return Ok(self.resolve_timed_out_approval(&args, tool_call_id, timeout)),
+            },
+            None => request.await,
+        };
+        let status = match result {
             Ok(s) => s,
             Err(err) => {
                 warn!("Failed to request tool approval: {}", err);
@@ -170,6 +221,13 @@ impl acp::Client for AcpClient {
     }
 
     async fn session_notification(&self, args: acp::SessionNotification) -> Result<(), acp::Error> {
+        if let Some(usage) = serde_json::to_value(&args)
+            .ok()
+            .and_then(|value| TokenUsage::from_meta(&value))
+        {
+            self.send_event(AcpEvent::Usage(usage));
+        }
+
         // Convert to typed events
         let event = match args.update {
             acp::SessionUpdate::AgentMessageChunk(chunk) => Some(AcpEvent::Message(chunk.content)),
@@ -1,8 +1,11 @@
 use std::{
+    cell::Cell,
+    collections::VecDeque,
     path::{Path, PathBuf},
     process::Stdio,
     rc::Rc,
     sync::Arc,
+    time::Duration,
 };
 
 use agent_client_protocol as proto;
@@ -15,21 +18,110 @@ use tokio_util::{
     io::ReaderStream,
 };
 use tracing::error;
-use workspace_utils::{approvals::ApprovalStatus, stream_lines::LinesStreamExt};
+use workspace_utils::{
+    approvals::ApprovalStatus, shell::get_shell_command, stream_lines::LinesStreamExt,
+};
 
 use super::{AcpClient, SessionManager};
 use crate::{
-    approvals::ExecutorApprovalService,
+    approvals::{ApprovalTimeoutAction, ExecutorApprovalService},
     command::{CmdOverrides, CommandParts},
     env::ExecutionEnv,
-    executors::{ExecutorError, ExecutorExitResult, SpawnedChild, acp::AcpEvent},
+    executors::{
+        ExecutorError, ExecutorExitResult, SpawnedChild,
+        acp::{AcpEvent, TokenUsage, UsageAccumulator},
+    },
 };
 
+/// Running total of reported tokens, checked against a hard ceiling for the whole run
+#[derive(Debug, Clone)]
+pub struct TokenBudget {
+    budget: u32,
+    usage: UsageAccumulator,
+}
+
+impl TokenBudget {
+    pub fn new(budget: u32) -> Self {
+        Self {
+            budget,
+            usage: UsageAccumulator::default(),
+        }
+    }
+
+    pub fn used(&self) -> u64 {
+        self.usage.total().total()
+    }
+
+    /// Add `usage` to the running total, failing once it goes past the budget
+    pub fn record(&mut self, usage: &TokenUsage) -> Result<(), ExecutorError> {
+        self.usage.record(usage);
+        let used = self.used();
+        if used > u64::from(self.budget) {
+            return Err(ExecutorError::BudgetExceeded {
+                used,
+                budget: self.budget,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Resolve approval requests nobody answers within `after`. The wait restarts with every
+/// request rather than running against a deadline for the whole session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApprovalTimeout {
+    pub after: Duration,
+    pub action: ApprovalTimeoutAction,
+}
+
+/// Shell command run in the worktree after the agent's turn completes, e.g. a formatter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostTurnCommand {
+    pub command: String,
+    /// Fail the run on a non-zero exit instead of only reporting it
+    pub fail_on_error: bool,
+}
+
+impl PostTurnCommand {
+    /// Run the command with `cwd` as working directory, capturing stdout and stderr
+    pub async fn run(&self, cwd: &Path) -> AcpEvent {
+        let (shell_cmd, shell_arg) = get_shell_command();
+        let result = Command::new(shell_cmd)
+            .arg(shell_arg)
+            .arg(&self.command)
+            .current_dir(cwd)
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await;
+        let (exit_code, output) = match result {
+            Ok(out) => {
+                let mut output = String::from_utf8_lossy(&out.stdout).into_owned();
+                output.push_str(&String::from_utf8_lossy(&out.stderr));
+                (out.status.code(), output)
+            }
+            Err(e) => (None, format!("Failed to run post-turn command: {e}")),
+        };
+        AcpEvent::PostTurnCommand {
+            command: self.command.clone(),
+            exit_code,
+            output,
+        }
+    }
+}
+
 /// Reusable harness for ACP-based conns (Gemini, Qwen, etc.)
 pub struct AcpAgentHarness {
     session_namespace: String,
     model: Option<String>,
     mode: Option<String>,
+    seed: Option<u64>,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+    system_prompt: Option<String>,
+    token_budget: Option<u32>,
+    post_turn_command: Option<PostTurnCommand>,
+    approval_timeout: Option<ApprovalTimeout>,
 }
 
 impl Default for AcpAgentHarness {
@@ -46,15 +138,37 @@ impl AcpAgentHarness {
             session_namespace: "gemini_sessions".to_string(),
             model: None,
             mode: None,
+            seed: None,
+            temperature: None,
+            max_tokens: None,
+            system_prompt: None,
+            token_budget: None,
+            post_turn_command: None,
+            approval_timeout: None,
         }
     }
 
+    /// Whether `session_id` can be resumed, i.e. its log is still in this harness's
+    /// session namespace
+    pub fn session_exists(&self, session_id: &str) -> bool {
+        SessionManager::new(self.session_namespace.clone())
+            .map(|sessions| sessions.session_exists(session_id))
+            .unwrap_or(false)
+    }
+
     /// Create a harness with a custom session namespace (e.g. for Qwen)
     pub fn with_session_namespace(namespace: impl Into<String>) -> Self {
         Self {
             session_namespace: namespace.into(),
             model: None,
             mode: None,
+            seed: None,
+            temperature: None,
+            max_tokens: None,
+            system_prompt: None,
+            token_budget: None,
+            post_turn_command: None,
+            approval_timeout: None,
         }
     }
 
@@ -68,6 +182,73 @@ impl AcpAgentHarness {
         self
     }
 
+    /// Request a sampling seed for the session. Only honored by providers that support it.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Request a sampling temperature for the session
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Cap the number of tokens the model may generate per response
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// System-level instruction for the session, sent whenever a session is created, including
+    /// the fresh session a follow-up forks into
+    pub fn with_system_prompt(mut self, system_prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(system_prompt.into());
+        self
+    }
+
+    /// Cancel the session once reported token usage exceeds `budget` across the whole run
+    pub fn with_token_budget(mut self, budget: u32) -> Self {
+        self.token_budget = Some(budget);
+        self
+    }
+
+    /// Run `command` in the worktree once the agent's turn completes successfully
+    pub fn with_post_turn_command(mut self, command: PostTurnCommand) -> Self {
+        self.post_turn_command = Some(command);
+        self
+    }
+
+    /// Stop waiting on the approval service after `timeout.after` and apply `timeout.action`
+    pub fn with_approval_timeout(mut self, timeout: ApprovalTimeout) -> Self {
+        self.approval_timeout = Some(timeout);
+        self
+    }
+
+    /// Extra `_meta` fields sent with `session/new`, if any were configured
+    pub(crate) fn session_meta(&self) -> Option<serde_json::Map<String, serde_json::Value>> {
+        let mut meta = serde_json::Map::new();
+        if let Some(seed) = self.seed {
+            meta.insert("seed".to_string(), serde_json::Value::from(seed));
+        }
+        if let Some(temperature) = self.temperature {
+            meta.insert(
+                "temperature".to_string(),
+                serde_json::Value::from(f64::from(temperature)),
+            );
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            meta.insert("maxTokens".to_string(), serde_json::Value::from(max_tokens));
+        }
+        if let Some(system_prompt) = &self.system_prompt {
+            meta.insert(
+                "systemPrompt".to_string(),
+                serde_json::Value::String(system_prompt.clone()),
+            );
+        }
+        (!meta.is_empty()).then_some(meta)
+    }
+
     pub async fn spawn_with_command(
         &self,
         current_dir: &Path,
@@ -77,6 +258,34 @@ impl AcpAgentHarness {
         cmd_overrides: &CmdOverrides,
         approvals: Option<std::sync::Arc<dyn ExecutorApprovalService>>,
     ) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_sequence_with_command(
+            current_dir,
+            vec![prompt],
+            command_parts,
+            env,
+            cmd_overrides,
+            approvals,
+        )
+        .await
+    }
+
+    /// Run `prompts` one after another in a single new session. Each prompt after the first is
+    /// sent once the previous one completes, preceded by an `AcpEvent::PlanStep` marker; the
+    /// sequence stops at the first prompt that errors.
+    pub async fn spawn_sequence_with_command(
+        &self,
+        current_dir: &Path,
+        prompts: Vec<String>,
+        command_parts: CommandParts,
+        env: &ExecutionEnv,
+        cmd_overrides: &CmdOverrides,
+        approvals: Option<std::sync::Arc<dyn ExecutorApprovalService>>,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let mut prompts = VecDeque::from(prompts);
+        let prompt = prompts.pop_front().ok_or_else(|| {
+            ExecutorError::InvalidConfig("a prompt sequence needs at least one prompt".to_string())
+        })?;
+
         let (program_path, args) = command_parts.into_resolved().await?;
         let mut command = Command::new(program_path);
         command
@@ -85,25 +294,32 @@ impl AcpAgentHarness {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1");
+            .args(&args);
 
         env.clone()
             .with_profile(cmd_overrides)
             .apply_to_command(&mut command);
+        command.env("NODE_NO_WARNINGS", "1");
 
         let mut child = command.group_spawn()?;
 
         let (exit_tx, exit_rx) = tokio::sync::oneshot::channel::<ExecutorExitResult>();
+        let (interrupt_tx, interrupt_rx) = tokio::sync::oneshot::channel::<()>();
         Self::bootstrap_acp_connection(
             &mut child,
             current_dir.to_path_buf(),
             None,
             prompt,
+            prompts,
             Some(exit_tx),
             self.session_namespace.clone(),
             self.model.clone(),
             self.mode.clone(),
+            self.session_meta(),
+            self.token_budget,
+            self.post_turn_command.clone(),
+            self.approval_timeout,
+            interrupt_rx,
             approvals,
         )
         .await?;
@@ -111,7 +327,7 @@ impl AcpAgentHarness {
         Ok(SpawnedChild {
             child,
             exit_signal: Some(exit_rx),
-            interrupt_sender: None,
+            interrupt_sender: Some(interrupt_tx),
         })
     }
 
@@ -134,25 +350,32 @@ impl AcpAgentHarness {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
-            .args(&args)
-            .env("NODE_NO_WARNINGS", "1");
+            .args(&args);
 
         env.clone()
             .with_profile(cmd_overrides)
             .apply_to_command(&mut command);
+        command.env("NODE_NO_WARNINGS", "1");
 
         let mut child = command.group_spawn()?;
 
         let (exit_tx, exit_rx) = tokio::sync::oneshot::channel::<ExecutorExitResult>();
+        let (interrupt_tx, interrupt_rx) = tokio::sync::oneshot::channel::<()>();
         Self::bootstrap_acp_connection(
             &mut child,
             current_dir.to_path_buf(),
             Some(session_id.to_string()),
             prompt,
+            VecDeque::new(),
             Some(exit_tx),
             self.session_namespace.clone(),
             self.model.clone(),
             self.mode.clone(),
+            self.session_meta(),
+            self.token_budget,
+            self.post_turn_command.clone(),
+            self.approval_timeout,
+            interrupt_rx,
             approvals,
         )
         .await?;
@@ -160,7 +383,7 @@ impl AcpAgentHarness {
         Ok(SpawnedChild {
             child,
             exit_signal: Some(exit_rx),
-            interrupt_sender: None,
+            interrupt_sender: Some(interrupt_tx),
         })
     }
 
@@ -170,10 +393,16 @@ impl AcpAgentHarness {
         cwd: PathBuf,
         existing_session: Option<String>,
         prompt: String,
+        mut queued_prompts: VecDeque<String>,
         exit_signal: Option<tokio::sync::oneshot::Sender<ExecutorExitResult>>,
         session_namespace: String,
         model: Option<String>,
         mode: Option<String>,
+        session_meta: Option<serde_json::Map<String, serde_json::Value>>,
+        token_budget: Option<u32>,
+        post_turn_command: Option<PostTurnCommand>,
+        approval_timeout: Option<ApprovalTimeout>,
+        interrupt_rx: tokio::sync::oneshot::Receiver<()>,
         approvals: Option<std::sync::Arc<dyn ExecutorApprovalService>>,
     ) -> Result<(), ExecutorError> {
         // Take child's stdio for ACP wiring
@@ -231,17 +460,22 @@ impl AcpAgentHarness {
         let outgoing = acp_out_writer.compat_write();
         let incoming = acp_incoming_reader.compat();
 
-        // Process ACP -> stdin
-        let stdin_shutdown_rx = shutdown_rx.clone();
+        // Process ACP -> stdin. Dropping stdin on shutdown is what tells the agent to exit, so
+        // don't wait for another outgoing line before noticing it
+        let mut stdin_shutdown_rx = shutdown_rx.clone();
         tokio::spawn(async move {
             let mut child_stdin = orig_stdin;
             let mut lines = ReaderStream::new(acp_out_reader)
                 .map(|res| res.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
                 .lines();
-            while let Some(result) = lines.next().await {
-                if *stdin_shutdown_rx.borrow() {
-                    break;
-                }
+            loop {
+                let result = tokio::select! {
+                    next = lines.next() => match next {
+                        Some(result) => result,
+                        None => break,
+                    },
+                    _ = stdin_shutdown_rx.changed() => break,
+                };
                 match result {
                     Ok(line) => {
                         // Use \r\n on Windows for compatibility with buggy ACP implementations
@@ -290,7 +524,8 @@ impl AcpAgentHarness {
                         let session_manager = std::sync::Arc::new(session_manager);
 
                         // Create ACP client with approvals support
-                        let client = AcpClient::new(event_tx.clone(), approvals.clone());
+                        let client = AcpClient::new(event_tx.clone(), approvals.clone())
+                            .with_approval_timeout(approval_timeout);
                         let client_feedback_handle = client.clone();
 
                         client.record_user_prompt_event(&prompt);
@@ -320,14 +555,17 @@ impl AcpAgentHarness {
                                 let _ = session_manager.fork_session(&existing, &new_ui_id);
 
                                 let history = session_manager.read_session_raw(&new_ui_id).ok();
-                                let meta =
-                                    history.map(|h| serde_json::json!({ "history_jsonl": h }));
+                                let mut meta = session_meta.clone().unwrap_or_default();
+                                if let Some(h) = history {
+                                    meta.insert(
+                                        "history_jsonl".to_string(),
+                                        serde_json::Value::String(h),
+                                    );
+                                }
 
                                 let mut req = proto::NewSessionRequest::new(cwd.clone());
-                                if let Some(m) = meta
-                                    && let Some(obj) = m.as_object()
-                                {
-                                    req = req.meta(obj.clone());
+                                if !meta.is_empty() {
+                                    req = req.meta(meta);
                                 }
                                 match conn.new_session(req).await {
                                     Ok(resp) => {
@@ -343,10 +581,11 @@ impl AcpAgentHarness {
                                 }
                             } else {
                                 // New session
-                                match conn
-                                    .new_session(proto::NewSessionRequest::new(cwd.clone()))
-                                    .await
-                                {
+                                let mut req = proto::NewSessionRequest::new(cwd.clone());
+                                if let Some(meta) = session_meta.clone() {
+                                    req = req.meta(meta);
+                                }
+                                match conn.new_session(req).await {
                                     Ok(resp) => {
                                         let sid = resp.session_id.0.to_string();
                                         (sid.clone(), sid, prompt)
@@ -388,14 +627,55 @@ impl AcpAgentHarness {
                             }
                         }
 
+                        // Let the agent wind the turn down itself when interrupted; an ACP
+                        // cancel only ends the turn, so the prompt loop then stops and the
+                        // cleanup below closes the agent's stdin to make it exit
+                        let interrupted = Rc::new(Cell::new(false));
+                        let interrupted_for_task = interrupted.clone();
+                        let conn_for_interrupt = conn.clone();
+                        let acp_session_id_for_interrupt = acp_session_id.clone();
+                        tokio::task::spawn_local(async move {
+                            if interrupt_rx.await.is_ok() {
+                                interrupted_for_task.set(true);
+                                let _ = conn_for_interrupt
+                                    .cancel(proto::CancelNotification::new(proto::SessionId::new(
+                                        acp_session_id_for_interrupt,
+                                    )))
+                                    .await;
+                            }
+                        });
+
                         // Start raw event forwarder and persistence
                         let app_tx_clone = log_tx.clone();
                         let sess_id_for_writer = display_session_id.clone();
                         let sm_for_writer = session_manager.clone();
                         let conn_for_cancel = conn.clone();
                         let acp_session_id_for_cancel = acp_session_id.clone();
+                        let budget_exceeded = Rc::new(Cell::new(false));
+                        let budget_exceeded_for_writer = budget_exceeded.clone();
+                        let mut budget_tracker = token_budget.map(TokenBudget::new);
                         tokio::task::spawn_local(async move {
                             while let Some(event) = event_rx.recv().await {
+                                if let AcpEvent::Usage(usage) = &event
+                                    && !budget_exceeded_for_writer.get()
+                                    && let Some(tracker) = budget_tracker.as_mut()
+                                    && let Err(ExecutorError::BudgetExceeded { used, budget }) =
+                                        tracker.record(usage)
+                                {
+                                    // Surface the warning before the cancel lands
+                                    budget_exceeded_for_writer.set(true);
+                                    let _ = app_tx_clone.send(
+                                        AcpEvent::BudgetExceeded { used, budget }.to_string(),
+                                    );
+                                    let _ = conn_for_cancel
+                                        .cancel(proto::CancelNotification::new(
+                                            proto::SessionId::new(
+                                                acp_session_id_for_cancel.clone(),
+                                            ),
+                                        ))
+                                        .await;
+                                }
+
                                 if let AcpEvent::ApprovalResponse(resp) = &event
                                     && let ApprovalStatus::Denied {
                                         reason: Some(reason),
@@ -435,8 +715,13 @@ impl AcpAgentHarness {
                         );
 
                         let mut current_req = Some(initial_req);
+                        let total_steps = queued_prompts.len() + 1;
+                        let mut step_failed = false;
 
                         while let Some(req) = current_req.take() {
+                            if budget_exceeded.get() || interrupted.get() {
+                                break;
+                            }
                             tracing::trace!(?req, "sending ACP prompt request");
                             // Send the prompt and await completion to obtain stop_reason
                             match conn.prompt(req).await {
@@ -459,6 +744,7 @@ impl AcpAgentHarness {
                                         let _ = log_tx
                                             .send(AcpEvent::Error(format!("{e}")).to_string());
                                     }
+                                    step_failed = true;
                                 }
                             }
 
@@ -469,7 +755,9 @@ impl AcpAgentHarness {
                                 .join("\n")
                                 .trim()
                                 .to_string();
-                            if !feedback.is_empty() {
+                            if budget_exceeded.get() || interrupted.get() {
+                                break;
+                            } else if !feedback.is_empty() {
                                 tracing::trace!(?feedback, "sending ACP follow-up feedback");
                                 let session_id = proto::SessionId::new(acp_session_id.clone());
                                 let feedback_req = proto::PromptRequest::new(
@@ -479,12 +767,61 @@ impl AcpAgentHarness {
                                     ))],
                                 );
                                 current_req = Some(feedback_req);
+                            } else if !step_failed
+                                && let Some(next_prompt) = queued_prompts.pop_front()
+                            {
+                                let index = total_steps - queued_prompts.len() - 1;
+                                let _ = log_tx.send(
+                                    AcpEvent::PlanStep {
+                                        index,
+                                        total: total_steps,
+                                    }
+                                    .to_string(),
+                                );
+                                client_feedback_handle.record_user_prompt_event(&next_prompt);
+                                let _ = session_manager.append_raw_line(
+                                    &display_session_id,
+                                    &serde_json::to_string(
+                                        &serde_json::json!({ "user": next_prompt }),
+                                    )
+                                    .unwrap_or_default(),
+                                );
+                                current_req = Some(proto::PromptRequest::new(
+                                    proto::SessionId::new(acp_session_id.clone()),
+                                    vec![proto::ContentBlock::Text(proto::TextContent::new(
+                                        next_prompt,
+                                    ))],
+                                ));
+                            }
+                        }
+
+                        let mut post_turn_failed = false;
+                        if let Some(post_turn) = &post_turn_command
+                            && !step_failed
+                            && !budget_exceeded.get()
+                            && !interrupted.get()
+                        {
+                            let event = post_turn.run(&cwd).await;
+                            if let AcpEvent::PostTurnCommand { exit_code, .. } = &event
+                                && *exit_code != Some(0)
+                            {
+                                tracing::warn!(
+                                    "Post-turn command `{}` exited with {:?}",
+                                    post_turn.command,
+                                    exit_code
+                                );
+                                post_turn_failed = post_turn.fail_on_error;
                             }
+                            let _ = log_tx.send(event.to_string());
                         }
 
                         // Notify container of completion
                         if let Some(tx) = exit_signal_tx.take() {
-                            let _ = tx.send(ExecutorExitResult::Success);
+                            let _ = tx.send(if budget_exceeded.get() || post_turn_failed {
+                                ExecutorExitResult::Failure
+                            } else {
+                                ExecutorExitResult::Success
+                            });
                         }
 
                         // Cancel session work
@@ -507,3 +844,84 @@ impl AcpAgentHarness {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_trips_once_usage_crosses_ceiling() {
+        let mut budget = TokenBudget::new(1_000);
+        let usage = TokenUsage {
+            input_tokens: 300,
+            output_tokens: 100,
+            ..Default::default()
+        };
+
+        assert!(budget.record(&usage).is_ok());
+        assert!(budget.record(&usage).is_ok());
+        assert!(matches!(
+            budget.record(&usage),
+            Err(ExecutorError::BudgetExceeded {
+                used: 1_200,
+                budget: 1_000
+            })
+        ));
+        assert_eq!(budget.used(), 1_200);
+    }
+
+    #[test]
+    fn usage_is_read_from_notification_meta() {
+        let notification = serde_json::json!({
+            "sessionId": "s1",
+            "update": { "sessionUpdate": "agent_message_chunk" },
+            "_meta": { "usage": { "inputTokens": 12, "outputTokens": 30 } }
+        });
+        assert_eq!(
+            TokenUsage::from_meta(&notification),
+            Some(TokenUsage {
+                input_tokens: 12,
+                output_tokens: 30,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            TokenUsage::from_meta(&serde_json::json!({ "sessionId": "s1" })),
+            None
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn post_turn_command_runs_in_worktree_and_captures_output() {
+        let worktree = tempfile::tempdir().unwrap();
+        let command = PostTurnCommand {
+            command: "pwd; echo formatted; echo warning >&2".to_string(),
+            fail_on_error: false,
+        };
+
+        let AcpEvent::PostTurnCommand {
+            exit_code, output, ..
+        } = command.run(worktree.path()).await
+        else {
+            panic!("expected a post-turn command event");
+        };
+        assert_eq!(exit_code, Some(0));
+        let cwd = std::fs::canonicalize(worktree.path()).unwrap();
+        assert!(output.contains(cwd.file_name().unwrap().to_str().unwrap()));
+        assert!(output.contains("formatted"));
+        assert!(output.contains("warning"));
+
+        let failing = PostTurnCommand {
+            command: "exit 3".to_string(),
+            fail_on_error: true,
+        };
+        assert!(matches!(
+            failing.run(worktree.path()).await,
+            AcpEvent::PostTurnCommand {
+                exit_code: Some(3),
+                ..
+            }
+        ));
+    }
+}
@@ -2,15 +2,26 @@ pub mod client;
 pub mod harness;
 pub mod normalize_logs;
 pub mod session;
+pub mod side_by_side;
 
-use std::{fmt::Display, str::FromStr};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
 
 pub use client::AcpClient;
-pub use harness::AcpAgentHarness;
+pub use harness::{AcpAgentHarness, ApprovalTimeout, PostTurnCommand};
 pub use normalize_logs::*;
 use serde::{Deserialize, Serialize};
 pub use session::SessionManager;
-use workspace_utils::approvals::ApprovalStatus;
+use workspace_utils::{approvals::ApprovalStatus, log_msg::LogMsg, msg_store::MsgStore};
+
+use crate::{
+    approvals::ApprovalTimeoutAction,
+    logs::turn_result::{TurnUsage, UsageStats},
+};
 
 /// Parsed event types for internal processing
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,8 +37,34 @@ pub enum AcpEvent {
     CurrentMode(agent_client_protocol::SessionModeId),
     RequestPermission(agent_client_protocol::RequestPermissionRequest),
     ApprovalResponse(ApprovalResponse),
+    /// Nobody answered an approval request within the configured timeout, so `action` was
+    /// applied instead
+    ApprovalTimedOut {
+        tool_call_id: String,
+        action: ApprovalTimeoutAction,
+        after_secs: u64,
+    },
     Error(String),
     Done(String),
+    /// A queued prompt is about to run; `index` is zero-based
+    PlanStep {
+        index: usize,
+        total: usize,
+    },
+    /// Tokens consumed by one model response, taken from a notification's `_meta.usage`
+    Usage(TokenUsage),
+    /// The run's token budget was exceeded and the session is being cancelled
+    BudgetExceeded {
+        used: u64,
+        budget: u32,
+    },
+    /// The configured post-turn command ran in the worktree; `exit_code` is `None` if it
+    /// could not be started or was killed by a signal
+    PostTurnCommand {
+        command: String,
+        exit_code: Option<i32>,
+        output: String,
+    },
     Other(agent_client_protocol::SessionNotification),
 }
 
@@ -50,3 +87,117 @@ pub struct ApprovalResponse {
     pub tool_call_id: String,
     pub status: ApprovalStatus,
 }
+
+/// Token counts reported by the agent for a single model response
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default, alias = "inputTokens")]
+    pub input_tokens: u64,
+    #[serde(default, alias = "outputTokens")]
+    pub output_tokens: u64,
+    /// Response the counts belong to; a later report for the same message supersedes this one
+    #[serde(default, alias = "messageId", skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    #[serde(default, alias = "modelId", skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl TokenUsage {
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// Usage attached to a serialized ACP message under `_meta.usage`, if any
+    pub fn from_meta(message: &serde_json::Value) -> Option<Self> {
+        let usage = message.pointer("/_meta/usage")?;
+        serde_json::from_value(usage.clone()).ok()
+    }
+
+    /// Total of the `Usage` events in the raw ACP event log of `store`; `None` if the agent
+    /// reported none
+    pub fn sum_from_store(store: &MsgStore) -> Option<Self> {
+        let stdout: String = store
+            .get_history()
+            .into_iter()
+            .filter_map(|msg| match msg {
+                LogMsg::Stdout(chunk) => Some(chunk),
+                _ => None,
+            })
+            .collect();
+        let mut accumulator = UsageAccumulator::default();
+        for line in stdout.lines() {
+            if let Ok(AcpEvent::Usage(usage)) = AcpEvent::from_str(line.trim()) {
+                accumulator.record(&usage);
+            }
+        }
+        accumulator.has_reports().then(|| accumulator.total())
+    }
+}
+
+/// Running usage total for a run. Reports carrying a `message_id` replace earlier partial
+/// reports for that message, so streamed updates are not counted twice.
+#[derive(Debug, Clone, Default)]
+pub struct UsageAccumulator {
+    unkeyed: TokenUsage,
+    by_message: HashMap<String, TokenUsage>,
+    model: Option<String>,
+    reports: usize,
+}
+
+impl UsageAccumulator {
+    pub fn record(&mut self, usage: &TokenUsage) {
+        match &usage.message_id {
+            Some(id) => {
+                self.by_message.insert(id.clone(), usage.clone());
+            }
+            None => {
+                self.unkeyed.input_tokens += usage.input_tokens;
+                self.unkeyed.output_tokens += usage.output_tokens;
+            }
+        }
+        if let Some(model) = &usage.model {
+            self.model = Some(model.clone());
+        }
+        self.reports += 1;
+    }
+
+    pub fn has_reports(&self) -> bool {
+        self.reports > 0
+    }
+
+    pub fn total(&self) -> TokenUsage {
+        self.by_message.values().fold(
+            TokenUsage {
+                input_tokens: self.unkeyed.input_tokens,
+                output_tokens: self.unkeyed.output_tokens,
+                ..Default::default()
+            },
+            |total, usage| TokenUsage {
+                input_tokens: total.input_tokens + usage.input_tokens,
+                output_tokens: total.output_tokens + usage.output_tokens,
+                ..total
+            },
+        )
+    }
+
+    pub fn stats(&self) -> UsageStats {
+        let total = self.total();
+        UsageStats {
+            prompt_tokens: total.input_tokens,
+            completion_tokens: total.output_tokens,
+            model: self.model.clone(),
+        }
+    }
+}
+
+/// Latest usage totals of a run, shared with the executor that normalizes its logs
+pub type UsageSlot = Arc<Mutex<Option<UsageStats>>>;
+
+impl From<TokenUsage> for TurnUsage {
+    fn from(usage: TokenUsage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+        }
+    }
+}
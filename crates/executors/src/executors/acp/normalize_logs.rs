@@ -11,18 +11,30 @@ use serde::Deserialize;
 use workspace_utils::{approvals::ApprovalStatus, msg_store::MsgStore};
 
 pub use super::AcpAgentHarness;
-use super::AcpEvent;
+use super::{AcpEvent, UsageAccumulator, UsageSlot, side_by_side::side_by_side_for_changes};
 use crate::{
-    approvals::ToolCallMetadata,
+    approvals::{ApprovalTimeoutAction, ToolCallMetadata},
+    executors::ExecutorError,
     logs::{
-        ActionType, FileChange, NormalizedEntry, NormalizedEntryError, NormalizedEntryType,
-        TodoItem, ToolResult, ToolResultValueType, ToolStatus as LogToolStatus,
+        ActionType, FileChange, LineRange, NormalizedEntry, NormalizedEntryError,
+        NormalizedEntryType, TodoItem, ToolResult, ToolResultValueType,
+        ToolStatus as LogToolStatus,
         stderr_processor::normalize_stderr_logs,
+        turn_result::UsageStats,
         utils::{ConversationPatch, EntryIndexProvider},
     },
 };
 
 pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
+    normalize_logs_with_usage(msg_store, worktree_path, None);
+}
+
+/// Like [`normalize_logs`], also keeping `usage_slot` updated with the run's usage totals
+pub fn normalize_logs_with_usage(
+    msg_store: Arc<MsgStore>,
+    worktree_path: &Path,
+    usage_slot: Option<UsageSlot>,
+) {
     // stderr normalization
     let entry_index = EntryIndexProvider::start_from(&msg_store);
     normalize_stderr_logs(msg_store.clone(), entry_index.clone());
@@ -36,6 +48,9 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
         let mut stored_session_id = false;
         let mut streaming: StreamingState = StreamingState::default();
         let mut tool_states: ToolStates = HashMap::new();
+        let mut usage = UsageAccumulator::default();
+        let mut usage_entry_index: Option<usize> = None;
+        let mut file_edits = TurnFileEdits::default();
 
         let mut stdout_lines = msg_store.stdout_lines_stream();
         while let Some(Ok(line)) = stdout_lines.next().await {
@@ -63,6 +78,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                     AcpEvent::Done(_) => {
                         streaming.assistant_text = None;
                         streaming.thinking_text = None;
+                        file_edits = TurnFileEdits::default();
                     }
                     AcpEvent::Message(content) => {
                         streaming.thinking_text = None;
@@ -171,6 +187,18 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                         };
                         msg_store.push_patch(ConversationPatch::add_normalized_entry(idx, entry));
                     }
+                    AcpEvent::PlanStep { index, total } => {
+                        streaming.assistant_text = None;
+                        streaming.thinking_text = None;
+                        let idx = entry_index.next();
+                        let entry = NormalizedEntry {
+                            timestamp: None,
+                            entry_type: NormalizedEntryType::SystemMessage,
+                            content: format!("Plan step {} of {total}", index + 1),
+                            metadata: None,
+                        };
+                        msg_store.push_patch(ConversationPatch::add_normalized_entry(idx, entry));
+                    }
                     AcpEvent::CurrentMode(mode_id) => {
                         let idx = entry_index.next();
                         let entry = NormalizedEntry {
@@ -188,6 +216,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                                 &worktree_path,
                                 &mut streaming,
                                 &mut tool_states,
+                                &mut file_edits,
                                 &entry_index,
                                 &msg_store,
                             );
@@ -198,6 +227,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                         &worktree_path,
                         &mut streaming,
                         &mut tool_states,
+                        &mut file_edits,
                         &entry_index,
                         &msg_store,
                     ),
@@ -216,6 +246,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                                 &worktree_path,
                                 &mut streaming,
                                 &mut tool_states,
+                                &mut file_edits,
                                 &entry_index,
                                 &msg_store,
                             );
@@ -252,6 +283,96 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                                 .push_patch(ConversationPatch::add_normalized_entry(idx, entry));
                         }
                     }
+                    AcpEvent::ApprovalTimedOut {
+                        tool_call_id,
+                        action,
+                        after_secs,
+                    } => {
+                        let tool_name = tool_states
+                            .get(&tool_call_id)
+                            .map(|t| t.title.clone())
+                            .unwrap_or_else(|| "tool".to_string());
+                        let decision = match action {
+                            ApprovalTimeoutAction::Approve => "approved",
+                            ApprovalTimeoutAction::Deny => "denied",
+                        };
+                        let idx = entry_index.next();
+                        let entry = NormalizedEntry {
+                            timestamp: None,
+                            entry_type: NormalizedEntryType::SystemMessage,
+                            content: format!(
+                                "No approval decision for {tool_name} within {after_secs}s; {decision} automatically"
+                            ),
+                            metadata: None,
+                        };
+                        msg_store.push_patch(ConversationPatch::add_normalized_entry(idx, entry));
+                    }
+                    AcpEvent::BudgetExceeded { used, budget } => {
+                        let idx = entry_index.next();
+                        let entry = NormalizedEntry {
+                            timestamp: None,
+                            entry_type: NormalizedEntryType::ErrorMessage {
+                                error_type: NormalizedEntryError::Other,
+                            },
+                            content: ExecutorError::BudgetExceeded { used, budget }.to_string(),
+                            metadata: None,
+                        };
+                        msg_store.push_patch(ConversationPatch::add_normalized_entry(idx, entry));
+                    }
+                    AcpEvent::PostTurnCommand {
+                        command,
+                        exit_code,
+                        output,
+                    } => {
+                        let idx = entry_index.next();
+                        let status = if exit_code == Some(0) {
+                            LogToolStatus::Success
+                        } else {
+                            LogToolStatus::Failed
+                        };
+                        let entry = NormalizedEntry {
+                            timestamp: None,
+                            entry_type: NormalizedEntryType::ToolUse {
+                                tool_name: "post_turn_command".to_string(),
+                                action_type: ActionType::CommandRun {
+                                    command: command.clone(),
+                                    result: Some(crate::logs::CommandRunResult {
+                                        exit_status: exit_code.map(|code| {
+                                            crate::logs::CommandExitStatus::ExitCode { code }
+                                        }),
+                                        output: Some(output),
+                                    }),
+                                },
+                                status,
+                            },
+                            content: command,
+                            metadata: None,
+                        };
+                        msg_store.push_patch(ConversationPatch::add_normalized_entry(idx, entry));
+                    }
+                    AcpEvent::Usage(report) => {
+                        usage.record(&report);
+                        let stats = usage.stats();
+                        if let Some(slot) = &usage_slot
+                            && let Ok(mut slot) = slot.lock()
+                        {
+                            *slot = Some(stats.clone());
+                        }
+                        // One entry per run, replaced as totals grow
+                        let entry = usage_entry(&stats);
+                        match usage_entry_index {
+                            Some(idx) => {
+                                msg_store.push_patch(ConversationPatch::replace(idx, entry))
+                            }
+                            None => {
+                                let idx = entry_index.next();
+                                usage_entry_index = Some(idx);
+                                msg_store.push_patch(ConversationPatch::add_normalized_entry(
+                                    idx, entry,
+                                ));
+                            }
+                        }
+                    }
                     AcpEvent::User(_) | AcpEvent::Other(_) => (),
                 }
             }
@@ -262,6 +383,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
             worktree_path: &Path,
             streaming: &mut StreamingState,
             tool_states: &mut ToolStates,
+            file_edits: &mut TurnFileEdits,
             entry_index: &EntryIndexProvider,
             msg_store: &Arc<MsgStore>,
         ) {
@@ -275,6 +397,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                 tool_data.index = entry_index.next();
             }
             let action = map_to_action_type(tool_data);
+            let metadata = tool_call_metadata(tool_data, &action);
             let entry = NormalizedEntry {
                 timestamp: None,
                 entry_type: NormalizedEntryType::ToolUse {
@@ -283,10 +406,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                     status: convert_tool_status(&tool_data.status),
                 },
                 content: get_tool_content(tool_data),
-                metadata: serde_json::to_value(ToolCallMetadata {
-                    tool_call_id: tool_data.id.0.to_string(),
-                })
-                .ok(),
+                metadata,
             };
             let patch = if is_new {
                 ConversationPatch::add_normalized_entry(tool_data.index, entry)
@@ -294,6 +414,52 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                 ConversationPatch::replace(tool_data.index, entry)
             };
             msg_store.push_patch(patch);
+
+            if tool_data.kind == agent_client_protocol::ToolKind::Edit {
+                for content in &tool_data.content {
+                    let agent_client_protocol::ToolCallContent::Diff(diff) = content else {
+                        continue;
+                    };
+                    let path = if diff.path.as_os_str().is_empty() {
+                        tool_data.path.clone().unwrap_or_default()
+                    } else {
+                        PathBuf::from(workspace_utils::path::make_path_relative(
+                            &diff.path.to_string_lossy(),
+                            &worktree_path.to_string_lossy(),
+                        ))
+                    };
+                    if let Some(patch) = file_edits.record(
+                        path.to_string_lossy().to_string(),
+                        tool_data.id.0.as_ref(),
+                        diff.old_text.as_deref().unwrap_or(""),
+                        &diff.new_text,
+                        entry_index,
+                    ) {
+                        msg_store.push_patch(patch);
+                    }
+                }
+            }
+        }
+
+        /// Tool call id, plus a `side_by_side` view of any unified-diff edits
+        fn tool_call_metadata(
+            tc: &PartialToolCallData,
+            action: &ActionType,
+        ) -> Option<serde_json::Value> {
+            let mut metadata = serde_json::to_value(ToolCallMetadata {
+                tool_call_id: tc.id.0.to_string(),
+            })
+            .ok()?;
+            if let ActionType::FileEdit { changes, .. } = action {
+                let side_by_side = side_by_side_for_changes(changes);
+                if !side_by_side.is_empty()
+                    && let Some(fields) = metadata.as_object_mut()
+                    && let Ok(value) = serde_json::to_value(side_by_side)
+                {
+                    fields.insert("side_by_side".to_string(), value);
+                }
+            }
+            Some(metadata)
         }
 
         fn map_to_action_type(tc: &PartialToolCallData) -> ActionType {
@@ -606,6 +772,26 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
     });
 }
 
+/// System entry summarizing `stats`, with the structured numbers under `metadata.usage`
+fn usage_entry(stats: &UsageStats) -> NormalizedEntry {
+    let model = stats
+        .model
+        .as_deref()
+        .map(|model| format!(" ({model})"))
+        .unwrap_or_default();
+    NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::SystemMessage,
+        content: format!(
+            "Tokens used: {} prompt, {} completion{model}",
+            stats.prompt_tokens, stats.completion_tokens
+        ),
+        metadata: serde_json::to_value(stats)
+            .ok()
+            .map(|usage| serde_json::json!({ "usage": usage })),
+    }
+}
+
 struct PartialToolCallData {
     index: usize,
     id: agent_client_protocol::ToolCallId,
@@ -756,6 +942,141 @@ struct StreamingText {
     content: String,
 }
 
+/// File edits reported during the current turn, coalesced into one `FileEdit` entry per path
+#[derive(Debug, Default)]
+struct TurnFileEdits {
+    files: HashMap<String, FileEditState>,
+}
+
+#[derive(Debug)]
+struct FileEditState {
+    index: Option<usize>,
+    edits: Vec<ReportedEdit>,
+}
+
+/// Before and after text of one tool call's edit. Tool calls repeat their diff on every
+/// update, so there is one of these per tool call rather than per report.
+#[derive(Debug)]
+struct ReportedEdit {
+    tool_call_id: String,
+    old_text: String,
+    new_text: String,
+}
+
+impl TurnFileEdits {
+    /// Record an edit to `path` and return the patch adding or updating its `FileEdit` entry,
+    /// or `None` when nothing changed
+    fn record(
+        &mut self,
+        path: String,
+        tool_call_id: &str,
+        old_text: &str,
+        new_text: &str,
+        entry_index: &EntryIndexProvider,
+    ) -> Option<json_patch::Patch> {
+        let state = self
+            .files
+            .entry(path.clone())
+            .or_insert_with(|| FileEditState {
+                index: None,
+                edits: Vec::new(),
+            });
+        match state
+            .edits
+            .iter_mut()
+            .find(|e| e.tool_call_id == tool_call_id)
+        {
+            Some(edit) if edit.old_text == old_text && edit.new_text == new_text => return None,
+            Some(edit) => {
+                edit.old_text = old_text.to_string();
+                edit.new_text = new_text.to_string();
+            }
+            None => state.edits.push(ReportedEdit {
+                tool_call_id: tool_call_id.to_string(),
+                old_text: old_text.to_string(),
+                new_text: new_text.to_string(),
+            }),
+        }
+
+        let entry = file_edit_entry(path, &state.hunks_for(&path))?;
+        Some(match state.index {
+            Some(index) => ConversationPatch::replace(index, entry),
+            None => {
+                let index = entry_index.next();
+                state.index = Some(index);
+                ConversationPatch::add_normalized_entry(index, entry)
+            }
+        })
+    }
+}
+
+impl FileEditState {
+    /// Hunks of the file's net change. An edit that starts from the text the previous one
+    /// left behind is folded into it, so repeated edits of one region show up once.
+    fn hunks_for(&self, path: &str) -> Vec<String> {
+        let mut runs: Vec<(&str, &str)> = Vec::new();
+        for edit in &self.edits {
+            match runs.last_mut() {
+                Some(run) if run.1 == edit.old_text => run.1 = edit.new_text.as_str(),
+                _ => runs.push((edit.old_text.as_str(), edit.new_text.as_str())),
+            }
+        }
+        runs.into_iter()
+            .filter(|(old, new)| old != new)
+            .flat_map(|(old, new)| {
+                workspace_utils::diff::extract_unified_diff_hunks(
+                    &workspace_utils::diff::create_unified_diff(path, old, new),
+                )
+            })
+            .collect()
+    }
+}
+
+fn file_edit_entry(path: String, hunks: &[String]) -> Option<NormalizedEntry> {
+    static HUNK_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@").expect("valid regex")
+    });
+
+    if hunks.is_empty() {
+        return None;
+    }
+    let mut old_span: Option<(usize, usize)> = None;
+    let mut new_span: Option<(usize, usize)> = None;
+    let widen = |span: &mut Option<(usize, usize)>, start: usize, count: usize| {
+        let (lo, hi) = span.get_or_insert((start, start + count));
+        *lo = (*lo).min(start);
+        *hi = (*hi).max(start + count);
+    };
+    for caps in hunks.iter().filter_map(|h| HUNK_HEADER.captures(h)) {
+        let num = |i: usize, default: usize| {
+            caps.get(i)
+                .and_then(|m| m.as_str().parse().ok())
+                .unwrap_or(default)
+        };
+        widen(&mut old_span, num(1, 0), num(2, 1));
+        widen(&mut new_span, num(3, 0), num(4, 1));
+    }
+    let range = |span: Option<(usize, usize)>| {
+        span.map(|(lo, hi)| LineRange {
+            start: lo,
+            count: hi - lo,
+        })
+        .unwrap_or_default()
+    };
+
+    Some(NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::FileEdit {
+            path: path.clone(),
+            old_range: range(old_span),
+            new_range: range(new_span),
+            hunk: hunks.concat(),
+        },
+        content: path,
+        metadata: None,
+    })
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct EditInput {
@@ -767,3 +1088,74 @@ struct EditInput {
     #[serde(default)]
     new_string: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::utils::patch::extract_normalized_entry_from_patch;
+
+    fn file_edit(patch: &json_patch::Patch) -> (String, LineRange, LineRange, String) {
+        let (_, entry) = extract_normalized_entry_from_patch(patch).unwrap();
+        match entry.entry_type {
+            NormalizedEntryType::FileEdit {
+                path,
+                old_range,
+                new_range,
+                hunk,
+            } => (path, old_range, new_range, hunk),
+            other => panic!("expected a file edit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn edits_to_one_file_coalesce_within_a_turn() {
+        let entry_index = EntryIndexProvider::test_new();
+        let mut edits = TurnFileEdits::default();
+        let original = "a\nb\nc\n";
+
+        let first = edits
+            .record(
+                "src/lib.rs".into(),
+                "call-1",
+                original,
+                "a\nB\nc\n",
+                &entry_index,
+            )
+            .unwrap();
+        let (path, old_range, new_range, hunk) = file_edit(&first);
+        assert_eq!(path, "src/lib.rs");
+        assert_eq!(old_range, LineRange { start: 1, count: 3 });
+        assert_eq!(new_range, LineRange { start: 1, count: 3 });
+        assert!(hunk.contains("-b\n+B\n"));
+
+        // The same report again changes nothing
+        assert!(
+            edits
+                .record(
+                    "src/lib.rs".into(),
+                    "call-1",
+                    original,
+                    "a\nB\nc\n",
+                    &entry_index
+                )
+                .is_none()
+        );
+
+        let second = edits
+            .record(
+                "src/lib.rs".into(),
+                "call-2",
+                "a\nB\nc\n",
+                "a\nB\nC\n",
+                &entry_index,
+            )
+            .unwrap();
+        let (_, _, _, hunk) = file_edit(&second);
+        assert!(hunk.contains("-b\n-c\n+B\n+C\n"));
+
+        // Both edits share the entry added first
+        let ops = serde_json::to_value(&second).unwrap();
+        assert_eq!(ops[0]["op"], "replace");
+        assert_eq!(entry_index.current(), 1);
+    }
+}
@@ -9,7 +9,13 @@ use serde::{Deserialize, Serialize};
 
 use crate::executors::acp::AcpEvent;
 
-/// Manages session persistence and state for ACP interactions
+/// Manages session persistence and state for ACP interactions.
+///
+/// Each namespace (e.g. `opencode_sessions`) is a directory under `~/.vibe-kanban`
+/// (`~/.vibe-kanban/dev` in debug builds) holding one `<session_id>.jsonl` log per session,
+/// keyed by the session id reported to vibe-kanban rather than the agent's own ACP id. The
+/// agent keeps no state between runs, so a follow-up forks that log under a new id and
+/// replays it as resume context; a session whose file is gone cannot be resumed.
 pub struct SessionManager {
     base_dir: PathBuf,
 }
@@ -74,6 +80,11 @@ impl SessionManager {
             AcpEvent::SessionStart(..)
             | AcpEvent::Error(..)
             | AcpEvent::Done(..)
+            | AcpEvent::PlanStep { .. }
+            | AcpEvent::Usage(..)
+            | AcpEvent::BudgetExceeded { .. }
+            | AcpEvent::PostTurnCommand { .. }
+            | AcpEvent::ApprovalTimedOut { .. }
             | AcpEvent::Other(..) => return None,
 
             AcpEvent::User(..)
@@ -110,6 +121,11 @@ impl SessionManager {
         serde_json::to_string(&event).ok()
     }
 
+    /// Whether a log for `session_id` is on disk
+    pub fn session_exists(&self, session_id: &str) -> bool {
+        self.session_file_path(session_id).is_file()
+    }
+
     /// Read the raw JSONL content of a session
     pub fn read_session_raw(&self, session_id: &str) -> Result<String> {
         let path = self.session_file_path(session_id);
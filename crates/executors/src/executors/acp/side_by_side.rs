@@ -0,0 +1,261 @@
+//! Structured side-by-side view of unified diffs captured from edit tool calls.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::logs::FileChange;
+
+/// One file of a patch as aligned old/new rows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct SideBySideDiff {
+    pub file: String,
+    /// The patch changed a binary file; no hunks are available
+    pub binary: bool,
+    pub hunks: Vec<Hunk>,
+}
+
+/// Rows of a hunk. `old_lines[i]` and `new_lines[i]` sit on the same row; `None` is padding
+/// where one side has fewer lines than the other.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct Hunk {
+    pub old_lines: Vec<Option<String>>,
+    pub new_lines: Vec<Option<String>>,
+}
+
+impl Hunk {
+    fn push_row(&mut self, old: Option<String>, new: Option<String>) {
+        self.old_lines.push(old);
+        self.new_lines.push(new);
+    }
+}
+
+/// Removed and added lines waiting to be paired up into rows
+#[derive(Default)]
+struct PendingChange {
+    removed: Vec<String>,
+    added: Vec<String>,
+}
+
+impl PendingChange {
+    fn flush_into(&mut self, hunk: &mut Hunk) {
+        let rows = self.removed.len().max(self.added.len());
+        let mut removed = self.removed.drain(..);
+        let mut added = self.added.drain(..);
+        for _ in 0..rows {
+            hunk.push_row(removed.next(), added.next());
+        }
+    }
+}
+
+/// Parse a unified diff, possibly covering several files, into side-by-side form.
+/// Changed lines are paired in order within each run of removals and additions.
+pub fn parse_side_by_side(patch: &str) -> Vec<SideBySideDiff> {
+    let mut files: Vec<SideBySideDiff> = Vec::new();
+    let mut current: Option<SideBySideDiff> = None;
+    let mut hunk: Option<Hunk> = None;
+    let mut pending = PendingChange::default();
+    // Set after a `diff --git` line so the `---` header that follows doesn't start another file
+    let mut awaiting_header = false;
+
+    let finish_hunk = |current: &mut Option<SideBySideDiff>,
+                       hunk: &mut Option<Hunk>,
+                       pending: &mut PendingChange| {
+        if let Some(mut h) = hunk.take() {
+            pending.flush_into(&mut h);
+            if let Some(file) = current.as_mut() {
+                file.hunks.push(h);
+            }
+        }
+    };
+
+    for line in patch.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            finish_hunk(&mut current, &mut hunk, &mut pending);
+            files.extend(current.take());
+            let file = rest
+                .split_once(" b/")
+                .map(|(_, new)| new)
+                .unwrap_or(rest)
+                .to_string();
+            current = Some(new_file(file));
+            awaiting_header = true;
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            if hunk.is_some() && !line.starts_with("--- a/") && !line.starts_with("--- /dev/null") {
+                // A removed line that happens to start with "-- "
+                pending.removed.push(line[1..].to_string());
+                continue;
+            }
+            finish_hunk(&mut current, &mut hunk, &mut pending);
+            if !awaiting_header {
+                files.extend(current.take());
+                current = Some(new_file(strip_side_prefix(path)));
+            }
+            awaiting_header = false;
+        } else if let Some(path) = line.strip_prefix("+++ ")
+            && hunk.is_none()
+        {
+            let path = strip_side_prefix(path);
+            if let Some(file) = current.as_mut()
+                && path != "/dev/null"
+            {
+                file.file = path;
+            }
+        } else if line.starts_with("@@") {
+            finish_hunk(&mut current, &mut hunk, &mut pending);
+            if current.is_none() {
+                current = Some(new_file(String::new()));
+            }
+            hunk = Some(Hunk::default());
+        } else if line.starts_with("Binary files ") || line.starts_with("GIT binary patch") {
+            finish_hunk(&mut current, &mut hunk, &mut pending);
+            let file = current.get_or_insert_with(|| new_file(binary_file_name(line)));
+            file.binary = true;
+        } else if let Some(h) = hunk.as_mut() {
+            if let Some(text) = line.strip_prefix('-') {
+                pending.removed.push(text.to_string());
+            } else if let Some(text) = line.strip_prefix('+') {
+                pending.added.push(text.to_string());
+            } else if line.starts_with('\\') {
+                // "\ No newline at end of file"
+            } else {
+                pending.flush_into(h);
+                let text = line.strip_prefix(' ').unwrap_or(line).to_string();
+                h.push_row(Some(text.clone()), Some(text));
+            }
+        }
+    }
+
+    finish_hunk(&mut current, &mut hunk, &mut pending);
+    files.extend(current);
+    files
+}
+
+/// Side-by-side form of every unified-diff edit in `changes`
+pub fn side_by_side_for_changes(changes: &[FileChange]) -> Vec<SideBySideDiff> {
+    changes
+        .iter()
+        .filter_map(|change| match change {
+            FileChange::Edit { unified_diff, .. } => Some(parse_side_by_side(unified_diff)),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+fn new_file(file: String) -> SideBySideDiff {
+    SideBySideDiff {
+        file,
+        binary: false,
+        hunks: Vec::new(),
+    }
+}
+
+fn strip_side_prefix(path: &str) -> String {
+    let path = path.split('\t').next().unwrap_or(path).trim();
+    path.strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// File name from "Binary files a/x and b/y differ", preferring the new side
+fn binary_file_name(line: &str) -> String {
+    line.strip_prefix("Binary files ")
+        .and_then(|rest| rest.strip_suffix(" differ"))
+        .and_then(|rest| rest.split_once(" and "))
+        .map(|(old, new)| {
+            if new == "/dev/null" {
+                strip_side_prefix(old)
+            } else {
+                strip_side_prefix(new)
+            }
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,5 +1,6 @@
+ fn main() {
+-    let a = 1;
+-    let b = 2;
++    let a = 10;
++    let b = 20;
++    let c = 30;
+     println!(\"{a}\");
+-    dbg!(b);
+ }
+@@ -20,2 +21,2 @@ fn helper() {
+-old tail
++new tail
+\\ No newline at end of file
+diff --git a/assets/logo.png b/assets/logo.png
+index 3333333..4444444 100644
+Binary files a/assets/logo.png and b/assets/logo.png differ
+";
+
+    fn some(lines: &[&str]) -> Vec<Option<String>> {
+        lines.iter().map(|l| Some(l.to_string())).collect()
+    }
+
+    #[test]
+    fn parses_fixture_patch_into_rows() {
+        let files = parse_side_by_side(FIXTURE);
+        assert_eq!(files.len(), 2);
+
+        let lib = &files[0];
+        assert_eq!(lib.file, "src/lib.rs");
+        assert!(!lib.binary);
+        assert_eq!(lib.hunks.len(), 2);
+
+        let first = &lib.hunks[0];
+        let mut expected_old = some(&["fn main() {", "    let a = 1;", "    let b = 2;"]);
+        expected_old.push(None);
+        expected_old.extend(some(&["    println!(\"{a}\");", "    dbg!(b);", "}"]));
+        let mut expected_new = some(&[
+            "fn main() {",
+            "    let a = 10;",
+            "    let b = 20;",
+            "    let c = 30;",
+            "    println!(\"{a}\");",
+        ]);
+        expected_new.push(None);
+        expected_new.extend(some(&["}"]));
+        assert_eq!(first.old_lines, expected_old);
+        assert_eq!(first.new_lines, expected_new);
+
+        let second = &lib.hunks[1];
+        assert_eq!(second.old_lines, some(&["old tail"]));
+        assert_eq!(second.new_lines, some(&["new tail"]));
+
+        let logo = &files[1];
+        assert_eq!(logo.file, "assets/logo.png");
+        assert!(logo.binary);
+        assert!(logo.hunks.is_empty());
+    }
+
+    #[test]
+    fn parses_header_only_diff_and_new_file() {
+        let patch = "--- a/notes.md\n+++ b/notes.md\n@@ -0,0 +1,2 @@\n+one\n+two\n";
+        let files = parse_side_by_side(patch);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].file, "notes.md");
+        assert_eq!(files[0].hunks[0].old_lines, vec![None, None]);
+        assert_eq!(files[0].hunks[0].new_lines, some(&["one", "two"]));
+    }
+
+    #[test]
+    fn binary_marker_without_git_header() {
+        let files = parse_side_by_side("Binary files /dev/null and b/img.gif differ\n");
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].file, "img.gif");
+        assert!(files[0].binary);
+    }
+}
@@ -0,0 +1,438 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use futures::StreamExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::{fs, process::Command};
+use ts_rs::TS;
+use uuid::Uuid;
+use workspace_utils::{
+    msg_store::MsgStore, path::get_vibe_kanban_temp_dir, shell::resolve_executable_path_blocking,
+};
+
+use crate::{
+    command::{CmdOverrides, CommandBuilder, apply_overrides},
+    env::ExecutionEnv,
+    executors::{
+        AppendPrompt, AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
+    },
+    logs::{
+        ActionType, FileChange, NormalizedEntry, NormalizedEntryType, ToolStatus,
+        stderr_processor::normalize_stderr_logs,
+        utils::{ConversationPatch, EntryIndexProvider},
+    },
+    stdout_dup,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct Aider {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+}
+
+impl Aider {
+    const BASE_COMMAND: &'static str = "aider";
+
+    const SESSION_PREFIX: &'static str = "[aider-session] ";
+
+    fn build_command_builder(&self, history_file: &str) -> CommandBuilder {
+        let mut builder = CommandBuilder::new(Self::BASE_COMMAND).params([
+            "--yes-always",
+            "--no-pretty",
+            "--no-stream",
+            "--no-check-update",
+            "--no-show-model-warnings",
+            "--chat-history-file",
+            history_file,
+        ]);
+
+        if let Some(model) = &self.model {
+            builder = builder.extend_params(["--model", model]);
+        }
+
+        apply_overrides(builder, &self.cmd)
+    }
+
+    /// Chat history file backing `session_id`; follow-ups restore the conversation from it
+    async fn history_file(session_id: &Uuid) -> Result<PathBuf, ExecutorError> {
+        let dir = get_vibe_kanban_temp_dir().join("aider_history");
+        fs::create_dir_all(&dir).await.map_err(ExecutorError::Io)?;
+        Ok(dir.join(format!("{session_id}.md")))
+    }
+
+    async fn spawn_with_history(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: Uuid,
+        restore_history: bool,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let history_file = Self::history_file(&session_id).await?;
+        let builder = self.build_command_builder(&history_file.to_string_lossy());
+        let command_parts = if restore_history {
+            builder.build_follow_up(&["--restore-chat-history".to_string()])?
+        } else {
+            builder.build_initial()?
+        };
+        let (program_path, mut args) = command_parts.into_resolved().await?;
+
+        // Passed after shell-splitting so the prompt is never re-tokenized
+        args.push("--message".to_string());
+        args.push(self.append_prompt.combine_prompt(prompt));
+
+        let mut command = Command::new(program_path);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .current_dir(current_dir)
+            .args(&args);
+
+        env.clone()
+            .with_profile(&self.cmd)
+            .apply_to_command(&mut command);
+
+        let mut child = command.group_spawn()?;
+
+        let (_, appender) = stdout_dup::tee_stdout_with_appender(&mut child)?;
+        appender.append_line(format!("{}{}\n", Self::SESSION_PREFIX, session_id));
+
+        Ok(child.into())
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for Aider {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_with_history(current_dir, prompt, Uuid::new_v4(), false, env)
+            .await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let session_id = Uuid::parse_str(session_id).map_err(|_| {
+            ExecutorError::FollowUpNotSupported(format!("invalid Aider session id `{session_id}`"))
+        })?;
+        self.spawn_with_history(current_dir, prompt, session_id, true, env)
+            .await
+    }
+
+    /// Parses Aider's `--no-pretty` stdout: SEARCH/REPLACE blocks become file edits,
+    /// `Commit <hash> <message>` lines become system messages and the rest is assistant text.
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, _worktree_path: &Path) {
+        let entry_index_provider = EntryIndexProvider::start_from(&msg_store);
+        normalize_stderr_logs(msg_store.clone(), entry_index_provider.clone());
+
+        tokio::spawn(async move {
+            let mut stdout_lines = msg_store.stdout_lines_stream();
+            let mut parser = AiderLogParser::default();
+
+            while let Some(Ok(line)) = stdout_lines.next().await {
+                if let Some(session_id) = line.strip_prefix(Self::SESSION_PREFIX) {
+                    msg_store.push_session_id(session_id.trim().to_string());
+                    continue;
+                }
+
+                for entry in parser.process_line(&line) {
+                    let id = entry_index_provider.next();
+                    msg_store.push_patch(ConversationPatch::add_normalized_entry(id, entry));
+                }
+            }
+
+            for entry in parser.finish() {
+                let id = entry_index_provider.next();
+                msg_store.push_patch(ConversationPatch::add_normalized_entry(id, entry));
+            }
+        });
+    }
+
+    fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+
+    fn get_availability_info(&self) -> AvailabilityInfo {
+        if resolve_executable_path_blocking(Self::BASE_COMMAND).is_some() {
+            AvailabilityInfo::InstallationFound
+        } else {
+            AvailabilityInfo::NotFound
+        }
+    }
+}
+
+const SEARCH_MARKER: &str = "<<<<<<< SEARCH";
+const DIVIDER_MARKER: &str = "=======";
+const REPLACE_MARKER: &str = ">>>>>>> REPLACE";
+const COMMIT_PREFIX: &str = "Commit ";
+const APPLIED_EDIT_PREFIX: &str = "Applied edit to ";
+
+#[derive(Debug, Default)]
+enum EditBlockState {
+    #[default]
+    Text,
+    Search {
+        path: String,
+        search: Vec<String>,
+    },
+    Replace {
+        path: String,
+        search: Vec<String>,
+        replace: Vec<String>,
+    },
+}
+
+/// Line-oriented parser for Aider's plain output
+#[derive(Debug, Default)]
+struct AiderLogParser {
+    text: Vec<String>,
+    state: EditBlockState,
+}
+
+impl AiderLogParser {
+    fn process_line(&mut self, line: &str) -> Vec<NormalizedEntry> {
+        let line = strip_ansi_escapes::strip_str(line);
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        match std::mem::take(&mut self.state) {
+            EditBlockState::Text => self.process_text_line(line),
+            EditBlockState::Search { path, mut search } => {
+                if line.trim_end() == DIVIDER_MARKER {
+                    self.state = EditBlockState::Replace {
+                        path,
+                        search,
+                        replace: Vec::new(),
+                    };
+                } else {
+                    search.push(line.to_string());
+                    self.state = EditBlockState::Search { path, search };
+                }
+                Vec::new()
+            }
+            EditBlockState::Replace {
+                path,
+                search,
+                mut replace,
+            } => {
+                if line.trim_end() == REPLACE_MARKER {
+                    vec![file_edit_entry(path, &search, &replace)]
+                } else {
+                    replace.push(line.to_string());
+                    self.state = EditBlockState::Replace {
+                        path,
+                        search,
+                        replace,
+                    };
+                    Vec::new()
+                }
+            }
+        }
+    }
+
+    fn process_text_line(&mut self, line: &str) -> Vec<NormalizedEntry> {
+        if line.trim_end() == SEARCH_MARKER {
+            // The edited path is the last non-fence line Aider printed before the block
+            while self.text.last().is_some_and(|l| is_fence(l)) {
+                self.text.pop();
+            }
+            let path = self.text.pop().unwrap_or_default().trim().to_string();
+            while self.text.last().is_some_and(|l| is_fence(l)) {
+                self.text.pop();
+            }
+            self.state = EditBlockState::Search {
+                path,
+                search: Vec::new(),
+            };
+            return self.flush_text().into_iter().collect();
+        }
+
+        // The edit entry already covers the change; the closing fence carries no content
+        if line.starts_with(APPLIED_EDIT_PREFIX) || (is_fence(line) && self.text.is_empty()) {
+            return Vec::new();
+        }
+
+        if let Some(rest) = line.strip_prefix(COMMIT_PREFIX)
+            && let Some((hash, message)) = rest.split_once(' ')
+            && hash.len() >= 7
+            && hash.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            let mut entries: Vec<NormalizedEntry> = self.flush_text().into_iter().collect();
+            entries.push(NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::SystemMessage,
+                content: format!("Committed {hash}: {}", message.trim()),
+                metadata: None,
+            });
+            return entries;
+        }
+
+        self.text.push(line.to_string());
+        Vec::new()
+    }
+
+    /// Flush any trailing text; an unterminated edit block is reported as plain text
+    fn finish(&mut self) -> Vec<NormalizedEntry> {
+        match std::mem::take(&mut self.state) {
+            EditBlockState::Text => {}
+            EditBlockState::Search { path, search } => {
+                self.text.push(path);
+                self.text.push(SEARCH_MARKER.to_string());
+                self.text.extend(search);
+            }
+            EditBlockState::Replace {
+                path,
+                search,
+                replace,
+            } => {
+                self.text.push(path);
+                self.text.push(SEARCH_MARKER.to_string());
+                self.text.extend(search);
+                self.text.push(DIVIDER_MARKER.to_string());
+                self.text.extend(replace);
+            }
+        }
+        self.flush_text().into_iter().collect()
+    }
+
+    fn flush_text(&mut self) -> Option<NormalizedEntry> {
+        let content = std::mem::take(&mut self.text).join("\n");
+        let content = content.trim();
+        if content.is_empty() {
+            return None;
+        }
+        Some(NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: content.to_string(),
+            metadata: None,
+        })
+    }
+}
+
+fn is_fence(line: &str) -> bool {
+    line.trim_start().starts_with("```")
+}
+
+fn file_edit_entry(path: String, search: &[String], replace: &[String]) -> NormalizedEntry {
+    let old = lines_to_content(search);
+    let new = lines_to_content(replace);
+    let unified_diff = workspace_utils::diff::create_unified_diff(&path, &old, &new);
+    NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::ToolUse {
+            tool_name: "edit".to_string(),
+            action_type: ActionType::FileEdit {
+                path: path.clone(),
+                changes: vec![FileChange::Edit {
+                    unified_diff,
+                    has_line_numbers: false,
+                }],
+            },
+            status: ToolStatus::Success,
+        },
+        content: path,
+        metadata: None,
+    }
+}
+
+fn lines_to_content(lines: &[String]) -> String {
+    if lines.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(output: &str) -> Vec<NormalizedEntry> {
+        let mut parser = AiderLogParser::default();
+        let mut entries: Vec<NormalizedEntry> = output
+            .lines()
+            .flat_map(|line| parser.process_line(line))
+            .collect();
+        entries.extend(parser.finish());
+        entries
+    }
+
+    #[test]
+    fn test_parses_edit_blocks_and_commits() {
+        let output = "\
+I'll rename the greeting.
+
+src/main.rs
+```rust
+<<<<<<< SEARCH
+    println!(\"hello\");
+=======
+    println!(\"hi\");
+>>>>>>> REPLACE
+```
+
+Applied edit to src/main.rs
+Commit 1a2b3c4 refactor: Shorten greeting
+Done.";
+
+        let entries = parse(output);
+        assert_eq!(entries.len(), 4, "{entries:?}");
+
+        assert!(matches!(
+            entries[0].entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(entries[0].content, "I'll rename the greeting.");
+
+        let NormalizedEntryType::ToolUse {
+            action_type: ActionType::FileEdit { path, changes },
+            ..
+        } = &entries[1].entry_type
+        else {
+            panic!("expected a file edit, got {:?}", entries[1]);
+        };
+        assert_eq!(path, "src/main.rs");
+        let [FileChange::Edit { unified_diff, .. }] = changes.as_slice() else {
+            panic!("expected a single edit, got {changes:?}");
+        };
+        assert!(unified_diff.contains("-    println!(\"hello\");"));
+        assert!(unified_diff.contains("+    println!(\"hi\");"));
+
+        assert!(matches!(
+            entries[2].entry_type,
+            NormalizedEntryType::SystemMessage
+        ));
+        assert_eq!(
+            entries[2].content,
+            "Committed 1a2b3c4: refactor: Shorten greeting"
+        );
+        assert_eq!(entries[3].content, "Done.");
+    }
+
+    #[test]
+    fn test_unterminated_edit_block_is_kept_as_text() {
+        let entries = parse("src/lib.rs\n<<<<<<< SEARCH\nfn a() {}");
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].content.contains("fn a() {}"));
+    }
+}
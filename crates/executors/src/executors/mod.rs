@@ -1,4 +1,8 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use command_group::AsyncGroupChild;
@@ -18,13 +22,20 @@ use crate::{
     command::CommandBuildError,
     env::ExecutionEnv,
     executors::{
-        amp::Amp, claude::ClaudeCode, codex::Codex, copilot::Copilot, cursor::CursorAgent,
-        droid::Droid, gemini::Gemini, opencode::Opencode, qwen::QwenCode,
+        aider::Aider, amp::Amp, claude::ClaudeCode, codex::Codex, copilot::Copilot,
+        cursor::CursorAgent, droid::Droid, gemini::Gemini, opencode::Opencode, qwen::QwenCode,
+        shell::ShellExecutor,
+    },
+    logs::{
+        NormalizedEntry, NormalizedEntryType,
+        turn_result::{TurnResult, UsageStats},
+        utils::{ConversationPatch, EntryIndexProvider, patch::latest_normalized_entries},
     },
     mcp_config::McpConfig,
 };
 
 pub mod acp;
+pub mod aider;
 pub mod amp;
 pub mod claude;
 pub mod codex;
@@ -34,6 +45,8 @@ pub mod droid;
 pub mod gemini;
 pub mod opencode;
 pub mod qwen;
+pub mod recording;
+pub mod shell;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -68,8 +81,32 @@ pub enum ExecutorError {
     ExecutableNotFound { program: String },
     #[error("Setup helper not supported")]
     SetupHelperNotSupported,
+    #[error("Dry run not supported")]
+    DryRunNotSupported,
+    #[error("Session {0} not found; start a new session instead")]
+    SessionNotFound(String),
     #[error("Auth required: {0}")]
     AuthRequired(String),
+    #[error("Invalid executor configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Token budget of {budget} exceeded ({used} tokens used), cancelling the turn")]
+    BudgetExceeded { used: u64, budget: u32 },
+}
+
+impl ExecutorError {
+    /// Whether the same spawn may succeed if tried again, e.g. after a network blip while the
+    /// agent is being downloaded. Configuration, authentication and missing-binary errors are not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ExecutorError::SpawnError(err) | ExecutorError::Io(err) => !matches!(
+                err.kind(),
+                std::io::ErrorKind::NotFound
+                    | std::io::ErrorKind::PermissionDenied
+                    | std::io::ErrorKind::InvalidInput
+            ),
+            _ => false,
+        }
+    }
 }
 
 #[enum_dispatch]
@@ -100,6 +137,8 @@ pub enum CodingAgent {
     QwenCode,
     Copilot,
     Droid,
+    Aider,
+    Shell(ShellExecutor),
 }
 
 impl CodingAgent {
@@ -166,7 +205,7 @@ impl CodingAgent {
                 BaseAgentCapability::SetupHelper,
             ],
             Self::CursorAgent(_) => vec![BaseAgentCapability::SetupHelper],
-            Self::Copilot(_) => vec![],
+            Self::Copilot(_) | Self::Aider(_) | Self::Shell(_) => vec![],
         }
     }
 }
@@ -175,9 +214,15 @@ impl CodingAgent {
 #[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
 #[ts(export)]
 pub enum AvailabilityInfo {
-    LoginDetected { last_auth_timestamp: i64 },
+    LoginDetected {
+        last_auth_timestamp: i64,
+    },
     InstallationFound,
     NotFound,
+    /// The agent is installed but failed to start
+    Broken {
+        reason: String,
+    },
 }
 
 impl AvailabilityInfo {
@@ -189,6 +234,17 @@ impl AvailabilityInfo {
     }
 }
 
+/// The process `spawn` would launch, as reported by `dry_run`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpawnedCommandPreview {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    /// Variables added to or changed from the `env` passed in, with secret-looking values masked
+    pub env: BTreeMap<String, String>,
+    /// The prompt as the agent would receive it
+    pub prompt: String,
+}
+
 #[async_trait]
 #[enum_dispatch(CodingAgent)]
 pub trait StandardCodingAgentExecutor {
@@ -209,6 +265,41 @@ pub trait StandardCodingAgentExecutor {
     ) -> Result<SpawnedChild, ExecutorError>;
     fn normalize_logs(&self, _raw_logs_event_store: Arc<MsgStore>, _worktree_path: &Path);
 
+    /// What `spawn` would launch for `prompt` in `current_dir`, without launching anything
+    async fn dry_run(
+        &self,
+        _current_dir: &Path,
+        _prompt: &str,
+        _env: &ExecutionEnv,
+    ) -> Result<SpawnedCommandPreview, ExecutorError> {
+        Err(ExecutorError::DryRunNotSupported)
+    }
+
+    /// Commit-message style summary of a finished session's normalized logs: a one-line
+    /// title, then optionally a blank line and a body. `None` when there is nothing to summarize.
+    fn summarize_session(&self, _store: &MsgStore) -> Option<String> {
+        None
+    }
+
+    /// Structured result of the turn logged in `store`: final message, edited files, tool
+    /// calls, token usage and success state
+    fn turn_result(&self, store: &MsgStore) -> TurnResult {
+        TurnResult::from_entries(&latest_normalized_entries(store))
+    }
+
+    /// The agent's MCP config file at `default_mcp_config_path` deep-merged with the servers
+    /// vibe-kanban provides, for agents that are handed the merged copy at spawn. The user's
+    /// file is only read.
+    fn effective_mcp_config(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Token usage of the most recent run whose logs this executor normalized, for agents
+    /// that report it
+    fn last_usage(&self) -> Option<UsageStats> {
+        None
+    }
+
     // MCP configuration methods
     fn default_mcp_config_path(&self) -> Option<std::path::PathBuf>;
 
@@ -228,6 +319,12 @@ pub trait StandardCodingAgentExecutor {
             AvailabilityInfo::NotFound
         }
     }
+
+    /// Slower check that actually starts the agent, for an explicit "test connection". Defaults
+    /// to [`Self::get_availability_info`].
+    async fn probe_availability(&self) -> AvailabilityInfo {
+        self.get_availability_info()
+    }
 }
 
 /// Result communicated through the exit signal
@@ -237,6 +334,8 @@ pub enum ExecutorExitResult {
     Success,
     /// Process should be marked as failed (non-zero exit)
     Failure,
+    /// Process ran longer than its allowed duration and should be killed and marked as failed
+    TimedOut(std::time::Duration),
 }
 
 /// Optional exit notification from an executor.
@@ -267,6 +366,48 @@ impl From<AsyncGroupChild> for SpawnedChild {
     }
 }
 
+/// How long `abort_child` gives an interrupted agent to exit before killing it
+const ABORT_GRACE: std::time::Duration = std::time::Duration::from_secs(5);
+
+impl SpawnedChild {
+    /// Cancel the session and stop the agent together with every process it started, then
+    /// record the cancellation in `msg_store`. See [`abort_child`].
+    pub async fn abort(mut self, msg_store: &MsgStore) -> Result<(), ExecutorError> {
+        abort_child(&mut self.child, self.interrupt_sender.take()).await?;
+
+        let entry = NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::SystemMessage,
+            content: "Session cancelled".to_string(),
+            metadata: None,
+        };
+        let index = EntryIndexProvider::start_from(msg_store).next();
+        msg_store.push_patch(ConversationPatch::add_normalized_entry(index, entry));
+        Ok(())
+    }
+}
+
+/// Stop `child` for callers that keep it apart from its interrupt sender (e.g. the container's
+/// process store). Agents with an interrupt channel (e.g. ACP session cancel) are asked to wind
+/// down first; whatever is still running is then killed as a process group.
+pub async fn abort_child(
+    child: &mut AsyncGroupChild,
+    interrupt: Option<InterruptSender>,
+) -> Result<(), ExecutorError> {
+    if let Some(interrupt) = interrupt
+        && interrupt.send(()).is_ok()
+    {
+        let _ = tokio::time::timeout(ABORT_GRACE, child.wait()).await;
+    }
+
+    if child.try_wait().map_err(ExecutorError::Io)?.is_none() {
+        workspace_utils::process::kill_process_group(child)
+            .await
+            .map_err(ExecutorError::Io)?;
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
 #[serde(transparent)]
 #[schemars(
@@ -296,6 +437,40 @@ mod tests {
 
     use super::*;
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn abort_kills_process_group_and_records_cancellation() {
+        use command_group::AsyncCommandGroup;
+
+        let child = tokio::process::Command::new("sh")
+            .args(["-c", "sleep 30 & sleep 30"])
+            .group_spawn()
+            .unwrap();
+        let msg_store = MsgStore::new();
+
+        SpawnedChild::from(child).abort(&msg_store).await.unwrap();
+
+        let entries = latest_normalized_entries(&msg_store);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].content, "Session cancelled");
+    }
+
+    #[test]
+    fn only_transient_spawn_failures_are_retryable() {
+        let io = |kind| std::io::Error::new(kind, "boom");
+        assert!(ExecutorError::SpawnError(io(std::io::ErrorKind::ConnectionReset)).is_retryable());
+        assert!(ExecutorError::Io(io(std::io::ErrorKind::TimedOut)).is_retryable());
+        assert!(!ExecutorError::SpawnError(io(std::io::ErrorKind::NotFound)).is_retryable());
+        assert!(!ExecutorError::AuthRequired("login".to_string()).is_retryable());
+        assert!(!ExecutorError::InvalidConfig("unknown model".to_string()).is_retryable());
+        assert!(
+            !ExecutorError::ExecutableNotFound {
+                program: "npx".to_string()
+            }
+            .is_retryable()
+        );
+    }
+
     #[test]
     fn test_cursor_agent_deserialization() {
         // Test that CURSOR_AGENT is accepted
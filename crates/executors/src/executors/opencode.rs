@@ -1,90 +1,705 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::Path,
+    process::Stdio,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
 
 use async_trait::async_trait;
+use backon::{ExponentialBuilder, Retryable};
 use derivative::Derivative;
+use ignore::WalkBuilder;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use ts_rs::TS;
-use workspace_utils::msg_store::MsgStore;
+use workspace_utils::{msg_store::MsgStore, path::get_vibe_kanban_temp_dir};
 
 use crate::{
-    approvals::ExecutorApprovalService,
+    approvals::{
+        ApprovalPolicy, ApprovalTimeoutAction, ExecutorApprovalService,
+        NoopExecutorApprovalService, PolicyApprovalService,
+    },
     command::{CmdOverrides, CommandBuilder, apply_overrides},
     env::ExecutionEnv,
     executors::{
-        AppendPrompt, AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
-        acp::AcpAgentHarness,
+        AppendPrompt, AvailabilityInfo, CodingAgent, ExecutorError, SpawnedChild,
+        SpawnedCommandPreview, StandardCodingAgentExecutor,
+        acp::{AcpAgentHarness, ApprovalTimeout, PostTurnCommand, TokenUsage, UsageSlot},
+        recording::Recording,
     },
+    logs::{
+        NormalizedEntry,
+        sink::{OutputSink, spawn_sink_forwarder},
+        turn_result::{TurnResult, TurnUsage, UsageStats},
+        utils::patch::latest_normalized_entries,
+    },
+    mcp_config::deep_merge,
 };
 
+/// How opencode treats a tool call
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionLevel {
+    Allow,
+    #[default]
+    Ask,
+    Deny,
+}
+
+/// Per-tool approval levels; tools left out default to `ask`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct OpencodePermissions {
+    #[serde(default)]
+    pub edit: PermissionLevel,
+    #[serde(default)]
+    pub bash: PermissionLevel,
+    #[serde(default)]
+    pub webfetch: PermissionLevel,
+    #[serde(default)]
+    pub doom_loop: PermissionLevel,
+    #[serde(default)]
+    pub external_directory: PermissionLevel,
+}
+
+/// How opencode is started
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, TS, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OpencodeLauncher {
+    /// `npx -y opencode-ai@<version>`
+    #[default]
+    Npx,
+    /// `bunx opencode-ai@<version>`
+    Bunx,
+    /// An installed `opencode` binary, e.g. for air-gapped machines; `version` is ignored
+    GlobalBinary { path: String },
+}
+
 #[derive(Derivative, Clone, Serialize, Deserialize, TS, JsonSchema)]
 #[derivative(Debug, PartialEq)]
 pub struct Opencode {
     #[serde(default)]
     pub append_prompt: AppendPrompt,
+    /// System-level instruction sent with every new and follow-up session, ahead of the
+    /// conversation. `append_prompt` is still appended to each user message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none", alias = "agent")]
     pub mode: Option<String>,
+    /// `opencode-ai` npm version to run, e.g. `1.2.0` or `latest`; defaults to the pinned version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// How opencode is started; defaults to `npx`
+    #[serde(default)]
+    pub launcher: OpencodeLauncher,
     /// Auto-approve agent actions
     #[serde(default = "default_to_true")]
     pub auto_approve: bool,
+    /// Sampling seed for reproducible runs. Determinism depends on the provider honoring it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Sampling temperature between 0.0 and 2.0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// Maximum tokens the model may generate per response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// Extra HTTP headers sent to the model provider, e.g. for a corporate gateway
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_headers: Option<Vec<(String, String)>>,
+    /// Per-tool approval levels exported as `OPENCODE_PERMISSION`. Without them every tool asks
+    /// unless `auto_approve` is on. An `OPENCODE_PERMISSION` already set in `env` takes precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<OpencodePermissions>,
+    /// Bash command prefixes (e.g. `npm test`) that run without asking when approvals are on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bash_allowlist: Vec<String>,
+    /// Seconds to wait for each approval decision before applying `approval_timeout_action`;
+    /// unset waits indefinitely
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_timeout_secs: Option<u64>,
+    /// Decision recorded when an approval request times out
+    #[serde(default)]
+    pub approval_timeout_action: ApprovalTimeoutAction,
+    /// Rules answering approval requests before they reach a human; calls the policy leaves at
+    /// `ask` go to the approval service. Ignored when `auto_approve` is on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_policy: Option<ApprovalPolicy>,
+    /// Proxy URL for outbound traffic, exported as `HTTP_PROXY`/`HTTPS_PROXY` for opencode and npx
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_proxy: Option<String>,
+    /// Comma-separated hosts that bypass `http_proxy`, exported as `NO_PROXY`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+    /// Glob patterns (e.g. `OPENCODE_*`, `PATH`) of inherited environment variables opencode
+    /// may see; an empty list admits every variable that is not denied. Variables vibe-kanban
+    /// sets for the run, including opencode's permissions and MCP config, are always kept.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_allowlist: Vec<String>,
+    /// Glob patterns (e.g. `AWS_*`) of environment variables withheld from opencode, even if
+    /// allowlisted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_denylist: Vec<String>,
+    /// Prepend a `.gitignore`-aware overview of the worktree to the initial prompt
+    #[serde(default)]
+    pub include_file_tree: bool,
+    /// Hard ceiling on total tokens for a run; the turn is cancelled once usage exceeds it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_budget: Option<u32>,
+    /// Shell command run in the worktree after each completed turn, e.g. `cargo fmt`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_turn_command: Option<String>,
+    /// Fail the turn when `post_turn_command` exits non-zero instead of only warning
+    #[serde(default)]
+    pub post_turn_fail_on_error: bool,
     #[serde(flatten)]
     pub cmd: CmdOverrides,
     #[serde(skip)]
     #[ts(skip)]
     #[derivative(Debug = "ignore", PartialEq = "ignore")]
     pub approvals: Option<Arc<dyn ExecutorApprovalService>>,
+    /// Extra consumer of normalized entries, fed in addition to the `MsgStore`
+    #[serde(skip)]
+    #[ts(skip)]
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    pub output_sink: Option<Arc<dyn OutputSink>>,
+    /// Usage totals of the most recent run normalized by this instance
+    #[serde(skip)]
+    #[ts(skip)]
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    pub usage: UsageSlot,
 }
 
 impl Opencode {
-    fn build_command_builder(&self) -> CommandBuilder {
-        let builder = CommandBuilder::new("npx -y opencode-ai@1.1.3").extend_params(["acp"]);
-        apply_overrides(builder, &self.cmd)
+    /// Invocation of opencode through the configured `launcher`, without a subcommand
+    fn base_command(&self) -> Result<String, ExecutorError> {
+        let runner = match &self.launcher {
+            OpencodeLauncher::Npx => "npx -y",
+            OpencodeLauncher::Bunx => "bunx",
+            OpencodeLauncher::GlobalBinary { path } => {
+                let path = path.trim();
+                if path.is_empty() || path.contains('"') {
+                    return Err(ExecutorError::InvalidConfig(format!(
+                        "invalid opencode binary path `{path}`"
+                    )));
+                }
+                // Quoted so that paths with spaces survive splitting the command line
+                return Ok(format!("\"{path}\""));
+            }
+        };
+        let version = match self.version.as_deref().map(str::trim) {
+            None => DEFAULT_OPENCODE_VERSION,
+            Some(version) if version.is_empty() || version.contains(char::is_whitespace) => {
+                return Err(ExecutorError::InvalidConfig(format!(
+                    "invalid opencode version `{version}`"
+                )));
+            }
+            Some(version) => version,
+        };
+        Ok(format!("{runner} opencode-ai@{version}"))
     }
 
-    fn harness() -> AcpAgentHarness {
-        AcpAgentHarness::with_session_namespace("opencode_sessions")
+    fn build_command_builder(&self) -> Result<CommandBuilder, ExecutorError> {
+        let builder = CommandBuilder::new(self.base_command()?).extend_params(["acp"]);
+        Ok(apply_overrides(builder, &self.cmd))
     }
-}
 
-#[async_trait]
-impl StandardCodingAgentExecutor for Opencode {
-    fn use_approvals(&mut self, approvals: Arc<dyn ExecutorApprovalService>) {
-        self.approvals = Some(approvals);
+    /// Models opencode can use, as `provider/model`. Listing is slow, so the result is cached
+    /// per command for the lifetime of the process; concurrent callers share one listing.
+    pub async fn list_models(
+        &self,
+        current_dir: &Path,
+        env: &ExecutionEnv,
+    ) -> Result<Vec<String>, ExecutorError> {
+        let base = match &self.cmd.base_command_override {
+            Some(base) => base.clone(),
+            None => self.base_command()?,
+        };
+        let cell = MODEL_LIST_CACHE
+            .lock()
+            .unwrap()
+            .entry(base.clone())
+            .or_default()
+            .clone();
+        cell.get_or_try_init(|| self.run_list_models(base, current_dir, env))
+            .await
+            .cloned()
     }
 
-    async fn spawn(
+    /// Run `opencode models` without consulting the cache
+    async fn run_list_models(
         &self,
+        base: String,
         current_dir: &Path,
-        prompt: &str,
         env: &ExecutionEnv,
-    ) -> Result<SpawnedChild, ExecutorError> {
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+    ) -> Result<Vec<String>, ExecutorError> {
+        let (program, args) = CommandBuilder::new(base)
+            .extend_params(["models"])
+            .build_initial()?
+            .into_resolved()
+            .await?;
+        let mut command = tokio::process::Command::new(program);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::null())
+            .current_dir(current_dir)
+            .args(&args);
+        env.clone()
+            .with_profile(&self.cmd)
+            .apply_to_command(&mut command);
 
-        let mut harness = Self::harness();
-        if let Some(model) = &self.model {
-            harness = harness.with_model(model);
+        let output = tokio::time::timeout(MODEL_LIST_TIMEOUT, command.output())
+            .await
+            .map_err(|_| {
+                ExecutorError::Io(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "listing opencode models timed out",
+                ))
+            })?
+            .map_err(ExecutorError::Io)?;
+        if !output.status.success() {
+            return Err(ExecutorError::Io(std::io::Error::other(format!(
+                "`opencode models` exited with {}",
+                output.status
+            ))));
         }
-        if let Some(agent) = &self.mode {
-            harness = harness.with_mode(agent);
+
+        Ok(parse_model_list(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Output of `opencode --version` run through the configured command
+    async fn version_check(&self) -> Result<String, ExecutorError> {
+        let base = match &self.cmd.base_command_override {
+            Some(base) => base.clone(),
+            None => self.base_command()?,
+        };
+        let (program, args) = CommandBuilder::new(base)
+            .extend_params(["--version"])
+            .build_initial()?
+            .into_resolved()
+            .await?;
+        let mut command = tokio::process::Command::new(program);
+        command.kill_on_drop(true).stdin(Stdio::null()).args(&args);
+        self.setup_env(&ExecutionEnv::new())?
+            .with_profile(&self.cmd)
+            .apply_to_command(&mut command);
+
+        let output = tokio::time::timeout(VERSION_CHECK_TIMEOUT, command.output())
+            .await
+            .map_err(|_| {
+                ExecutorError::Io(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!(
+                        "`opencode --version` timed out after {}s",
+                        VERSION_CHECK_TIMEOUT.as_secs()
+                    ),
+                ))
+            })?
+            .map_err(ExecutorError::Io)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(ExecutorError::Io(std::io::Error::other(format!(
+                "`opencode --version` exited with {}: {}",
+                output.status,
+                stderr.trim()
+            ))));
         }
-        let opencode_command = self.build_command_builder().build_initial()?;
-        let approvals = if self.auto_approve {
-            None
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// vibe-kanban's own MCP server in opencode's config shape, merged over the user's config
+    fn mcp_overlay(&self) -> serde_json::Value {
+        let servers = CodingAgent::Opencode(self.clone()).preconfigured_mcp();
+        let server = servers
+            .get(VIBE_KANBAN_MCP_SERVER)
+            .cloned()
+            .unwrap_or_default();
+        serde_json::json!({ "mcp": { VIBE_KANBAN_MCP_SERVER: server } })
+    }
+
+    /// Write `effective_mcp_config` to a temp file keyed by `current_dir` and point
+    /// `OPENCODE_CONFIG` at it, unless `env` already sets `OPENCODE_CONFIG`. Every run in the
+    /// same worktree overwrites the same file, so they don't pile up.
+    async fn write_mcp_config(
+        &self,
+        current_dir: &Path,
+        env: &mut ExecutionEnv,
+    ) -> Result<(), ExecutorError> {
+        if env.contains_key(OPENCODE_CONFIG) {
+            return Ok(());
+        }
+        let Some(config) = self.effective_mcp_config() else {
+            return Ok(());
+        };
+        let dir = get_vibe_kanban_temp_dir().join("opencode_config");
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(ExecutorError::Io)?;
+        let path = dir.join(mcp_config_file_name(current_dir));
+        tokio::fs::write(&path, serde_json::to_string_pretty(&config)?)
+            .await
+            .map_err(ExecutorError::Io)?;
+        env.insert(OPENCODE_CONFIG, path.to_string_lossy());
+        Ok(())
+    }
+
+    /// Fail before spawning when `model` is set but opencode does not list it. A failed
+    /// listing is only logged so that an unreachable registry does not block the run.
+    async fn ensure_model_available(
+        &self,
+        current_dir: &Path,
+        env: &ExecutionEnv,
+    ) -> Result<(), ExecutorError> {
+        let Some(model) = self.model.as_deref() else {
+            return Ok(());
+        };
+        match self.list_models(current_dir, env).await {
+            Ok(models) => check_model_listed(model, &models),
+            Err(err) => {
+                tracing::warn!("Could not list opencode models, skipping model check: {err}");
+                Ok(())
+            }
+        }
+    }
+
+    /// Provider section of the opencode config, keyed by the provider prefix of `model`
+    fn provider_config(&self) -> Result<Option<serde_json::Value>, ExecutorError> {
+        let Some(headers) = self.provider_headers.as_ref().filter(|h| !h.is_empty()) else {
+            return Ok(None);
+        };
+        let provider = self
+            .model
+            .as_deref()
+            .and_then(|model| model.split_once('/'))
+            .map(|(provider, _)| provider)
+            .ok_or_else(|| {
+                ExecutorError::InvalidConfig(
+                    "provider_headers requires a model in `provider/model` form".to_string(),
+                )
+            })?;
+
+        let mut header_map = serde_json::Map::new();
+        for (name, value) in headers {
+            if !is_valid_header_name(name) {
+                return Err(ExecutorError::InvalidConfig(format!(
+                    "invalid provider header name `{name}`"
+                )));
+            }
+            header_map.insert(name.clone(), serde_json::Value::String(value.clone()));
+        }
+
+        Ok(Some(serde_json::json!({
+            "provider": {
+                provider: { "options": { "headers": header_map } }
+            }
+        })))
+    }
+
+    /// Value for `OPENCODE_PERMISSION`, or `None` when everything is auto-approved
+    fn permission_config(&self) -> Result<Option<serde_json::Value>, ExecutorError> {
+        let permissions = match &self.permissions {
+            Some(permissions) => permissions.clone(),
+            None if self.auto_approve => return Ok(None),
+            None => OpencodePermissions::default(),
+        };
+
+        let bash = if self.bash_allowlist.is_empty() {
+            serde_json::json!(permissions.bash)
         } else {
-            self.approvals.clone()
+            let mut rules = serde_json::Map::new();
+            for entry in &self.bash_allowlist {
+                let prefix = entry.trim();
+                if prefix.is_empty() {
+                    return Err(ExecutorError::InvalidConfig(
+                        "bash_allowlist entries must not be empty".to_string(),
+                    ));
+                }
+                rules.insert(prefix.to_string(), serde_json::json!("allow"));
+                rules.insert(format!("{prefix} *"), serde_json::json!("allow"));
+            }
+            rules.insert("*".to_string(), serde_json::json!(permissions.bash));
+            serde_json::Value::Object(rules)
         };
-        let env = setup_approvals_env(self.auto_approve, env);
-        harness
-            .spawn_with_command(
+
+        Ok(Some(serde_json::json!({
+            "edit": permissions.edit,
+            "bash": bash,
+            "webfetch": permissions.webfetch,
+            "doom_loop": permissions.doom_loop,
+            "external_directory": permissions.external_directory,
+        })))
+    }
+
+    /// Reject sampling settings the provider would refuse once the session is already running
+    fn validate_sampling(&self) -> Result<(), ExecutorError> {
+        if let Some(temperature) = self.temperature
+            && !(0.0..=2.0).contains(&temperature)
+        {
+            return Err(ExecutorError::InvalidConfig(format!(
+                "temperature must be between 0.0 and 2.0, got {temperature}"
+            )));
+        }
+        Ok(())
+    }
+
+    fn setup_env(&self, env: &ExecutionEnv) -> Result<ExecutionEnv, ExecutorError> {
+        let mut env = env.clone();
+        if let Some(permission) = self.permission_config()?
+            && !env.contains_key(OPENCODE_PERMISSION)
+        {
+            env.insert(OPENCODE_PERMISSION, permission.to_string());
+        }
+        if let Some(config) = self.provider_config()?
+            && !env.contains_key(OPENCODE_CONFIG_CONTENT)
+        {
+            tracing::debug!(config = %redact_secrets(&config), "injecting opencode config");
+            env.insert(OPENCODE_CONFIG_CONTENT, config.to_string());
+        }
+        let proxy_vars = [
+            (self.http_proxy.as_deref(), PROXY_VARS),
+            (self.no_proxy.as_deref(), NO_PROXY_VARS),
+        ];
+        for (value, keys) in proxy_vars {
+            let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+                continue;
+            };
+            for key in keys {
+                if !env.contains_key(key) {
+                    env.insert(*key, value);
+                }
+            }
+        }
+        Ok(env)
+    }
+
+    /// `env` restricted by `env_allowlist` and `env_denylist`, or unchanged when both are empty
+    fn filter_env(&self, env: ExecutionEnv) -> Result<ExecutionEnv, ExecutorError> {
+        if self.env_allowlist.is_empty() && self.env_denylist.is_empty() {
+            return Ok(env);
+        }
+        for pattern in self.env_allowlist.iter().chain(&self.env_denylist) {
+            globset::Glob::new(pattern).map_err(|e| {
+                ExecutorError::InvalidConfig(format!("invalid env filter pattern `{pattern}`: {e}"))
+            })?;
+        }
+        Ok(env.filtered(&self.env_allowlist, &self.env_denylist))
+    }
+
+    /// The env a spawn in `current_dir` runs with: `env` filtered first, then opencode's own
+    /// variables and the MCP config added, so the filter can never drop them
+    async fn spawn_env(
+        &self,
+        current_dir: &Path,
+        env: &ExecutionEnv,
+    ) -> Result<ExecutionEnv, ExecutorError> {
+        let mut env = self.setup_env(&self.filter_env(env.clone())?)?;
+        self.write_mcp_config(current_dir, &mut env).await?;
+        Ok(env)
+    }
+
+    /// `prompts` with `append_prompt` applied and, if enabled, the file tree prepended to the first
+    fn prepare_prompts(&self, current_dir: &Path, prompts: &[String]) -> Vec<String> {
+        let mut prompts: Vec<String> = prompts
+            .iter()
+            .map(|prompt| self.append_prompt.combine_prompt(prompt))
+            .collect();
+        if self.include_file_tree
+            && let Some(first) = prompts.first_mut()
+        {
+            let tree = build_file_tree(current_dir, FILE_TREE_LIMIT);
+            *first = format!("Repository file tree:\n```\n{tree}```\n\n{first}");
+        }
+        prompts
+    }
+
+    /// Approval service for a run in `current_dir`: none when auto-approving, otherwise the
+    /// one from `use_approvals` behind `approval_policy` when that is set
+    fn run_approvals(
+        &self,
+        current_dir: &Path,
+    ) -> Result<Option<Arc<dyn ExecutorApprovalService>>, ExecutorError> {
+        if self.auto_approve {
+            return Ok(None);
+        }
+        let Some(policy) = &self.approval_policy else {
+            return Ok(self.approvals.clone());
+        };
+        let fallback = self
+            .approvals
+            .clone()
+            .unwrap_or_else(|| Arc::new(NoopExecutorApprovalService));
+        let service = PolicyApprovalService::new(policy, current_dir, fallback)
+            .map_err(|e| ExecutorError::InvalidConfig(e.to_string()))?;
+        Ok(Some(Arc::new(service)))
+    }
+
+    /// Run `prompts` in order within one new session, each as a follow-up to the previous.
+    /// A `PlanStep` marker precedes every step after the first; an erroring step ends the run.
+    pub async fn spawn_sequence(
+        &self,
+        current_dir: &Path,
+        prompts: &[String],
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        self.validate_sampling()?;
+        let prompts = self.prepare_prompts(current_dir, prompts);
+        let harness = self.harness();
+        let opencode_command = self.build_command_builder()?.build_initial()?;
+        let approvals = self.run_approvals(current_dir)?;
+        let env = self.spawn_env(current_dir, env).await?;
+        self.ensure_model_available(current_dir, &env).await?;
+        spawn_with_retry(|| {
+            harness.spawn_sequence_with_command(
                 current_dir,
-                combined_prompt,
-                opencode_command,
+                prompts.clone(),
+                opencode_command.clone(),
                 &env,
                 &self.cmd,
-                approvals,
+                approvals.clone(),
             )
+        })
+        .await
+    }
+
+    /// The configuration a spawn would use, with secrets redacted. Does not spawn anything.
+    /// `env` is filtered by `env_allowlist`/`env_denylist` as at spawn; `mcp_config` is what a
+    /// spawn writes to the file `OPENCODE_CONFIG` points at, unless `env` already sets it.
+    pub fn resolved_config(&self, env: &ExecutionEnv) -> serde_json::Value {
+        let (command, command_error) = match self.build_command_builder() {
+            Ok(builder) => {
+                let mut command = vec![builder.base];
+                command.extend(builder.params.unwrap_or_default());
+                (command, None)
+            }
+            Err(err) => (Vec::new(), Some(err.to_string())),
+        };
+
+        let (env_vars, config_error) = match self.filter_env(env.clone()) {
+            Ok(filtered) => match self.setup_env(&filtered) {
+                Ok(env) => (env.with_profile(&self.cmd).vars, None),
+                Err(err) => (filtered.with_profile(&self.cmd).vars, Some(err.to_string())),
+            },
+            Err(err) => (HashMap::new(), Some(err.to_string())),
+        };
+        let mcp_config = if env.contains_key(OPENCODE_CONFIG) {
+            None
+        } else {
+            self.effective_mcp_config().map(|c| redact_secrets(&c))
+        };
+        let env_vars: serde_json::Map<String, serde_json::Value> = env_vars
+            .into_iter()
+            .map(|(key, value)| {
+                let value = if looks_secret(&key) {
+                    serde_json::Value::String("***".to_string())
+                } else {
+                    // Inline JSON config (permissions, provider config) is expanded for readability
+                    serde_json::from_str::<serde_json::Value>(&value)
+                        .ok()
+                        .filter(|v| v.is_object())
+                        .map(|v| redact_secrets(&v))
+                        .unwrap_or(serde_json::Value::String(value))
+                };
+                (key, value)
+            })
+            .collect();
+
+        serde_json::json!({
+            "command": command,
+            "model": self.model,
+            "mode": self.mode,
+            "seed": self.seed,
+            "temperature": self.temperature,
+            "max_tokens": self.max_tokens,
+            "system_prompt": self.system_prompt,
+            "auto_approve": self.auto_approve,
+            "approval": {
+                "policy": self.approval_policy,
+                "timeout_secs": self.approval_timeout_secs,
+                "timeout_action": self.approval_timeout_action,
+                "bash_allowlist": self.bash_allowlist,
+            },
+            "append_prompt": self.append_prompt.get(),
+            "mcp_config_path": self.default_mcp_config_path(),
+            "mcp_config": mcp_config,
+            "env": env_vars,
+            "config_error": command_error.or(config_error),
+        })
+    }
+
+    /// Also deliver normalized entries to `sink`, e.g. to tee them into a log aggregator
+    pub fn with_output_sink(mut self, sink: Arc<dyn OutputSink>) -> Self {
+        self.output_sink = Some(sink);
+        self
+    }
+
+    /// Sessions live in the `opencode_sessions` namespace, see [`SessionManager`] for the layout
+    ///
+    /// [`SessionManager`]: crate::executors::acp::session::SessionManager
+    fn harness(&self) -> AcpAgentHarness {
+        let mut harness = AcpAgentHarness::with_session_namespace("opencode_sessions");
+        if let Some(model) = &self.model {
+            harness = harness.with_model(model);
+        }
+        if let Some(agent) = &self.mode {
+            harness = harness.with_mode(agent);
+        }
+        if let Some(seed) = self.seed {
+            harness = harness.with_seed(seed);
+        }
+        if let Some(system_prompt) = self
+            .system_prompt
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+        {
+            harness = harness.with_system_prompt(system_prompt);
+        }
+        if let Some(temperature) = self.temperature {
+            harness = harness.with_temperature(temperature);
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            harness = harness.with_max_tokens(max_tokens);
+        }
+        if let Some(budget) = self.token_budget {
+            harness = harness.with_token_budget(budget);
+        }
+        if let Some(secs) = self.approval_timeout_secs {
+            harness = harness.with_approval_timeout(ApprovalTimeout {
+                after: Duration::from_secs(secs),
+                action: self.approval_timeout_action,
+            });
+        }
+        if let Some(command) = self
+            .post_turn_command
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+        {
+            harness = harness.with_post_turn_command(PostTurnCommand {
+                command: command.to_string(),
+                fail_on_error: self.post_turn_fail_on_error,
+            });
+        }
+        harness
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for Opencode {
+    fn use_approvals(&mut self, approvals: Arc<dyn ExecutorApprovalService>) {
+        self.approvals = Some(approvals);
+    }
+
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_sequence(current_dir, &[prompt.to_string()], env)
             .await
     }
 
@@ -95,36 +710,118 @@ impl StandardCodingAgentExecutor for Opencode {
         session_id: &str,
         env: &ExecutionEnv,
     ) -> Result<SpawnedChild, ExecutorError> {
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
-        let mut harness = Self::harness();
-        if let Some(model) = &self.model {
-            harness = harness.with_model(model);
-        }
-        if let Some(agent) = &self.mode {
-            harness = harness.with_mode(agent);
+        self.validate_sampling()?;
+        let harness = self.harness();
+        if !harness.session_exists(session_id) {
+            return Err(ExecutorError::SessionNotFound(session_id.to_string()));
         }
-        let opencode_command = self.build_command_builder().build_follow_up(&[])?;
-        let approvals = if self.auto_approve {
-            None
-        } else {
-            self.approvals.clone()
-        };
-        let env = setup_approvals_env(self.auto_approve, env);
-        harness
-            .spawn_follow_up_with_command(
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let opencode_command = self.build_command_builder()?.build_follow_up(&[])?;
+        let approvals = self.run_approvals(current_dir)?;
+        let env = self.spawn_env(current_dir, env).await?;
+        self.ensure_model_available(current_dir, &env).await?;
+        spawn_with_retry(|| {
+            harness.spawn_follow_up_with_command(
                 current_dir,
-                combined_prompt,
+                combined_prompt.clone(),
                 session_id,
-                opencode_command,
+                opencode_command.clone(),
                 &env,
                 &self.cmd,
-                approvals,
+                approvals.clone(),
             )
-            .await
+        })
+        .await
+    }
+
+    /// Builds the command and environment exactly as `spawn` does, but never writes the merged
+    /// MCP config or starts opencode, so `OPENCODE_CONFIG` is not part of the preview
+    async fn dry_run(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedCommandPreview, ExecutorError> {
+        self.validate_sampling()?;
+        let prompt = self
+            .prepare_prompts(current_dir, &[prompt.to_string()])
+            .remove(0);
+        let (program, args) = self
+            .build_command_builder()?
+            .build_initial()?
+            .into_resolved()
+            .await?;
+        let spawn_env = self
+            .setup_env(&self.filter_env(env.clone())?)?
+            .with_profile(&self.cmd);
+        let env = spawn_env
+            .vars
+            .into_iter()
+            .filter(|(key, value)| env.vars.get(key) != Some(value))
+            .map(|(key, value)| {
+                let value = redact_env_value(&key, value);
+                (key, value)
+            })
+            .collect();
+        Ok(SpawnedCommandPreview {
+            program,
+            args,
+            env,
+            prompt,
+        })
     }
 
     fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
-        crate::executors::acp::normalize_logs(msg_store, worktree_path);
+        if let Some(sink) = &self.output_sink {
+            spawn_sink_forwarder(msg_store.clone(), sink.clone());
+        }
+        crate::executors::acp::normalize_logs_with_usage(
+            msg_store,
+            worktree_path,
+            Some(self.usage.clone()),
+        );
+    }
+
+    fn last_usage(&self) -> Option<UsageStats> {
+        let mut stats = self.usage.lock().ok()?.clone()?;
+        if stats.model.is_none() {
+            stats.model = self.model.clone();
+        }
+        Some(stats)
+    }
+
+    fn summarize_session(&self, store: &MsgStore) -> Option<String> {
+        summarize_entries(&latest_normalized_entries(store))
+    }
+
+    fn turn_result(&self, store: &MsgStore) -> TurnResult {
+        TurnResult {
+            usage: TokenUsage::sum_from_store(store).map(TurnUsage::from),
+            ..TurnResult::from_entries(&latest_normalized_entries(store))
+        }
+    }
+
+    fn effective_mcp_config(&self) -> Option<serde_json::Value> {
+        let user_config = self
+            .default_mcp_config_path()
+            .and_then(|path| {
+                std::fs::read_to_string(&path)
+                    .ok()
+                    .map(|content| (path, content))
+            })
+            .and_then(|(path, content)| match serde_json::from_str(&content) {
+                Ok(config) => Some(config),
+                Err(err) => {
+                    tracing::warn!(
+                        "Ignoring unparsable opencode config {}: {err}",
+                        path.display()
+                    );
+                    None
+                }
+            });
+        let mut config = user_config.unwrap_or_else(|| serde_json::json!({}));
+        deep_merge(&mut config, self.mcp_overlay());
+        Some(config)
     }
 
     fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
@@ -154,16 +851,1119 @@ impl StandardCodingAgentExecutor for Opencode {
             AvailabilityInfo::NotFound
         }
     }
+
+    /// Run `opencode --version`, so that a broken node or npx install is reported instead of
+    /// the config directory left behind by an earlier run
+    async fn probe_availability(&self) -> AvailabilityInfo {
+        match self.version_check().await {
+            Ok(version) => {
+                tracing::debug!("opencode probe succeeded: {version}");
+                AvailabilityInfo::InstallationFound
+            }
+            Err(ExecutorError::ExecutableNotFound { .. }) => AvailabilityInfo::NotFound,
+            Err(err) => AvailabilityInfo::Broken {
+                reason: err.to_string(),
+            },
+        }
+    }
+}
+
+/// Replays a recorded opencode session through the ACP normalizer as if it were live.
+/// Record with [`crate::executors::recording::RecordingExecutor`].
+#[derive(Debug, Clone)]
+pub struct OpencodeReplay {
+    recording: Recording,
+}
+
+impl OpencodeReplay {
+    pub fn from_recording(path: impl AsRef<Path>) -> Result<Self, ExecutorError> {
+        Ok(Self {
+            recording: Recording::load(path)?,
+        })
+    }
+
+    pub fn replay(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
+        self.recording.replay_into(&msg_store);
+        crate::executors::acp::normalize_logs(msg_store, worktree_path);
+    }
+}
+
+impl From<Recording> for OpencodeReplay {
+    fn from(recording: Recording) -> Self {
+        Self { recording }
+    }
+}
+
+/// Title from the agent's closing message, or from the files it edited when it said nothing.
+/// The body carries the rest of that message and the list of edited files.
+fn summarize_entries(entries: &[NormalizedEntry]) -> Option<String> {
+    let turn = TurnResult::from_entries(entries);
+    let edited = turn.files_edited;
+
+    let mut lines = turn
+        .final_message
+        .iter()
+        .flat_map(|message| message.lines());
+    let title = lines
+        .by_ref()
+        .map(|line| line.trim().trim_start_matches(['#', '*', '-', ' ']).trim())
+        .find(|line| !line.is_empty())
+        .map(|line| truncate_title(line.trim_end_matches(['.', ':', '*'])))
+        .or_else(|| match edited.as_slice() {
+            [] => None,
+            [only] => Some(format!("Update {only}")),
+            many => Some(format!("Update {} files", many.len())),
+        })?;
+
+    let mut body = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+    if !edited.is_empty() {
+        if !body.is_empty() {
+            body.push_str("\n\n");
+        }
+        body.push_str("Files changed:");
+        for path in &edited {
+            body.push_str(&format!("\n- {path}"));
+        }
+    }
+
+    Some(if body.is_empty() {
+        title
+    } else {
+        format!("{title}\n\n{body}")
+    })
+}
+
+/// Shorten `line` to `SUMMARY_TITLE_MAX` chars plus an ellipsis, on a word boundary where
+/// possible
+fn truncate_title(line: &str) -> String {
+    if line.chars().count() <= SUMMARY_TITLE_MAX {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(SUMMARY_TITLE_MAX).collect();
+    let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(head, _)| head);
+    format!("{}…", cut.trim_end())
+}
+
+/// Retry `spawn` with exponential backoff while it fails with a retryable error, e.g. when
+/// `npx` hits a registry or network blip while starting opencode. Such a failure usually
+/// only shows once the process is running, so a child that exits unsuccessfully within
+/// `STARTUP_GRACE` of being spawned, before its session is up, counts as a retryable failure.
+async fn spawn_with_retry<F, Fut>(mut spawn: F) -> Result<SpawnedChild, ExecutorError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<SpawnedChild, ExecutorError>>,
+{
+    let mut attempt = 0;
+    (|| {
+        let spawned = spawn();
+        async move {
+            let mut spawned = spawned.await?;
+            ensure_started(&mut spawned).await?;
+            Ok(spawned)
+        }
+    })
+    .retry(
+        ExponentialBuilder::default()
+            .with_min_delay(Duration::from_millis(500))
+            .with_max_delay(Duration::from_secs(10))
+            .with_max_times(SPAWN_MAX_RETRIES)
+            .with_jitter(),
+    )
+    .when(ExecutorError::is_retryable)
+    .notify(|err: &ExecutorError, dur: Duration| {
+        attempt += 1;
+        tracing::warn!(
+            "opencode spawn failed (attempt {attempt}/{}), retrying after {:.2}s: {err}",
+            SPAWN_MAX_RETRIES + 1,
+            dur.as_secs_f64()
+        );
+    })
+    .await
+}
+
+/// Fail if the spawned child exits unsuccessfully within `STARTUP_GRACE`
+async fn ensure_started(spawned: &mut SpawnedChild) -> Result<(), ExecutorError> {
+    match tokio::time::timeout(STARTUP_GRACE, spawned.child.wait()).await {
+        // Still running: bootstrap is under way
+        Err(_) => Ok(()),
+        Ok(Ok(status)) if status.success() => Ok(()),
+        Ok(Ok(status)) => Err(ExecutorError::SpawnError(std::io::Error::other(format!(
+            "opencode exited with {status} while starting"
+        )))),
+        Ok(Err(e)) => Err(ExecutorError::Io(e)),
+    }
+}
+
+const DEFAULT_OPENCODE_VERSION: &str = "1.1.3";
+const SPAWN_MAX_RETRIES: usize = 3;
+const STARTUP_GRACE: Duration = Duration::from_secs(3);
+const MODEL_LIST_TIMEOUT: Duration = Duration::from_secs(60);
+const VERSION_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Name of the merged MCP config for the worktree at `current_dir`, stable across runs
+fn mcp_config_file_name(current_dir: &Path) -> String {
+    let digest = Sha256::digest(current_dir.to_string_lossy().as_bytes());
+    let hex = digest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!("{}.json", &hex[..16])
+}
+
+/// `opencode models` output keyed by base command. The map lock is only held to look up a
+/// key's cell, so one slow listing does not block listings for other commands.
+static MODEL_LIST_CACHE: LazyLock<
+    std::sync::Mutex<HashMap<String, Arc<tokio::sync::OnceCell<Vec<String>>>>>,
+> = LazyLock::new(Default::default);
+
+/// `provider/model` lines of `opencode models` output
+fn parse_model_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(|line| strip_ansi_escapes::strip_str(line).trim().to_string())
+        .filter(|line| !line.is_empty() && !line.contains(char::is_whitespace))
+        .filter(|line| {
+            line.split_once('/')
+                .is_some_and(|(p, m)| !p.is_empty() && !m.is_empty())
+        })
+        .collect()
+}
+
+/// An empty list means the output could not be understood, so the model is let through
+fn check_model_listed(model: &str, models: &[String]) -> Result<(), ExecutorError> {
+    if models.is_empty() || models.iter().any(|listed| listed == model) {
+        Ok(())
+    } else {
+        Err(ExecutorError::InvalidConfig(format!(
+            "model `{model}` is not available in opencode; run `opencode models` to list configured models"
+        )))
+    }
+}
+const OPENCODE_CONFIG: &str = "OPENCODE_CONFIG";
+const VIBE_KANBAN_MCP_SERVER: &str = "vibe_kanban";
+const OPENCODE_CONFIG_CONTENT: &str = "OPENCODE_CONFIG_CONTENT";
+const OPENCODE_PERMISSION: &str = "OPENCODE_PERMISSION";
+const FILE_TREE_LIMIT: usize = 200;
+const SUMMARY_TITLE_MAX: usize = 72;
+
+/// Indented listing of `root`, honoring `.gitignore`, with at most `limit` entries
+pub fn build_file_tree(root: &Path, limit: usize) -> String {
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+
+    let mut tree = String::new();
+    let mut count = 0;
+    for entry in walker.flatten().filter(|entry| entry.depth() > 0) {
+        if count == limit {
+            tree.push_str(&format!("... (truncated at {limit} entries)\n"));
+            break;
+        }
+        let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+        tree.push_str(&format!(
+            "{}{}{}\n",
+            "  ".repeat(entry.depth() - 1),
+            entry.file_name().to_string_lossy(),
+            if is_dir { "/" } else { "" }
+        ));
+        count += 1;
+    }
+    tree
 }
 
+// npm and most HTTP clients read the lowercase spellings, others only the uppercase ones
+const PROXY_VARS: &[&str] = &["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"];
+const NO_PROXY_VARS: &[&str] = &["NO_PROXY", "no_proxy"];
+
 fn default_to_true() -> bool {
     true
 }
 
-fn setup_approvals_env(auto_approve: bool, env: &ExecutionEnv) -> ExecutionEnv {
-    let mut env = env.clone();
-    if !auto_approve && !env.contains_key("OPENCODE_PERMISSION") {
-        env.insert("OPENCODE_PERMISSION", r#"{"edit": "ask", "bash": "ask", "webfetch": "ask", "doom_loop": "ask", "external_directory": "ask"}"#);
+/// RFC 7230 token characters
+fn is_valid_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
+fn looks_secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    ["key", "token", "secret", "auth", "password", "cookie"]
+        .iter()
+        .any(|needle| name.contains(needle))
+}
+
+/// `value` of env var `key` with secrets masked: wholesale for secret-looking keys, per entry
+/// for inline JSON config
+fn redact_env_value(key: &str, value: String) -> String {
+    if looks_secret(key) {
+        return "***".to_string();
+    }
+    match serde_json::from_str::<serde_json::Value>(&value) {
+        Ok(json) if json.is_object() => redact_secrets(&json).to_string(),
+        _ => value,
+    }
+}
+
+/// Copy of `value` with secret-looking object entries masked, for logging
+fn redact_secrets(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| {
+                let v = if v.is_string() && looks_secret(k) {
+                    serde_json::Value::String("***".to_string())
+                } else {
+                    redact_secrets(v)
+                };
+                (k.clone(), v)
+            })
+            .collect(),
+        serde_json::Value::Array(items) => items.iter().map(redact_secrets).collect(),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use workspace_utils::approvals::ApprovalStatus;
+
+    use super::*;
+    use crate::{
+        executors::acp::AcpEvent,
+        logs::{ActionType, NormalizedEntryType},
+    };
+
+    fn opencode() -> Opencode {
+        serde_json::from_value(serde_json::json!({})).unwrap()
+    }
+
+    #[test]
+    fn seed_reaches_harness_only_when_configured() {
+        assert!(opencode().harness().session_meta().is_none());
+
+        let configured = Opencode {
+            seed: Some(42),
+            ..opencode()
+        };
+        let meta = configured.harness().session_meta().unwrap();
+        assert_eq!(meta.get("seed"), Some(&serde_json::json!(42)));
+    }
+
+    #[test]
+    fn sampling_settings_reach_harness_and_are_validated() {
+        let configured = Opencode {
+            temperature: Some(0.5),
+            max_tokens: Some(4096),
+            ..opencode()
+        };
+        assert!(configured.validate_sampling().is_ok());
+        let meta = configured.harness().session_meta().unwrap();
+        assert_eq!(meta.get("temperature"), Some(&serde_json::json!(0.5)));
+        assert_eq!(meta.get("maxTokens"), Some(&serde_json::json!(4096)));
+
+        for invalid in [-0.1, 2.5, f32::NAN] {
+            let configured = Opencode {
+                temperature: Some(invalid),
+                ..opencode()
+            };
+            assert!(configured.validate_sampling().is_err());
+        }
+    }
+
+    #[test]
+    fn mcp_overlay_adds_vibe_kanban_server() {
+        let overlay = opencode().mcp_overlay();
+        let server = &overlay["mcp"]["vibe_kanban"];
+        assert_eq!(server["type"], "local");
+        assert_eq!(server["command"][0], "npx");
+
+        let mut user = serde_json::json!({ "mcp": { "mine": { "type": "remote" } } });
+        deep_merge(&mut user, overlay);
+        assert_eq!(user["mcp"]["mine"]["type"], "remote");
+        assert_eq!(user["mcp"]["vibe_kanban"]["type"], "local");
+    }
+
+    #[tokio::test]
+    async fn explicit_opencode_config_is_not_replaced() {
+        let mut env = ExecutionEnv::new();
+        env.insert(OPENCODE_CONFIG, "/custom/opencode.json");
+        opencode()
+            .write_mcp_config(Path::new("/tmp/worktree"), &mut env)
+            .await
+            .unwrap();
+        assert_eq!(
+            env.vars.get(OPENCODE_CONFIG).map(String::as_str),
+            Some("/custom/opencode.json")
+        );
+    }
+
+    #[test]
+    fn mcp_config_is_reused_per_worktree() {
+        let a = mcp_config_file_name(Path::new("/tmp/worktree-a"));
+        assert_eq!(a, mcp_config_file_name(Path::new("/tmp/worktree-a")));
+        assert_ne!(a, mcp_config_file_name(Path::new("/tmp/worktree-b")));
+    }
+
+    #[test]
+    fn model_list_is_parsed_and_checked() {
+        let output =
+            "\u{1b}[1manthropic/claude-sonnet-4\u{1b}[0m\nopenai/gpt-5\n\nWarning: cache stale\n";
+        let models = parse_model_list(output);
+        assert_eq!(models, vec!["anthropic/claude-sonnet-4", "openai/gpt-5"]);
+
+        assert!(check_model_listed("openai/gpt-5", &models).is_ok());
+        assert!(matches!(
+            check_model_listed("openai/gpt-9", &models),
+            Err(ExecutorError::InvalidConfig(_))
+        ));
+        assert!(check_model_listed("openai/gpt-9", &[]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn model_check_is_skipped_without_model() {
+        let result = opencode()
+            .ensure_model_available(Path::new("/nonexistent"), &ExecutionEnv::new())
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn system_prompt_reaches_session_meta_separately_from_append_prompt() {
+        let configured = Opencode {
+            system_prompt: Some("Follow the repo's CONTRIBUTING.md".to_string()),
+            append_prompt: AppendPrompt(Some("\nRun the tests.".to_string())),
+            ..opencode()
+        };
+        let meta = configured.harness().session_meta().unwrap();
+        assert_eq!(
+            meta.get("systemPrompt"),
+            Some(&serde_json::json!("Follow the repo's CONTRIBUTING.md"))
+        );
+        assert_eq!(
+            configured.append_prompt.combine_prompt("Fix the bug"),
+            "Fix the bug\nRun the tests."
+        );
+
+        let blank = Opencode {
+            system_prompt: Some("  ".to_string()),
+            ..opencode()
+        };
+        assert!(blank.harness().session_meta().is_none());
+    }
+
+    #[test]
+    fn version_pin_overrides_default_package() {
+        let base = |opencode: &Opencode| opencode.build_command_builder().unwrap().base;
+        assert_eq!(base(&opencode()), "npx -y opencode-ai@1.1.3");
+
+        let pinned = Opencode {
+            version: Some("1.2.0".to_string()),
+            ..opencode()
+        };
+        assert_eq!(base(&pinned), "npx -y opencode-ai@1.2.0");
+
+        for invalid in ["", "  ", "1.2.0 acp"] {
+            let configured = Opencode {
+                version: Some(invalid.to_string()),
+                ..opencode()
+            };
+            assert!(configured.build_command_builder().is_err());
+            assert!(configured.resolved_config(&ExecutionEnv::new())["config_error"].is_string());
+        }
+    }
+
+    #[test]
+    fn launcher_controls_base_command() {
+        let with_launcher = |launcher: OpencodeLauncher| Opencode {
+            launcher,
+            version: Some("1.2.0".to_string()),
+            ..opencode()
+        };
+        let base = |opencode: &Opencode| opencode.build_command_builder().unwrap().base;
+        assert_eq!(
+            base(&with_launcher(OpencodeLauncher::Bunx)),
+            "bunx opencode-ai@1.2.0"
+        );
+        let global = with_launcher(OpencodeLauncher::GlobalBinary {
+            path: " /opt/open code/bin/opencode ".to_string(),
+        });
+        assert_eq!(base(&global), "\"/opt/open code/bin/opencode\"");
+
+        for invalid in ["", "  ", "/bin/\"opencode"] {
+            let configured = with_launcher(OpencodeLauncher::GlobalBinary {
+                path: invalid.to_string(),
+            });
+            assert!(configured.build_command_builder().is_err());
+        }
+
+        let parsed: Opencode = serde_json::from_value(serde_json::json!({
+            "launcher": { "type": "global_binary", "path": "/usr/local/bin/opencode" },
+        }))
+        .unwrap();
+        assert_eq!(
+            parsed.launcher,
+            OpencodeLauncher::GlobalBinary {
+                path: "/usr/local/bin/opencode".to_string()
+            }
+        );
+        assert_eq!(opencode().launcher, OpencodeLauncher::Npx);
+    }
+
+    #[test]
+    fn provider_headers_reach_config_and_are_redacted() {
+        let configured = Opencode {
+            model: Some("gateway/gpt-4o".to_string()),
+            provider_headers: Some(vec![
+                ("X-Gateway-Key".to_string(), "s3cr3t".to_string()),
+                ("X-Team".to_string(), "platform".to_string()),
+            ]),
+            ..opencode()
+        };
+        let config = configured.provider_config().unwrap().unwrap();
+        let headers = &config["provider"]["gateway"]["options"]["headers"];
+        assert_eq!(headers["X-Gateway-Key"], "s3cr3t");
+        assert_eq!(headers["X-Team"], "platform");
+
+        let redacted = redact_secrets(&config);
+        let headers = &redacted["provider"]["gateway"]["options"]["headers"];
+        assert_eq!(headers["X-Gateway-Key"], "***");
+        assert_eq!(headers["X-Team"], "platform");
+    }
+
+    #[test]
+    fn resolved_config_redacts_secrets() {
+        let configured = Opencode {
+            model: Some("gateway/gpt-4o".to_string()),
+            auto_approve: false,
+            provider_headers: Some(vec![("X-Api-Key".to_string(), "s3cr3t".to_string())]),
+            ..opencode()
+        };
+        let mut env = ExecutionEnv::new();
+        env.insert("OPENAI_API_KEY", "sk-123");
+        env.insert("VK_PROJECT_NAME", "demo");
+
+        let resolved = configured.resolved_config(&env);
+        assert_eq!(resolved["model"], "gateway/gpt-4o");
+        assert_eq!(resolved["env"]["OPENAI_API_KEY"], "***");
+        assert_eq!(resolved["env"]["VK_PROJECT_NAME"], "demo");
+        assert_eq!(resolved["env"]["OPENCODE_PERMISSION"]["bash"], "ask");
+        assert_eq!(
+            resolved["env"]["OPENCODE_CONFIG_CONTENT"]["provider"]["gateway"]["options"]["headers"]
+                ["X-Api-Key"],
+            "***"
+        );
+        assert!(!resolved.to_string().contains("s3cr3t"));
+    }
+
+    #[test]
+    fn resolved_config_matches_what_spawn_uses() {
+        let configured = Opencode {
+            temperature: Some(0.2),
+            max_tokens: Some(4096),
+            system_prompt: Some("Be terse".to_string()),
+            approval_timeout_secs: Some(30),
+            env_denylist: vec!["AWS_*".to_string()],
+            ..opencode()
+        };
+        let mut env = ExecutionEnv::new();
+        env.insert("AWS_SECRET", "hidden");
+        env.insert("VK_PROJECT_NAME", "demo");
+
+        let resolved = configured.resolved_config(&env);
+        assert!(resolved["env"].get("AWS_SECRET").is_none());
+        assert_eq!(resolved["env"]["VK_PROJECT_NAME"], "demo");
+        assert_eq!(resolved["max_tokens"], 4096);
+        assert_eq!(resolved["system_prompt"], "Be terse");
+        assert_eq!(resolved["approval"]["timeout_secs"], 30);
+        assert_eq!(
+            resolved["mcp_config"]["mcp"]["vibe_kanban"]["type"],
+            "local"
+        );
+
+        env.insert(OPENCODE_CONFIG, "/custom/opencode.json");
+        assert!(configured.resolved_config(&env)["mcp_config"].is_null());
+    }
+
+    #[test]
+    fn bash_allowlist_shapes_permission_env() {
+        let configured = Opencode {
+            auto_approve: false,
+            bash_allowlist: vec!["npm test".to_string(), "cargo build".to_string()],
+            ..opencode()
+        };
+        let env = configured.setup_env(&ExecutionEnv::new()).unwrap();
+        let permission: serde_json::Value =
+            serde_json::from_str(env.vars.get(OPENCODE_PERMISSION).unwrap()).unwrap();
+        assert_eq!(permission["bash"]["npm test"], "allow");
+        assert_eq!(permission["bash"]["cargo build *"], "allow");
+        assert_eq!(permission["bash"]["*"], "ask");
+        assert_eq!(permission["edit"], "ask");
+
+        let default = Opencode {
+            auto_approve: false,
+            ..opencode()
+        };
+        let env = default.setup_env(&ExecutionEnv::new()).unwrap();
+        let permission: serde_json::Value =
+            serde_json::from_str(env.vars.get(OPENCODE_PERMISSION).unwrap()).unwrap();
+        assert_eq!(permission["bash"], "ask");
+
+        let invalid = Opencode {
+            auto_approve: false,
+            bash_allowlist: vec!["  ".to_string()],
+            ..opencode()
+        };
+        assert!(invalid.setup_env(&ExecutionEnv::new()).is_err());
+    }
+
+    #[test]
+    fn granular_permissions_replace_blanket_ask() {
+        let configured = Opencode {
+            permissions: Some(OpencodePermissions {
+                edit: PermissionLevel::Allow,
+                webfetch: PermissionLevel::Deny,
+                ..Default::default()
+            }),
+            ..opencode()
+        };
+        let env = configured.setup_env(&ExecutionEnv::new()).unwrap();
+        let permission: serde_json::Value =
+            serde_json::from_str(env.vars.get(OPENCODE_PERMISSION).unwrap()).unwrap();
+        assert_eq!(permission["edit"], "allow");
+        assert_eq!(permission["bash"], "ask");
+        assert_eq!(permission["webfetch"], "deny");
+
+        let mut base = ExecutionEnv::new();
+        base.insert(OPENCODE_PERMISSION, r#"{"edit":"deny"}"#);
+        let env = configured.setup_env(&base).unwrap();
+        assert_eq!(
+            env.vars.get(OPENCODE_PERMISSION).unwrap(),
+            r#"{"edit":"deny"}"#
+        );
+
+        assert!(opencode().permission_config().unwrap().is_none());
+    }
+
+    #[test]
+    fn provider_headers_reject_malformed_names() {
+        let configured = Opencode {
+            model: Some("gateway/gpt-4o".to_string()),
+            provider_headers: Some(vec![("Bad Header".to_string(), "x".to_string())]),
+            ..opencode()
+        };
+        assert!(configured.provider_config().is_err());
+    }
+
+    #[tokio::test]
+    async fn channel_approvals_are_used_when_not_auto_approving() {
+        let (service, mut receiver) =
+            crate::approvals::ChannelApprovalService::new(std::time::Duration::from_secs(5));
+        let mut configured = Opencode {
+            auto_approve: false,
+            ..opencode()
+        };
+        configured.use_approvals(Arc::new(service));
+
+        let approvals = configured.approvals.clone().unwrap();
+        tokio::spawn(async move {
+            let pending = receiver.recv().await.unwrap();
+            pending.respond(ApprovalStatus::Approved);
+        });
+        let status = approvals
+            .request_tool_approval("bash", serde_json::json!({}), "call-1")
+            .await
+            .unwrap();
+        assert!(matches!(status, ApprovalStatus::Approved));
+    }
+
+    #[tokio::test]
+    async fn approval_policy_answers_before_the_approval_service() {
+        let (service, mut receiver) =
+            crate::approvals::ChannelApprovalService::new(std::time::Duration::from_secs(5));
+        let mut configured: Opencode = serde_json::from_value(serde_json::json!({
+            "auto_approve": false,
+            "approval_policy": {
+                "rules": [{ "tool": "read", "decision": "allow" }],
+                "default": "ask",
+            },
+        }))
+        .unwrap();
+        configured.use_approvals(Arc::new(service));
+
+        let approvals = configured
+            .run_approvals(Path::new("/tmp/worktree"))
+            .unwrap()
+            .unwrap();
+        let status = approvals
+            .request_tool_approval("read", serde_json::json!({}), "call-1")
+            .await
+            .unwrap();
+        assert!(matches!(status, ApprovalStatus::Approved));
+        assert!(receiver.try_recv().is_err());
+
+        tokio::spawn(async move {
+            let pending = receiver.recv().await.unwrap();
+            pending.respond(ApprovalStatus::Denied { reason: None });
+        });
+        let status = approvals
+            .request_tool_approval("bash", serde_json::json!({}), "call-2")
+            .await
+            .unwrap();
+        assert!(matches!(status, ApprovalStatus::Denied { reason: None }));
+
+        configured.auto_approve = true;
+        assert!(
+            configured
+                .run_approvals(Path::new("/tmp/worktree"))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn probe_distinguishes_missing_and_broken_installs() {
+        let with_base = |base: &str| Opencode {
+            cmd: CmdOverrides {
+                base_command_override: Some(base.to_string()),
+                ..Default::default()
+            },
+            ..opencode()
+        };
+
+        assert!(matches!(
+            with_base("vk-no-such-opencode-binary")
+                .probe_availability()
+                .await,
+            AvailabilityInfo::NotFound
+        ));
+        #[cfg(unix)]
+        {
+            assert!(matches!(
+                with_base("true").probe_availability().await,
+                AvailabilityInfo::InstallationFound
+            ));
+            assert!(matches!(
+                with_base("false").probe_availability().await,
+                AvailabilityInfo::Broken { .. }
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn approval_timeout_is_recorded_as_system_message() {
+        let msg_store = Arc::new(MsgStore::new());
+        opencode().normalize_logs(msg_store.clone(), Path::new("/tmp/test-worktree"));
+
+        msg_store.push_stdout(format!(
+            "{}\n",
+            AcpEvent::ApprovalTimedOut {
+                tool_call_id: "call-1".to_string(),
+                action: ApprovalTimeoutAction::Deny,
+                after_secs: 30,
+            }
+        ));
+        tokio::time::sleep(tokio::time::Duration::from_millis(150)).await;
+        msg_store.push_finished();
+
+        let entries = latest_normalized_entries(&msg_store);
+        assert!(entries.iter().any(|e| {
+            matches!(e.entry_type, NormalizedEntryType::SystemMessage)
+                && e.content.contains("within 30s; denied automatically")
+        }));
+    }
+
+    #[tokio::test]
+    async fn dry_run_previews_command_without_spawning() {
+        let configured = Opencode {
+            append_prompt: AppendPrompt(Some("Be brief.".to_string())),
+            http_proxy: Some("http://proxy:8080".to_string()),
+            cmd: CmdOverrides {
+                base_command_override: Some("sh".to_string()),
+                additional_params: Some(vec!["--print-logs".to_string()]),
+                env: Some(HashMap::from([(
+                    "OPENAI_API_KEY".to_string(),
+                    "sk-test".to_string(),
+                )])),
+            },
+            ..opencode()
+        };
+        let mut env = ExecutionEnv::new();
+        env.insert("VK_PROJECT_NAME", "demo");
+
+        let preview = configured
+            .dry_run(Path::new("/tmp"), "Fix the bug", &env)
+            .await
+            .unwrap();
+
+        assert!(preview.program.ends_with("sh"));
+        assert_eq!(preview.args, vec!["acp", "--print-logs"]);
+        assert!(preview.prompt.starts_with("Fix the bug"));
+        assert!(preview.prompt.contains("Be brief."));
+        assert_eq!(
+            preview.env.get("HTTP_PROXY").map(String::as_str),
+            Some("http://proxy:8080")
+        );
+        assert_eq!(
+            preview.env.get("OPENAI_API_KEY").map(String::as_str),
+            Some("***")
+        );
+        assert!(!preview.env.contains_key("VK_PROJECT_NAME"));
+    }
+
+    #[tokio::test]
+    async fn follow_up_on_unknown_session_reports_session_not_found() {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        assert!(!opencode().harness().session_exists(&session_id));
+
+        let result = opencode()
+            .spawn_follow_up(
+                Path::new("/tmp"),
+                "continue",
+                &session_id,
+                &ExecutionEnv::new(),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(ExecutorError::SessionNotFound(id)) if id == session_id
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_is_retried_when_child_exits_while_starting() {
+        use command_group::AsyncCommandGroup;
+
+        let mut attempts = 0;
+        let result = spawn_with_retry(|| {
+            attempts += 1;
+            async {
+                let child = tokio::process::Command::new("sh")
+                    .args(["-c", "exit 1"])
+                    .group_spawn()?;
+                Ok(SpawnedChild::from(child))
+            }
+        })
+        .await;
+        assert!(matches!(result, Err(ExecutorError::SpawnError(_))));
+        assert_eq!(attempts, SPAWN_MAX_RETRIES + 1);
+    }
+
+    #[test]
+    fn env_filter_applies_only_when_configured() {
+        let mut env = ExecutionEnv::new();
+        env.insert("OPENCODE_PERMISSION", "{}");
+        env.insert("AWS_SECRET_ACCESS_KEY", "secret");
+
+        let unfiltered = opencode().filter_env(env.clone()).unwrap();
+        assert_eq!(unfiltered.vars, env.vars);
+
+        let configured = Opencode {
+            env_allowlist: vec!["OPENCODE_*".to_string(), "AWS_*".to_string()],
+            env_denylist: vec!["AWS_*".to_string()],
+            ..opencode()
+        };
+        let filtered = configured.filter_env(env.clone()).unwrap();
+        assert!(filtered.contains_key("OPENCODE_PERMISSION"));
+        assert!(!filtered.contains_key("AWS_SECRET_ACCESS_KEY"));
+
+        let invalid = Opencode {
+            env_denylist: vec!["AWS_[".to_string()],
+            ..opencode()
+        };
+        assert!(matches!(
+            invalid.filter_env(env),
+            Err(ExecutorError::InvalidConfig(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn env_filter_keeps_opencode_vars() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut env = ExecutionEnv::new();
+        env.insert("VK_PROJECT_ID", "project");
+        let configured = Opencode {
+            auto_approve: false,
+            env_allowlist: vec!["PATH".to_string()],
+            ..opencode()
+        };
+
+        let env = configured.spawn_env(dir.path(), &env).await.unwrap();
+        assert!(env.contains_key(OPENCODE_PERMISSION));
+        assert!(env.contains_key("VK_PROJECT_ID"));
+        assert!(!env.contains_key("HOME"));
+    }
+
+    #[tokio::test]
+    async fn replay_feeds_recorded_frames_through_normalizer() {
+        let frames = [
+            crate::executors::acp::AcpEvent::SessionStart("sess-1".to_string()).to_string(),
+            crate::executors::acp::AcpEvent::Done("end_turn".to_string()).to_string(),
+        ];
+        let replay = OpencodeReplay::from(Recording::parse(&frames.join("\n")));
+        let msg_store = Arc::new(MsgStore::new());
+
+        replay.replay(msg_store.clone(), Path::new("/tmp/test-worktree"));
+        tokio::time::sleep(tokio::time::Duration::from_millis(150)).await;
+
+        let history = msg_store.get_history();
+        assert!(history.iter().any(|m| matches!(
+            m,
+            workspace_utils::log_msg::LogMsg::SessionId(id) if id == "sess-1"
+        )));
+    }
+
+    #[tokio::test]
+    async fn normalized_entries_reach_output_sink() {
+        struct ChannelSink(std::sync::mpsc::Sender<String>);
+
+        impl OutputSink for ChannelSink {
+            fn on_entry(&self, _index: usize, entry: &crate::logs::NormalizedEntry) {
+                let _ = self.0.send(entry.content.clone());
+            }
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let executor = opencode().with_output_sink(Arc::new(ChannelSink(tx)));
+        let msg_store = Arc::new(MsgStore::new());
+        executor.normalize_logs(msg_store.clone(), Path::new("/tmp/test-worktree"));
+
+        msg_store.push_stdout(format!(
+            "{}\n",
+            crate::executors::acp::AcpEvent::Error("provider unavailable".to_string())
+        ));
+        tokio::time::sleep(tokio::time::Duration::from_millis(150)).await;
+        msg_store.push_finished();
+
+        let delivered: Vec<String> = rx.try_iter().collect();
+        assert!(
+            delivered
+                .iter()
+                .any(|content| content.contains("provider unavailable"))
+        );
+    }
+
+    fn entry(entry_type: crate::logs::NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn file_edit(path: &str) -> NormalizedEntry {
+        entry(
+            NormalizedEntryType::ToolUse {
+                tool_name: "edit".to_string(),
+                action_type: ActionType::FileEdit {
+                    path: path.to_string(),
+                    changes: vec![],
+                },
+                status: crate::logs::ToolStatus::Success,
+            },
+            path,
+        )
+    }
+
+    #[test]
+    fn summarizes_final_message_and_edited_files() {
+        use crate::logs::utils::ConversationPatch;
+
+        let store = MsgStore::new();
+        let entries = [
+            entry(NormalizedEntryType::UserMessage, "add retries"),
+            file_edit("src/fetch.rs"),
+            entry(NormalizedEntryType::AssistantMessage, "Working on it"),
+            file_edit("src/fetch.rs"),
+            file_edit("tests/fetch.rs"),
+        ];
+        for (index, e) in entries.into_iter().enumerate() {
+            store.push_patch(ConversationPatch::add_normalized_entry(index, e));
+        }
+        // Streaming updates replace the earlier message in place
+        store.push_patch(ConversationPatch::replace(
+            2,
+            entry(
+                NormalizedEntryType::AssistantMessage,
+                "## Added retries to the fetcher.\n\nRequests are retried three times.",
+            ),
+        ));
+
+        assert_eq!(
+            opencode().summarize_session(&store).as_deref(),
+            Some(
+                "Added retries to the fetcher\n\nRequests are retried three times.\n\n\
+                 Files changed:\n- src/fetch.rs\n- tests/fetch.rs"
+            )
+        );
+    }
+
+    #[test]
+    fn turn_result_includes_reported_usage() {
+        use crate::logs::utils::ConversationPatch;
+
+        let store = MsgStore::new();
+        store.push_patch(ConversationPatch::add_normalized_entry(
+            0,
+            file_edit("src/fetch.rs"),
+        ));
+        store.push_patch(ConversationPatch::add_normalized_entry(
+            1,
+            entry(NormalizedEntryType::AssistantMessage, "Done"),
+        ));
+        assert_eq!(opencode().turn_result(&store).usage, None);
+
+        let usage = |input_tokens, output_tokens| {
+            AcpEvent::Usage(TokenUsage {
+                input_tokens,
+                output_tokens,
+                ..Default::default()
+            })
+        };
+        store.push_stdout(format!("{}\n", usage(100, 20)));
+        store.push_stdout(format!("{}\n", AcpEvent::Done("end_turn".to_string())));
+        store.push_stdout(format!("{}\n", usage(50, 5)));
+
+        let result = opencode().turn_result(&store);
+        assert_eq!(
+            result,
+            TurnResult {
+                final_message: Some("Done".to_string()),
+                files_edited: vec!["src/fetch.rs".to_string()],
+                tool_calls: 1,
+                usage: Some(TurnUsage {
+                    input_tokens: 150,
+                    output_tokens: 25,
+                }),
+                success: true,
+                error: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn streamed_usage_is_counted_once_per_message() {
+        let executor = opencode();
+        let store = Arc::new(MsgStore::new());
+        let usage = |message_id: &str, input_tokens, output_tokens| {
+            AcpEvent::Usage(TokenUsage {
+                input_tokens,
+                output_tokens,
+                message_id: Some(message_id.to_string()),
+                model: Some("anthropic/claude-sonnet-4".to_string()),
+            })
+        };
+        // Partial report for m1, its final report, then a second message
+        for event in [
+            usage("m1", 100, 5),
+            usage("m1", 100, 40),
+            usage("m2", 150, 10),
+        ] {
+            store.push_stdout(format!("{event}\n"));
+        }
+        store.push_finished();
+
+        assert_eq!(executor.last_usage(), None);
+        executor.normalize_logs(store.clone(), Path::new("/tmp/test-worktree"));
+        tokio::time::sleep(tokio::time::Duration::from_millis(150)).await;
+
+        let expected = UsageStats {
+            prompt_tokens: 250,
+            completion_tokens: 50,
+            model: Some("anthropic/claude-sonnet-4".to_string()),
+        };
+        assert_eq!(executor.last_usage(), Some(expected.clone()));
+
+        let entries = latest_normalized_entries(&store);
+        let [entry] = entries.as_slice() else {
+            panic!("expected a single usage entry, got {entries:?}");
+        };
+        let reported: UsageStats =
+            serde_json::from_value(entry.metadata.as_ref().unwrap()["usage"].clone()).unwrap();
+        assert_eq!(reported, expected);
+        assert_eq!(
+            TokenUsage::sum_from_store(&store).map(TurnUsage::from),
+            Some(TurnUsage {
+                input_tokens: 250,
+                output_tokens: 50,
+            })
+        );
+    }
+
+    #[test]
+    fn summary_falls_back_to_edits_and_needs_content() {
+        assert_eq!(summarize_entries(&[]), None);
+        assert_eq!(
+            summarize_entries(&[entry(NormalizedEntryType::UserMessage, "hi")]),
+            None
+        );
+        assert_eq!(
+            summarize_entries(&[file_edit("README.md")]).as_deref(),
+            Some("Update README.md\n\nFiles changed:\n- README.md")
+        );
+
+        let long = "word ".repeat(30);
+        let summary =
+            summarize_entries(&[entry(NormalizedEntryType::AssistantMessage, &long)]).unwrap();
+        assert!(summary.ends_with('…'));
+        assert!(summary.chars().count() <= SUMMARY_TITLE_MAX + 1);
+    }
+
+    #[test]
+    fn proxy_settings_are_exported_only_when_configured() {
+        let env = opencode().setup_env(&ExecutionEnv::new()).unwrap();
+        for key in PROXY_VARS.iter().chain(NO_PROXY_VARS) {
+            assert!(!env.contains_key(key), "{key} should be unset");
+        }
+
+        let configured = Opencode {
+            http_proxy: Some("http://proxy.internal:3128".to_string()),
+            no_proxy: Some("localhost,127.0.0.1".to_string()),
+            ..opencode()
+        };
+        let mut base = ExecutionEnv::new();
+        base.insert("NO_PROXY", ".corp");
+        let env = configured.setup_env(&base).unwrap();
+        for key in PROXY_VARS {
+            assert_eq!(env.vars.get(*key).unwrap(), "http://proxy.internal:3128");
+        }
+        assert_eq!(env.vars.get("no_proxy").unwrap(), "localhost,127.0.0.1");
+        assert_eq!(env.vars.get("NO_PROXY").unwrap(), ".corp");
+    }
+
+    #[test]
+    fn file_tree_respects_gitignore_and_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("target/debug")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+        std::fs::write(root.join("target/debug/app"), "").unwrap();
+        std::fs::write(root.join("build.log"), "").unwrap();
+        std::fs::write(root.join("Cargo.toml"), "").unwrap();
+
+        let tree = build_file_tree(root, 10);
+        assert_eq!(tree, ".gitignore\nCargo.toml\nsrc/\n  main.rs\n");
+
+        let truncated = build_file_tree(root, 2);
+        assert_eq!(
+            truncated,
+            ".gitignore\nCargo.toml\n... (truncated at 2 entries)\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_prompt_sequence_is_rejected() {
+        let result = opencode()
+            .spawn_sequence(Path::new("."), &[], &ExecutionEnv::new())
+            .await;
+        assert!(matches!(result, Err(ExecutorError::InvalidConfig(_))));
     }
-    env
 }
@@ -0,0 +1,178 @@
+//! Record and replay raw executor output.
+//!
+//! [`RecordingExecutor`] wraps any executor and tees the child's stdout into a file during a real
+//! run. [`Recording`] loads such a file and pushes the frames into a [`MsgStore`] as if they were
+//! streamed live, so normalizers can be exercised deterministically without calling a model.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use tokio::io::AsyncWriteExt;
+use workspace_utils::msg_store::MsgStore;
+
+use crate::{
+    actions::ExecutorAction,
+    approvals::ExecutorApprovalService,
+    env::ExecutionEnv,
+    executors::{AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor},
+    stdout_dup::duplicate_stdout,
+};
+
+/// Wraps an executor and writes every stdout chunk of the spawned process to `path`.
+#[derive(Debug, Clone)]
+pub struct RecordingExecutor<E> {
+    inner: E,
+    path: PathBuf,
+}
+
+impl<E> RecordingExecutor<E> {
+    pub fn new(inner: E, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            path: path.into(),
+        }
+    }
+
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+
+    async fn record(&self, mut spawned: SpawnedChild) -> Result<SpawnedChild, ExecutorError> {
+        let mut stdout = duplicate_stdout(&mut spawned.child)?;
+        let mut file = tokio::fs::File::create(&self.path)
+            .await
+            .map_err(ExecutorError::Io)?;
+        let path = self.path.clone();
+
+        tokio::spawn(async move {
+            while let Some(Ok(chunk)) = stdout.next().await {
+                if let Err(e) = file.write_all(chunk.as_bytes()).await {
+                    tracing::warn!("Failed to write recording to {}: {e}", path.display());
+                    return;
+                }
+            }
+            let _ = file.flush().await;
+        });
+
+        Ok(spawned)
+    }
+}
+
+#[async_trait]
+impl<E> StandardCodingAgentExecutor for RecordingExecutor<E>
+where
+    E: StandardCodingAgentExecutor + Send + Sync,
+{
+    fn use_approvals(&mut self, approvals: Arc<dyn ExecutorApprovalService>) {
+        self.inner.use_approvals(approvals);
+    }
+
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let spawned = self.inner.spawn(current_dir, prompt, env).await?;
+        self.record(spawned).await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let spawned = self
+            .inner
+            .spawn_follow_up(current_dir, prompt, session_id, env)
+            .await?;
+        self.record(spawned).await
+    }
+
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
+        self.inner.normalize_logs(msg_store, worktree_path);
+    }
+
+    fn default_mcp_config_path(&self) -> Option<PathBuf> {
+        self.inner.default_mcp_config_path()
+    }
+
+    async fn get_setup_helper_action(&self) -> Result<ExecutorAction, ExecutorError> {
+        self.inner.get_setup_helper_action().await
+    }
+
+    fn get_availability_info(&self) -> AvailabilityInfo {
+        self.inner.get_availability_info()
+    }
+
+    async fn probe_availability(&self) -> AvailabilityInfo {
+        self.inner.probe_availability().await
+    }
+}
+
+/// Raw stdout frames captured by [`RecordingExecutor`], one per line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    frames: Vec<String>,
+}
+
+impl Recording {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ExecutorError> {
+        let contents = std::fs::read_to_string(path).map_err(ExecutorError::Io)?;
+        Ok(Self::parse(&contents))
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        Self {
+            frames: contents
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    pub fn frames(&self) -> &[String] {
+        &self.frames
+    }
+
+    /// Push every frame to `msg_store` as stdout, then mark the stream finished.
+    pub fn replay_into(&self, msg_store: &MsgStore) {
+        for frame in &self.frames {
+            msg_store.push_stdout(format!("{frame}\n"));
+        }
+        msg_store.push_finished();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use workspace_utils::log_msg::LogMsg;
+
+    use super::*;
+
+    #[test]
+    fn parse_skips_blank_lines() {
+        let recording = Recording::parse("{\"a\":1}\n\n  \n{\"b\":2}\n");
+        assert_eq!(recording.frames(), ["{\"a\":1}", "{\"b\":2}"]);
+    }
+
+    #[test]
+    fn replay_pushes_frames_then_finishes() {
+        let recording = Recording::parse("one\ntwo\n");
+        let msg_store = MsgStore::new();
+        recording.replay_into(&msg_store);
+
+        let history = msg_store.get_history();
+        assert_eq!(history.len(), 3);
+        assert!(matches!(&history[0], LogMsg::Stdout(s) if s == "one\n"));
+        assert!(matches!(&history[1], LogMsg::Stdout(s) if s == "two\n"));
+        assert!(matches!(history[2], LogMsg::Finished));
+    }
+}
@@ -0,0 +1,188 @@
+use std::{path::Path, process::Stdio, sync::Arc};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use futures::StreamExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use ts_rs::TS;
+use uuid::Uuid;
+use workspace_utils::{msg_store::MsgStore, shell::resolve_executable_path_blocking};
+
+use crate::{
+    command::{CmdOverrides, CommandBuilder, apply_overrides},
+    env::ExecutionEnv,
+    executors::{AvailabilityInfo, ExecutorError, SpawnedChild, StandardCodingAgentExecutor},
+    logs::{
+        NormalizedEntry, NormalizedEntryType,
+        stderr_processor::normalize_stderr_logs,
+        utils::{ConversationPatch, EntryIndexProvider},
+    },
+    stdout_dup,
+};
+
+/// Runs a fixed shell command instead of a coding agent, for deterministic steps that should
+/// still go through the executor log pipeline. The prompt is exported as `VK_PROMPT`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct ShellExecutor {
+    /// Command to run in the worktree, e.g. `cargo test --workspace`
+    pub command: String,
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+}
+
+impl ShellExecutor {
+    const SESSION_PREFIX: &'static str = "[shell-session] ";
+
+    const PROMPT_VAR: &'static str = "VK_PROMPT";
+
+    fn build_command_builder(&self) -> Result<CommandBuilder, ExecutorError> {
+        if self.command.trim().is_empty() {
+            return Err(ExecutorError::InvalidConfig(
+                "shell executor command must not be empty".to_string(),
+            ));
+        }
+        Ok(apply_overrides(
+            CommandBuilder::new(self.command.trim()),
+            &self.cmd,
+        ))
+    }
+
+    async fn spawn_command(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let (program_path, args) = self
+            .build_command_builder()?
+            .build_initial()?
+            .into_resolved()
+            .await?;
+
+        let mut command = Command::new(program_path);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .current_dir(current_dir)
+            .args(&args);
+
+        env.clone()
+            .with_profile(&self.cmd)
+            .apply_to_command(&mut command);
+        command.env(Self::PROMPT_VAR, prompt);
+
+        let mut child = command.group_spawn()?;
+
+        let (_, appender) = stdout_dup::tee_stdout_with_appender(&mut child)?;
+        appender.append_line(format!("{}{}\n", Self::SESSION_PREFIX, session_id));
+
+        Ok(child.into())
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for ShellExecutor {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_command(current_dir, prompt, &Uuid::new_v4().to_string(), env)
+            .await
+    }
+
+    /// The command keeps no state, so a follow-up simply runs it again
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_command(current_dir, prompt, session_id, env)
+            .await
+    }
+
+    /// Every stdout line becomes its own text entry; stderr is clustered into error entries
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, _worktree_path: &Path) {
+        let entry_index_provider = EntryIndexProvider::start_from(&msg_store);
+        normalize_stderr_logs(msg_store.clone(), entry_index_provider.clone());
+
+        tokio::spawn(async move {
+            let mut stdout_lines = msg_store.stdout_lines_stream();
+            while let Some(Ok(line)) = stdout_lines.next().await {
+                if let Some(session_id) = line.strip_prefix(Self::SESSION_PREFIX) {
+                    msg_store.push_session_id(session_id.trim().to_string());
+                    continue;
+                }
+                let Some(entry) = line_entry(&line) else {
+                    continue;
+                };
+                let id = entry_index_provider.next();
+                msg_store.push_patch(ConversationPatch::add_normalized_entry(id, entry));
+            }
+        });
+    }
+
+    fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+
+    fn get_availability_info(&self) -> AvailabilityInfo {
+        let program = shlex::split(self.command.trim())
+            .and_then(|parts| parts.into_iter().next())
+            .and_then(|program| resolve_executable_path_blocking(&program));
+        if program.is_some() {
+            AvailabilityInfo::InstallationFound
+        } else {
+            AvailabilityInfo::NotFound
+        }
+    }
+}
+
+fn line_entry(line: &str) -> Option<NormalizedEntry> {
+    let content = strip_ansi_escapes::strip_str(line.trim_end());
+    if content.trim().is_empty() {
+        return None;
+    }
+    Some(NormalizedEntry {
+        timestamp: None,
+        entry_type: NormalizedEntryType::AssistantMessage,
+        content,
+        metadata: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_become_text_entries() {
+        let entry = line_entry("\u{1b}[32mtest result: ok\u{1b}[0m\r").unwrap();
+        assert!(matches!(
+            entry.entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(entry.content, "test result: ok");
+        assert!(line_entry("   ").is_none());
+    }
+
+    #[test]
+    fn test_empty_command_is_rejected() {
+        let shell: ShellExecutor = serde_json::from_value(serde_json::json!({
+            "command": "  "
+        }))
+        .unwrap();
+        assert!(matches!(
+            shell.build_command_builder(),
+            Err(ExecutorError::InvalidConfig(_))
+        ));
+    }
+}
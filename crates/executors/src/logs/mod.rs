@@ -4,7 +4,9 @@ use ts_rs::TS;
 use workspace_utils::approvals::ApprovalStatus;
 
 pub mod plain_text_processor;
+pub mod sink;
 pub mod stderr_processor;
+pub mod turn_result;
 pub mod utils;
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -95,6 +97,22 @@ pub enum NormalizedEntryType {
         execution_processes: usize,
         needs_setup: bool,
     },
+    /// Net change to `path` over the current turn, as unified diff hunks. The ranges span
+    /// every hunk in `hunk`.
+    FileEdit {
+        path: String,
+        old_range: LineRange,
+        new_range: LineRange,
+        hunk: String,
+    },
+}
+
+/// `count` lines of a file starting at line `start`, numbered as in unified diff hunk headers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LineRange {
+    pub start: usize,
+    pub count: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -0,0 +1,97 @@
+//! Forward normalized log entries to an external consumer alongside the `MsgStore`.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use tokio::task::JoinHandle;
+use workspace_utils::{log_msg::LogMsg, msg_store::MsgStore};
+
+use crate::logs::{NormalizedEntry, utils::patch::extract_normalized_entry_from_patch};
+
+/// Receives every normalized entry an executor produces, e.g. to tee them into a log
+/// aggregator. Entries are updated in place while they stream, so the same `index` may be
+/// delivered several times; the latest delivery wins.
+pub trait OutputSink: Send + Sync {
+    fn on_entry(&self, index: usize, entry: &NormalizedEntry);
+
+    /// Called once the process has finished and no more entries will arrive
+    fn on_finished(&self) {}
+}
+
+/// Feed the normalized entries written to `msg_store`, past and future, into `sink`
+/// until the store is finished
+pub fn spawn_sink_forwarder(msg_store: Arc<MsgStore>, sink: Arc<dyn OutputSink>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut stream = msg_store.history_plus_stream();
+        while let Some(Ok(msg)) = stream.next().await {
+            match msg {
+                LogMsg::JsonPatch(patch) => {
+                    if let Some((index, entry)) = extract_normalized_entry_from_patch(&patch) {
+                        sink.on_entry(index, &entry);
+                    }
+                }
+                LogMsg::Finished => break,
+                _ => {}
+            }
+        }
+        sink.on_finished();
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::logs::{NormalizedEntryType, utils::ConversationPatch};
+
+    #[derive(Default)]
+    struct CollectingSink {
+        entries: Mutex<Vec<(usize, String)>>,
+    }
+
+    impl OutputSink for CollectingSink {
+        fn on_entry(&self, index: usize, entry: &NormalizedEntry) {
+            self.entries
+                .lock()
+                .unwrap()
+                .push((index, entry.content.clone()));
+        }
+    }
+
+    fn message(content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn forwards_history_and_live_entries_until_finished() {
+        let msg_store = Arc::new(MsgStore::new());
+        msg_store.push_patch(ConversationPatch::add_normalized_entry(0, message("first")));
+
+        let sink = Arc::new(CollectingSink::default());
+        let forwarder = spawn_sink_forwarder(msg_store.clone(), sink.clone());
+
+        msg_store.push_stdout("raw output is not forwarded");
+        msg_store.push_patch(ConversationPatch::replace(0, message("first, edited")));
+        msg_store.push_patch(ConversationPatch::add_normalized_entry(
+            1,
+            message("second"),
+        ));
+        msg_store.push_finished();
+        forwarder.await.unwrap();
+
+        assert_eq!(
+            *sink.entries.lock().unwrap(),
+            vec![
+                (0, "first".to_string()),
+                (0, "first, edited".to_string()),
+                (1, "second".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Structured outcome of an agent turn, for consumers that should not parse log text.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::logs::{ActionType, NormalizedEntry, NormalizedEntryType};
+
+/// What an agent turn produced, aggregated from its normalized entries
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, TS)]
+pub struct TurnResult {
+    /// The agent's closing message, trimmed
+    pub final_message: Option<String>,
+    /// Paths edited during the turn, in order of first edit
+    pub files_edited: Vec<String>,
+    pub tool_calls: usize,
+    /// Token totals, for agents that report usage
+    pub usage: Option<TurnUsage>,
+    pub success: bool,
+    /// The last error reported when the turn did not succeed
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, TS)]
+pub struct TurnUsage {
+    #[ts(type = "number")]
+    pub input_tokens: u64,
+    #[ts(type = "number")]
+    pub output_tokens: u64,
+}
+
+/// Token usage of a run as reported by the agent, with the model that served it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct UsageStats {
+    #[ts(type = "number")]
+    pub prompt_tokens: u64,
+    #[ts(type = "number")]
+    pub completion_tokens: u64,
+    pub model: Option<String>,
+}
+
+impl TurnResult {
+    /// Aggregate the final version of each entry of a turn. The turn succeeded if no
+    /// error entry was logged; usage is left for the executor to fill in.
+    pub fn from_entries(entries: &[NormalizedEntry]) -> Self {
+        let mut result = Self::default();
+        for entry in entries {
+            match &entry.entry_type {
+                NormalizedEntryType::ToolUse { action_type, .. } => {
+                    result.tool_calls += 1;
+                    if let ActionType::FileEdit { path, .. } = action_type
+                        && !result.files_edited.contains(path)
+                    {
+                        result.files_edited.push(path.clone());
+                    }
+                }
+                NormalizedEntryType::ErrorMessage { .. } => {
+                    result.error = Some(entry.content.trim().to_string());
+                }
+                _ => {}
+            }
+        }
+
+        result.final_message = entries
+            .iter()
+            .rev()
+            .find(|e| matches!(e.entry_type, NormalizedEntryType::AssistantMessage))
+            .map(|e| e.content.trim())
+            .filter(|content| !content.is_empty())
+            .map(str::to_string);
+        result.success = result.error.is_none();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::{NormalizedEntryError, ToolStatus};
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn tool(action_type: ActionType) -> NormalizedEntry {
+        entry(
+            NormalizedEntryType::ToolUse {
+                tool_name: "tool".to_string(),
+                action_type,
+                status: ToolStatus::Success,
+            },
+            "",
+        )
+    }
+
+    fn file_edit(path: &str) -> NormalizedEntry {
+        tool(ActionType::FileEdit {
+            path: path.to_string(),
+            changes: vec![],
+        })
+    }
+
+    #[test]
+    fn aggregates_successful_turn() {
+        let result = TurnResult::from_entries(&[
+            entry(NormalizedEntryType::UserMessage, "add retries"),
+            entry(NormalizedEntryType::AssistantMessage, "Looking around"),
+            tool(ActionType::FileRead {
+                path: "src/fetch.rs".to_string(),
+            }),
+            file_edit("src/fetch.rs"),
+            file_edit("tests/fetch.rs"),
+            file_edit("src/fetch.rs"),
+            entry(NormalizedEntryType::AssistantMessage, "  Added retries.\n"),
+        ]);
+
+        assert_eq!(
+            result,
+            TurnResult {
+                final_message: Some("Added retries.".to_string()),
+                files_edited: vec!["src/fetch.rs".to_string(), "tests/fetch.rs".to_string()],
+                tool_calls: 4,
+                usage: None,
+                success: true,
+                error: None,
+            }
+        );
+    }
+
+    #[test]
+    fn error_entry_marks_turn_failed() {
+        let result = TurnResult::from_entries(&[
+            entry(NormalizedEntryType::AssistantMessage, "Starting"),
+            entry(
+                NormalizedEntryType::ErrorMessage {
+                    error_type: NormalizedEntryError::Other,
+                },
+                "Token budget exceeded",
+            ),
+        ]);
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("Token budget exceeded"));
+        assert_eq!(result.final_message.as_deref(), Some("Starting"));
+
+        assert_eq!(
+            TurnResult::from_entries(&[]),
+            TurnResult {
+                success: true,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -1,10 +1,10 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use json_patch::Patch;
 use serde::{Deserialize, Serialize};
 use serde_json::{from_value, json, to_value};
 use ts_rs::TS;
-use workspace_utils::{diff::Diff, msg_store::MsgStore};
+use workspace_utils::{diff::Diff, log_msg::LogMsg, msg_store::MsgStore};
 
 use crate::logs::{NormalizedEntry, utils::EntryIndexProvider};
 
@@ -142,6 +142,19 @@ pub fn extract_normalized_entry_from_patch(patch: &Patch) -> Option<(usize, Norm
     })
 }
 
+/// The final version of each normalized entry in `store`, in entry order
+pub fn latest_normalized_entries(store: &MsgStore) -> Vec<NormalizedEntry> {
+    let mut entries = BTreeMap::new();
+    for msg in store.get_history() {
+        if let LogMsg::JsonPatch(patch) = msg
+            && let Some((index, entry)) = extract_normalized_entry_from_patch(&patch)
+        {
+            entries.insert(index, entry);
+        }
+    }
+    entries.into_values().collect()
+}
+
 pub fn upsert_normalized_entry(
     msg_store: &Arc<MsgStore>,
     index: usize,
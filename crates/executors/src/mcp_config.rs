@@ -85,6 +85,45 @@ pub async fn write_agent_config(
     Ok(())
 }
 
+/// Recursively merge `overlay` into `base`. Objects merge key by key, arrays of named objects
+/// merge item by item on their `name`, and any other value in `overlay` replaces the one in `base`.
+pub fn deep_merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Array(base), Value::Array(overlay))
+            if base
+                .iter()
+                .chain(&overlay)
+                .all(|item| item_name(item).is_some()) =>
+        {
+            for item in overlay {
+                let name = item_name(&item).map(str::to_string);
+                match base
+                    .iter_mut()
+                    .find(|existing| item_name(existing) == name.as_deref())
+                {
+                    Some(existing) => deep_merge(existing, item),
+                    None => base.push(item),
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn item_name(item: &Value) -> Option<&str> {
+    item.get("name").and_then(Value::as_str)
+}
+
 type ServerMap = Map<String, Value>;
 
 fn is_http_server(s: &Map<String, Value>) -> bool {
@@ -293,7 +332,11 @@ impl CodingAgent {
         use Adapter::*;
 
         let adapter = match self {
-            CodingAgent::ClaudeCode(_) | CodingAgent::Amp(_) | CodingAgent::Droid(_) => Passthrough,
+            CodingAgent::ClaudeCode(_)
+            | CodingAgent::Amp(_)
+            | CodingAgent::Droid(_)
+            | CodingAgent::Aider(_)
+            | CodingAgent::Shell(_) => Passthrough,
             CodingAgent::QwenCode(_) | CodingAgent::Gemini(_) => Gemini,
             CodingAgent::CursorAgent(_) => Cursor,
             CodingAgent::Codex(_) => Codex,
@@ -305,3 +348,45 @@ impl CodingAgent {
         apply_adapter(adapter, canonical)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn deep_merge_combines_objects_and_named_arrays() {
+        let mut base = json!({
+            "theme": "dark",
+            "mcp": { "mine": { "type": "local", "enabled": false } },
+            "servers": [{ "name": "a", "url": "http://a" }, { "name": "b", "url": "http://b" }],
+            "plugins": ["x"]
+        });
+        deep_merge(
+            &mut base,
+            json!({
+                "mcp": { "vibe_kanban": { "type": "local" }, "mine": { "enabled": true } },
+                "servers": [{ "name": "b", "url": "http://b2" }, { "name": "c", "url": "http://c" }],
+                "plugins": ["y"]
+            }),
+        );
+
+        assert_eq!(
+            base,
+            json!({
+                "theme": "dark",
+                "mcp": {
+                    "mine": { "type": "local", "enabled": true },
+                    "vibe_kanban": { "type": "local" }
+                },
+                "servers": [
+                    { "name": "a", "url": "http://a" },
+                    { "name": "b", "url": "http://b2" },
+                    { "name": "c", "url": "http://c" }
+                ],
+                "plugins": ["y"]
+            })
+        );
+    }
+}
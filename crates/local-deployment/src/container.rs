@@ -17,7 +17,7 @@ use db::{
             ExecutionContext, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
         },
         execution_process_repo_state::ExecutionProcessRepoState,
-        project_repo::ProjectRepo,
+        project_repo::{ProjectRepo, ProjectRepoError, parse_env_file},
         repo::Repo,
         scratch::{DraftFollowUpData, Scratch, ScratchType},
         task::{Task, TaskStatus},
@@ -31,10 +31,13 @@ use executors::{
         Executable, ExecutorAction, ExecutorActionType,
         coding_agent_follow_up::CodingAgentFollowUpRequest,
         coding_agent_initial::CodingAgentInitialRequest,
+        script::{ScriptContext, ScriptRequest},
     },
     approvals::{ExecutorApprovalService, NoopExecutorApprovalService},
     env::ExecutionEnv,
-    executors::{BaseCodingAgent, ExecutorExitResult, ExecutorExitSignal, InterruptSender},
+    executors::{
+        BaseCodingAgent, ExecutorExitResult, ExecutorExitSignal, InterruptSender, abort_child,
+    },
     logs::{NormalizedEntryType, utils::patch::extract_normalized_entry_from_patch},
     profile::ExecutorProfileId,
 };
@@ -51,18 +54,20 @@ use services::services::{
     notification::NotificationService,
     queued_message::QueuedMessageService,
     share::SharePublisher,
-    workspace_manager::{RepoWorkspaceInput, WorkspaceManager},
+    workspace_manager::{RepoWorkspaceInput, RepoWorktree, WorkspaceManager},
+    worktree_manager::WorktreeManager,
 };
 use tokio::{sync::RwLock, task::JoinHandle};
 use tokio_util::io::ReaderStream;
 use utils::{
     log_msg::LogMsg,
     msg_store::MsgStore,
+    process::kill_process_group,
     text::{git_branch_id, short_uuid, truncate_to_char_boundary},
 };
 use uuid::Uuid;
 
-use crate::{command, copy};
+use crate::copy;
 
 #[derive(Clone)]
 pub struct LocalContainerService {
@@ -372,7 +377,7 @@ impl LocalContainerService {
                     // Executor signaled completion: kill group and use the provided result
                     if let Some(child_lock) = child_store.read().await.get(&exec_id).cloned() {
                         let mut child = child_lock.write().await ;
-                        if let Err(err) = command::kill_process_group(&mut child).await {
+                        if let Err(err) = kill_process_group(&mut child).await {
                             tracing::error!("Failed to kill process group after exit signal: {} {}", exec_id, err);
                         }
                     }
@@ -381,6 +386,15 @@ impl LocalContainerService {
                     status_result = match exit_result {
                         Ok(ExecutorExitResult::Success) => Ok(success_exit_status()),
                         Ok(ExecutorExitResult::Failure) => Ok(failure_exit_status()),
+                        Ok(ExecutorExitResult::TimedOut(timeout)) => {
+                            if let Some(msg) = msg_stores.read().await.get(&exec_id) {
+                                msg.push_stderr(format!(
+                                    "Timed out after {}s; the process was killed",
+                                    timeout.as_secs()
+                                ));
+                            }
+                            Ok(timed_out_exit_status())
+                        }
                         Err(_) => Ok(success_exit_status()), // Channel closed, assume success
                     };
                 }
@@ -440,6 +454,16 @@ impl LocalContainerService {
                         }
                     };
 
+                    if success
+                        && matches!(
+                            ctx.execution_process.run_reason,
+                            ExecutionProcessRunReason::SetupScript
+                        )
+                        && let Err(e) = container.record_setup_fingerprint(&ctx).await
+                    {
+                        tracing::warn!("Failed to record setup fingerprint: {}", e);
+                    }
+
                     let should_start_next = if matches!(
                         ctx.execution_process.run_reason,
                         ExecutionProcessRunReason::CodingAgent
@@ -818,7 +842,7 @@ impl LocalContainerService {
 
         let project_repos =
             ProjectRepo::find_by_project_id_with_names(&self.db.pool, ctx.project.id).await?;
-        let cleanup_action = self.cleanup_actions_for_repos(&project_repos);
+        let cleanup_action = self.auto_cleanup_action(&ctx.project, &ctx.workspace, &project_repos);
 
         let working_dir = ctx
             .workspace
@@ -852,6 +876,120 @@ impl LocalContainerService {
         )
         .await
     }
+
+    /// Add each repo's `worktree_gitignore` patterns to its new worktree's git excludes
+    async fn write_worktree_excludes(
+        &self,
+        project_id: Uuid,
+        worktrees: &[RepoWorktree],
+    ) -> Result<(), ContainerError> {
+        let project_repos =
+            ProjectRepo::find_by_project_id_with_names(&self.db.pool, project_id).await?;
+        for worktree in worktrees {
+            let Some(patterns) = project_repos
+                .iter()
+                .find(|pr| pr.repo_id == worktree.repo_id)
+                .and_then(|pr| pr.worktree_gitignore.as_deref())
+            else {
+                continue;
+            };
+            WorktreeManager::write_worktree_excludes(&worktree.worktree_path, patterns).await?;
+        }
+        Ok(())
+    }
+
+    /// Refuse to create another worktree for any repo already at its `max_worktrees`
+    async fn ensure_worktree_capacity(
+        &self,
+        project_id: Uuid,
+        repos: &[Repo],
+    ) -> Result<(), ContainerError> {
+        for repo in repos {
+            let Some(project_repo) =
+                ProjectRepo::find_by_project_and_repo(&self.db.pool, project_id, repo.id).await?
+            else {
+                continue;
+            };
+            let Some(limit) = project_repo.max_worktrees else {
+                continue;
+            };
+            let active = WorkspaceRepo::count_active_worktrees(&self.db.pool, repo.id).await?;
+            if !project_repo.can_create_worktree(active) {
+                return Err(ProjectRepoError::WorktreeLimitReached { limit }.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge the env file of the repo named `repo_name`, read from its worktree, then its
+    /// configured env vars into `env`. A missing or unreadable file is logged and skipped;
+    /// env vars that fail to parse are an error.
+    async fn merge_repo_env(
+        &self,
+        project_id: Uuid,
+        repo_name: &str,
+        workspace_dir: &Path,
+        env: &mut ExecutionEnv,
+    ) -> Result<(), ContainerError> {
+        let repos = ProjectRepo::find_by_project_id_with_names(&self.db.pool, project_id).await?;
+        let Some(repo) = repos.into_iter().find(|repo| repo.repo_name == repo_name) else {
+            return Ok(());
+        };
+        if let Some(env_file) = repo.env_file.as_deref() {
+            let path = workspace_dir.join(&repo.repo_name).join(env_file);
+            merge_env_file(&path, &repo.repo_name, env).await;
+        }
+        for (key, value) in repo.env_vars()? {
+            env.insert(key, value);
+        }
+        Ok(())
+    }
+}
+
+/// Repo whose setup or cleanup script `action` runs, if it is one
+fn repo_script_target(action: &ExecutorAction) -> Option<&str> {
+    match action.typ() {
+        ExecutorActionType::ScriptRequest(ScriptRequest {
+            context: ScriptContext::SetupScript | ScriptContext::CleanupScript,
+            working_dir: Some(repo_name),
+            ..
+        }) => Some(repo_name),
+        _ => None,
+    }
+}
+
+async fn merge_env_file(path: &Path, repo_name: &str, env: &mut ExecutionEnv) {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => {
+            for (key, value) in parse_env_file(&contents) {
+                env.insert(key, value);
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            tracing::warn!(
+                "Env file {} for repo {} not found, skipping",
+                path.display(),
+                repo_name
+            );
+        }
+        Err(e) => {
+            tracing::warn!("Failed to read env file {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Exit code 124, as reported by coreutils `timeout`
+fn timed_out_exit_status() -> std::process::ExitStatus {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        ExitStatusExt::from_raw(124 << 8)
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::ExitStatusExt;
+        ExitStatusExt::from_raw(124)
+    }
 }
 
 fn failure_exit_status() -> std::process::ExitStatus {
@@ -917,6 +1055,8 @@ impl ContainerService for LocalContainerService {
 
         let repositories =
             WorkspaceRepo::find_repos_for_workspace(&self.db.pool, workspace.id).await?;
+        self.ensure_worktree_capacity(task.project_id, &repositories)
+            .await?;
 
         let target_branches: HashMap<_, _> = workspace_repos
             .iter()
@@ -945,6 +1085,9 @@ impl ContainerService for LocalContainerService {
         Self::create_workspace_config_files(&created_workspace.workspace_dir, &repositories)
             .await?;
 
+        self.write_worktree_excludes(task.project_id, &created_workspace.worktrees)
+            .await?;
+
         Workspace::update_container_ref(
             &self.db.pool,
             workspace.id,
@@ -1081,6 +1224,13 @@ impl ContainerService for LocalContainerService {
             .await?
             .ok_or(ContainerError::Other(anyhow!("Project not found for task")))?;
 
+        // A repo's env file and vars only reach its own setup and cleanup scripts, never the
+        // agent. Merged first so the VK_* variables below always take precedence.
+        if let Some(repo_name) = repo_script_target(executor_action) {
+            self.merge_repo_env(project.id, repo_name, &current_dir, &mut env)
+                .await?;
+        }
+
         env.insert("VK_PROJECT_NAME", &project.name);
         env.insert("VK_PROJECT_ID", project.id.to_string());
         env.insert("VK_TASK_ID", task.id.to_string());
@@ -1138,45 +1288,16 @@ impl ContainerService for LocalContainerService {
             .await?;
 
         // Try graceful interrupt first, then force kill
-        if let Some(interrupt_sender) = self.take_interrupt_sender(&execution_process.id).await {
-            // Send interrupt signal (ignore error if receiver dropped)
-            let _ = interrupt_sender.send(());
-
-            // Wait for graceful exit with timeout
-            let graceful_exit = {
-                let mut child_guard = child.write().await;
-                tokio::time::timeout(Duration::from_secs(5), child_guard.wait()).await
-            };
-
-            match graceful_exit {
-                Ok(Ok(_)) => {
-                    tracing::debug!(
-                        "Process {} exited gracefully after interrupt",
-                        execution_process.id
-                    );
-                }
-                Ok(Err(e)) => {
-                    tracing::info!("Error waiting for process {}: {}", execution_process.id, e);
-                }
-                Err(_) => {
-                    tracing::debug!(
-                        "Graceful shutdown timed out for process {}, force killing",
-                        execution_process.id
-                    );
-                }
-            }
-        }
-
-        // Kill the child process and remove from the store
+        let interrupt_sender = self.take_interrupt_sender(&execution_process.id).await;
         {
             let mut child_guard = child.write().await;
-            if let Err(e) = command::kill_process_group(&mut child_guard).await {
+            if let Err(e) = abort_child(&mut child_guard, interrupt_sender).await {
                 tracing::error!(
                     "Failed to stop execution process {}: {}",
                     execution_process.id,
                     e
                 );
-                return Err(e);
+                return Err(e.into());
             }
         }
         self.remove_child_from_store(&execution_process.id).await;
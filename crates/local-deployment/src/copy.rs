@@ -5,36 +5,34 @@ use std::{
 };
 
 use anyhow::anyhow;
+use db::models::project_repo::{copy_file_patterns, parse_copy_file_entry};
 use globwalk::GlobWalkerBuilder;
 use services::services::container::ContainerError;
 
-/// Normalize pattern for cross-platform glob matching (convert backslashes to forward slashes)
-fn normalize_pattern(pattern: &str) -> String {
-    pattern.replace('\\', "/")
-}
-
 /// Copy project files from source to target directory based on glob patterns.
+/// `src -> dest` entries copy a file or directory to a different path in the target.
 /// Skips files that already exist at target with same size.
 pub(crate) fn copy_project_files_impl(
     source_dir: &Path,
     target_dir: &Path,
     copy_files: &str,
 ) -> Result<(), ContainerError> {
-    let patterns: Vec<&str> = copy_files
-        .split(',')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect();
-
-    // Track files to avoid duplicates
+    // Track target files to avoid duplicates
     let mut seen = HashSet::new();
 
-    for pattern in patterns {
-        let pattern = normalize_pattern(pattern);
+    for entry in copy_file_patterns(copy_files) {
+        let (pattern, dest) = match parse_copy_file_entry(entry) {
+            Ok(mapping) => mapping,
+            Err(e) => {
+                tracing::warn!("Skipping copy_files entry '{entry}': {e}");
+                continue;
+            }
+        };
         let pattern_path = source_dir.join(&pattern);
+        let dest_path = target_dir.join(&dest);
 
         if pattern_path.is_file() {
-            if let Err(e) = copy_single_file(&pattern_path, source_dir, target_dir, &mut seen) {
+            if let Err(e) = copy_single_file(&pattern_path, source_dir, &dest_path, &mut seen) {
                 tracing::warn!(
                     "Failed to copy file {} (from {}): {}",
                     pattern,
@@ -45,11 +43,17 @@ pub(crate) fn copy_project_files_impl(
             continue;
         }
 
-        let glob_pattern = if pattern_path.is_dir() {
+        // Matches keep their path relative to the matched directory, or to the project root
+        // for globs
+        let (glob_pattern, from, to) = if pattern_path.is_dir() {
             // For directories, append /** to match all contents recursively
-            format!("{pattern}/**")
+            (
+                format!("{pattern}/**"),
+                pattern_path.as_path(),
+                dest_path.as_path(),
+            )
         } else {
-            pattern.clone()
+            (pattern.clone(), source_dir, target_dir)
         };
 
         let walker = match GlobWalkerBuilder::from_patterns(source_dir, &[&glob_pattern])
@@ -64,7 +68,19 @@ pub(crate) fn copy_project_files_impl(
         };
 
         for entry in walker.flatten() {
-            if let Err(e) = copy_single_file(entry.path(), source_dir, target_dir, &mut seen) {
+            let copied = entry
+                .path()
+                .strip_prefix(from)
+                .map_err(|e| {
+                    ContainerError::Other(anyhow!(
+                        "Failed to get relative path for {:?}: {e}",
+                        entry.path()
+                    ))
+                })
+                .and_then(|relative| {
+                    copy_single_file(entry.path(), source_dir, &to.join(relative), &mut seen)
+                });
+            if let Err(e) = copied {
                 tracing::warn!("Failed to copy file {:?}: {e}", entry.path());
             }
         }
@@ -76,7 +92,7 @@ pub(crate) fn copy_project_files_impl(
 fn copy_single_file(
     source_file: &Path,
     source_root: &Path,
-    target_root: &Path,
+    target_file: &Path,
     seen: &mut HashSet<PathBuf>,
 ) -> Result<bool, ContainerError> {
     let canonical_source = source_root.canonicalize()?;
@@ -88,18 +104,10 @@ fn copy_single_file(
         )));
     }
 
-    if !seen.insert(canonical_file.clone()) {
+    if !seen.insert(target_file.to_path_buf()) {
         return Ok(false);
     }
 
-    let relative_path = source_file.strip_prefix(source_root).map_err(|e| {
-        ContainerError::Other(anyhow!(
-            "Failed to get relative path for {source_file:?}: {e}"
-        ))
-    })?;
-
-    let target_file = target_root.join(relative_path);
-
     if target_file.exists() {
         return Ok(false);
     }
@@ -109,7 +117,7 @@ fn copy_single_file(
     {
         fs::create_dir_all(parent)?;
     }
-    fs::copy(source_file, &target_file)?;
+    fs::copy(source_file, target_file)?;
 
     Ok(true)
 }
@@ -309,6 +317,32 @@ mod tests {
         assert_eq!(fs::read_to_string(target_file).unwrap(), "library code");
     }
 
+    #[test]
+    fn test_copy_project_files_renamed_destination() {
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+
+        fs::write(source_dir.path().join(".env.example"), "PORT=3000").unwrap();
+        let dev_dir = source_dir.path().join("config/dev");
+        fs::create_dir_all(&dev_dir).unwrap();
+        fs::write(dev_dir.join("app.toml"), "config").unwrap();
+
+        copy_project_files_impl(
+            source_dir.path(),
+            target_dir.path(),
+            ".env.example -> .env, .env.example, config/dev -> config/local, .env -> ../.env",
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(target_dir.path().join(".env")).unwrap(),
+            "PORT=3000"
+        );
+        assert!(target_dir.path().join(".env.example").exists());
+        assert!(target_dir.path().join("config/local/app.toml").exists());
+        assert!(!target_dir.path().join("config/dev").exists());
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_symlink_loop_is_skipped() {
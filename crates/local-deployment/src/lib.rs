@@ -31,7 +31,6 @@ use utils::{
 use uuid::Uuid;
 
 use crate::container::LocalContainerService;
-mod command;
 pub mod container;
 mod copy;
 
@@ -24,6 +24,7 @@ fn generate_types_content() -> String {
         db::models::project_repo::ProjectRepo::decl(),
         db::models::project_repo::CreateProjectRepo::decl(),
         db::models::project_repo::UpdateProjectRepo::decl(),
+        db::models::project_repo::SetupStep::decl(),
         db::models::workspace_repo::WorkspaceRepo::decl(),
         db::models::workspace_repo::CreateWorkspaceRepo::decl(),
         db::models::workspace_repo::RepoWithTargetBranch::decl(),
@@ -89,6 +90,8 @@ fn generate_types_content() -> String {
         utils::api::projects::RemoteProject::decl(),
         utils::api::projects::ListProjectsResponse::decl(),
         utils::api::projects::RemoteProjectMembersResponse::decl(),
+        server::routes::projects::AddProjectRepositoriesRequest::decl(),
+        server::routes::projects::ReorderProjectRepositoriesRequest::decl(),
         server::routes::projects::CreateRemoteProjectRequest::decl(),
         server::routes::projects::LinkToExistingRequest::decl(),
         server::routes::repo::RegisterRepoRequest::decl(),
@@ -137,6 +140,16 @@ fn generate_types_content() -> String {
         server::routes::task_attempts::pr::GetPrCommentsError::decl(),
         server::routes::task_attempts::pr::GetPrCommentsQuery::decl(),
         services::services::github::UnifiedPrComment::decl(),
+        services::services::github::AuthorAssociation::decl(),
+        services::services::github::ReviewState::decl(),
+        services::services::github::ReactionSummary::decl(),
+        services::services::github::ParticipantRole::decl(),
+        services::services::github::Participant::decl(),
+        services::services::github::Label::decl(),
+        services::services::github::Mergeability::decl(),
+        services::services::github::CheckRun::decl(),
+        services::services::github::CheckStatus::decl(),
+        services::services::github::CheckConclusion::decl(),
         server::routes::task_attempts::RepoBranchStatus::decl(),
         services::services::filesystem::DirectoryEntry::decl(),
         services::services::filesystem::DirectoryListResponse::decl(),
@@ -181,10 +194,19 @@ fn generate_types_content() -> String {
         executors::executors::cursor::CursorAgent::decl(),
         executors::executors::copilot::Copilot::decl(),
         executors::executors::opencode::Opencode::decl(),
+        executors::executors::opencode::OpencodePermissions::decl(),
+        executors::executors::opencode::PermissionLevel::decl(),
+        executors::executors::opencode::OpencodeLauncher::decl(),
+        executors::approvals::ApprovalTimeoutAction::decl(),
+        executors::approvals::PolicyDecision::decl(),
+        executors::approvals::ApprovalRule::decl(),
+        executors::approvals::ApprovalPolicy::decl(),
         executors::executors::qwen::QwenCode::decl(),
         executors::executors::droid::Droid::decl(),
         executors::executors::droid::Autonomy::decl(),
         executors::executors::droid::ReasoningEffortLevel::decl(),
+        executors::executors::aider::Aider::decl(),
+        executors::executors::shell::ShellExecutor::decl(),
         executors::executors::AppendPrompt::decl(),
         executors::actions::coding_agent_initial::CodingAgentInitialRequest::decl(),
         executors::actions::coding_agent_follow_up::CodingAgentFollowUpRequest::decl(),
@@ -192,6 +214,7 @@ fn generate_types_content() -> String {
         executors::logs::CommandRunResult::decl(),
         executors::logs::NormalizedEntry::decl(),
         executors::logs::NormalizedEntryType::decl(),
+        executors::logs::LineRange::decl(),
         executors::logs::FileChange::decl(),
         executors::logs::ActionType::decl(),
         executors::logs::TodoItem::decl(),
@@ -200,6 +223,11 @@ fn generate_types_content() -> String {
         executors::logs::ToolResultValueType::decl(),
         executors::logs::ToolStatus::decl(),
         executors::logs::utils::patch::PatchType::decl(),
+        executors::logs::turn_result::TurnResult::decl(),
+        executors::logs::turn_result::TurnUsage::decl(),
+        executors::logs::turn_result::UsageStats::decl(),
+        executors::executors::acp::side_by_side::SideBySideDiff::decl(),
+        executors::executors::acp::side_by_side::Hunk::decl(),
         serde_json::Value::decl(),
     ];
 
@@ -286,6 +314,14 @@ fn generate_schemas() -> Result<HashMap<&'static str, String>, serde_json::Error
             "droid",
             generate_json_schema::<executors::executors::droid::Droid>()?,
         ),
+        (
+            "aider",
+            generate_json_schema::<executors::executors::aider::Aider>()?,
+        ),
+        (
+            "shell",
+            generate_json_schema::<executors::executors::shell::ShellExecutor>()?,
+        ),
     ]);
     println!(
         "✅ JSON schemas generated. {} schemas created.",
@@ -122,6 +122,9 @@ impl IntoResponse for ApiError {
             },
             ApiError::GitHubService(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitHubServiceError"),
             ApiError::Deployment(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DeploymentError"),
+            ApiError::Container(ContainerError::ProjectRepo(
+                ProjectRepoError::WorktreeLimitReached { .. },
+            )) => (StatusCode::CONFLICT, "ContainerError"),
             ApiError::Container(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
             ApiError::Executor(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ExecutorError"),
             ApiError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DatabaseError"),
@@ -329,6 +332,10 @@ impl From<ProjectServiceError> for ApiError {
             ProjectServiceError::NotGitRepository(path) => {
                 ApiError::BadRequest(format!("Path is not a git repository: {}", path.display()))
             }
+            ProjectServiceError::RepoWithoutCommits(path) => ApiError::BadRequest(format!(
+                "Repository has no commits yet, commit something before adding it: {}",
+                path.display()
+            )),
             ProjectServiceError::DuplicateGitRepoPath => ApiError::Conflict(
                 "A project with this git repository path already exists".to_string(),
             ),
@@ -362,6 +369,10 @@ impl From<RepoServiceError> for ApiError {
             RepoServiceError::NotGitRepository(path) => {
                 ApiError::BadRequest(format!("Path is not a git repository: {}", path.display()))
             }
+            RepoServiceError::NoCommits(path) => ApiError::BadRequest(format!(
+                "Repository has no commits yet, commit something before adding it: {}",
+                path.display()
+            )),
             RepoServiceError::NotFound => ApiError::BadRequest("Repository not found".to_string()),
             RepoServiceError::DirectoryAlreadyExists(path) => {
                 ApiError::BadRequest(format!("Directory already exists: {}", path.display()))
@@ -386,6 +397,54 @@ impl From<ProjectRepoError> for ApiError {
             ProjectRepoError::AlreadyExists => {
                 ApiError::Conflict("Repository already exists in project".to_string())
             }
+            ProjectRepoError::InvalidBaseBranch(branch) => {
+                ApiError::BadRequest(format!("Invalid base branch name: {branch}"))
+            }
+            ProjectRepoError::InvalidSetupRetries(retries) => {
+                ApiError::BadRequest(format!("Setup retries must not be negative, got {retries}"))
+            }
+            ProjectRepoError::InvalidEnvFile(path) => ApiError::BadRequest(format!(
+                "Env file must be a path inside the repository: {path}"
+            )),
+            ProjectRepoError::InvalidSetupPaths(reason) => ApiError::BadRequest(format!(
+                "Setup paths must be a JSON array of globs: {reason}"
+            )),
+            ProjectRepoError::InvalidSetupTimeout(secs) => ApiError::BadRequest(format!(
+                "Setup timeout must be a positive number of seconds, got {secs}"
+            )),
+            ProjectRepoError::InvalidMaxWorktrees(max) => {
+                ApiError::BadRequest(format!("Max worktrees must be at least 1, got {max}"))
+            }
+            ProjectRepoError::InvalidSetupConcurrency(limit) => ApiError::BadRequest(format!(
+                "Setup concurrency must not be negative, got {limit}"
+            )),
+            ProjectRepoError::WorktreeLimitReached { limit } => ApiError::Conflict(format!(
+                "Repository already has {limit} active worktrees, the configured maximum"
+            )),
+            ProjectRepoError::InvalidSetupDependsOn(reason) => ApiError::BadRequest(format!(
+                "Setup dependencies must be a JSON array of repository ids: {reason}"
+            )),
+            ProjectRepoError::InvalidEnvVars(reason) => ApiError::BadRequest(format!(
+                "Env vars must be a JSON object of string values: {reason}"
+            )),
+            ProjectRepoError::InvalidSetupSteps(reason) => ApiError::BadRequest(format!(
+                "Setup steps must be a JSON array of {{name, command}} objects: {reason}"
+            )),
+            ProjectRepoError::InvalidCopyFiles(reason) => ApiError::BadRequest(format!(
+                "Copy files must be globs relative to the repository: {reason}"
+            )),
+            ProjectRepoError::InvalidOrder => ApiError::BadRequest(
+                "Reorder must list every repository of the project exactly once".to_string(),
+            ),
+            ProjectRepoError::SharedAcrossProjects(_) => {
+                ApiError::Conflict("Repository is also used by other projects".to_string())
+            }
+            ProjectRepoError::DependencyCycle(_) => ApiError::BadRequest(
+                "Setup dependencies must not form a cycle between repositories".to_string(),
+            ),
+            ProjectRepoError::Conflict => ApiError::Conflict(
+                "Repository settings were changed elsewhere; reload and try again".to_string(),
+            ),
         }
     }
 }
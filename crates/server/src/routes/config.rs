@@ -41,6 +41,7 @@ pub fn router() -> Router<DeploymentImpl> {
             get(check_editor_availability),
         )
         .route("/agents/check-availability", get(check_agent_availability))
+        .route("/agents/probe-availability", get(probe_agent_availability))
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -485,3 +486,19 @@ async fn check_agent_availability(
 
     ResponseJson(ApiResponse::success(info))
 }
+
+/// Like `check_agent_availability`, but starts the agent to confirm it actually runs
+async fn probe_agent_availability(
+    State(_deployment): State<DeploymentImpl>,
+    Query(query): Query<CheckAgentAvailabilityQuery>,
+) -> ResponseJson<ApiResponse<AvailabilityInfo>> {
+    let profiles = ExecutorConfigs::get_cached();
+    let profile_id = ExecutorProfileId::new(query.executor);
+
+    let info = match profiles.get_coding_agent(&profile_id) {
+        Some(agent) => agent.probe_availability().await,
+        None => AvailabilityInfo::NotFound,
+    };
+
+    ResponseJson(ApiResponse::success(info))
+}
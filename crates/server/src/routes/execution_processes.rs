@@ -14,6 +14,10 @@ use db::models::{
     execution_process_repo_state::ExecutionProcessRepoState,
 };
 use deployment::Deployment;
+use executors::{
+    actions::ExecutorActionType, executors::StandardCodingAgentExecutor,
+    logs::turn_result::TurnResult, profile::ExecutorConfigs,
+};
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::Deserialize;
 use services::services::container::ContainerService;
@@ -243,11 +247,44 @@ pub async fn get_execution_process_repo_states(
     Ok(ResponseJson(ApiResponse::success(repo_states)))
 }
 
+/// Structured result of a coding agent turn, built from its logs while they are in memory
+pub async fn get_execution_process_turn_result(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TurnResult>>, ApiError> {
+    let action = execution_process
+        .executor_action()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let executor_profile_id = match action.typ() {
+        ExecutorActionType::CodingAgentInitialRequest(request) => &request.executor_profile_id,
+        ExecutorActionType::CodingAgentFollowUpRequest(request) => &request.executor_profile_id,
+        ExecutorActionType::ScriptRequest(_) => {
+            return Err(ApiError::BadRequest(
+                "Execution process is not a coding agent run".to_string(),
+            ));
+        }
+    };
+    let store = deployment
+        .container()
+        .get_msg_store_by_id(&execution_process.id)
+        .await
+        .ok_or(ApiError::ExecutionProcess(
+            ExecutionProcessError::ExecutionProcessNotFound,
+        ))?;
+    let executor = ExecutorConfigs::get_cached()
+        .get_coding_agent(executor_profile_id)
+        .ok_or_else(|| ApiError::BadRequest("Unknown executor profile".to_string()))?;
+    Ok(ResponseJson(ApiResponse::success(
+        executor.turn_result(&store),
+    )))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let workspace_id_router = Router::new()
         .route("/", get(get_execution_process_by_id))
         .route("/stop", post(stop_execution_process))
         .route("/repo-states", get(get_execution_process_repo_states))
+        .route("/turn-result", get(get_execution_process_turn_result))
         .route("/raw-logs/ws", get(stream_raw_logs_ws))
         .route("/normalized-logs/ws", get(stream_normalized_logs_ws))
         .layer(from_fn_with_state(
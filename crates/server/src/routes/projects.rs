@@ -10,7 +10,7 @@ use axum::{
     http::StatusCode,
     middleware::from_fn_with_state,
     response::{IntoResponse, Json as ResponseJson},
-    routing::{get, post},
+    routing::{get, post, put},
 };
 use db::models::{
     project::{CreateProject, Project, ProjectError, SearchResult, UpdateProject},
@@ -38,6 +38,17 @@ pub struct LinkToExistingRequest {
     pub remote_project_id: Uuid,
 }
 
+#[derive(Deserialize, TS)]
+pub struct AddProjectRepositoriesRequest {
+    pub repositories: Vec<CreateProjectRepo>,
+}
+
+#[derive(Deserialize, TS)]
+pub struct ReorderProjectRepositoriesRequest {
+    /// Every repository id of the project, in the new order
+    pub repo_ids: Vec<Uuid>,
+}
+
 #[derive(Deserialize, TS)]
 pub struct CreateRemoteProjectRequest {
     pub organization_id: Uuid,
@@ -515,6 +526,66 @@ pub async fn add_project_repository(
     }
 }
 
+pub async fn add_project_repositories(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<AddProjectRepositoriesRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<Repo>>>, ApiError> {
+    match deployment
+        .project()
+        .add_repositories(
+            &deployment.db().pool,
+            deployment.repo(),
+            project.id,
+            &payload.repositories,
+        )
+        .await
+    {
+        Ok(repositories) => {
+            deployment
+                .track_if_analytics_allowed(
+                    "project_repositories_added",
+                    serde_json::json!({
+                        "project_id": project.id.to_string(),
+                        "repository_count": repositories.len(),
+                    }),
+                )
+                .await;
+
+            Ok(ResponseJson(ApiResponse::success(repositories)))
+        }
+        Err(
+            e @ (ProjectServiceError::PathNotFound(_)
+            | ProjectServiceError::PathNotDirectory(_)
+            | ProjectServiceError::NotGitRepository(_)
+            | ProjectServiceError::RepoWithoutCommits(_)
+            | ProjectServiceError::DuplicateRepositoryName),
+        ) => {
+            tracing::warn!(
+                "Failed to add repositories to project {}: {}",
+                project.id,
+                e
+            );
+            Ok(ResponseJson(ApiResponse::error(&e.to_string())))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn reorder_project_repositories(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ReorderProjectRepositoriesRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<Repo>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    ProjectRepo::reorder(pool, project.id, &payload.repo_ids).await?;
+    let repositories = deployment
+        .project()
+        .get_repositories(pool, project.id)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(repositories)))
+}
+
 pub async fn delete_project_repository(
     State(deployment): State<DeploymentImpl>,
     Path((project_id, repo_id)): Path<(Uuid, Uuid)>,
@@ -600,6 +671,8 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
             "/repositories",
             get(get_project_repositories).post(add_project_repository),
         )
+        .route("/repositories/bulk", post(add_project_repositories))
+        .route("/repositories/order", put(reorder_project_repositories))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_project_middleware,
@@ -177,9 +177,10 @@ pub async fn follow_up(
     let prompt = payload.prompt;
 
     let project_repos = ProjectRepo::find_by_project_id_with_names(pool, project.id).await?;
-    let cleanup_action = deployment
-        .container()
-        .cleanup_actions_for_repos(&project_repos);
+    let cleanup_action =
+        deployment
+            .container()
+            .auto_cleanup_action(&project, &workspace, &project_repos);
 
     let working_dir = workspace
         .agent_working_dir
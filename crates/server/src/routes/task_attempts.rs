@@ -117,6 +117,37 @@ pub struct WorkspaceRepoInput {
     pub target_branch: String,
 }
 
+/// Resolve the fork point for each requested repo. A blank `target_branch` falls back to the
+/// project repo's configured `base_branch`, then to the repo's remote default branch.
+pub(crate) async fn resolve_workspace_repos(
+    deployment: &DeploymentImpl,
+    project_id: Uuid,
+    repos: &[WorkspaceRepoInput],
+) -> Result<Vec<CreateWorkspaceRepo>, ApiError> {
+    let pool = &deployment.db().pool;
+    let mut resolved = Vec::with_capacity(repos.len());
+    for input in repos {
+        let requested = input.target_branch.trim();
+        let target_branch = if !requested.is_empty() {
+            requested.to_string()
+        } else {
+            let repo = Repo::find_by_id(pool, input.repo_id)
+                .await?
+                .ok_or(RepoError::NotFound)?;
+            let default_branch = || async { deployment.git().get_default_branch(&repo.path) };
+            match ProjectRepo::find_by_project_and_repo(pool, project_id, input.repo_id).await? {
+                Some(project_repo) => project_repo.resolve_base_branch(default_branch).await?,
+                None => default_branch().await?,
+            }
+        };
+        resolved.push(CreateWorkspaceRepo {
+            repo_id: input.repo_id,
+            target_branch,
+        });
+    }
+    Ok(resolved)
+}
+
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct RunAgentSetupRequest {
     pub executor_profile_id: ExecutorProfileId,
@@ -176,14 +207,7 @@ pub async fn create_task_attempt(
     )
     .await?;
 
-    let workspace_repos: Vec<CreateWorkspaceRepo> = payload
-        .repos
-        .iter()
-        .map(|r| CreateWorkspaceRepo {
-            repo_id: r.repo_id,
-            target_branch: r.target_branch.clone(),
-        })
-        .collect();
+    let workspace_repos = resolve_workspace_repos(&deployment, project.id, &payload.repos).await?;
 
     WorkspaceRepo::create_many(pool, workspace.id, &workspace_repos).await?;
     if let Err(err) = deployment
@@ -1149,31 +1173,37 @@ pub async fn start_dev_server(
         }
     }
 
-    // Get dev script from project (dev_script is project-level, not per-repo)
-    let dev_script = match &project.dev_script {
-        Some(script) if !script.is_empty() => script.clone(),
-        _ => {
-            return Ok(ResponseJson(ApiResponse::error(
-                "No dev server script configured for this project",
-            )));
-        }
-    };
-
-    let working_dir = project
-        .dev_script_working_dir
-        .as_ref()
-        .filter(|dir| !dir.is_empty())
-        .cloned();
-
-    let executor_action = ExecutorAction::new(
-        ExecutorActionType::ScriptRequest(ScriptRequest {
-            script: dev_script,
-            language: ScriptRequestLanguage::Bash,
-            context: ScriptContext::DevServer,
-            working_dir,
-        }),
-        None,
-    );
+    // The project-level dev script, plus a dev server for each repo that configures one
+    let mut executor_actions = Vec::new();
+    if let Some(dev_script) = project.dev_script.clone().filter(|s| !s.is_empty()) {
+        let working_dir = project
+            .dev_script_working_dir
+            .as_ref()
+            .filter(|dir| !dir.is_empty())
+            .cloned();
+
+        executor_actions.push(ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: dev_script,
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::DevServer,
+                working_dir,
+                timeout_secs: None,
+            }),
+            None,
+        ));
+    }
+    let project_repos = ProjectRepo::find_by_project_id_with_names(pool, project.id).await?;
+    executor_actions.extend(project_repos.iter().filter_map(|repo| {
+        deployment
+            .container()
+            .dev_server_action_for_repo(&workspace, repo)
+    }));
+    if executor_actions.is_empty() {
+        return Ok(ResponseJson(ApiResponse::error(
+            "No dev server script configured for this project",
+        )));
+    }
 
     // Get or create a session for dev server
     let session = match Session::find_latest_by_workspace_id(pool, workspace.id).await? {
@@ -1191,15 +1221,17 @@ pub async fn start_dev_server(
         }
     };
 
-    deployment
-        .container()
-        .start_execution(
-            &workspace,
-            &session,
-            &executor_action,
-            &ExecutionProcessRunReason::DevServer,
-        )
-        .await?;
+    for executor_action in &executor_actions {
+        deployment
+            .container()
+            .start_execution(
+                &workspace,
+                &session,
+                executor_action,
+                &ExecutionProcessRunReason::DevServer,
+            )
+            .await?;
+    }
 
     deployment
         .track_if_analytics_allowed(
@@ -1305,7 +1337,7 @@ pub async fn run_setup_script(
     let project_repos = ProjectRepo::find_by_project_id_with_names(pool, project.id).await?;
     let executor_action = match deployment
         .container()
-        .setup_actions_for_repos(&project_repos)
+        .setup_actions_for_repos(&workspace, &project_repos)
     {
         Some(action) => action,
         None => {
@@ -1389,7 +1421,7 @@ pub async fn run_cleanup_script(
     let project_repos = ProjectRepo::find_by_project_id_with_names(pool, project.id).await?;
     let executor_action = match deployment
         .container()
-        .cleanup_actions_for_repos(&project_repos)
+        .cleanup_actions_for_repos(&workspace, &project_repos)
     {
         Some(action) => action,
         None => {
@@ -5,9 +5,11 @@ use axum::{
     extract::{Query, State},
     response::Json as ResponseJson,
 };
+use chrono::{DateTime, Utc};
 use db::models::{
     execution_process::{ExecutionProcess, ExecutionProcessRunReason},
     merge::{Merge, MergeStatus},
+    project_repo::ProjectRepo,
     repo::{Repo, RepoError},
     session::{CreateSession, Session},
     task::{Task, TaskStatus},
@@ -15,16 +17,20 @@ use db::models::{
     workspace_repo::WorkspaceRepo,
 };
 use deployment::Deployment;
-use executors::actions::{
-    ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
-    coding_agent_initial::CodingAgentInitialRequest,
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
+        coding_agent_initial::CodingAgentInitialRequest,
+    },
+    executors::StandardCodingAgentExecutor,
+    profile::ExecutorConfigs,
 };
 use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
     container::ContainerService,
     git::{GitCliError, GitServiceError},
-    github::{CreatePrRequest, GitHubService, GitHubServiceError, UnifiedPrComment},
+    github::{CreatePrRequest, GitHubService, GitHubServiceError, PrState, UnifiedPrComment},
 };
 use ts_rs::TS;
 use utils::response::ApiResponse;
@@ -84,6 +90,10 @@ pub enum GetPrCommentsError {
 #[derive(Debug, Deserialize, TS)]
 pub struct GetPrCommentsQuery {
     pub repo_id: Uuid,
+    /// Only return comments created after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Flag review comments whose code is no longer in the PR diff. Costs an extra diff fetch.
+    pub detect_outdated: Option<bool>,
 }
 
 pub const DEFAULT_PR_DESCRIPTION_PROMPT: &str = r#"Update the GitHub PR that was just created with a better title and description.
@@ -183,6 +193,51 @@ async fn trigger_pr_description_follow_up(
     Ok(())
 }
 
+/// Summary of the workspace's latest coding agent run, while its logs are still in memory
+async fn latest_session_summary(deployment: &DeploymentImpl, workspace_id: Uuid) -> Option<String> {
+    let process = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+        &deployment.db().pool,
+        workspace_id,
+        &ExecutionProcessRunReason::CodingAgent,
+    )
+    .await
+    .ok()??;
+    let store = deployment
+        .container()
+        .get_msg_store_by_id(&process.id)
+        .await?;
+    let executor_profile_id = match process.executor_action().ok()?.typ() {
+        ExecutorActionType::CodingAgentInitialRequest(request) => &request.executor_profile_id,
+        ExecutorActionType::CodingAgentFollowUpRequest(request) => &request.executor_profile_id,
+        ExecutorActionType::ScriptRequest(_) => return None,
+    };
+    ExecutorConfigs::get_cached()
+        .get_coding_agent(executor_profile_id)?
+        .summarize_session(&store)
+}
+
+/// PR title and body for a request that left the title blank: the agent's session summary,
+/// falling back to the task title. An explicit body is kept.
+fn pr_title_and_body(
+    summary: Option<&str>,
+    task_title: &str,
+    body: Option<String>,
+) -> (String, Option<String>) {
+    let Some(summary) = summary else {
+        return (task_title.to_string(), body);
+    };
+    let (title, summary_body) = match summary.split_once("\n\n") {
+        Some((title, rest)) => (title, Some(rest.trim().to_string())),
+        None => (summary, None),
+    };
+    (title.trim().to_string(), body.or(summary_body))
+}
+
+/// Repos flagged with `auto_open_pr = false` (vendored or generated code) never get PRs
+fn pr_creation_allowed(project_repo: Option<&ProjectRepo>) -> bool {
+    project_repo.is_none_or(|pr| pr.auto_open_pr)
+}
+
 pub async fn create_github_pr(
     Extension(workspace): Extension<Workspace>,
     State(deployment): State<DeploymentImpl>,
@@ -199,6 +254,19 @@ pub async fn create_github_pr(
         .await?
         .ok_or(RepoError::NotFound)?;
 
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::Workspace(WorkspaceError::TaskNotFound))?;
+    let project_repo =
+        ProjectRepo::find_by_project_and_repo(pool, task.project_id, workspace_repo.repo_id)
+            .await?;
+    if !pr_creation_allowed(project_repo.as_ref()) {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Pull requests are disabled for this repository",
+        )));
+    }
+
     let repo_path = repo.path;
     let target_branch = if let Some(branch) = request.target_branch {
         branch
@@ -278,13 +346,22 @@ pub async fn create_github_pr(
     } else {
         target_branch
     };
+    let (title, body) = if request.title.trim().is_empty() {
+        let summary = latest_session_summary(&deployment, workspace.id).await;
+        pr_title_and_body(summary.as_deref(), &task.title, request.body.clone())
+    } else {
+        (request.title.clone(), request.body.clone())
+    };
+
     // Create the PR using GitHub service
     let pr_request = CreatePrRequest {
-        title: request.title.clone(),
-        body: request.body.clone(),
+        title,
+        body,
         head_branch: workspace.branch.clone(),
         base_branch: norm_target_branch_name.clone(),
         draft: request.draft,
+        reviewers: Vec::new(),
+        assignees: Vec::new(),
     };
     let github_service = GitHubService::new()?;
     let repo_info = github_service.get_repo_info(&repo_path).await?;
@@ -392,10 +469,10 @@ pub async fn attach_existing_pr(
 
     // List all PRs for branch (open, closed, and merged)
     let prs = github_service
-        .list_all_prs_for_branch(&repo_info, &workspace.branch)
+        .list_all_prs_for_branch(&repo_info, &workspace.branch, &[PrState::All])
         .await?;
 
-    // Take the first PR (prefer open, but also accept merged/closed)
+    // Take the most recent PR, whether open, merged or closed
     if let Some(pr_info) = prs.into_iter().next() {
         // Save PR info to database
         let merge = Merge::create_pr(
@@ -491,7 +568,12 @@ pub async fn get_pr_comments(
 
     // Fetch comments from GitHub
     match github_service
-        .get_pr_comments(&repo_info, pr_info.number)
+        .get_pr_comments(
+            &repo_info,
+            pr_info.number,
+            query.since,
+            query.detect_outdated.unwrap_or(false),
+        )
         .await
     {
         Ok(comments) => Ok(ResponseJson(ApiResponse::success(PrCommentsResponse {
@@ -516,3 +598,51 @@ pub async fn get_pr_comments(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn project_repo(auto_open_pr: bool) -> ProjectRepo {
+        ProjectRepo {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            repo_id: Uuid::new_v4(),
+            setup_concurrency: 1,
+            auto_open_pr,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn blank_pr_title_uses_session_summary() {
+        let summary = "Add retries to the fetcher\n\nFiles changed:\n- src/fetch.rs";
+        assert_eq!(
+            pr_title_and_body(Some(summary), "Task", None),
+            (
+                "Add retries to the fetcher".to_string(),
+                Some("Files changed:\n- src/fetch.rs".to_string())
+            )
+        );
+        assert_eq!(
+            pr_title_and_body(Some(summary), "Task", Some("Mine".to_string())),
+            (
+                "Add retries to the fetcher".to_string(),
+                Some("Mine".to_string())
+            )
+        );
+        assert_eq!(
+            pr_title_and_body(None, "Task", None),
+            ("Task".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn auto_open_pr_gates_pr_creation() {
+        assert!(pr_creation_allowed(Some(&project_repo(true))));
+        assert!(!pr_creation_allowed(Some(&project_repo(false))));
+        assert!(pr_creation_allowed(None));
+    }
+}
@@ -18,7 +18,7 @@ use db::models::{
     repo::Repo,
     task::{CreateTask, Task, TaskWithAttemptStatus, UpdateTask},
     workspace::{CreateWorkspace, Workspace},
-    workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
+    workspace_repo::WorkspaceRepo,
 };
 use deployment::Deployment;
 use executors::profile::ExecutorProfileId;
@@ -33,8 +33,10 @@ use utils::{api::oauth::LoginStatus, response::ApiResponse};
 use uuid::Uuid;
 
 use crate::{
-    DeploymentImpl, error::ApiError, middleware::load_task_middleware,
-    routes::task_attempts::WorkspaceRepoInput,
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_task_middleware,
+    routes::task_attempts::{WorkspaceRepoInput, resolve_workspace_repos},
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -211,14 +213,7 @@ pub async fn create_task_and_start(
     )
     .await?;
 
-    let workspace_repos: Vec<CreateWorkspaceRepo> = payload
-        .repos
-        .iter()
-        .map(|r| CreateWorkspaceRepo {
-            repo_id: r.repo_id,
-            target_branch: r.target_branch.clone(),
-        })
-        .collect();
+    let workspace_repos = resolve_workspace_repos(&deployment, project.id, &payload.repos).await?;
     WorkspaceRepo::create_many(&deployment.db().pool, workspace.id, &workspace_repos).await?;
 
     let is_attempt_running = deployment
@@ -2,10 +2,12 @@ use std::{
     collections::HashMap,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::{Error as AnyhowError, anyhow};
 use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
 use db::{
     DBService,
     models::{
@@ -19,12 +21,15 @@ use db::{
             CreateExecutionProcessRepoState, ExecutionProcessRepoState,
         },
         project::{Project, UpdateProject},
-        project_repo::{ProjectRepo, ProjectRepoWithName},
+        project_repo::{
+            self, ProjectRepo, ProjectRepoError, ProjectRepoWithName, SetupStep, cleanup_batches,
+            setup_dependencies,
+        },
         repo::Repo,
         session::{CreateSession, Session, SessionError},
         task::{Task, TaskStatus},
         workspace::{Workspace, WorkspaceError},
-        workspace_repo::WorkspaceRepo,
+        workspace_repo::{RepoWithTargetBranch, WorkspaceRepo},
     },
 };
 use executors::{
@@ -38,18 +43,22 @@ use executors::{
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
 use futures::{StreamExt, future};
+use sha2::{Digest, Sha256};
 use sqlx::Error as SqlxError;
 use thiserror::Error;
 use tokio::{sync::RwLock, task::JoinHandle};
 use utils::{
+    assets::asset_dir,
     log_msg::LogMsg,
     msg_store::MsgStore,
+    process::kill_process_group,
+    shell::get_shell_command,
     text::{git_branch_id, short_uuid},
 };
 use uuid::Uuid;
 
 use crate::services::{
-    git::{GitService, GitServiceError},
+    git::{DiffTarget, GitService, GitServiceError},
     notification::NotificationService,
     share::SharePublisher,
     workspace_manager::WorkspaceError as WorkspaceManagerError,
@@ -73,10 +82,18 @@ pub enum ContainerError {
     WorkspaceManager(#[from] WorkspaceManagerError),
     #[error(transparent)]
     Session(#[from] SessionError),
+    #[error(transparent)]
+    ProjectRepo(#[from] ProjectRepoError),
     #[error("Io error: {0}")]
     Io(#[from] std::io::Error),
     #[error("Failed to kill process: {0}")]
     KillFailed(std::io::Error),
+    #[error("Bootstrap script for repo {repo} failed: {error} (log: {})", .log.display())]
+    BootstrapFailed {
+        repo: String,
+        log: PathBuf,
+        error: std::io::Error,
+    },
     #[error(transparent)]
     Other(#[from] AnyhowError), // Catches any unclassified errors
 }
@@ -413,6 +430,7 @@ pub trait ContainerService {
                                 } else {
                                     project.default_agent_working_dir.clone()
                                 },
+                                disable_cleanup: None,
                             },
                         )
                         .await?;
@@ -424,9 +442,13 @@ pub trait ContainerService {
         Ok(())
     }
 
-    fn cleanup_actions_for_repos(&self, repos: &[ProjectRepoWithName]) -> Option<ExecutorAction> {
-        let repos_with_cleanup: Vec<_> = repos
-            .iter()
+    fn cleanup_actions_for_repos(
+        &self,
+        workspace: &Workspace,
+        repos: &[ProjectRepoWithName],
+    ) -> Option<ExecutorAction> {
+        let repos_with_cleanup: Vec<_> = in_cleanup_order(repos)
+            .into_iter()
             .filter(|r| r.cleanup_script.is_some())
             .collect();
 
@@ -438,10 +460,11 @@ pub trait ContainerService {
         let first = iter.next()?;
         let mut root_action = ExecutorAction::new(
             ExecutorActionType::ScriptRequest(ScriptRequest {
-                script: first.cleanup_script.clone().unwrap(),
+                script: cleanup_command(workspace, first)?,
                 language: ScriptRequestLanguage::Bash,
                 context: ScriptContext::CleanupScript,
                 working_dir: Some(first.repo_name.clone()),
+                timeout_secs: None,
             }),
             None,
         );
@@ -449,10 +472,11 @@ pub trait ContainerService {
         for repo in iter {
             root_action = root_action.append_action(ExecutorAction::new(
                 ExecutorActionType::ScriptRequest(ScriptRequest {
-                    script: repo.cleanup_script.clone().unwrap(),
+                    script: cleanup_command(workspace, repo)?,
                     language: ScriptRequestLanguage::Bash,
                     context: ScriptContext::CleanupScript,
                     working_dir: Some(repo.repo_name.clone()),
+                    timeout_secs: None,
                 }),
                 None,
             ));
@@ -461,32 +485,222 @@ pub trait ContainerService {
         Some(root_action)
     }
 
-    fn setup_actions_for_repos(&self, repos: &[ProjectRepoWithName]) -> Option<ExecutorAction> {
-        let repos_with_setup: Vec<_> = repos.iter().filter(|r| r.setup_script.is_some()).collect();
+    /// Run each repo's one-time bootstrap script in the original repository, stamping it on
+    /// success. Output is kept at [`bootstrap_log_path`]; a script that fails or runs past
+    /// `BOOTSTRAP_TIMEOUT` is an error and runs again on the next workspace start.
+    async fn run_pending_bootstraps(&self, project_id: Uuid) -> Result<(), ContainerError> {
+        let pool = &self.db().pool;
+        for project_repo in ProjectRepo::find_by_project_id(pool, project_id).await? {
+            if !project_repo.needs_bootstrap() {
+                continue;
+            }
+            let Some(repo) = Repo::find_by_id(pool, project_repo.repo_id).await? else {
+                continue;
+            };
+            let script = project_repo.bootstrap_script.as_deref().unwrap_or_default();
+            let log = bootstrap_log_path(project_repo.id);
+            if let Err(error) =
+                run_bootstrap_script(&repo.path, script, &log, BOOTSTRAP_TIMEOUT).await
+            {
+                return Err(ContainerError::BootstrapFailed {
+                    repo: repo.name,
+                    log,
+                    error,
+                });
+            }
+            tracing::info!("Bootstrapped repo {} for project {}", repo.name, project_id);
+            ProjectRepo::mark_bootstrapped(pool, project_repo.id).await?;
+        }
+        Ok(())
+    }
 
-        if repos_with_setup.is_empty() {
+    /// Whether a repo's setup script should run for this workspace. A worktree that has never
+    /// completed setup always runs it; after that, repos with `setup_paths` only re-run setup
+    /// when the worktree's changes against the target branch match one of the globs. If the
+    /// history or the changes can't be determined, setup runs.
+    async fn setup_required(
+        &self,
+        workspace: &Workspace,
+        project_repo: &ProjectRepoWithName,
+        target_branches: &[RepoWithTargetBranch],
+    ) -> bool {
+        if project_repo.setup_paths.is_none() {
+            return true;
+        }
+        let (Some(container_ref), Some(target)) = (
+            workspace.container_ref.as_ref(),
+            target_branches
+                .iter()
+                .find(|r| r.repo.id == project_repo.repo_id),
+        ) else {
+            return true;
+        };
+        let worktree_path = Path::new(container_ref).join(&project_repo.repo_name);
+        match ProjectRepo::last_setup_fingerprint(
+            &self.db().pool,
+            project_repo.repo_id,
+            &worktree_path.to_string_lossy(),
+        )
+        .await
+        {
+            Ok(Some(_)) => {}
+            Ok(None) => return true,
+            Err(e) => {
+                tracing::warn!("Failed to load setup history, running setup: {}", e);
+                return true;
+            }
+        }
+        let changed = self
+            .git()
+            .get_base_commit(&target.repo.path, &workspace.branch, &target.target_branch)
+            .and_then(|base_commit| {
+                self.git().get_diffs(
+                    DiffTarget::Worktree {
+                        worktree_path: &worktree_path,
+                        base_commit: &base_commit,
+                    },
+                    None,
+                )
+            });
+        match changed {
+            Ok(diffs) => {
+                let changed: Vec<String> = diffs.iter().map(GitService::diff_path).collect();
+                project_repo.setup_required_for(&changed)
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Could not determine changed files for {}, running setup: {}",
+                    project_repo.repo_name,
+                    e
+                );
+                true
+            }
+        }
+    }
+
+    /// Worktree path and current setup fingerprint for a repo in this workspace, if the
+    /// worktree exists and the repo has a setup script
+    fn current_setup_fingerprint(
+        &self,
+        workspace: &Workspace,
+        project_repo: &ProjectRepoWithName,
+    ) -> Option<(String, String)> {
+        let script = repo_setup_script(project_repo)?;
+        let worktree_path =
+            Path::new(workspace.container_ref.as_ref()?).join(&project_repo.repo_name);
+        let head = self.git().get_head_info(&worktree_path).ok()?;
+        Some((
+            worktree_path.to_string_lossy().to_string(),
+            setup_fingerprint(&script, &head.oid),
+        ))
+    }
+
+    /// Whether the reused worktree last completed setup with the same fingerprint
+    async fn setup_fingerprint_matches(
+        &self,
+        workspace: &Workspace,
+        project_repo: &ProjectRepoWithName,
+    ) -> bool {
+        let Some((worktree_path, fingerprint)) =
+            self.current_setup_fingerprint(workspace, project_repo)
+        else {
+            return false;
+        };
+        match ProjectRepo::last_setup_fingerprint(
+            &self.db().pool,
+            project_repo.repo_id,
+            &worktree_path,
+        )
+        .await
+        {
+            Ok(last) => last.as_deref() == Some(fingerprint.as_str()),
+            Err(e) => {
+                tracing::warn!("Failed to load setup fingerprint: {}", e);
+                false
+            }
+        }
+    }
+
+    /// After a setup script succeeds, remember its fingerprint for the worktree it ran in
+    async fn record_setup_fingerprint(&self, ctx: &ExecutionContext) -> Result<(), ContainerError> {
+        let Ok(action) = ctx.execution_process.executor_action() else {
+            return Ok(());
+        };
+        let ExecutorActionType::ScriptRequest(ScriptRequest {
+            context: ScriptContext::SetupScript,
+            working_dir: Some(repo_name),
+            ..
+        }) = action.typ()
+        else {
+            return Ok(());
+        };
+        let project_repos =
+            ProjectRepo::find_by_project_id_with_names(&self.db().pool, ctx.project.id).await?;
+        let Some(project_repo) = project_repos.iter().find(|pr| &pr.repo_name == repo_name) else {
+            return Ok(());
+        };
+        if skip_setup_offline(project_repo, offline_mode()) {
+            // The script was replaced by a warning; the worktree is not actually set up
+            return Ok(());
+        }
+        if let Some((worktree_path, fingerprint)) =
+            self.current_setup_fingerprint(&ctx.workspace, project_repo)
+        {
+            ProjectRepo::record_setup_success(
+                &self.db().pool,
+                project_repo.repo_id,
+                &worktree_path,
+                &fingerprint,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Cleanup to chain after an agent run, unless the project has automatic cleanup disabled
+    fn auto_cleanup_action(
+        &self,
+        project: &Project,
+        workspace: &Workspace,
+        repos: &[ProjectRepoWithName],
+    ) -> Option<ExecutorAction> {
+        if project.disable_cleanup {
             return None;
         }
+        self.cleanup_actions_for_repos(workspace, repos)
+    }
+
+    fn setup_actions_for_repos(
+        &self,
+        workspace: &Workspace,
+        repos: &[ProjectRepoWithName],
+    ) -> Option<ExecutorAction> {
+        let repos_with_setup: Vec<_> = repos
+            .iter()
+            .filter_map(|r| rendered_setup_script(workspace, r).map(|script| (r, script)))
+            .collect();
 
         let mut iter = repos_with_setup.iter();
-        let first = iter.next()?;
+        let (first, script) = iter.next()?;
         let mut root_action = ExecutorAction::new(
             ExecutorActionType::ScriptRequest(ScriptRequest {
-                script: first.setup_script.clone().unwrap(),
+                script: setup_command(first, script, offline_mode()),
                 language: ScriptRequestLanguage::Bash,
                 context: ScriptContext::SetupScript,
                 working_dir: Some(first.repo_name.clone()),
+                timeout_secs: Some(first.setup_timeout_secs()),
             }),
             None,
         );
 
-        for repo in iter {
+        for (repo, script) in iter {
             root_action = root_action.append_action(ExecutorAction::new(
                 ExecutorActionType::ScriptRequest(ScriptRequest {
-                    script: repo.setup_script.clone().unwrap(),
+                    script: setup_command(repo, script, offline_mode()),
                     language: ScriptRequestLanguage::Bash,
                     context: ScriptContext::SetupScript,
                     working_dir: Some(repo.repo_name.clone()),
+                    timeout_secs: Some(repo.setup_timeout_secs()),
                 }),
                 None,
             ));
@@ -495,33 +709,91 @@ pub trait ContainerService {
         Some(root_action)
     }
 
-    fn setup_action_for_repo(repo: &ProjectRepoWithName) -> Option<ExecutorAction> {
-        repo.setup_script.as_ref().map(|script| {
+    fn setup_action_for_repo(
+        workspace: &Workspace,
+        repo: &ProjectRepoWithName,
+    ) -> Option<ExecutorAction> {
+        rendered_setup_script(workspace, repo).map(|script| {
             ExecutorAction::new(
                 ExecutorActionType::ScriptRequest(ScriptRequest {
-                    script: script.clone(),
+                    script: setup_command(repo, &script, offline_mode()),
                     language: ScriptRequestLanguage::Bash,
                     context: ScriptContext::SetupScript,
                     working_dir: Some(repo.repo_name.clone()),
+                    timeout_secs: Some(repo.setup_timeout_secs()),
                 }),
                 None,
             )
         })
     }
 
+    /// `repos` sorted by the project's setup order, dependencies first. If the order cannot
+    /// be loaded, e.g. because of a dependency cycle, it is logged and the given order kept.
+    async fn in_setup_order<'a>(
+        &self,
+        project_id: Uuid,
+        mut repos: Vec<&'a ProjectRepoWithName>,
+    ) -> Vec<&'a ProjectRepoWithName> {
+        match ProjectRepo::setup_order(&self.db().pool, project_id).await {
+            Ok(batches) => {
+                let order: Vec<Uuid> = batches.into_iter().flatten().collect();
+                repos.sort_by_key(|r| order.iter().position(|id| *id == r.repo_id));
+            }
+            Err(e) => tracing::warn!("Running setup scripts in configured order: {e}"),
+        }
+        repos
+    }
+
+    /// Setup scripts of `repos` chained one after another, with nothing run afterwards
+    fn build_setup_lane(
+        workspace: &Workspace,
+        repos: &[&ProjectRepoWithName],
+    ) -> Option<ExecutorAction> {
+        let mut actions = repos
+            .iter()
+            .rev()
+            .filter_map(|repo| Self::setup_action_for_repo(workspace, repo));
+        let last = actions.next()?;
+        Some(actions.fold(last, |next, mut action| {
+            action.next_action = Some(Box::new(next));
+            action
+        }))
+    }
+
+    /// The repo's dev server as a standalone action, meant to run until stopped
+    fn dev_server_action_for_repo(
+        &self,
+        workspace: &Workspace,
+        repo: &ProjectRepoWithName,
+    ) -> Option<ExecutorAction> {
+        let script = repo.dev_server_script()?;
+        Some(ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: repo.render_script(script, &script_context(workspace, repo)),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::DevServer,
+                working_dir: Some(repo.repo_name.clone()),
+                timeout_secs: None,
+            }),
+            None,
+        ))
+    }
+
     fn build_sequential_setup_chain(
+        workspace: &Workspace,
         repos: &[&ProjectRepoWithName],
         next_action: ExecutorAction,
     ) -> ExecutorAction {
         let mut chained = next_action;
         for repo in repos.iter().rev() {
-            if let Some(script) = &repo.setup_script {
+            if let Some(script) = rendered_setup_script(workspace, repo) {
                 chained = ExecutorAction::new(
                     ExecutorActionType::ScriptRequest(ScriptRequest {
-                        script: script.clone(),
+                        script: setup_command(repo, &script, offline_mode()),
                         language: ScriptRequestLanguage::Bash,
                         context: ScriptContext::SetupScript,
                         working_dir: Some(repo.repo_name.clone()),
+                        timeout_secs: Some(repo.setup_timeout_secs()),
                     }),
                     Some(Box::new(chained)),
                 );
@@ -900,6 +1172,8 @@ pub trait ContainerService {
             .await?
             .ok_or(SqlxError::RowNotFound)?;
 
+        self.run_pending_bootstraps(project.id).await?;
+
         let project_repos =
             ProjectRepo::find_by_project_id_with_names(&self.db().pool, project.id).await?;
 
@@ -918,16 +1192,34 @@ pub trait ContainerService {
         )
         .await?;
 
-        let prompt = task.to_prompt();
+        let prompt = with_repo_context(task.to_prompt(), &project_repos);
 
-        let repos_with_setup: Vec<_> = project_repos
-            .iter()
-            .filter(|pr| pr.setup_script.is_some())
-            .collect();
+        let target_branches = WorkspaceRepo::find_repos_with_target_branch_for_workspace(
+            &self.db().pool,
+            workspace.id,
+        )
+        .await?;
+        let mut repos_with_setup = Vec::new();
+        for pr in project_repos.iter().filter(|pr| pr.has_setup()) {
+            if !self.setup_required(&workspace, pr, &target_branches).await {
+                continue;
+            }
+            if self.setup_fingerprint_matches(&workspace, pr).await {
+                tracing::info!(
+                    "Skipping setup for {}: worktree already set up for the same inputs",
+                    pr.repo_name
+                );
+                continue;
+            }
+            repos_with_setup.push(pr);
+        }
 
-        let all_parallel = repos_with_setup.iter().all(|pr| pr.parallel_setup_script);
+        let repos_with_setup = self.in_setup_order(project.id, repos_with_setup).await;
+        let concurrency = project_repo::effective_setup_concurrency(
+            repos_with_setup.iter().map(|pr| pr.setup_concurrency),
+        );
 
-        let cleanup_action = self.cleanup_actions_for_repos(&project_repos);
+        let cleanup_action = self.auto_cleanup_action(&project, &workspace, &project_repos);
 
         let working_dir = workspace
             .agent_working_dir
@@ -944,10 +1236,13 @@ pub trait ContainerService {
             cleanup_action.map(Box::new),
         );
 
-        let execution_process = if all_parallel {
-            // All parallel: start each setup independently, then start coding agent
-            for repo in &repos_with_setup {
-                if let Some(action) = Self::setup_action_for_repo(repo)
+        let execution_process = if concurrency != Some(1) {
+            // Concurrent: start up to `concurrency` lanes of setups independently, each
+            // running its repos one after another, then start the coding agent. Repos that
+            // depend on each other share a lane so they still run in setup order.
+            let lanes = setup_lanes(&repos_with_setup, concurrency);
+            for lane in &lanes {
+                if let Some(action) = Self::build_setup_lane(&workspace, lane)
                     && let Err(e) = self
                         .start_execution(
                             &workspace,
@@ -969,7 +1264,8 @@ pub trait ContainerService {
             .await?
         } else {
             // Any sequential: chain ALL setups → coding agent via next_action
-            let main_action = Self::build_sequential_setup_chain(&repos_with_setup, coding_action);
+            let main_action =
+                Self::build_sequential_setup_chain(&workspace, &repos_with_setup, coding_action);
             self.start_execution(
                 &workspace,
                 &session,
@@ -1196,3 +1492,493 @@ pub trait ContainerService {
         Ok(())
     }
 }
+
+/// Prepend each repo's configured `context_prompt` to the task prompt
+pub fn with_repo_context(prompt: String, repos: &[ProjectRepoWithName]) -> String {
+    let sections: Vec<String> = repos
+        .iter()
+        .filter_map(|repo| {
+            repo.context_prompt
+                .as_deref()
+                .map(str::trim)
+                .filter(|ctx| !ctx.is_empty())
+                .map(|ctx| format!("Context for repository `{}`:\n{ctx}", repo.repo_name))
+        })
+        .collect();
+
+    if sections.is_empty() {
+        prompt
+    } else {
+        format!("{}\n\n{prompt}", sections.join("\n\n"))
+    }
+}
+
+/// Bootstrap scripts, and everything they started, are killed after this long
+const BOOTSTRAP_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Where the output of a project repo's latest bootstrap run is kept
+pub fn bootstrap_log_path(project_repo_id: Uuid) -> PathBuf {
+    asset_dir()
+        .join("bootstrap_logs")
+        .join(format!("{project_repo_id}.log"))
+}
+
+/// Run `script` in `repo_path` with its output written to `log_path`, killing its process
+/// group if it is still running after `timeout`
+async fn run_bootstrap_script(
+    repo_path: &Path,
+    script: &str,
+    log_path: &Path,
+    timeout: Duration,
+) -> std::io::Result<()> {
+    if let Some(dir) = log_path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    let log = std::fs::File::create(log_path)?;
+    let (shell_cmd, shell_arg) = get_shell_command();
+    let mut child = tokio::process::Command::new(shell_cmd)
+        .arg(shell_arg)
+        .arg(script)
+        .current_dir(repo_path)
+        .stdin(std::process::Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .group_spawn()?;
+    let status = match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(status) => status?,
+        Err(_) => {
+            kill_process_group(&mut child).await?;
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("timed out after {}s", timeout.as_secs()),
+            ));
+        }
+    };
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("exited with {status}")))
+    }
+}
+
+/// Fingerprint of a repo's setup inputs: the setup script and the worktree's HEAD commit
+pub fn setup_fingerprint(script: &str, head_oid: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(script.as_bytes());
+    hasher.update([0]);
+    hasher.update(head_oid.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// `repos` ordered so each is cleaned up before the repos its setup depends on. A dependency
+/// cycle is logged and the given order kept.
+fn in_cleanup_order(repos: &[ProjectRepoWithName]) -> Vec<&ProjectRepoWithName> {
+    let graph: Vec<_> = repos
+        .iter()
+        .map(|r| (r.repo_id, setup_dependencies(r.setup_depends_on.as_deref())))
+        .collect();
+    match cleanup_batches(&graph) {
+        Ok(batches) => batches
+            .into_iter()
+            .flatten()
+            .filter_map(|id| repos.iter().find(|r| r.repo_id == id))
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Running cleanup scripts in configured order: {e}");
+            repos.iter().collect()
+        }
+    }
+}
+
+/// Offline mode, enabled by setting `VK_OFFLINE`, skips setup scripts marked as needing
+/// network access
+pub fn offline_mode() -> bool {
+    std::env::var("VK_OFFLINE").is_ok()
+}
+
+fn skip_setup_offline(repo: &ProjectRepoWithName, offline: bool) -> bool {
+    offline && repo.setup_requires_network
+}
+
+/// Script run for a repo's setup step. When offline and the setup needs the network, it is
+/// replaced by a warning in the process log so the rest of the chain still runs.
+fn setup_command(repo: &ProjectRepoWithName, script: &str, offline: bool) -> String {
+    if !skip_setup_offline(repo, offline) {
+        return with_setup_retries(script, repo.setup_retries);
+    }
+    let warning = format!(
+        "Skipping setup script for {}: it requires network access and offline mode is enabled",
+        repo.repo_name
+    );
+    tracing::warn!("{warning}");
+    format!("echo {} >&2", shell_quote(&warning))
+}
+
+/// Placeholder values for a repo's scripts in `workspace`
+fn script_context(
+    workspace: &Workspace,
+    repo: &ProjectRepoWithName,
+) -> project_repo::ScriptContext {
+    let worktree_path = workspace
+        .container_ref
+        .as_deref()
+        .map(|dir| Path::new(dir).join(&repo.repo_name))
+        .unwrap_or_else(|| PathBuf::from(&repo.repo_name));
+    project_repo::ScriptContext {
+        worktree_path: worktree_path.to_string_lossy().into_owned(),
+        repo_name: repo.repo_name.clone(),
+        branch_name: workspace.branch.clone(),
+    }
+}
+
+/// The repo's setup script with its placeholders filled in for `workspace`
+fn rendered_setup_script(workspace: &Workspace, repo: &ProjectRepoWithName) -> Option<String> {
+    let script = repo_setup_script(repo)?;
+    Some(repo.render_script(&script, &script_context(workspace, repo)))
+}
+
+/// The repo's cleanup script with its placeholders filled in for `workspace`
+fn cleanup_command(workspace: &Workspace, repo: &ProjectRepoWithName) -> Option<String> {
+    let script = repo.cleanup_script.as_deref()?;
+    Some(repo.render_script(script, &script_context(workspace, repo)))
+}
+
+/// The setup script for a repo: a plain `setup_script` as is, or its setup steps joined
+/// into one script that announces each step and names the one that failed. Steps that
+/// don't parse become a script that reports why and fails.
+fn repo_setup_script(repo: &ProjectRepoWithName) -> Option<String> {
+    if !repo.has_setup() {
+        return None;
+    }
+    match repo.setup_steps() {
+        Ok(steps) => render_setup_steps(&steps),
+        Err(e) => {
+            tracing::error!("Invalid setup steps for {}: {e}", repo.repo_name);
+            Some(format!("echo {} >&2\nexit 1", shell_quote(&e.to_string())))
+        }
+    }
+}
+
+fn render_setup_steps(steps: &[SetupStep]) -> Option<String> {
+    match steps {
+        [] => None,
+        [
+            SetupStep {
+                name: None,
+                command,
+            },
+        ] => Some(command.clone()),
+        steps => Some(
+            steps
+                .iter()
+                .enumerate()
+                .map(|(i, step)| {
+                    let name = shell_quote(
+                        &step
+                            .name
+                            .clone()
+                            .unwrap_or_else(|| format!("step {}", i + 1)),
+                    );
+                    format!(
+                        r#"echo "==> Setup step "{name} >&2
+(
+{command}
+) || {{
+  vk_status=$?
+  echo "Setup step "{name}" failed with exit status $vk_status" >&2
+  exit "$vk_status"
+}}"#,
+                        command = step.command
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+    }
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Wrap a setup script so it is re-run up to `retries` times on non-zero exit, with a
+/// linear backoff between attempts. Windows `cmd` scripts are left untouched.
+pub fn with_setup_retries(script: &str, retries: Option<i64>) -> String {
+    let retries = retries.unwrap_or(0);
+    if retries <= 0 || cfg!(windows) {
+        return script.to_string();
+    }
+
+    format!(
+        r#"vk_attempt=0
+while :; do
+  (
+{script}
+  ) && exit 0
+  vk_status=$?
+  vk_attempt=$((vk_attempt + 1))
+  if [ "$vk_attempt" -gt {retries} ]; then
+    exit "$vk_status"
+  fi
+  echo "Setup script exited with status $vk_status, retrying ($vk_attempt/{retries})" >&2
+  sleep $((vk_attempt * 2))
+done"#
+    )
+}
+
+/// Split `repos`, given in setup order, round-robin into at most `concurrency` lanes, `None`
+/// giving each repo its own lane. A repo shares a lane with the repos its setup depends on,
+/// and repos keep their relative order within a lane.
+fn setup_lanes<'a>(
+    repos: &[&'a ProjectRepoWithName],
+    concurrency: Option<usize>,
+) -> Vec<Vec<&'a ProjectRepoWithName>> {
+    // Repos linked by dependencies, each group in setup order
+    let mut groups: Vec<Vec<&'a ProjectRepoWithName>> = Vec::new();
+    for repo in repos {
+        let deps = setup_dependencies(repo.setup_depends_on.as_deref());
+        let (linked, mut rest): (Vec<_>, Vec<_>) = groups
+            .into_iter()
+            .partition(|group| group.iter().any(|r| deps.contains(&r.repo_id)));
+        let mut group: Vec<_> = linked.into_iter().flatten().collect();
+        group.push(*repo);
+        rest.push(group);
+        groups = rest;
+    }
+
+    let lane_count = concurrency
+        .unwrap_or(groups.len())
+        .clamp(1, groups.len().max(1));
+    let mut lanes = vec![Vec::new(); lane_count];
+    for (i, group) in groups.into_iter().enumerate() {
+        lanes[i % lane_count].extend(group);
+    }
+    lanes.retain(|lane| !lane.is_empty());
+    lanes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project_repo(name: &str, context_prompt: Option<&str>) -> ProjectRepoWithName {
+        ProjectRepoWithName {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            repo_id: Uuid::new_v4(),
+            repo_name: name.to_string(),
+            setup_concurrency: 1,
+            context_prompt: context_prompt.map(str::to_string),
+            auto_open_pr: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn repo_context_is_prepended_to_prompt() {
+        let repos = vec![
+            project_repo("api", Some("Run `cargo test` before finishing.")),
+            project_repo("web", None),
+            project_repo("docs", Some("  ")),
+        ];
+        let prompt = with_repo_context("Fix the bug".to_string(), &repos);
+        assert_eq!(
+            prompt,
+            "Context for repository `api`:\nRun `cargo test` before finishing.\n\nFix the bug"
+        );
+
+        let untouched = with_repo_context("Fix the bug".to_string(), &repos[1..]);
+        assert_eq!(untouched, "Fix the bug");
+    }
+
+    #[test]
+    fn setup_lanes_cap_concurrent_setups() {
+        let repos: Vec<_> = ["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|name| project_repo(name, None))
+            .collect();
+        let refs: Vec<&ProjectRepoWithName> = repos.iter().collect();
+        let names = |lanes: Vec<Vec<&ProjectRepoWithName>>| {
+            lanes
+                .iter()
+                .map(|lane| {
+                    lane.iter()
+                        .map(|r| r.repo_name.as_str())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(setup_lanes(&refs, Some(2))),
+            [vec!["a", "c", "e"], vec!["b", "d"]]
+        );
+        assert_eq!(setup_lanes(&refs, None).len(), 5);
+        assert_eq!(setup_lanes(&refs, Some(10)).len(), 5);
+        assert!(setup_lanes(&[], None).is_empty());
+    }
+
+    #[test]
+    fn setup_lanes_keep_dependent_repos_together() {
+        let mut repos: Vec<_> = ["a", "b", "c"]
+            .into_iter()
+            .map(|name| project_repo(name, None))
+            .collect();
+        repos[2].setup_depends_on = Some(serde_json::json!([repos[0].repo_id]).to_string());
+        let refs: Vec<&ProjectRepoWithName> = repos.iter().collect();
+
+        let lanes = setup_lanes(&refs, None);
+        let names: Vec<Vec<&str>> = lanes
+            .iter()
+            .map(|lane| lane.iter().map(|r| r.repo_name.as_str()).collect())
+            .collect();
+        assert_eq!(names, [vec!["b"], vec!["a", "c"]]);
+    }
+
+    #[test]
+    fn setup_fingerprint_changes_with_script_and_head() {
+        let base = setup_fingerprint("pnpm install", "abc123");
+        assert_eq!(base, setup_fingerprint("pnpm install", "abc123"));
+        assert_ne!(
+            base,
+            setup_fingerprint("pnpm install --frozen-lockfile", "abc123")
+        );
+        assert_ne!(base, setup_fingerprint("pnpm install", "def456"));
+    }
+
+    #[test]
+    fn setup_retries_wrap_script_only_when_positive() {
+        assert_eq!(with_setup_retries("npm ci", None), "npm ci");
+        assert_eq!(with_setup_retries("npm ci", Some(0)), "npm ci");
+
+        let wrapped = with_setup_retries("npm ci", Some(2));
+        if cfg!(windows) {
+            assert_eq!(wrapped, "npm ci");
+        } else {
+            assert!(wrapped.contains("\nnpm ci\n"));
+            assert!(wrapped.contains(r#"-gt 2 ]"#));
+        }
+    }
+
+    #[test]
+    fn network_setup_is_replaced_by_warning_when_offline() {
+        let mut repo = project_repo("web", None);
+        assert_eq!(setup_command(&repo, "npm ci", true), "npm ci");
+
+        repo.setup_requires_network = true;
+        assert_eq!(setup_command(&repo, "npm ci", false), "npm ci");
+        let offline = setup_command(&repo, "npm ci", true);
+        assert!(!offline.contains("npm ci"));
+        assert!(offline.starts_with("echo 'Skipping setup script for web:"));
+        assert!(offline.ends_with(">&2"));
+    }
+
+    #[test]
+    fn plain_setup_script_runs_unchanged() {
+        let mut repo = project_repo("web", None);
+        assert_eq!(repo_setup_script(&repo), None);
+
+        repo.setup_script = Some("npm ci".to_string());
+        assert_eq!(repo_setup_script(&repo).as_deref(), Some("npm ci"));
+
+        repo.setup_steps = Some("not json".to_string());
+        let invalid = repo_setup_script(&repo).unwrap();
+        assert!(invalid.starts_with("echo 'Setup steps must be"));
+        assert!(invalid.ends_with("exit 1"));
+    }
+
+    #[test]
+    fn scripts_are_rendered_for_the_workspace() {
+        let mut repo = project_repo("web", None);
+        repo.setup_script = Some("cp .env ${WORKTREE_PATH}/.env # ${BRANCH_NAME}".to_string());
+        repo.cleanup_script = Some("echo cleaned ${REPO_NAME} in $${HOME}".to_string());
+        let workspace = Workspace {
+            id: Uuid::new_v4(),
+            task_id: Uuid::new_v4(),
+            container_ref: Some("/tmp/worktrees/vk-1".to_string()),
+            branch: "vk/1-fix".to_string(),
+            agent_working_dir: None,
+            setup_completed_at: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+
+        let expected_path = Path::new("/tmp/worktrees/vk-1").join("web");
+        assert_eq!(
+            rendered_setup_script(&workspace, &repo).unwrap(),
+            format!("cp .env {}/.env # vk/1-fix", expected_path.display())
+        );
+        assert_eq!(
+            cleanup_command(&workspace, &repo).unwrap(),
+            "echo cleaned web in ${HOME}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn setup_steps_report_the_failing_step() {
+        let mut repo = project_repo("web", None);
+        repo.setup_steps = Some(
+            r#"[{"name": "install", "command": "echo installed"},
+                {"name": "migrate", "command": "exit 3"},
+                {"name": "seed", "command": "echo seeded"}]"#
+                .to_string(),
+        );
+
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(repo_setup_script(&repo).unwrap())
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(stdout, "installed\n");
+        assert!(stderr.contains("==> Setup step install"));
+        assert!(stderr.contains("Setup step migrate failed with exit status 3"));
+        assert!(!stderr.contains("seed"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn setup_retries_rerun_failing_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let counter = dir.path().join("count");
+        let script = format!("echo x >> {0}; [ $(wc -l < {0}) -ge 2 ]", counter.display());
+
+        let status = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(with_setup_retries(&script, Some(3)))
+            .status()
+            .unwrap();
+        let attempts = std::fs::read_to_string(&counter).unwrap().lines().count();
+
+        assert!(status.success());
+        assert_eq!(attempts, 2);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn bootstrap_script_output_is_logged_and_runaways_are_killed() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("logs").join("bootstrap.log");
+
+        run_bootstrap_script(
+            dir.path(),
+            "echo primed; echo warn >&2",
+            &log,
+            BOOTSTRAP_TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "primed\nwarn\n");
+
+        let failed = run_bootstrap_script(dir.path(), "exit 4", &log, BOOTSTRAP_TIMEOUT).await;
+        assert!(failed.unwrap_err().to_string().contains("exit status: 4"));
+
+        let timed_out =
+            run_bootstrap_script(dir.path(), "sleep 30", &log, Duration::from_millis(100)).await;
+        assert_eq!(timed_out.unwrap_err().kind(), std::io::ErrorKind::TimedOut);
+    }
+}
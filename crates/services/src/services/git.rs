@@ -1042,6 +1042,35 @@ impl GitService {
         }
     }
 
+    /// Whether `HEAD` of the repository at `repo_path` resolves to a commit. A fresh
+    /// `git init` sits on an unborn branch, from which no worktree can be created.
+    pub fn has_commits(&self, repo_path: &Path) -> Result<bool, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        match repo.head() {
+            Ok(head) => Ok(head.peel_to_commit().is_ok()),
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The branch the default remote's HEAD points at (e.g. `develop` for
+    /// `refs/remotes/origin/HEAD -> origin/develop`), falling back to the current
+    /// branch when the remote HEAD is unknown (no remote, or never fetched)
+    pub fn get_default_branch(&self, repo_path: &Path) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let remote = self.default_remote_name(&repo);
+        let prefix = format!("refs/remotes/{remote}/");
+        let remote_head = repo
+            .find_reference(&format!("{prefix}HEAD"))
+            .ok()
+            .and_then(|r| r.symbolic_target().map(|t| t.to_string()))
+            .and_then(|target| target.strip_prefix(&prefix).map(|b| b.to_string()));
+        match remote_head {
+            Some(branch) => Ok(branch),
+            None => Ok(self.get_head_info(repo_path)?.branch),
+        }
+    }
+
     /// Get the commit OID (as hex string) for a given branch without modifying HEAD
     pub fn get_branch_oid(
         &self,
@@ -1,21 +1,45 @@
-use std::{path::Path, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    pin::Pin,
+    process::Stdio,
+    sync::{Arc, LazyLock},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
 
-use backon::{ExponentialBuilder, Retryable};
 use chrono::{DateTime, Utc};
-use db::models::merge::PullRequestInfo;
-use serde::Serialize;
+use db::models::merge::{MergeStatus, PullRequestInfo};
+use futures::{StreamExt, stream};
+use moka::future::Cache;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
 use thiserror::Error;
-use tokio::task;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader, ReadBuf},
+    process::{Child, ChildStdout},
+    sync::RwLock,
+    task,
+};
 use tracing::info;
 use ts_rs::TS;
 
+mod api;
 mod cli;
 
-use cli::{GhCli, GhCliError, PrComment, PrReviewComment};
-pub use cli::{PrCommentAuthor, ReviewCommentUser};
+use api::GhApi;
+use async_trait::async_trait;
+use cli::{GhCli, GhCliError, PrComment, PrPeople, RepoContent};
+pub use cli::{
+    PrCommentAuthor, PrReview, PrReviewComment, ReactionSummary, ReviewCommentUser, ReviewState,
+};
+
+use crate::services::vcs::{
+    CliFailure, RetryableError, VcsProvider, classify_cli_failure, with_retry,
+};
 
 /// Unified PR comment that can be either a general comment or review comment
-#[derive(Debug, Clone, Serialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(tag = "comment_type", rename_all = "snake_case")]
 #[ts(tag = "comment_type", rename_all = "snake_case")]
 pub enum UnifiedPrComment {
@@ -23,34 +47,150 @@ pub enum UnifiedPrComment {
     General {
         id: String,
         author: String,
-        author_association: String,
+        author_association: AuthorAssociation,
+        /// GitHub's association string as received, for values newer than the enum
+        author_association_raw: String,
         body: String,
         created_at: DateTime<Utc>,
         url: String,
+        #[serde(default)]
+        reactions: ReactionSummary,
     },
     /// Inline review comment (on code)
     Review {
         id: i64,
         author: String,
-        author_association: String,
+        author_association: AuthorAssociation,
+        /// GitHub's association string as received, for values newer than the enum
+        author_association_raw: String,
         body: String,
         created_at: DateTime<Utc>,
         url: String,
         path: String,
         line: Option<i64>,
         diff_hunk: String,
+        #[serde(default)]
+        reactions: ReactionSummary,
+        /// The commented code is gone from the PR's current diff, e.g. after a force-push.
+        /// Only computed when requested; `false` otherwise.
+        #[serde(default)]
+        outdated: bool,
+    },
+    /// A submitted review's verdict and top-level body
+    ReviewSummary {
+        id: String,
+        author: String,
+        author_association: AuthorAssociation,
+        /// GitHub's association string as received, for values newer than the enum
+        author_association_raw: String,
+        state: ReviewState,
+        body: String,
+        created_at: DateTime<Utc>,
+        url: String,
     },
 }
 
+/// How a comment's author relates to the repository, from GitHub's `author_association`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum AuthorAssociation {
+    Owner,
+    Member,
+    Collaborator,
+    Contributor,
+    None,
+    /// Any other value, e.g. `FIRST_TIME_CONTRIBUTOR` or `MANNEQUIN`
+    Other(String),
+}
+
+impl From<&str> for AuthorAssociation {
+    fn from(raw: &str) -> Self {
+        match raw.to_ascii_uppercase().as_str() {
+            "OWNER" => Self::Owner,
+            "MEMBER" => Self::Member,
+            "COLLABORATOR" => Self::Collaborator,
+            "CONTRIBUTOR" => Self::Contributor,
+            "NONE" | "" => Self::None,
+            _ => Self::Other(raw.to_string()),
+        }
+    }
+}
+
 impl UnifiedPrComment {
-    fn created_at(&self) -> DateTime<Utc> {
+    pub(crate) fn created_at(&self) -> DateTime<Utc> {
         match self {
             UnifiedPrComment::General { created_at, .. } => *created_at,
             UnifiedPrComment::Review { created_at, .. } => *created_at,
+            UnifiedPrComment::ReviewSummary { created_at, .. } => *created_at,
+        }
+    }
+
+    pub fn author_association(&self) -> &AuthorAssociation {
+        match self {
+            UnifiedPrComment::General {
+                author_association, ..
+            }
+            | UnifiedPrComment::Review {
+                author_association, ..
+            }
+            | UnifiedPrComment::ReviewSummary {
+                author_association, ..
+            } => author_association,
+        }
+    }
+
+    pub fn author_association_raw(&self) -> &str {
+        match self {
+            UnifiedPrComment::General {
+                author_association_raw,
+                ..
+            }
+            | UnifiedPrComment::Review {
+                author_association_raw,
+                ..
+            }
+            | UnifiedPrComment::ReviewSummary {
+                author_association_raw,
+                ..
+            } => author_association_raw,
+        }
+    }
+
+    /// The first ```suggestion block of a review comment, if any
+    pub fn suggestion(&self) -> Option<Suggestion> {
+        self.suggestions().into_iter().next()
+    }
+
+    /// Every ```suggestion block of a review comment, in order. General comments and review
+    /// summaries aren't anchored to a file so never carry suggestions.
+    pub fn suggestions(&self) -> Vec<Suggestion> {
+        match self {
+            UnifiedPrComment::General { .. } | UnifiedPrComment::ReviewSummary { .. } => Vec::new(),
+            UnifiedPrComment::Review {
+                body, path, line, ..
+            } => parse_suggestion_blocks(body)
+                .into_iter()
+                .map(|replacement| Suggestion {
+                    path: path.clone(),
+                    line: *line,
+                    replacement,
+                })
+                .collect(),
         }
     }
 }
 
+/// A reviewer's proposed replacement for the commented line(s) of a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Suggestion {
+    pub path: String,
+    /// Line the review comment is anchored to; `None` for outdated comments
+    pub line: Option<i64>,
+    /// Replacement text, without the trailing newline. Empty means delete the line(s).
+    pub replacement: String,
+}
+
 #[derive(Debug, Error)]
 pub enum GitHubServiceError {
     #[error("Repository error: {0}")]
@@ -63,6 +203,20 @@ pub enum GitHubServiceError {
     InsufficientPermissions(GhCliError),
     #[error("GitHub repository not found or no access: {0}")]
     RepoNotFoundOrNoAccess(GhCliError),
+    #[error("File not found: {0}")]
+    FileNotFound(String),
+    #[error("Path is a directory, not a file: {0}")]
+    NotAFile(String),
+    #[error("Invalid PR reference '{0}': expected #N, N, or owner/repo#N")]
+    InvalidPrReference(String),
+    #[error("Invalid commit status: {0}")]
+    InvalidCommitStatus(String),
+    #[error("Pull request is not mergeable: {0}")]
+    NotMergeable(GhCliError),
+    #[error("Invalid reviewer or assignee: {0}")]
+    InvalidReviewer(String),
+    #[error("GitHub rate limit exceeded, retry after {}s", retry_after.as_secs())]
+    RateLimited { retry_after: Duration },
     #[error(
         "GitHub CLI is not installed or not available in PATH. Please install it from https://cli.github.com/ and authenticate with 'gh auth login'"
     )]
@@ -74,31 +228,156 @@ impl From<GhCliError> for GitHubServiceError {
         match &error {
             GhCliError::AuthFailed(_) => Self::AuthFailed(error),
             GhCliError::NotAvailable => Self::GhCliNotInstalled(error),
-            GhCliError::CommandFailed(msg) => {
-                let lower = msg.to_ascii_lowercase();
-                if lower.contains("403") || lower.contains("forbidden") {
-                    Self::InsufficientPermissions(error)
-                } else if lower.contains("404") || lower.contains("not found") {
-                    Self::RepoNotFoundOrNoAccess(error)
-                } else {
-                    Self::PullRequest(msg.to_string())
-                }
+            GhCliError::CommandFailed(msg) if is_rate_limited(msg) => Self::RateLimited {
+                retry_after: rate_limit_retry_after(msg, Utc::now()),
+            },
+            GhCliError::CommandFailed(msg) if is_unresolved_user(msg) => {
+                Self::InvalidReviewer(msg.to_string())
             }
+            GhCliError::CommandFailed(msg) => match classify_cli_failure(msg) {
+                CliFailure::NotMergeable => Self::NotMergeable(error),
+                CliFailure::Forbidden => Self::InsufficientPermissions(error),
+                CliFailure::NotFound => Self::RepoNotFoundOrNoAccess(error),
+                CliFailure::Other => Self::PullRequest(msg.to_string()),
+            },
             GhCliError::UnexpectedOutput(msg) => Self::PullRequest(msg.to_string()),
         }
     }
 }
 
-impl GitHubServiceError {
-    pub fn should_retry(&self) -> bool {
+impl RetryableError for GitHubServiceError {
+    fn should_retry(&self) -> bool {
         !matches!(
             self,
             GitHubServiceError::AuthFailed(_)
                 | GitHubServiceError::InsufficientPermissions(_)
                 | GitHubServiceError::RepoNotFoundOrNoAccess(_)
                 | GitHubServiceError::GhCliNotInstalled(_)
+                | GitHubServiceError::FileNotFound(_)
+                | GitHubServiceError::NotAFile(_)
+                | GitHubServiceError::InvalidPrReference(_)
+                | GitHubServiceError::InvalidCommitStatus(_)
+                | GitHubServiceError::NotMergeable(_)
+                | GitHubServiceError::InvalidReviewer(_)
         )
     }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            GitHubServiceError::RateLimited { retry_after } => Some(*retry_after),
+            _ => None,
+        }
+    }
+}
+
+/// `gh pr edit` fails with e.g. "could not add label: 'foo' not found"
+fn is_label_not_found(stderr: &str) -> bool {
+    let lower = stderr.to_ascii_lowercase();
+    lower.contains("label") && lower.contains("not found")
+}
+
+/// The requested labels named in a label-not-found error, or all of them if none are quoted
+fn missing_labels(stderr: &str, requested: &[String]) -> Vec<String> {
+    let named: Vec<String> = requested
+        .iter()
+        .filter(|label| stderr.contains(&format!("'{label}'")))
+        .cloned()
+        .collect();
+    if named.is_empty() {
+        requested.to_vec()
+    } else {
+        named
+    }
+}
+
+/// Primary (`API rate limit exceeded`) and secondary (`exceeded a secondary rate limit`)
+/// limits both arrive as HTTP 403 or 429
+fn is_rate_limited(stderr: &str) -> bool {
+    let lower = stderr.to_ascii_lowercase();
+    lower.contains("rate limit")
+        || lower.contains("http 429")
+        || lower.contains("too many requests")
+}
+
+/// How long to back off after a rate limit, from a `Retry-After` or `X-RateLimit-Reset`
+/// header when `gh` printed one. Secondary limits usually carry neither, and clear within
+/// about a minute.
+fn rate_limit_retry_after(stderr: &str, now: DateTime<Utc>) -> Duration {
+    let header = |name: &str| {
+        stderr.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim().parse::<i64>().ok())
+                .flatten()
+        })
+    };
+    if let Some(secs) = header("retry-after") {
+        return Duration::from_secs(secs.max(0) as u64);
+    }
+    if let Some(reset) = header("x-ratelimit-reset") {
+        return Duration::from_secs((reset - now.timestamp()).max(0) as u64);
+    }
+    DEFAULT_RATE_LIMIT_WAIT
+}
+
+/// `gh` reports unknown `--reviewer`/`--assignee` logins as "could not resolve to a User";
+/// an unresolvable repository is a different failure
+fn is_unresolved_user(stderr: &str) -> bool {
+    let lower = stderr.to_ascii_lowercase();
+    lower.contains("could not resolve") && !lower.contains("repository")
+}
+
+/// How someone is involved in a PR
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticipantRole {
+    Author,
+    Reviewer,
+    RequestedReviewer,
+    Commenter,
+}
+
+/// A PR participant with every role they hold, e.g. to @-mention on updates
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct Participant {
+    pub login: String,
+    pub roles: Vec<ParticipantRole>,
+}
+
+/// A label defined on a repository, for picking existing labels in the UI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct Label {
+    pub name: String,
+    /// Hex color without the leading `#`
+    pub color: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// State of a commit status check, as accepted by the GitHub statuses API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum StatusState {
+    Pending,
+    Success,
+    Failure,
+    Error,
+}
+
+/// Reason shown when locking a conversation, as accepted by the GitHub lock API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+pub enum LockReason {
+    #[strum(serialize = "off-topic")]
+    OffTopic,
+    #[strum(serialize = "too heated")]
+    TooHeated,
+    #[strum(serialize = "resolved")]
+    Resolved,
+    #[strum(serialize = "spam")]
+    Spam,
 }
 
 #[derive(Debug, Clone)]
@@ -114,11 +393,210 @@ pub struct CreatePrRequest {
     pub head_branch: String,
     pub base_branch: String,
     pub draft: Option<bool>,
+    /// Logins to request a review from
+    pub reviewers: Vec<String>,
+    pub assignees: Vec<String>,
+}
+
+/// How `gh pr merge` combines the PR into its base branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    Merge,
+    Squash,
+    Rebase,
+}
+
+/// Which pull requests a branch listing returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrState {
+    Open,
+    Closed,
+    Merged,
+    All,
+}
+
+impl PrState {
+    /// The single state to ask the host for; several states fetch everything and are
+    /// narrowed down afterwards by [`PrState::admits`]
+    pub fn query_state(states: &[PrState]) -> PrState {
+        match states {
+            [state] => *state,
+            _ => PrState::All,
+        }
+    }
+
+    /// Whether a PR in `status` belongs in a listing of `states`. No states means all of them.
+    pub fn admits(states: &[PrState], status: &MergeStatus) -> bool {
+        states.is_empty()
+            || states.iter().any(|state| match state {
+                PrState::Open => matches!(status, MergeStatus::Open),
+                PrState::Closed => matches!(status, MergeStatus::Closed),
+                PrState::Merged => matches!(status, MergeStatus::Merged),
+                PrState::All => true,
+            })
+    }
+
+    pub fn as_gh_state(self) -> &'static str {
+        match self {
+            PrState::Open => "open",
+            PrState::Closed => "closed",
+            PrState::Merged => "merged",
+            PrState::All => "all",
+        }
+    }
+}
+
+/// PRs in `states`, newest first. PR numbers grow with creation time within a repo.
+pub(crate) fn select_prs(
+    mut prs: Vec<PullRequestInfo>,
+    states: &[PrState],
+) -> Vec<PullRequestInfo> {
+    prs.retain(|pr| PrState::admits(states, &pr.status));
+    prs.sort_by_key(|pr| std::cmp::Reverse(pr.number));
+    prs
+}
+
+/// Whether a PR can be merged as it stands, without attempting the merge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum Mergeability {
+    Clean,
+    Conflicting,
+    /// GitHub hasn't finished computing mergeability
+    Unknown,
+    /// Mergeable in principle but held back, e.g. by required reviews or checks, a draft
+    /// state, or a base branch the PR is behind
+    Blocked,
+}
+
+/// A CI check reported on a PR's head commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct CheckRun {
+    pub name: String,
+    pub status: CheckStatus,
+    /// Set once the check has completed
+    pub conclusion: Option<CheckConclusion>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Queued,
+    InProgress,
+    Completed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckConclusion {
+    Success,
+    Failure,
+    Neutral,
+    Cancelled,
+    Skipped,
+    TimedOut,
+    ActionRequired,
+    Stale,
+}
+
+/// Verdict of a PR review posted with `gh pr review`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrReviewSubmission {
+    Approve,
+    RequestChanges { body: String },
+    Comment { body: String },
+}
+
+impl PrReviewSubmission {
+    /// `gh pr review` flags selecting the verdict and its body
+    pub fn cli_args(&self) -> Vec<String> {
+        match self {
+            PrReviewSubmission::Approve => vec!["--approve".to_string()],
+            PrReviewSubmission::RequestChanges { body } => {
+                vec![
+                    "--request-changes".to_string(),
+                    "--body".to_string(),
+                    body.clone(),
+                ]
+            }
+            PrReviewSubmission::Comment { body } => {
+                vec!["--comment".to_string(), "--body".to_string(), body.clone()]
+            }
+        }
+    }
+}
+
+impl MergeStrategy {
+    pub fn as_flag(self) -> &'static str {
+        match self {
+            MergeStrategy::Merge => "--merge",
+            MergeStrategy::Squash => "--squash",
+            MergeStrategy::Rebase => "--rebase",
+        }
+    }
+}
+
+async fn canonical_repo_path(repo_path: &Path) -> PathBuf {
+    tokio::fs::canonicalize(repo_path)
+        .await
+        .unwrap_or_else(|_| repo_path.to_path_buf())
+}
+
+fn origin_remote_url(repo_path: &Path) -> Option<String> {
+    let repo = git2::Repository::open(repo_path).ok()?;
+    let remote = repo.find_remote("origin").ok()?;
+    remote.url().map(str::to_string)
+}
+
+/// Org membership changes rarely, so reviewer autocomplete can tolerate a few minutes of staleness
+const PR_STATUS_CONCURRENCY: usize = 8;
+
+/// Extra polls while GitHub is still computing a PR's mergeability
+const MERGEABILITY_POLL_RETRIES: usize = 2;
+const MERGEABILITY_POLL_DELAY: Duration = Duration::from_secs(2);
+
+/// Back-off after a rate limit that didn't say when it resets
+const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+static ORG_MEMBERS_CACHE: LazyLock<Cache<String, Vec<String>>> = LazyLock::new(|| {
+    Cache::builder()
+        .max_capacity(64)
+        .time_to_live(Duration::from_secs(300))
+        .build()
+});
+
+/// Labels change rarely; a short TTL keeps autocomplete fast without going stale for long
+static LABELS_CACHE: LazyLock<Cache<String, Vec<Label>>> = LazyLock::new(|| {
+    Cache::builder()
+        .max_capacity(64)
+        .time_to_live(Duration::from_secs(300))
+        .build()
+});
+
+/// How long a resolved repo stays cached while its remote is unchanged
+const REPO_INFO_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone)]
+struct CachedRepoInfo {
+    info: GitHubRepoInfo,
+    /// The `origin` URL at lookup time; a different URL means the cached repo is stale
+    remote_url: Option<String>,
+    fetched_at: Instant,
+}
+
+impl CachedRepoInfo {
+    fn is_fresh(&self, remote_url: Option<&str>) -> bool {
+        self.fetched_at.elapsed() < REPO_INFO_TTL && self.remote_url.as_deref() == remote_url
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct GitHubService {
     gh_cli: GhCli,
+    /// REST client for read-only calls, when constructed with `with_api_client`
+    api: Option<GhApi>,
+    /// Repo info by canonical repo path, shared between clones of the service
+    repo_info_cache: Arc<RwLock<HashMap<PathBuf, CachedRepoInfo>>>,
 }
 
 impl GitHubService {
@@ -126,21 +604,94 @@ impl GitHubService {
     pub fn new() -> Result<Self, GitHubServiceError> {
         Ok(Self {
             gh_cli: GhCli::new(),
+            api: None,
+            repo_info_cache: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Like `new`, but serve PR status, branch PR listings and comments from the REST API
+    /// using the token `gh` is logged in with, instead of spawning `gh` per call. Writes
+    /// still go through the CLI.
+    pub async fn with_api_client() -> Result<Self, GitHubServiceError> {
+        let service = Self::new()?;
+        let api = service
+            .run_cli("reading the gh auth token", |cli| {
+                GhApi::from_gh_token(&cli)
+            })
+            .await?;
+        Ok(Self {
+            api: Some(api),
+            ..service
         })
     }
 
+    /// Retry a GitHub call with exponential backoff, skipping errors that won't resolve on retry
+    async fn with_retry<T, F, Fut>(&self, op: F) -> Result<T, GitHubServiceError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, GitHubServiceError>>,
+    {
+        with_retry(op).await
+    }
+
+    /// Run a blocking GitHub CLI call off the async runtime
+    async fn run_cli<T, F>(&self, action: &str, f: F) -> Result<T, GitHubServiceError>
+    where
+        F: FnOnce(GhCli) -> Result<T, GhCliError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let cli = self.gh_cli.clone();
+        task::spawn_blocking(move || f(cli))
+            .await
+            .map_err(|err| {
+                GitHubServiceError::PullRequest(format!(
+                    "Failed to execute GitHub CLI for {action}: {err}"
+                ))
+            })?
+            .map_err(GitHubServiceError::from)
+    }
+
+    /// Resolve the GitHub repo of a local checkout. Results are cached per path for a few
+    /// minutes, or until the `origin` remote changes.
     pub async fn get_repo_info(
         &self,
         repo_path: &Path,
     ) -> Result<GitHubRepoInfo, GitHubServiceError> {
+        let key = canonical_repo_path(repo_path).await;
+        let path = key.clone();
+        let remote_url = task::spawn_blocking(move || origin_remote_url(&path))
+            .await
+            .map_err(|err| {
+                GitHubServiceError::Repository(format!("Failed to read repo remote: {err}"))
+            })?;
+        if let Some(cached) = self.repo_info_cache.read().await.get(&key)
+            && cached.is_fresh(remote_url.as_deref())
+        {
+            return Ok(cached.info.clone());
+        }
+
         let cli = self.gh_cli.clone();
-        let path = repo_path.to_path_buf();
-        task::spawn_blocking(move || cli.get_repo_info(&path))
+        let path = key.clone();
+        let info = task::spawn_blocking(move || cli.get_repo_info(&path))
             .await
             .map_err(|err| {
                 GitHubServiceError::Repository(format!("Failed to get repo info: {err}"))
-            })?
-            .map_err(Into::into)
+            })??;
+        self.repo_info_cache.write().await.insert(
+            key,
+            CachedRepoInfo {
+                info: info.clone(),
+                remote_url,
+                fetched_at: Instant::now(),
+            },
+        );
+        Ok(info)
+    }
+
+    /// Drop the cached repo info for a checkout, e.g. after changing its remote
+    pub async fn invalidate_repo_info(&self, repo_path: &Path) {
+        let key = canonical_repo_path(repo_path).await;
+        self.repo_info_cache.write().await.remove(&key);
     }
 
     pub async fn check_token(&self) -> Result<(), GitHubServiceError> {
@@ -170,30 +721,40 @@ impl GitHubService {
         repo_info: &GitHubRepoInfo,
         request: &CreatePrRequest,
     ) -> Result<PullRequestInfo, GitHubServiceError> {
-        (|| async { self.create_pr_via_cli(repo_info, request).await })
-            .retry(
-                &ExponentialBuilder::default()
-                    .with_min_delay(Duration::from_secs(1))
-                    .with_max_delay(Duration::from_secs(30))
-                    .with_max_times(3)
-                    .with_jitter(),
-            )
-            .when(|e: &GitHubServiceError| e.should_retry())
-            .notify(|err: &GitHubServiceError, dur: Duration| {
-                tracing::warn!(
-                    "GitHub API call failed, retrying after {:.2}s: {}",
-                    dur.as_secs_f64(),
-                    err
-                );
-            })
+        self.with_retry(|| async { self.create_pr_via_cli(repo_info, request).await })
             .await
     }
 
+    /// Creates the PR unless one is already open for the same head and base, in which case
+    /// that PR is returned. A retry after a creation whose response was lost thus finds the
+    /// PR instead of opening a duplicate.
     async fn create_pr_via_cli(
         &self,
         repo_info: &GitHubRepoInfo,
         request: &CreatePrRequest,
     ) -> Result<PullRequestInfo, GitHubServiceError> {
+        let owner = repo_info.owner.clone();
+        let repo = repo_info.repo_name.clone();
+        let head = request.head_branch.clone();
+        let base = request.base_branch.clone();
+        let existing = self
+            .run_cli(
+                &format!("listing PRs on branch '{}'", request.head_branch),
+                move |cli| cli.list_prs_for_branch(&owner, &repo, &head, Some(&base), PrState::All),
+            )
+            .await?;
+        if let Some(pr) = first_open_pr(existing) {
+            info!(
+                "GitHub PR #{} is already open for {} -> {} in {}/{}, not creating another",
+                pr.number,
+                request.head_branch,
+                request.base_branch,
+                repo_info.owner,
+                repo_info.repo_name
+            );
+            return Ok(pr);
+        }
+
         let cli = self.gh_cli.clone();
         let request_clone = request.clone();
         let repo_clone = repo_info.clone();
@@ -218,207 +779,1927 @@ impl GitHubService {
         &self,
         pr_url: &str,
     ) -> Result<PullRequestInfo, GitHubServiceError> {
-        (|| async {
-            let cli = self.gh_cli.clone();
+        if let Some(api) = &self.api {
+            let (repo_info, pr_number) = parse_pr_url(pr_url)?;
+            return self
+                .with_retry(|| async {
+                    api.view_pr(&repo_info.owner, &repo_info.repo_name, pr_number)
+                        .await
+                        .map_err(GitHubServiceError::from)
+                })
+                .await;
+        }
+        self.with_retry(|| async {
             let url = pr_url.to_string();
-            let pr = task::spawn_blocking(move || cli.view_pr(&url))
-                .await
-                .map_err(|err| {
-                    GitHubServiceError::PullRequest(format!(
-                        "Failed to execute GitHub CLI for viewing PR at {pr_url}: {err}"
-                    ))
-                })?;
-            let pr = pr.map_err(GitHubServiceError::from)?;
-            Ok(pr)
-        })
-        .retry(
-            &ExponentialBuilder::default()
-                .with_min_delay(Duration::from_secs(1))
-                .with_max_delay(Duration::from_secs(30))
-                .with_max_times(3)
-                .with_jitter(),
-        )
-        .when(|err: &GitHubServiceError| err.should_retry())
-        .notify(|err: &GitHubServiceError, dur: Duration| {
-            tracing::warn!(
-                "GitHub API call failed, retrying after {:.2}s: {}",
-                dur.as_secs_f64(),
-                err
-            );
+            self.run_cli(&format!("viewing PR at {pr_url}"), move |cli| {
+                cli.view_pr(&url)
+            })
+            .await
         })
         .await
     }
 
-    /// List all pull requests for a branch (including closed/merged)
-    pub async fn list_all_prs_for_branch(
+    /// Merge the PR at `pr_url` and return its resulting state. A PR with conflicts or failing
+    /// requirements fails with `NotMergeable` without retrying.
+    pub async fn merge_pr(
         &self,
-        repo_info: &GitHubRepoInfo,
-        branch_name: &str,
-    ) -> Result<Vec<PullRequestInfo>, GitHubServiceError> {
-        (|| async {
-            let owner = repo_info.owner.clone();
-            let repo = repo_info.repo_name.clone();
-            let branch = branch_name.to_string();
-            let cli = self.gh_cli.clone();
-            let prs = task::spawn_blocking({
-                let owner = owner.clone();
-                let repo = repo.clone();
-                let branch = branch.clone();
-                move || cli.list_prs_for_branch(&owner, &repo, &branch)
+        pr_url: &str,
+        strategy: MergeStrategy,
+    ) -> Result<PullRequestInfo, GitHubServiceError> {
+        self.with_retry(|| async {
+            let url = pr_url.to_string();
+            self.run_cli(&format!("merging PR at {pr_url}"), move |cli| {
+                cli.merge_pr(&url, strategy)
             })
             .await
-            .map_err(|err| {
-                GitHubServiceError::PullRequest(format!(
-                    "Failed to execute GitHub CLI for listing PRs on branch '{branch_name}': {err}"
-                ))
-            })?;
-            let prs = prs.map_err(GitHubServiceError::from)?;
-            Ok(prs)
         })
-        .retry(
-            &ExponentialBuilder::default()
-                .with_min_delay(Duration::from_secs(1))
-                .with_max_delay(Duration::from_secs(30))
-                .with_max_times(3)
-                .with_jitter(),
-        )
-        .when(|e: &GitHubServiceError| e.should_retry())
-        .notify(|err: &GitHubServiceError, dur: Duration| {
-            tracing::warn!(
-                "GitHub API call failed, retrying after {:.2}s: {}",
-                dur.as_secs_f64(),
-                err
-            );
+        .await?;
+        info!("Merged GitHub PR {pr_url} ({strategy:?})");
+        // Read the state back separately so a failed lookup doesn't retry the merge
+        self.update_pr_status(pr_url).await
+    }
+
+    /// List the CI checks on a PR. Repos without CI report no checks rather than an error.
+    pub async fn get_pr_checks(&self, pr_url: &str) -> Result<Vec<CheckRun>, GitHubServiceError> {
+        self.with_retry(|| async {
+            let url = pr_url.to_string();
+            self.run_cli(&format!("listing checks of PR at {pr_url}"), move |cli| {
+                cli.pr_checks(&url)
+            })
+            .await
         })
         .await
     }
 
-    /// Fetch all comments (both general and review) for a pull request
-    pub async fn get_pr_comments(
+    /// Unified diff of a PR, or with `name_only` just its changed paths, one per line.
+    /// Use [`GitHubService::get_pr_diff_stream`] for diffs too large to hold in memory.
+    pub async fn get_pr_diff(
         &self,
-        repo_info: &GitHubRepoInfo,
-        pr_number: i64,
-    ) -> Result<Vec<UnifiedPrComment>, GitHubServiceError> {
-        // Fetch both types of comments in parallel
-        let (general_result, review_result) = tokio::join!(
-            self.fetch_general_comments(repo_info, pr_number),
-            self.fetch_review_comments(repo_info, pr_number)
-        );
-
-        let general_comments = general_result?;
-        let review_comments = review_result?;
-
-        // Convert and merge into unified timeline
-        let mut unified: Vec<UnifiedPrComment> = Vec::new();
-
-        for c in general_comments {
-            unified.push(UnifiedPrComment::General {
-                id: c.id,
-                author: c.author.login,
-                author_association: c.author_association,
-                body: c.body,
-                created_at: c.created_at,
-                url: c.url,
-            });
-        }
-
-        for c in review_comments {
-            unified.push(UnifiedPrComment::Review {
-                id: c.id,
-                author: c.user.login,
-                author_association: c.author_association,
-                body: c.body,
-                created_at: c.created_at,
-                url: c.html_url,
-                path: c.path,
-                line: c.line,
-                diff_hunk: c.diff_hunk,
-            });
-        }
-
-        // Sort by creation time
-        unified.sort_by_key(|c| c.created_at());
-
-        Ok(unified)
+        pr_url: &str,
+        name_only: bool,
+    ) -> Result<String, GitHubServiceError> {
+        self.with_retry(|| async {
+            let url = pr_url.to_string();
+            self.run_cli(&format!("fetching diff of PR at {pr_url}"), move |cli| {
+                cli.pr_diff(&url, name_only)
+            })
+            .await
+        })
+        .await
     }
 
-    async fn fetch_general_comments(
+    /// Unified diff of PR `pr_number` in `repo_info`. Unlike building a github.com URL for
+    /// [`GitHubService::get_pr_diff`], this lets `gh` resolve the host, so it works on GHES.
+    pub async fn get_pr_diff_by_number(
         &self,
         repo_info: &GitHubRepoInfo,
         pr_number: i64,
-    ) -> Result<Vec<PrComment>, GitHubServiceError> {
-        (|| async {
+    ) -> Result<String, GitHubServiceError> {
+        self.with_retry(|| async {
             let owner = repo_info.owner.clone();
             let repo = repo_info.repo_name.clone();
-            let cli = self.gh_cli.clone();
-            let comments = task::spawn_blocking({
-                let owner = owner.clone();
-                let repo = repo.clone();
-                move || cli.get_pr_comments(&owner, &repo, pr_number)
+            self.run_cli(&format!("fetching diff of PR #{pr_number}"), move |cli| {
+                cli.pr_diff_by_number(&owner, &repo, pr_number)
             })
             .await
-            .map_err(|err| {
-                GitHubServiceError::PullRequest(format!(
-                    "Failed to execute GitHub CLI for fetching PR #{pr_number} comments: {err}"
-                ))
-            })?;
-            comments.map_err(GitHubServiceError::from)
         })
-        .retry(
-            &ExponentialBuilder::default()
-                .with_min_delay(Duration::from_secs(1))
-                .with_max_delay(Duration::from_secs(30))
-                .with_max_times(3)
-                .with_jitter(),
-        )
-        .when(|e: &GitHubServiceError| e.should_retry())
-        .notify(|err: &GitHubServiceError, dur: Duration| {
-            tracing::warn!(
-                "GitHub API call failed, retrying after {:.2}s: {}",
-                dur.as_secs_f64(),
-                err
-            );
+        .await
+    }
+
+    /// Like [`GitHubService::get_pr_diff`], but reads `gh pr diff` output as it is produced.
+    /// The call waits for the first bytes so that a missing PR or an auth problem still
+    /// fails here; a failure after output has started only shows up as a truncated diff.
+    pub async fn get_pr_diff_stream(
+        &self,
+        pr_url: &str,
+        name_only: bool,
+    ) -> Result<impl AsyncRead + Send + Unpin + use<>, GitHubServiceError> {
+        self.with_retry(|| async {
+            let url = pr_url.to_string();
+            let cmd = self
+                .run_cli(&format!("fetching diff of PR at {pr_url}"), move |cli| {
+                    cli.pr_diff_command(&url, name_only)
+                })
+                .await?;
+            PrDiffStream::spawn(cmd).await
         })
         .await
     }
 
-    async fn fetch_review_comments(
+    /// Check whether a PR can be merged. GitHub computes mergeability lazily, so an
+    /// `Unknown` answer is polled a couple more times before being returned.
+    pub async fn get_pr_mergeability(
         &self,
-        repo_info: &GitHubRepoInfo,
-        pr_number: i64,
-    ) -> Result<Vec<PrReviewComment>, GitHubServiceError> {
-        (|| async {
-            let owner = repo_info.owner.clone();
-            let repo = repo_info.repo_name.clone();
-            let cli = self.gh_cli.clone();
-            let comments = task::spawn_blocking({
-                let owner = owner.clone();
-                let repo = repo.clone();
-                move || cli.get_pr_review_comments(&owner, &repo, pr_number)
+        pr_url: &str,
+    ) -> Result<Mergeability, GitHubServiceError> {
+        let mut attempts = 0;
+        loop {
+            let mergeability = self
+                .with_retry(|| async {
+                    let url = pr_url.to_string();
+                    self.run_cli(
+                        &format!("checking mergeability of PR at {pr_url}"),
+                        move |cli| cli.pr_mergeability(&url),
+                    )
+                    .await
+                })
+                .await?;
+            attempts += 1;
+            if mergeability != Mergeability::Unknown || attempts > MERGEABILITY_POLL_RETRIES {
+                return Ok(mergeability);
+            }
+            tokio::time::sleep(MERGEABILITY_POLL_DELAY).await;
+        }
+    }
+
+    /// Close a PR without merging, e.g. when its task is abandoned, and return its state.
+    /// A PR that is already closed or merged is returned as is.
+    pub async fn close_pr(
+        &self,
+        pr_url: &str,
+        comment: Option<String>,
+    ) -> Result<PullRequestInfo, GitHubServiceError> {
+        let current = self.update_pr_status(pr_url).await?;
+        if !matches!(current.status, MergeStatus::Open) {
+            return Ok(current);
+        }
+        self.with_retry(|| async {
+            let url = pr_url.to_string();
+            let comment = comment.clone();
+            self.run_cli(&format!("closing PR at {pr_url}"), move |cli| {
+                cli.close_pr(&url, comment.as_deref())
             })
             .await
-            .map_err(|err| {
-                GitHubServiceError::PullRequest(format!(
-                    "Failed to execute GitHub CLI for fetching PR #{pr_number} review comments: {err}"
-                ))
-            })?;
-            comments.map_err(GitHubServiceError::from)
         })
-        .retry(
-            &ExponentialBuilder::default()
-                .with_min_delay(Duration::from_secs(1))
-                .with_max_delay(Duration::from_secs(30))
-                .with_max_times(3)
-                .with_jitter(),
-        )
-        .when(|e: &GitHubServiceError| e.should_retry())
-        .notify(|err: &GitHubServiceError, dur: Duration| {
-            tracing::warn!(
-                "GitHub API call failed, retrying after {:.2}s: {}",
-                dur.as_secs_f64(),
-                err
-            );
+        .await?;
+        info!("Closed GitHub PR {pr_url}");
+        self.read_back_status(pr_url, MergeStatus::Closed).await
+    }
+
+    /// Reopen a closed PR and return its state. An open PR is returned as is; a merged one
+    /// can't be reopened.
+    pub async fn reopen_pr(&self, pr_url: &str) -> Result<PullRequestInfo, GitHubServiceError> {
+        let current = self.update_pr_status(pr_url).await?;
+        if matches!(current.status, MergeStatus::Open) {
+            return Ok(current);
+        }
+        self.with_retry(|| async {
+            let url = pr_url.to_string();
+            self.run_cli(&format!("reopening PR at {pr_url}"), move |cli| {
+                cli.reopen_pr(&url)
+            })
+            .await
+        })
+        .await?;
+        info!("Reopened GitHub PR {pr_url}");
+        self.read_back_status(pr_url, MergeStatus::Open).await
+    }
+
+    /// Read a PR back after changing its state. `gh pr view` can briefly lag the transition,
+    /// so the state we just set wins.
+    async fn read_back_status(
+        &self,
+        pr_url: &str,
+        status: MergeStatus,
+    ) -> Result<PullRequestInfo, GitHubServiceError> {
+        let pr = self.update_pr_status(pr_url).await?;
+        Ok(PullRequestInfo { status, ..pr })
+    }
+
+    /// Mark a draft PR ready for review, returning its updated state
+    pub async fn mark_pr_ready(&self, pr_url: &str) -> Result<PullRequestInfo, GitHubServiceError> {
+        self.set_pr_draft(pr_url, false).await
+    }
+
+    /// Convert a PR back to a draft, returning its updated state
+    pub async fn mark_pr_draft(&self, pr_url: &str) -> Result<PullRequestInfo, GitHubServiceError> {
+        self.set_pr_draft(pr_url, true).await
+    }
+
+    async fn set_pr_draft(
+        &self,
+        pr_url: &str,
+        draft: bool,
+    ) -> Result<PullRequestInfo, GitHubServiceError> {
+        self.with_retry(|| async {
+            let url = pr_url.to_string();
+            self.run_cli(
+                &format!("changing draft state of PR at {pr_url}"),
+                move |cli| cli.set_pr_draft(&url, draft),
+            )
+            .await
+        })
+        .await?;
+        let pr = self.update_pr_status(pr_url).await?;
+        // `gh pr view` can briefly lag the transition; report the state we just set
+        Ok(PullRequestInfo {
+            is_draft: draft,
+            ..pr
+        })
+    }
+
+    /// Post a review verdict on a PR. Approving your own PR fails with
+    /// `InsufficientPermissions`.
+    pub async fn submit_pr_review(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        review: PrReviewSubmission,
+    ) -> Result<(), GitHubServiceError> {
+        self.with_retry(|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            let review = review.clone();
+            self.run_cli(&format!("reviewing PR #{pr_number}"), move |cli| {
+                cli.submit_review(&owner, &repo, pr_number, &review)
+            })
+            .await
+        })
+        .await
+    }
+
+    /// Resolve `#N`, `N`, or `owner/repo#N` to the canonical PR URL, checking the PR exists.
+    /// Bare numbers refer to a PR in `repo_info`.
+    pub async fn resolve_pr_reference(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        reference: &str,
+    ) -> Result<String, GitHubServiceError> {
+        let (target, pr_number) = parse_pr_reference(repo_info, reference)?;
+        let url = format!(
+            "https://github.com/{}/{}/pull/{pr_number}",
+            target.owner, target.repo_name
+        );
+        let pr = self.update_pr_status(&url).await?;
+        Ok(pr.url)
+    }
+
+    /// Fetch the status of several PRs concurrently. Results are in the same order as `pr_urls`.
+    pub async fn update_pr_statuses(
+        &self,
+        pr_urls: &[String],
+    ) -> Vec<Result<PullRequestInfo, GitHubServiceError>> {
+        fan_out_ordered(pr_urls, PR_STATUS_CONCURRENCY, |url| {
+            self.update_pr_status(url)
+        })
+        .await
+    }
+
+    /// List the pull requests for a branch in any of `states`, newest first. An empty
+    /// `states` lists all of them, including closed and merged ones.
+    pub async fn list_all_prs_for_branch(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        branch_name: &str,
+        states: &[PrState],
+    ) -> Result<Vec<PullRequestInfo>, GitHubServiceError> {
+        let state = PrState::query_state(states);
+        let prs = if let Some(api) = &self.api {
+            self.with_retry(|| async {
+                api.list_prs_for_branch(
+                    &repo_info.owner,
+                    &repo_info.repo_name,
+                    branch_name,
+                    None,
+                    state,
+                )
+                .await
+                .map_err(GitHubServiceError::from)
+            })
+            .await?
+        } else {
+            self.with_retry(|| async {
+                let owner = repo_info.owner.clone();
+                let repo = repo_info.repo_name.clone();
+                let branch = branch_name.to_string();
+                self.run_cli(
+                    &format!("listing PRs on branch '{branch_name}'"),
+                    move |cli| cli.list_prs_for_branch(&owner, &repo, &branch, None, state),
+                )
+                .await
+            })
+            .await?
+        };
+        Ok(select_prs(prs, states))
+    }
+
+    /// Find the pull request containing commit `sha`, preferring an open one when the commit
+    /// belongs to several. Returns `None` when no PR contains it.
+    pub async fn find_pr_for_commit(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        sha: &str,
+    ) -> Result<Option<PullRequestInfo>, GitHubServiceError> {
+        let prs = self
+            .with_retry(|| async {
+                let owner = repo_info.owner.clone();
+                let repo = repo_info.repo_name.clone();
+                let sha = sha.to_string();
+                self.run_cli(&format!("finding PRs for commit {sha}"), move |cli| {
+                    cli.list_prs_for_commit(&owner, &repo, &sha)
+                })
+                .await
+            })
+            .await?;
+        Ok(preferred_commit_pr(prs))
+    }
+
+    /// Fetch the current head commit SHA of a pull request
+    pub async fn get_pr_head_sha(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+    ) -> Result<String, GitHubServiceError> {
+        self.with_retry(|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            self.run_cli(&format!("fetching PR #{pr_number} head SHA"), move |cli| {
+                cli.get_pr_head_sha(&owner, &repo, pr_number)
+            })
+            .await
+        })
+        .await
+    }
+
+    /// Files likely to conflict when merging a PR: paths changed both on the PR and on the
+    /// base branch since the merge base. Empty unless GitHub reports the PR as conflicting,
+    /// including while its mergeability is still `UNKNOWN` (not computed yet).
+    pub async fn get_conflicting_files(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+    ) -> Result<Vec<String>, GitHubServiceError> {
+        let details = self
+            .with_retry(|| async {
+                let owner = repo_info.owner.clone();
+                let repo = repo_info.repo_name.clone();
+                self.run_cli(
+                    &format!("fetching PR #{pr_number} merge details"),
+                    move |cli| cli.get_pr_merge_details(&owner, &repo, pr_number),
+                )
+                .await
+            })
+            .await?;
+
+        if !details.mergeable.eq_ignore_ascii_case("CONFLICTING") {
+            return Ok(Vec::new());
+        }
+
+        // What landed on the base branch since it diverged from the PR head
+        let base_changes = self
+            .with_retry(|| async {
+                let owner = repo_info.owner.clone();
+                let repo = repo_info.repo_name.clone();
+                let pr_head = details.head_ref_oid.clone();
+                let base_branch = details.base_ref_name.clone();
+                self.run_cli(
+                    &format!("comparing PR #{pr_number} against its base"),
+                    move |cli| cli.get_compare_files(&owner, &repo, &pr_head, &base_branch),
+                )
+                .await
+            })
+            .await?;
+
+        Ok(overlapping_paths(
+            details.files.into_iter().map(|f| f.path),
+            base_changes,
+        ))
+    }
+
+    /// Diff of what landed on `head` since its merge base with `base`, for any two refs
+    /// (branches, tags or SHAs). With `name_only`, just the changed paths, one per line.
+    pub async fn get_ref_diff(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        base: &str,
+        head: &str,
+        name_only: bool,
+    ) -> Result<String, GitHubServiceError> {
+        self.with_retry(|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            let base = base.to_string();
+            let head = head.to_string();
+            self.run_cli(&format!("comparing {base}...{head}"), move |cli| {
+                if name_only {
+                    cli.get_compare_files(&owner, &repo, &base, &head)
+                        .map(|files| files.join("\n"))
+                } else {
+                    cli.get_compare_diff(&owner, &repo, &base, &head)
+                }
+            })
+            .await
+        })
+        .await
+    }
+
+    /// How many commits `head` is behind `base`; 0 when it already contains all of base.
+    /// Useful to suggest a rebase before opening a PR from a stale branch.
+    pub async fn branch_behind_base(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        head: &str,
+        base: &str,
+    ) -> Result<u32, GitHubServiceError> {
+        self.with_retry(|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            let base = base.to_string();
+            let head = head.to_string();
+            self.run_cli(
+                &format!("checking how far {head} is behind {base}"),
+                move |cli| cli.get_compare_behind_by(&owner, &repo, &base, &head),
+            )
+            .await
+        })
+        .await
+    }
+
+    /// Fetch a file's contents as of `git_ref`, e.g. to compare against the base branch version
+    pub async fn get_file_contents(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        path: &str,
+        git_ref: &str,
+    ) -> Result<String, GitHubServiceError> {
+        let content = self
+            .with_retry(|| async {
+                let owner = repo_info.owner.clone();
+                let repo = repo_info.repo_name.clone();
+                let file_path = path.to_string();
+                let file_ref = git_ref.to_string();
+                self.run_cli(&format!("fetching {path}@{git_ref}"), move |cli| {
+                    cli.get_file_contents(&owner, &repo, &file_path, &file_ref)
+                })
+                .await
+            })
+            .await
+            .map_err(|err| match err {
+                GitHubServiceError::RepoNotFoundOrNoAccess(_) => {
+                    GitHubServiceError::FileNotFound(format!("{path}@{git_ref}"))
+                }
+                other => other,
+            })?;
+
+        match content {
+            RepoContent::File(contents) => Ok(contents),
+            RepoContent::Directory => Err(GitHubServiceError::NotAFile(path.to_string())),
+        }
+    }
+
+    /// Lock the conversation on the PR at `pr_url` so only collaborators can comment.
+    /// Locking an already locked PR succeeds.
+    pub async fn lock_pr_conversation(
+        &self,
+        pr_url: &str,
+        reason: Option<LockReason>,
+    ) -> Result<(), GitHubServiceError> {
+        let (repo_info, pr_number) = parse_pr_url(pr_url)?;
+        self.with_retry(|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            self.run_cli(&format!("locking conversation on {pr_url}"), move |cli| {
+                cli.lock_issue(
+                    &owner,
+                    &repo,
+                    pr_number,
+                    reason.map(|r| r.to_string()).as_deref(),
+                )
+            })
+            .await
+        })
+        .await
+    }
+
+    /// Unlock the conversation on the PR at `pr_url`
+    pub async fn unlock_pr_conversation(&self, pr_url: &str) -> Result<(), GitHubServiceError> {
+        let (repo_info, pr_number) = parse_pr_url(pr_url)?;
+        self.with_retry(|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            self.run_cli(&format!("unlocking conversation on {pr_url}"), move |cli| {
+                cli.unlock_issue(&owner, &repo, pr_number)
+            })
+            .await
+        })
+        .await
+    }
+
+    /// Report a commit status on `sha`, shown in the PR checks UI under `context`
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_commit_status(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        sha: &str,
+        state: StatusState,
+        context: &str,
+        description: &str,
+        target_url: Option<String>,
+    ) -> Result<(), GitHubServiceError> {
+        let context = context.trim();
+        if context.is_empty() {
+            return Err(GitHubServiceError::InvalidCommitStatus(
+                "context must not be empty".to_string(),
+            ));
+        }
+
+        self.with_retry(|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            let sha = sha.to_string();
+            let context = context.to_string();
+            let description = description.to_string();
+            let target_url = target_url.clone();
+            self.run_cli(
+                &format!("creating commit status '{context}' on {sha}"),
+                move |cli| {
+                    cli.create_commit_status(
+                        &owner,
+                        &repo,
+                        &sha,
+                        &state.to_string(),
+                        &context,
+                        &description,
+                        target_url.as_deref(),
+                    )
+                },
+            )
+            .await
+        })
+        .await?;
+
+        info!(
+            "Set commit status '{}' to {} on {} in {}/{}",
+            context, state, sha, repo_info.owner, repo_info.repo_name
+        );
+        Ok(())
+    }
+
+    /// List member logins of a GitHub organization, e.g. for reviewer autocomplete.
+    /// Returns an empty list when `org` is not an organization or is not visible to us.
+    pub async fn list_org_members(&self, org: &str) -> Result<Vec<String>, GitHubServiceError> {
+        if let Some(members) = ORG_MEMBERS_CACHE.get(org).await {
+            return Ok(members);
+        }
+
+        let result = self
+            .with_retry(|| async {
+                let org_name = org.to_string();
+                self.run_cli(&format!("listing members of org '{org}'"), move |cli| {
+                    cli.list_org_members(&org_name)
+                })
+                .await
+            })
+            .await;
+        let members = match result {
+            Ok(members) => members,
+            Err(GitHubServiceError::RepoNotFoundOrNoAccess(_)) => Vec::new(),
+            Err(err) => return Err(err),
+        };
+
+        ORG_MEMBERS_CACHE
+            .insert(org.to_string(), members.clone())
+            .await;
+        Ok(members)
+    }
+
+    /// The repository's default branch, e.g. to fall back to when a project repo has no
+    /// configured base branch
+    pub async fn get_default_branch(
+        &self,
+        repo_info: &GitHubRepoInfo,
+    ) -> Result<String, GitHubServiceError> {
+        self.with_retry(|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            self.run_cli(
+                &format!("reading the default branch of {owner}/{repo}"),
+                move |cli| cli.get_default_branch(&owner, &repo),
+            )
+            .await
+        })
+        .await
+    }
+
+    /// All labels defined on the repository, cached per repo for a few minutes
+    pub async fn list_labels(
+        &self,
+        repo_info: &GitHubRepoInfo,
+    ) -> Result<Vec<Label>, GitHubServiceError> {
+        let key = format!("{}/{}", repo_info.owner, repo_info.repo_name);
+        if let Some(labels) = LABELS_CACHE.get(&key).await {
+            return Ok(labels);
+        }
+
+        let labels = self
+            .with_retry(|| async {
+                let owner = repo_info.owner.clone();
+                let repo = repo_info.repo_name.clone();
+                self.run_cli(&format!("listing labels of {key}"), move |cli| {
+                    cli.list_labels(&owner, &repo)
+                })
+                .await
+            })
+            .await?;
+
+        LABELS_CACHE.insert(key, labels.clone()).await;
+        Ok(labels)
+    }
+
+    /// Make `labels` the exact label set of a PR
+    pub async fn set_pr_labels(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        labels: &[String],
+    ) -> Result<(), GitHubServiceError> {
+        let current = self
+            .with_retry(|| async {
+                let owner = repo_info.owner.clone();
+                let repo = repo_info.repo_name.clone();
+                self.run_cli(&format!("reading labels of PR #{pr_number}"), move |cli| {
+                    cli.get_pr_labels(&owner, &repo, pr_number)
+                })
+                .await
+            })
+            .await?;
+        let add = labels
+            .iter()
+            .filter(|label| !current.contains(label))
+            .cloned()
+            .collect();
+        let remove = current
+            .into_iter()
+            .filter(|label| !labels.contains(label))
+            .collect();
+        self.edit_pr_labels(repo_info, pr_number, add, remove).await
+    }
+
+    pub async fn add_pr_labels(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        labels: &[String],
+    ) -> Result<(), GitHubServiceError> {
+        self.edit_pr_labels(repo_info, pr_number, labels.to_vec(), Vec::new())
+            .await
+    }
+
+    pub async fn remove_pr_labels(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        labels: &[String],
+    ) -> Result<(), GitHubServiceError> {
+        self.edit_pr_labels(repo_info, pr_number, Vec::new(), labels.to_vec())
+            .await
+    }
+
+    /// Apply label changes to a PR. Labels that don't exist in the repo are reported by name
+    /// rather than created.
+    async fn edit_pr_labels(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<(), GitHubServiceError> {
+        if add.is_empty() && remove.is_empty() {
+            return Ok(());
+        }
+        let missing = self
+            .with_retry(|| async {
+                let owner = repo_info.owner.clone();
+                let repo = repo_info.repo_name.clone();
+                let add = add.clone();
+                let remove = remove.clone();
+                self.run_cli(
+                    &format!("editing labels of PR #{pr_number}"),
+                    move |cli| match cli.edit_pr_labels(&owner, &repo, pr_number, &add, &remove) {
+                        Ok(()) => Ok(Vec::new()),
+                        Err(GhCliError::CommandFailed(msg)) if is_label_not_found(&msg) => {
+                            Ok(missing_labels(&msg, &add))
+                        }
+                        Err(err) => Err(err),
+                    },
+                )
+                .await
+            })
+            .await?;
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(GitHubServiceError::Repository(format!(
+            "Labels not found in {}/{}: {}. Create them in the repository first.",
+            repo_info.owner,
+            repo_info.repo_name,
+            missing.join(", ")
+        )))
+    }
+
+    /// Fetch all comments (both general and review) for a pull request, across every page.
+    /// With `since`, only comments created after it are returned, for cheap polling. With
+    /// `detect_outdated`, the PR diff is fetched too so review comments on code that no
+    /// longer exists are flagged `outdated`.
+    pub async fn get_pr_comments(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        since: Option<DateTime<Utc>>,
+        detect_outdated: bool,
+    ) -> Result<Vec<UnifiedPrComment>, GitHubServiceError> {
+        // Fetch comments and review verdicts in parallel
+        let (general_result, review_result, summary_result) = tokio::join!(
+            self.fetch_general_comments(repo_info, pr_number, since),
+            self.fetch_review_comments(repo_info, pr_number, since),
+            self.get_pr_reviews(repo_info, pr_number)
+        );
+
+        let general_comments = general_result?;
+        let review_comments = review_result?;
+        let reviews = summary_result?;
+
+        let current_diff = if detect_outdated && !review_comments.is_empty() {
+            Some(self.get_pr_diff_by_number(repo_info, pr_number).await?)
+        } else {
+            None
+        };
+        let current_hunks = current_diff.as_deref().map(diff_lines_by_path);
+
+        // Convert and merge into unified timeline
+        let mut unified: Vec<UnifiedPrComment> = Vec::new();
+
+        for c in general_comments {
+            unified.push(UnifiedPrComment::General {
+                id: c.id,
+                author: c.author.login,
+                author_association: c.author_association.as_str().into(),
+                author_association_raw: c.author_association,
+                body: c.body,
+                created_at: c.created_at,
+                url: c.url,
+                reactions: c.reactions,
+            });
+        }
+
+        for c in review_comments {
+            let outdated = current_hunks
+                .as_ref()
+                .is_some_and(|hunks| is_outdated(&c, hunks));
+            unified.push(UnifiedPrComment::Review {
+                id: c.id,
+                author: c.user.login,
+                author_association: c.author_association.as_str().into(),
+                author_association_raw: c.author_association,
+                body: c.body,
+                created_at: c.created_at,
+                url: c.html_url,
+                path: c.path,
+                line: c.line,
+                diff_hunk: c.diff_hunk,
+                reactions: c.reactions,
+                outdated,
+            });
+        }
+
+        // Pending reviews aren't visible to others yet; plain comment-only reviews without a
+        // body only exist to hold their inline comments, which are listed above
+        for r in reviews {
+            let Some(submitted_at) = r.submitted_at else {
+                continue;
+            };
+            if since.is_some_and(|since| submitted_at <= since)
+                || (r.state == ReviewState::Commented && r.body.trim().is_empty())
+            {
+                continue;
+            }
+            unified.push(UnifiedPrComment::ReviewSummary {
+                id: r.id,
+                author: r.author.login,
+                author_association: r.author_association.as_str().into(),
+                author_association_raw: r.author_association,
+                state: r.state,
+                body: r.body,
+                created_at: submitted_at,
+                url: r.url,
+            });
+        }
+
+        // Sort by creation time
+        unified.sort_by_key(|c| c.created_at());
+
+        Ok(unified)
+    }
+
+    /// All reviewer suggestions across `comments`, in comment order, ready to apply to the
+    /// worktree
+    pub fn extract_suggestions(comments: &[UnifiedPrComment]) -> Vec<Suggestion> {
+        comments
+            .iter()
+            .flat_map(UnifiedPrComment::suggestions)
+            .collect()
+    }
+
+    /// PR comments whose author association (e.g. `OWNER`, `MEMBER`, `COLLABORATOR`) is in
+    /// `assoc`. An empty `assoc` keeps every comment.
+    pub async fn get_pr_comments_filtered(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        assoc: &[String],
+    ) -> Result<Vec<UnifiedPrComment>, GitHubServiceError> {
+        let comments = self
+            .get_pr_comments(repo_info, pr_number, None, false)
+            .await?;
+        Ok(filter_by_association(comments, assoc))
+    }
+
+    /// Everyone involved in a PR: author, reviewers, requested reviewers and commenters,
+    /// one entry per login in order of first appearance
+    pub async fn get_pr_participants(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+    ) -> Result<Vec<Participant>, GitHubServiceError> {
+        let (people, comments) = tokio::join!(
+            self.fetch_pr_people(repo_info, pr_number),
+            self.get_pr_comments(repo_info, pr_number, None, false)
+        );
+        let people = people?;
+        let comments = comments?;
+
+        let entries = std::iter::once((people.author.login, ParticipantRole::Author))
+            .chain(
+                people
+                    .reviews
+                    .into_iter()
+                    .map(|r| (r.author.login, ParticipantRole::Reviewer)),
+            )
+            .chain(
+                people
+                    .review_requests
+                    .into_iter()
+                    .filter_map(|r| r.login)
+                    .map(|login| (login, ParticipantRole::RequestedReviewer)),
+            )
+            .chain(comments.into_iter().map(|c| match c {
+                UnifiedPrComment::General { author, .. }
+                | UnifiedPrComment::Review { author, .. } => (author, ParticipantRole::Commenter),
+                UnifiedPrComment::ReviewSummary { author, .. } => {
+                    (author, ParticipantRole::Reviewer)
+                }
+            }));
+        Ok(merge_participants(entries))
+    }
+
+    async fn fetch_pr_people(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+    ) -> Result<PrPeople, GitHubServiceError> {
+        self.with_retry(|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            self.run_cli(&format!("fetching PR #{pr_number} reviewers"), move |cli| {
+                cli.get_pr_people(&owner, &repo, pr_number)
+            })
+            .await
+        })
+        .await
+    }
+
+    async fn fetch_general_comments(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PrComment>, GitHubServiceError> {
+        if let Some(api) = &self.api {
+            return self
+                .with_retry(|| async {
+                    api.get_pr_comments(&repo_info.owner, &repo_info.repo_name, pr_number, since)
+                        .await
+                        .map_err(GitHubServiceError::from)
+                })
+                .await;
+        }
+        self.with_retry(|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            self.run_cli(&format!("fetching PR #{pr_number} comments"), move |cli| {
+                cli.get_pr_comments(&owner, &repo, pr_number, since)
+            })
+            .await
+        })
+        .await
+    }
+
+    /// Submitted reviews of a PR with their verdicts and top-level bodies, oldest first.
+    /// Their inline comments come from [`GitHubService::get_pr_comments`].
+    pub async fn get_pr_reviews(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+    ) -> Result<Vec<PrReview>, GitHubServiceError> {
+        if let Some(api) = &self.api {
+            return self
+                .with_retry(|| async {
+                    api.get_pr_reviews(&repo_info.owner, &repo_info.repo_name, pr_number)
+                        .await
+                        .map_err(GitHubServiceError::from)
+                })
+                .await;
+        }
+        self.with_retry(|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            self.run_cli(&format!("fetching PR #{pr_number} reviews"), move |cli| {
+                cli.get_pr_reviews(&owner, &repo, pr_number)
+            })
+            .await
+        })
+        .await
+    }
+
+    /// Reply in the thread of review comment `comment_id`. The reply keeps the parent's
+    /// path and line so it can be placed in a local timeline. A missing parent comment fails
+    /// with `RepoNotFoundOrNoAccess`.
+    pub async fn reply_to_review_comment(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        comment_id: i64,
+        body: &str,
+    ) -> Result<PrReviewComment, GitHubServiceError> {
+        let parent = self
+            .with_retry(|| async {
+                let owner = repo_info.owner.clone();
+                let repo = repo_info.repo_name.clone();
+                self.run_cli(
+                    &format!("fetching review comment {comment_id}"),
+                    move |cli| cli.get_review_comment(&owner, &repo, comment_id),
+                )
+                .await
+            })
+            .await?;
+        let reply = self
+            .with_retry(|| async {
+                let owner = repo_info.owner.clone();
+                let repo = repo_info.repo_name.clone();
+                let body = body.to_string();
+                self.run_cli(
+                    &format!("replying to review comment {comment_id}"),
+                    move |cli| {
+                        cli.reply_review_comment(&owner, &repo, pr_number, comment_id, &body)
+                    },
+                )
+                .await
+            })
+            .await?;
+        Ok(in_parent_thread(reply, &parent))
+    }
+
+    async fn fetch_review_comments(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PrReviewComment>, GitHubServiceError> {
+        if let Some(api) = &self.api {
+            return self
+                .with_retry(|| async {
+                    api.get_pr_review_comments(
+                        &repo_info.owner,
+                        &repo_info.repo_name,
+                        pr_number,
+                        since,
+                    )
+                    .await
+                    .map_err(GitHubServiceError::from)
+                })
+                .await;
+        }
+        self.with_retry(|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            self.run_cli(
+                &format!("fetching PR #{pr_number} review comments"),
+                move |cli| cli.get_pr_review_comments(&owner, &repo, pr_number, since),
+            )
+            .await
         })
         .await
     }
 }
+
+fn filter_by_association(
+    comments: Vec<UnifiedPrComment>,
+    assoc: &[String],
+) -> Vec<UnifiedPrComment> {
+    if assoc.is_empty() {
+        return comments;
+    }
+    comments
+        .into_iter()
+        .filter(|c| {
+            assoc
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(c.author_association_raw()))
+        })
+        .collect()
+}
+
+/// `reply` positioned at its parent's path and line. GitHub omits the line on replies to
+/// outdated comments.
+fn in_parent_thread(mut reply: PrReviewComment, parent: &PrReviewComment) -> PrReviewComment {
+    reply.path = parent.path.clone();
+    reply.line = reply.line.or(parent.line);
+    reply
+}
+
+#[async_trait]
+impl VcsProvider for GitHubService {
+    type Error = GitHubServiceError;
+
+    async fn create_pr(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        request: &CreatePrRequest,
+    ) -> Result<PullRequestInfo, GitHubServiceError> {
+        GitHubService::create_pr(self, repo_info, request).await
+    }
+
+    async fn update_pr_status(&self, pr_url: &str) -> Result<PullRequestInfo, GitHubServiceError> {
+        GitHubService::update_pr_status(self, pr_url).await
+    }
+
+    async fn list_all_prs_for_branch(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        branch_name: &str,
+        states: &[PrState],
+    ) -> Result<Vec<PullRequestInfo>, GitHubServiceError> {
+        GitHubService::list_all_prs_for_branch(self, repo_info, branch_name, states).await
+    }
+
+    async fn get_pr_comments(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<UnifiedPrComment>, GitHubServiceError> {
+        GitHubService::get_pr_comments(self, repo_info, pr_number, since, false).await
+    }
+}
+
+/// Paths changed on both sides, sorted and without duplicates
+fn overlapping_paths(
+    pr_files: impl IntoIterator<Item = String>,
+    base_changes: Vec<String>,
+) -> Vec<String> {
+    let base_changes: HashSet<String> = base_changes.into_iter().collect();
+    let mut overlapping: Vec<String> = pr_files
+        .into_iter()
+        .filter(|path| base_changes.contains(path))
+        .collect();
+    overlapping.sort();
+    overlapping.dedup();
+    overlapping
+}
+
+/// Changed and context lines of each file in a unified diff, keyed by the file's new path
+/// (its old path for deletions). Hunk headers are dropped since line numbers shift on rebase.
+fn diff_lines_by_path(diff: &str) -> HashMap<&str, Vec<&str>> {
+    let mut files: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut old_path = None;
+    let mut current = None;
+    // `---`/`+++` are only file headers between `diff --git` and the first hunk; inside a
+    // hunk they are a removed `-- ` or added `++ ` line
+    let mut in_header = false;
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            old_path = None;
+            current = None;
+            in_header = true;
+        } else if line.starts_with("@@") {
+            in_header = false;
+        } else if in_header {
+            if let Some(path) = line.strip_prefix("--- ") {
+                old_path = path.strip_prefix("a/");
+            } else if let Some(path) = line.strip_prefix("+++ ") {
+                current = path.strip_prefix("b/").or(old_path);
+                if let Some(path) = current {
+                    files.entry(path).or_default();
+                }
+            }
+        } else if let Some(path) = current
+            && matches!(line.chars().next(), Some(' ' | '+' | '-'))
+        {
+            files.entry(path).or_default().push(line);
+        }
+    }
+    files
+}
+
+/// Whether a review comment's code is gone from the current diff. GitHub already drops the
+/// line of comments it knows are outdated; otherwise the comment's hunk must still appear
+/// verbatim in its file's diff.
+fn is_outdated(comment: &PrReviewComment, current: &HashMap<&str, Vec<&str>>) -> bool {
+    if comment.line.is_none() {
+        return true;
+    }
+    let Some(file_lines) = current.get(comment.path.as_str()) else {
+        return true;
+    };
+    let hunk: Vec<&str> = comment
+        .diff_hunk
+        .lines()
+        .filter(|line| !line.starts_with("@@"))
+        .collect();
+    !hunk.is_empty() && !file_lines.windows(hunk.len()).any(|window| window == hunk)
+}
+
+/// Contents of each ```suggestion fenced block in a comment body
+fn parse_suggestion_blocks(body: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    // Fence that opened the current block and the lines collected so far
+    let mut open: Option<(&str, Vec<&str>)> = None;
+
+    for line in body.lines() {
+        let trimmed = line.trim();
+        match open.as_mut() {
+            Some((fence, lines)) => {
+                if trimmed.starts_with(*fence) && trimmed.trim_start_matches('`').is_empty() {
+                    blocks.push(lines.join("\n"));
+                    open = None;
+                } else {
+                    lines.push(line.strip_suffix('\r').unwrap_or(line));
+                }
+            }
+            None => {
+                let fence_len = trimmed.len() - trimmed.trim_start_matches('`').len();
+                if fence_len >= 3 && trimmed[fence_len..].trim() == "suggestion" {
+                    open = Some((&trimmed[..fence_len], Vec::new()));
+                }
+            }
+        }
+    }
+    blocks
+}
+
+/// Run `f` over `items` with at most `limit` calls in flight, keeping input order
+async fn fan_out_ordered<'a, I, T, R, F, Fut>(items: I, limit: usize, f: F) -> Vec<R>
+where
+    I: IntoIterator<Item = &'a T>,
+    T: ?Sized + 'a,
+    F: FnMut(&'a T) -> Fut,
+    Fut: Future<Output = R>,
+{
+    stream::iter(items).map(f).buffered(limit).collect().await
+}
+
+fn merge_participants(
+    entries: impl IntoIterator<Item = (String, ParticipantRole)>,
+) -> Vec<Participant> {
+    let mut participants: Vec<Participant> = Vec::new();
+    for (login, role) in entries {
+        if login.is_empty() {
+            continue;
+        }
+        match participants.iter_mut().find(|p| p.login == login) {
+            Some(existing) => {
+                if !existing.roles.contains(&role) {
+                    existing.roles.push(role);
+                }
+            }
+            None => participants.push(Participant {
+                login,
+                roles: vec![role],
+            }),
+        }
+    }
+    participants
+}
+
+/// The open PR if there is one, otherwise the first listed
+fn preferred_commit_pr(prs: Vec<PullRequestInfo>) -> Option<PullRequestInfo> {
+    let open = prs
+        .iter()
+        .position(|pr| matches!(pr.status, MergeStatus::Open));
+    prs.into_iter().nth(open.unwrap_or(0))
+}
+
+/// The first open PR, ignoring closed and merged ones
+fn first_open_pr(prs: Vec<PullRequestInfo>) -> Option<PullRequestInfo> {
+    prs.into_iter()
+        .find(|pr| matches!(pr.status, MergeStatus::Open))
+}
+
+/// Split `https://github.com/owner/repo/pull/N` into the repo and PR number
+fn parse_pr_url(pr_url: &str) -> Result<(GitHubRepoInfo, i64), GitHubServiceError> {
+    let invalid = || GitHubServiceError::InvalidPrReference(pr_url.to_string());
+    let path = pr_url
+        .trim()
+        .trim_end_matches('/')
+        .split_once("github.com/")
+        .map(|(_, path)| path)
+        .ok_or_else(invalid)?;
+    match path.split('/').collect::<Vec<_>>()[..] {
+        [owner, repo_name, "pull", number]
+            if !owner.is_empty()
+                && !repo_name.is_empty()
+                && number.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            let pr_number = number.parse::<i64>().map_err(|_| invalid())?;
+            Ok((
+                GitHubRepoInfo {
+                    owner: owner.to_string(),
+                    repo_name: repo_name.to_string(),
+                },
+                pr_number,
+            ))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_pr_reference(
+    repo_info: &GitHubRepoInfo,
+    reference: &str,
+) -> Result<(GitHubRepoInfo, i64), GitHubServiceError> {
+    let invalid = || GitHubServiceError::InvalidPrReference(reference.to_string());
+    let trimmed = reference.trim();
+    let (repo, number) = match trimmed.split_once('#') {
+        Some((repo, number)) => (repo, number),
+        None => ("", trimmed),
+    };
+
+    let pr_number = number
+        .parse::<i64>()
+        .ok()
+        .filter(|n| *n > 0 && number.chars().all(|c| c.is_ascii_digit()))
+        .ok_or_else(invalid)?;
+
+    let target = if repo.is_empty() {
+        repo_info.clone()
+    } else {
+        match repo.split_once('/') {
+            Some((owner, repo_name))
+                if !owner.is_empty() && !repo_name.is_empty() && !repo_name.contains('/') =>
+            {
+                GitHubRepoInfo {
+                    owner: owner.to_string(),
+                    repo_name: repo_name.to_string(),
+                }
+            }
+            _ => return Err(invalid()),
+        }
+    };
+
+    Ok((target, pr_number))
+}
+
+/// Stdout of a running `gh pr diff`; the process is killed if the stream is dropped early.
+struct PrDiffStream {
+    reader: BufReader<ChildStdout>,
+    _child: Option<Child>,
+}
+
+impl PrDiffStream {
+    async fn spawn(cmd: std::process::Command) -> Result<Self, GitHubServiceError> {
+        let mut cmd = tokio::process::Command::from(cmd);
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = cmd
+            .spawn()
+            .map_err(|err| GhCliError::CommandFailed(err.to_string()))?;
+        let stdout = child.stdout.take().ok_or_else(|| {
+            GitHubServiceError::PullRequest("gh pr diff has no stdout".to_string())
+        })?;
+        let mut reader = BufReader::new(stdout);
+
+        let has_output = !reader
+            .fill_buf()
+            .await
+            .map_err(|err| GhCliError::CommandFailed(err.to_string()))?
+            .is_empty();
+        if has_output {
+            return Ok(Self {
+                reader,
+                _child: Some(child),
+            });
+        }
+
+        // No output at all: either an empty diff or `gh` failed before printing anything
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|err| GhCliError::CommandFailed(err.to_string()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(GhCli::classify_failure(output.status.code(), stderr).into());
+        }
+        Ok(Self {
+            reader,
+            _child: None,
+        })
+    }
+}
+
+impl AsyncRead for PrDiffStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.reader).poll_read(cx, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use db::models::merge::PrProvider;
+
+    use super::*;
+
+    #[test]
+    fn flags_review_comments_missing_from_current_diff() {
+        let diff = concat!(
+            "diff --git a/src/lib.rs b/src/lib.rs\n",
+            "--- a/src/lib.rs\n",
+            "+++ b/src/lib.rs\n",
+            "@@ -10,3 +12,4 @@ fn main() {\n",
+            " let a = 1;\n",
+            "+let b = 2;\n",
+            " let c = 3;\n",
+        );
+        let current = diff_lines_by_path(diff);
+        let comment = |path: &str, hunk: &str, line: Option<i64>| PrReviewComment {
+            id: 1,
+            user: ReviewCommentUser {
+                login: "reviewer".to_string(),
+            },
+            body: String::new(),
+            created_at: Utc::now(),
+            html_url: String::new(),
+            path: path.to_string(),
+            line,
+            side: None,
+            diff_hunk: hunk.to_string(),
+            author_association: "MEMBER".to_string(),
+            reactions: ReactionSummary::default(),
+        };
+
+        // Same lines under a shifted hunk header are still current
+        let kept = comment(
+            "src/lib.rs",
+            "@@ -8,3 +8,4 @@\n let a = 1;\n+let b = 2;",
+            Some(9),
+        );
+        assert!(!is_outdated(&kept, &current));
+        let rewritten = comment(
+            "src/lib.rs",
+            "@@ -10,2 +12,3 @@\n let a = 1;\n+let b = 5;",
+            Some(13),
+        );
+        assert!(is_outdated(&rewritten, &current));
+        let gone = comment("src/main.rs", "@@ -1 +1 @@\n+fn main() {}", Some(1));
+        assert!(is_outdated(&gone, &current));
+        let unanchored = comment("src/lib.rs", "@@ -10,1 +12,1 @@\n let a = 1;", None);
+        assert!(is_outdated(&unanchored, &current));
+    }
+
+    #[test]
+    fn conflicting_files_are_paths_changed_on_both_sides() {
+        let pr_files = ["src/b.rs", "src/a.rs", "README.md", "src/a.rs"].map(String::from);
+        let base_changes = vec![
+            "src/a.rs".to_string(),
+            "src/b.rs".to_string(),
+            "Cargo.lock".to_string(),
+        ];
+        assert_eq!(
+            overlapping_paths(pr_files, base_changes),
+            ["src/a.rs", "src/b.rs"]
+        );
+        assert!(overlapping_paths(["src/a.rs".to_string()], Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn diff_body_lines_that_look_like_headers_stay_in_their_file() {
+        let diff = concat!(
+            "diff --git a/README.md b/README.md\n",
+            "--- a/README.md\n",
+            "+++ b/README.md\n",
+            "@@ -1,3 +1,3 @@\n",
+            " # Title\n",
+            "--- old rule\n",
+            "+++ new rule\n",
+            "diff --git a/src/lib.rs b/src/lib.rs\n",
+            "--- a/src/lib.rs\n",
+            "+++ b/src/lib.rs\n",
+            "@@ -1 +1 @@\n",
+            "+fn lib() {}\n",
+        );
+        let files = diff_lines_by_path(diff);
+        assert_eq!(files.len(), 2);
+        assert_eq!(
+            files["README.md"],
+            [" # Title", "--- old rule", "+++ new rule"]
+        );
+        assert_eq!(files["src/lib.rs"], ["+fn lib() {}"]);
+    }
+
+    #[test]
+    fn rate_limits_carry_retry_after() {
+        let err: GitHubServiceError = GhCliError::CommandFailed(
+            "HTTP 403: You have exceeded a secondary rate limit".to_string(),
+        )
+        .into();
+        assert!(matches!(
+            err,
+            GitHubServiceError::RateLimited { retry_after } if retry_after == DEFAULT_RATE_LIMIT_WAIT
+        ));
+        assert!(err.should_retry());
+        assert_eq!(err.retry_after(), Some(DEFAULT_RATE_LIMIT_WAIT));
+
+        let now = Utc::now();
+        let reset = format!(
+            "HTTP 403: API rate limit exceeded\nX-RateLimit-Reset: {}",
+            now.timestamp() + 90
+        );
+        assert_eq!(rate_limit_retry_after(&reset, now), Duration::from_secs(90));
+        assert_eq!(
+            rate_limit_retry_after("HTTP 429: Too Many Requests\nRetry-After: 30", now),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn failed_pr_diff_maps_404_to_repo_not_found() {
+        let err: GitHubServiceError = GhCli::classify_failure(
+            Some(1),
+            "GraphQL: Could not resolve to a PullRequest (HTTP 404)".to_string(),
+        )
+        .into();
+        assert!(matches!(err, GitHubServiceError::RepoNotFoundOrNoAccess(_)));
+        let err: GitHubServiceError = GhCli::classify_failure(Some(4), String::new()).into();
+        assert!(matches!(err, GitHubServiceError::AuthFailed(_)));
+    }
+
+    fn repo_info() -> GitHubRepoInfo {
+        GitHubRepoInfo {
+            owner: "acme".to_string(),
+            repo_name: "widgets".to_string(),
+        }
+    }
+
+    #[test]
+    fn cached_repo_info_expires_or_follows_remote() {
+        let cached = CachedRepoInfo {
+            info: repo_info(),
+            remote_url: Some("git@github.com:acme/widgets.git".to_string()),
+            fetched_at: Instant::now(),
+        };
+        assert!(cached.is_fresh(Some("git@github.com:acme/widgets.git")));
+        assert!(!cached.is_fresh(Some("git@github.com:acme/gadgets.git")));
+        assert!(!cached.is_fresh(None));
+
+        let expired = CachedRepoInfo {
+            fetched_at: Instant::now() - REPO_INFO_TTL,
+            ..cached
+        };
+        assert!(!expired.is_fresh(Some("git@github.com:acme/widgets.git")));
+    }
+
+    #[tokio::test]
+    async fn clones_share_the_repo_info_cache() {
+        let service = GitHubService::new().unwrap();
+        let clone = service.clone();
+        let dir = std::env::temp_dir();
+        let key = canonical_repo_path(&dir).await;
+        clone.repo_info_cache.write().await.insert(
+            key.clone(),
+            CachedRepoInfo {
+                info: repo_info(),
+                remote_url: None,
+                fetched_at: Instant::now(),
+            },
+        );
+        assert!(service.repo_info_cache.read().await.contains_key(&key));
+
+        service.invalidate_repo_info(&dir).await;
+        assert!(clone.repo_info_cache.read().await.is_empty());
+    }
+
+    #[test]
+    fn names_missing_labels() {
+        let requested = vec!["ai-generated".to_string(), "bug".to_string()];
+        let stderr = "could not add label: 'ai-generated' not found";
+        assert!(is_label_not_found(stderr));
+        assert_eq!(missing_labels(stderr, &requested), ["ai-generated"]);
+        assert_eq!(
+            missing_labels("could not add label: not found", &requested),
+            requested
+        );
+        assert!(!is_label_not_found("HTTP 404: Not Found"));
+    }
+
+    #[test]
+    fn unresolved_reviewers_are_not_retried() {
+        let err = GitHubServiceError::from(GhCliError::CommandFailed(
+            "could not resolve to a User with the login of 'octcat'".to_string(),
+        ));
+        assert!(matches!(err, GitHubServiceError::InvalidReviewer(_)));
+        assert!(!err.should_retry());
+
+        let err = GitHubServiceError::from(GhCliError::CommandFailed(
+            "GraphQL: Could not resolve to a Repository with the name 'acme/nope'".to_string(),
+        ));
+        assert!(!matches!(err, GitHubServiceError::InvalidReviewer(_)));
+    }
+
+    #[test]
+    fn parses_pr_reference_forms() {
+        for reference in ["#12", "12", " 12 "] {
+            let (target, number) = parse_pr_reference(&repo_info(), reference).unwrap();
+            assert_eq!(
+                (target.owner.as_str(), target.repo_name.as_str()),
+                ("acme", "widgets")
+            );
+            assert_eq!(number, 12);
+        }
+
+        let (target, number) = parse_pr_reference(&repo_info(), "other/repo#7").unwrap();
+        assert_eq!(
+            (target.owner.as_str(), target.repo_name.as_str()),
+            ("other", "repo")
+        );
+        assert_eq!(number, 7);
+    }
+
+    #[test]
+    fn rejects_malformed_pr_references() {
+        for reference in [
+            "", "#", "#abc", "-3", "#0", "+5", "owner#1", "/repo#1", "a/b/c#1",
+        ] {
+            assert!(
+                matches!(
+                    parse_pr_reference(&repo_info(), reference),
+                    Err(GitHubServiceError::InvalidPrReference(_))
+                ),
+                "{reference:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn unmergeable_pr_is_not_retried() {
+        let err = GitHubServiceError::from(GhCliError::CommandFailed(
+            "GraphQL: Pull Request is not mergeable (mergePullRequest)".to_string(),
+        ));
+        assert!(matches!(err, GitHubServiceError::NotMergeable(_)));
+        assert!(!err.should_retry());
+
+        let err = GitHubServiceError::from(GhCliError::CommandFailed(
+            "HTTP 405: Base branch was modified".to_string(),
+        ));
+        assert!(matches!(err, GitHubServiceError::NotMergeable(_)));
+        assert_eq!(MergeStrategy::Squash.as_flag(), "--squash");
+    }
+
+    #[test]
+    fn review_verdicts_map_to_cli_flags() {
+        assert_eq!(PrReviewSubmission::Approve.cli_args(), ["--approve"]);
+        assert_eq!(
+            PrReviewSubmission::RequestChanges {
+                body: "Please add tests".to_string()
+            }
+            .cli_args(),
+            ["--request-changes", "--body", "Please add tests"]
+        );
+
+        let err = GitHubServiceError::from(GhCliError::CommandFailed(
+            "failed to create review: GraphQL: Review Can not approve your own pull request \
+             (addPullRequestReview)"
+                .to_string(),
+        ));
+        assert!(matches!(
+            err,
+            GitHubServiceError::InsufficientPermissions(_)
+        ));
+    }
+
+    #[test]
+    fn review_reply_keeps_parent_position() {
+        let comment = |id: i64, path: &str, line: Option<i64>| -> PrReviewComment {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "user": {"login": "octocat"},
+                "body": "text",
+                "created_at": "2025-01-02T03:04:05Z",
+                "html_url": format!("https://github.com/acme/widgets/pull/7#discussion_r{id}"),
+                "path": path,
+                "line": line,
+                "side": "RIGHT",
+                "diff_hunk": "@@ -1 +1 @@",
+                "author_association": "MEMBER",
+            }))
+            .unwrap()
+        };
+        let parent = comment(1, "src/lib.rs", Some(12));
+
+        let reply = in_parent_thread(comment(2, "", None), &parent);
+        assert_eq!(
+            (reply.id, reply.path.as_str(), reply.line),
+            (2, "src/lib.rs", Some(12))
+        );
+
+        let err = GitHubServiceError::from(GhCliError::CommandFailed(
+            "gh: Not Found (HTTP 404)".to_string(),
+        ));
+        assert!(matches!(err, GitHubServiceError::RepoNotFoundOrNoAccess(_)));
+    }
+
+    #[test]
+    fn parses_author_association() {
+        assert_eq!(AuthorAssociation::from("OWNER"), AuthorAssociation::Owner);
+        assert_eq!(
+            AuthorAssociation::from("collaborator"),
+            AuthorAssociation::Collaborator
+        );
+        assert_eq!(AuthorAssociation::from("NONE"), AuthorAssociation::None);
+        assert_eq!(
+            AuthorAssociation::from("FIRST_TIME_CONTRIBUTOR"),
+            AuthorAssociation::Other("FIRST_TIME_CONTRIBUTOR".to_string())
+        );
+    }
+
+    #[test]
+    fn filters_comments_by_author_association() {
+        let comment = |id: &str, assoc: &str| UnifiedPrComment::General {
+            id: id.to_string(),
+            author: "someone".to_string(),
+            author_association: assoc.into(),
+            author_association_raw: assoc.to_string(),
+            body: String::new(),
+            created_at: Utc::now(),
+            url: String::new(),
+            reactions: ReactionSummary::default(),
+        };
+        let comments = vec![
+            comment("a", "OWNER"),
+            comment("b", "FIRST_TIME_CONTRIBUTOR"),
+            comment("c", "MEMBER"),
+        ];
+        let ids = |comments: Vec<UnifiedPrComment>| {
+            comments
+                .into_iter()
+                .map(|c| match c {
+                    UnifiedPrComment::General { id, .. } => id,
+                    UnifiedPrComment::Review { id, .. } => id.to_string(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let maintainers = ["OWNER".to_string(), "member".to_string()];
+        assert_eq!(
+            ids(filter_by_association(comments.clone(), &maintainers)),
+            ["a", "c"]
+        );
+        assert_eq!(ids(filter_by_association(comments, &[])), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn cached_comments_without_reactions_deserialize() {
+        let cached = serde_json::json!({
+            "comment_type": "general",
+            "id": "IC_1",
+            "author": "alice",
+            "author_association": "OWNER",
+            "body": "LGTM",
+            "created_at": "2025-01-01T00:00:00Z",
+            "url": "https://github.com/acme/widgets/pull/7#issuecomment-1",
+        });
+        let comment: UnifiedPrComment = serde_json::from_value(cached).unwrap();
+        let UnifiedPrComment::General { reactions, .. } = &comment else {
+            panic!("expected a general comment");
+        };
+        assert_eq!(*reactions, ReactionSummary::default());
+
+        let json = serde_json::to_value(&comment).unwrap();
+        assert_eq!(json["comment_type"], "general");
+        assert_eq!(json["reactions"]["thumbs_up"], 0);
+    }
+
+    #[test]
+    fn status_state_accepts_only_known_states() {
+        for state in ["pending", "success", "failure", "error"] {
+            assert_eq!(state.parse::<StatusState>().unwrap().to_string(), state);
+        }
+        assert!("green".parse::<StatusState>().is_err());
+        assert!("".parse::<StatusState>().is_err());
+    }
+
+    #[test]
+    fn lock_reason_uses_api_values() {
+        assert_eq!(LockReason::OffTopic.to_string(), "off-topic");
+        assert_eq!(LockReason::TooHeated.to_string(), "too heated");
+        assert_eq!(LockReason::Resolved.to_string(), "resolved");
+        assert_eq!(LockReason::Spam.to_string(), "spam");
+    }
+
+    #[test]
+    fn parses_pr_urls() {
+        let (repo, number) = parse_pr_url("https://github.com/octo/widgets/pull/42/").unwrap();
+        assert_eq!(repo.owner, "octo");
+        assert_eq!(repo.repo_name, "widgets");
+        assert_eq!(number, 42);
+
+        assert!(parse_pr_url("https://github.com/octo/widgets/issues/42").is_err());
+        assert!(parse_pr_url("https://github.com/octo/widgets/pull/abc").is_err());
+        assert!(parse_pr_url("https://example.com/octo/widgets/pull/42").is_err());
+    }
+
+    #[tokio::test]
+    async fn fan_out_preserves_input_order() {
+        let items = [30u64, 0, 20, 10];
+        let results = fan_out_ordered(&items, 3, |delay| async move {
+            tokio::time::sleep(Duration::from_millis(*delay)).await;
+            if *delay == 20 {
+                Err(format!("failed {delay}"))
+            } else {
+                Ok(*delay)
+            }
+        })
+        .await;
+
+        assert_eq!(
+            results,
+            vec![Ok(30), Ok(0), Err("failed 20".to_string()), Ok(10)]
+        );
+    }
+
+    #[test]
+    fn participants_are_deduplicated_with_roles() {
+        let participants = merge_participants([
+            ("alice".to_string(), ParticipantRole::Author),
+            ("bob".to_string(), ParticipantRole::Reviewer),
+            ("carol".to_string(), ParticipantRole::RequestedReviewer),
+            ("bob".to_string(), ParticipantRole::Commenter),
+            ("alice".to_string(), ParticipantRole::Commenter),
+            ("bob".to_string(), ParticipantRole::Commenter),
+            (String::new(), ParticipantRole::Commenter),
+        ]);
+
+        assert_eq!(
+            participants,
+            vec![
+                Participant {
+                    login: "alice".to_string(),
+                    roles: vec![ParticipantRole::Author, ParticipantRole::Commenter],
+                },
+                Participant {
+                    login: "bob".to_string(),
+                    roles: vec![ParticipantRole::Reviewer, ParticipantRole::Commenter],
+                },
+                Participant {
+                    login: "carol".to_string(),
+                    roles: vec![ParticipantRole::RequestedReviewer],
+                },
+            ]
+        );
+    }
+
+    fn pr(number: i64, status: MergeStatus) -> PullRequestInfo {
+        PullRequestInfo {
+            number,
+            url: format!("https://github.com/o/r/pull/{number}"),
+            status,
+            merged_at: None,
+            merge_commit_sha: None,
+            is_draft: false,
+            provider: PrProvider::GitHub,
+        }
+    }
+    #[test]
+    fn selects_prs_by_state_newest_first() {
+        let prs = || {
+            vec![
+                pr(1, MergeStatus::Merged),
+                pr(3, MergeStatus::Open),
+                pr(2, MergeStatus::Closed),
+            ]
+        };
+        let numbers =
+            |prs: Vec<PullRequestInfo>| prs.iter().map(|pr| pr.number).collect::<Vec<_>>();
+        assert_eq!(numbers(select_prs(prs(), &[PrState::All])), [3, 2, 1]);
+        assert_eq!(numbers(select_prs(prs(), &[])), [3, 2, 1]);
+        assert_eq!(
+            numbers(select_prs(prs(), &[PrState::Open, PrState::Merged])),
+            [3, 1]
+        );
+        assert_eq!(
+            PrState::query_state(&[PrState::Open, PrState::Merged]),
+            PrState::All
+        );
+        assert_eq!(PrState::query_state(&[PrState::Merged]), PrState::Merged);
+    }
+
+    #[test]
+    fn commit_pr_prefers_open() {
+        let chosen = preferred_commit_pr(vec![
+            pr(1, MergeStatus::Closed),
+            pr(2, MergeStatus::Open),
+            pr(3, MergeStatus::Merged),
+        ]);
+        assert_eq!(chosen.map(|pr| pr.number), Some(2));
+
+        let chosen =
+            preferred_commit_pr(vec![pr(4, MergeStatus::Merged), pr(5, MergeStatus::Closed)]);
+        assert_eq!(chosen.map(|pr| pr.number), Some(4));
+
+        assert!(preferred_commit_pr(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn existing_pr_is_reused_only_while_open() {
+        let chosen = first_open_pr(vec![pr(1, MergeStatus::Merged), pr(2, MergeStatus::Open)]);
+        assert_eq!(chosen.map(|pr| pr.number), Some(2));
+
+        assert!(
+            first_open_pr(vec![pr(3, MergeStatus::Closed), pr(4, MergeStatus::Merged)]).is_none()
+        );
+    }
+
+    fn review_comment(body: &str) -> UnifiedPrComment {
+        UnifiedPrComment::Review {
+            id: 1,
+            author: "reviewer".to_string(),
+            author_association: AuthorAssociation::Member,
+            author_association_raw: "MEMBER".to_string(),
+            body: body.to_string(),
+            created_at: Utc::now(),
+            url: "https://github.com/acme/widgets/pull/1#discussion_r1".to_string(),
+            path: "src/lib.rs".to_string(),
+            line: Some(42),
+            diff_hunk: String::new(),
+            reactions: ReactionSummary::default(),
+            outdated: false,
+        }
+    }
+
+    #[test]
+    fn parses_single_and_multi_line_suggestions() {
+        let single = review_comment("Rename this:\n```suggestion\nlet total = 0;\n```");
+        assert_eq!(
+            single.suggestion(),
+            Some(Suggestion {
+                path: "src/lib.rs".to_string(),
+                line: Some(42),
+                replacement: "let total = 0;".to_string(),
+            })
+        );
+
+        let multi = review_comment(
+            "```suggestion\r\nfn a() {\r\n    b();\r\n}\r\n```\r\nand drop the next one\n```suggestion\n```",
+        );
+        let replacements: Vec<_> = multi
+            .suggestions()
+            .into_iter()
+            .map(|s| s.replacement)
+            .collect();
+        assert_eq!(replacements, vec!["fn a() {\n    b();\n}", ""]);
+    }
+
+    #[test]
+    fn ignores_other_code_blocks_and_general_comments() {
+        let fenced = review_comment("````suggestion\n```rust\nx\n```\n````\n```rust\ny\n```");
+        assert_eq!(
+            fenced.suggestion().map(|s| s.replacement).as_deref(),
+            Some("```rust\nx\n```")
+        );
+
+        let general = UnifiedPrComment::General {
+            id: "c1".to_string(),
+            author: "reviewer".to_string(),
+            author_association: AuthorAssociation::Member,
+            author_association_raw: "MEMBER".to_string(),
+            body: "```suggestion\nnope\n```".to_string(),
+            created_at: Utc::now(),
+            url: String::new(),
+            reactions: ReactionSummary::default(),
+        };
+        assert_eq!(general.suggestion(), None);
+        assert_eq!(review_comment("```rust\nz\n```").suggestion(), None);
+
+        let all = GitHubService::extract_suggestions(&[
+            general,
+            fenced,
+            review_comment("```suggestion\none\n```"),
+        ]);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].replacement, "one");
+    }
+}
@@ -0,0 +1,259 @@
+//! Read-only GitHub REST client built on `octocrab`.
+//!
+//! Avoids spawning a `gh` process per call for the hot read paths. It authenticates with the
+//! token `gh` is logged in with, and reports failures as `GhCliError` so they are classified
+//! exactly like CLI failures.
+
+use chrono::{DateTime, Utc};
+use db::models::merge::PullRequestInfo;
+use octocrab::Octocrab;
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use super::{
+    PrState,
+    cli::{
+        GhCli, GhCliError, PrComment, PrCommentAuthor, PrReview, PrReviewComment, ReactionSummary,
+        ReviewCommentUser, ReviewState, comments_endpoint,
+    },
+};
+
+const PAGE_SIZE: usize = 100;
+
+/// A REST issue comment, before reshaping into `PrComment`
+#[derive(Debug, Deserialize)]
+struct IssueComment {
+    node_id: String,
+    user: ReviewCommentUser,
+    author_association: String,
+    body: String,
+    created_at: DateTime<Utc>,
+    html_url: String,
+    #[serde(default)]
+    reactions: ReactionSummary,
+}
+
+impl From<IssueComment> for PrComment {
+    fn from(c: IssueComment) -> Self {
+        PrComment {
+            id: c.node_id,
+            author: PrCommentAuthor {
+                login: c.user.login,
+            },
+            author_association: c.author_association,
+            body: c.body,
+            created_at: c.created_at,
+            url: c.html_url,
+            reactions: c.reactions,
+        }
+    }
+}
+
+/// A REST pull request review, before reshaping into `PrReview`
+#[derive(Debug, Deserialize)]
+struct PullReview {
+    node_id: String,
+    user: ReviewCommentUser,
+    author_association: String,
+    state: ReviewState,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    submitted_at: Option<DateTime<Utc>>,
+    html_url: String,
+}
+
+impl From<PullReview> for PrReview {
+    fn from(r: PullReview) -> Self {
+        PrReview {
+            id: r.node_id,
+            author: PrCommentAuthor {
+                login: r.user.login,
+            },
+            author_association: r.author_association,
+            state: r.state,
+            body: r.body.unwrap_or_default(),
+            submitted_at: r.submitted_at,
+            url: r.html_url,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GhApi {
+    client: Octocrab,
+}
+
+impl GhApi {
+    /// Build a client from the token of the logged-in `gh` user.
+    pub fn from_gh_token(cli: &GhCli) -> Result<Self, GhCliError> {
+        let token = cli.auth_token()?;
+        let client = Octocrab::builder()
+            .personal_token(token)
+            .build()
+            .map_err(api_error)?;
+        Ok(Self { client })
+    }
+
+    pub async fn view_pr(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+    ) -> Result<PullRequestInfo, GhCliError> {
+        let value: Value = self
+            .client
+            .get(
+                format!("/repos/{owner}/{repo}/pulls/{pr_number}"),
+                None::<&()>,
+            )
+            .await
+            .map_err(api_error)?;
+        GhCli::extract_rest_pr_info(&value)
+    }
+
+    /// List pull requests for a branch (includes closed/merged), optionally only those
+    /// targeting `base`.
+    pub async fn list_prs_for_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+        base: Option<&str>,
+        state: PrState,
+    ) -> Result<Vec<PullRequestInfo>, GhCliError> {
+        let head: String =
+            url::form_urlencoded::byte_serialize(format!("{owner}:{branch}").as_bytes()).collect();
+        let base_filter = base
+            .map(|base| {
+                let base: String = url::form_urlencoded::byte_serialize(base.as_bytes()).collect();
+                format!("&base={base}")
+            })
+            .unwrap_or_default();
+        // The REST API has no merged state; merged PRs are closed ones with `merged_at` set
+        let state = match state {
+            PrState::Open => "open",
+            PrState::Closed | PrState::Merged => "closed",
+            PrState::All => "all",
+        };
+        let prs: Vec<Value> = self
+            .get_all_pages(&format!(
+                "/repos/{owner}/{repo}/pulls?state={state}&head={head}{base_filter}\
+                 &sort=created&direction=desc&per_page={PAGE_SIZE}"
+            ))
+            .await?;
+        prs.iter().map(GhCli::extract_rest_pr_info).collect()
+    }
+
+    pub async fn get_pr_comments(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PrComment>, GhCliError> {
+        let comments: Vec<IssueComment> = self
+            .get_all_pages(&comments_endpoint(
+                &format!("/repos/{owner}/{repo}/issues/{pr_number}/comments"),
+                since,
+            ))
+            .await?;
+        Ok(comments
+            .into_iter()
+            .map(PrComment::from)
+            .filter(|c| since.is_none_or(|since| c.created_at > since))
+            .collect())
+    }
+
+    pub async fn get_pr_review_comments(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PrReviewComment>, GhCliError> {
+        let comments: Vec<PrReviewComment> = self
+            .get_all_pages(&comments_endpoint(
+                &format!("/repos/{owner}/{repo}/pulls/{pr_number}/comments"),
+                since,
+            ))
+            .await?;
+        Ok(comments
+            .into_iter()
+            .filter(|c| since.is_none_or(|since| c.created_at > since))
+            .collect())
+    }
+
+    pub async fn get_pr_reviews(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+    ) -> Result<Vec<PrReview>, GhCliError> {
+        let reviews: Vec<PullReview> = self
+            .get_all_pages(&format!(
+                "/repos/{owner}/{repo}/pulls/{pr_number}/reviews?per_page={PAGE_SIZE}"
+            ))
+            .await?;
+        Ok(reviews.into_iter().map(PrReview::from).collect())
+    }
+
+    /// Follow `page=` until a short page; `route` must already set `per_page=100`
+    async fn get_all_pages<T: DeserializeOwned>(&self, route: &str) -> Result<Vec<T>, GhCliError> {
+        let mut items = Vec::new();
+        for page in 1.. {
+            let batch: Vec<T> = self
+                .client
+                .get(format!("{route}&page={page}"), None::<&()>)
+                .await
+                .map_err(api_error)?;
+            let done = batch.len() < PAGE_SIZE;
+            items.extend(batch);
+            if done {
+                break;
+            }
+        }
+        Ok(items)
+    }
+}
+
+/// Map an API failure onto the CLI error it corresponds to, keeping the HTTP status in the
+/// message so it is classified the same way
+fn api_error(err: octocrab::Error) -> GhCliError {
+    match err {
+        octocrab::Error::GitHub { source, .. } => {
+            let status = source.status_code.as_u16();
+            let msg = format!("HTTP {status}: {}", source.message);
+            if status == 401 {
+                GhCliError::AuthFailed(msg)
+            } else {
+                GhCliError::CommandFailed(msg)
+            }
+        }
+        other => GhCliError::CommandFailed(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reshapes_issue_comments() {
+        let raw = r#"{
+            "id": 11,
+            "node_id": "IC_kwDOA",
+            "user": {"login": "octocat"},
+            "author_association": "MEMBER",
+            "body": "Looks good",
+            "created_at": "2025-01-02T03:04:05Z",
+            "html_url": "https://github.com/o/r/pull/1#issuecomment-11",
+            "reactions": {"+1": 2, "total_count": 2}
+        }"#;
+        let comment = PrComment::from(serde_json::from_str::<IssueComment>(raw).unwrap());
+        assert_eq!(comment.id, "IC_kwDOA");
+        assert_eq!(comment.author.login, "octocat");
+        assert_eq!(comment.author_association, "MEMBER");
+        assert_eq!(comment.reactions.thumbs_up, 2);
+    }
+}
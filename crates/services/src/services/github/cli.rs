@@ -11,16 +11,53 @@ use std::{
     process::Command,
 };
 
+use base64::{Engine, prelude::BASE64_STANDARD};
 use chrono::{DateTime, Utc};
-use db::models::merge::{MergeStatus, PullRequestInfo};
-use serde::{Deserialize, Serialize};
+use db::models::merge::{MergeStatus, PrProvider, PullRequestInfo};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::Value;
 use tempfile::NamedTempFile;
 use thiserror::Error;
 use ts_rs::TS;
 use utils::shell::resolve_executable_path_blocking;
 
-use crate::services::github::{CreatePrRequest, GitHubRepoInfo};
+use crate::services::github::{
+    CheckConclusion, CheckRun, CheckStatus, CreatePrRequest, GitHubRepoInfo, Label, MergeStrategy,
+    Mergeability, PrReviewSubmission, PrState,
+};
+
+/// `gh label list` pages through results itself; this caps the total it returns
+const LABEL_LIST_LIMIT: &str = "1000";
+
+/// `gh pr list` returns only 30 PRs unless asked for more, and pages through up to this many
+const PR_LIST_LIMIT: &str = "1000";
+
+/// Maps a REST issue comment onto the `PrComment` shape, one object per line
+const PR_COMMENT_JQ: &str = ".[] | {id: .node_id, author: {login: .user.login}, \
+     authorAssociation: .author_association, body: .body, createdAt: .created_at, \
+     url: .html_url, reactions: .reactions}";
+
+/// Maps a REST pull request review onto the `PrReview` shape, one object per line
+const PR_REVIEW_JQ: &str = ".[] | {id: .node_id, author: {login: .user.login}, \
+     authorAssociation: .author_association, state: .state, body: (.body // \"\"), \
+     submittedAt: .submitted_at, url: .html_url}";
+
+/// Reaction counts on a comment, from the REST `reactions` object. Missing counts are zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct ReactionSummary {
+    #[serde(alias = "+1")]
+    pub thumbs_up: u32,
+    #[serde(alias = "-1")]
+    pub thumbs_down: u32,
+    pub laugh: u32,
+    pub hooray: u32,
+    pub confused: u32,
+    pub heart: u32,
+    pub rocket: u32,
+    pub eyes: u32,
+    pub total_count: u32,
+}
 
 /// Author information for a PR comment
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -38,6 +75,8 @@ pub struct PrComment {
     pub body: String,
     pub created_at: DateTime<Utc>,
     pub url: String,
+    #[serde(default)]
+    pub reactions: ReactionSummary,
 }
 
 /// User information for a review comment (from API response)
@@ -59,6 +98,81 @@ pub struct PrReviewComment {
     pub side: Option<String>,
     pub diff_hunk: String,
     pub author_association: String,
+    #[serde(default)]
+    pub reactions: ReactionSummary,
+}
+
+/// Merge-related details for a PR (from `gh pr view --json`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrMergeDetails {
+    pub mergeable: String,
+    pub base_ref_name: String,
+    pub head_ref_oid: String,
+    #[serde(default)]
+    pub files: Vec<PrFile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PrFile {
+    pub path: String,
+}
+
+/// Author, reviewers and requested reviewers of a PR (from `gh pr view --json`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrPeople {
+    pub author: PrCommentAuthor,
+    #[serde(default)]
+    pub reviews: Vec<PrReview>,
+    #[serde(default)]
+    pub review_requests: Vec<PrReviewRequest>,
+}
+
+/// A submitted review on a PR: its verdict and top-level body, without inline comments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrReview {
+    #[serde(default)]
+    pub id: String,
+    pub author: PrCommentAuthor,
+    #[serde(default)]
+    pub author_association: String,
+    pub state: ReviewState,
+    #[serde(default)]
+    pub body: String,
+    /// Unset while the review is still pending
+    #[serde(default)]
+    pub submitted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub url: String,
+}
+
+/// Verdict of a PR review, in GitHub's spelling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewState {
+    Approved,
+    ChangesRequested,
+    Commented,
+    Dismissed,
+    Pending,
+    #[serde(other)]
+    Unknown,
+}
+
+/// A requested reviewer. Team requests carry no login.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PrReviewRequest {
+    #[serde(default)]
+    pub login: Option<String>,
+}
+
+/// Result of a repository contents lookup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoContent {
+    File(String),
+    Directory,
 }
 
 /// High-level errors originating from the GitHub CLI.
@@ -111,11 +225,17 @@ impl GhCli {
             return Ok(String::from_utf8_lossy(&output.stdout).to_string());
         }
 
-        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(Self::classify_failure(
+            output.status.code(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
 
+    /// Turn the exit code and stderr of a failed `gh` invocation into an error.
+    pub fn classify_failure(code: Option<i32>, stderr: String) -> GhCliError {
         // Check exit code first - gh CLI uses exit code 4 for auth failures
-        if output.status.code() == Some(4) {
-            return Err(GhCliError::AuthFailed(stderr));
+        if code == Some(4) {
+            return GhCliError::AuthFailed(stderr);
         }
 
         // Fall back to string matching for older gh versions or other auth scenarios
@@ -126,10 +246,10 @@ impl GhCli {
             || lower.contains("unauthorized")
             || lower.contains("gh auth login")
         {
-            return Err(GhCliError::AuthFailed(stderr));
+            return GhCliError::AuthFailed(stderr);
         }
 
-        Err(GhCliError::CommandFailed(stderr))
+        GhCliError::CommandFailed(stderr)
     }
 
     pub fn get_repo_info(&self, repo_path: &Path) -> Result<GitHubRepoInfo, GhCliError> {
@@ -189,9 +309,30 @@ impl GhCli {
         if request.draft.unwrap_or(false) {
             args.push(OsString::from("--draft"));
         }
+        for reviewer in &request.reviewers {
+            args.push(OsString::from("--reviewer"));
+            args.push(OsString::from(reviewer));
+        }
+        for assignee in &request.assignees {
+            args.push(OsString::from("--assignee"));
+            args.push(OsString::from(assignee));
+        }
 
         let raw = self.run(args, None)?;
-        Self::parse_pr_create_text(&raw)
+        let mut pr = Self::parse_pr_create_text(&raw)?;
+        pr.is_draft = request.draft.unwrap_or(false);
+        Ok(pr)
+    }
+
+    /// The OAuth token `gh` is logged in with, for talking to the REST API directly.
+    pub fn auth_token(&self) -> Result<String, GhCliError> {
+        let token = self.run(["auth", "token"], None)?.trim().to_string();
+        if token.is_empty() {
+            return Err(GhCliError::AuthFailed(
+                "gh auth token returned no token".to_string(),
+            ));
+        }
+        Ok(token)
     }
 
     /// Ensure the GitHub CLI has valid auth.
@@ -211,36 +352,488 @@ impl GhCli {
                 "view",
                 pr_url,
                 "--json",
-                "number,url,state,mergedAt,mergeCommit",
+                "number,url,state,mergedAt,mergeCommit,isDraft",
             ],
             None,
         )?;
         Self::parse_pr_view(&raw)
     }
 
-    /// List pull requests for a branch (includes closed/merged).
+    /// List the CI checks of a pull request.
+    pub fn pr_checks(&self, pr_url: &str) -> Result<Vec<CheckRun>, GhCliError> {
+        match self.run(["pr", "checks", pr_url, "--json", "name,state"], None) {
+            Ok(raw) => Self::parse_pr_checks(&raw),
+            // `gh` treats a PR without any checks as an error
+            Err(GhCliError::CommandFailed(msg)) if msg.contains("no checks reported") => {
+                Ok(Vec::new())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Unified diff of a pull request, or just the changed paths with `name_only`.
+    pub fn pr_diff(&self, pr_url: &str, name_only: bool) -> Result<String, GhCliError> {
+        self.run(Self::pr_diff_args(pr_url, name_only), None)
+    }
+
+    /// Unified diff of pull request `pr_number` in `owner/repo`, leaving the host to `gh`.
+    pub fn pr_diff_by_number(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+    ) -> Result<String, GhCliError> {
+        self.run(Self::pr_diff_by_number_args(owner, repo, pr_number), None)
+    }
+
+    /// The `gh pr diff` invocation behind [`GhCli::pr_diff`], for callers that stream its stdout.
+    pub fn pr_diff_command(&self, pr_url: &str, name_only: bool) -> Result<Command, GhCliError> {
+        self.ensure_available()?;
+        let gh = resolve_executable_path_blocking("gh").ok_or(GhCliError::NotAvailable)?;
+        let mut cmd = Command::new(gh);
+        cmd.args(Self::pr_diff_args(pr_url, name_only));
+        Ok(cmd)
+    }
+
+    fn pr_diff_args(pr_url: &str, name_only: bool) -> Vec<&str> {
+        let mut args = vec!["pr", "diff", pr_url, "--color", "never"];
+        if name_only {
+            args.push("--name-only");
+        }
+        args
+    }
+
+    fn pr_diff_by_number_args(owner: &str, repo: &str, pr_number: i64) -> Vec<String> {
+        vec![
+            "pr".to_string(),
+            "diff".to_string(),
+            pr_number.to_string(),
+            "--repo".to_string(),
+            format!("{owner}/{repo}"),
+            "--color".to_string(),
+            "never".to_string(),
+        ]
+    }
+
+    /// Read whether a pull request can be merged, without merging it.
+    pub fn pr_mergeability(&self, pr_url: &str) -> Result<Mergeability, GhCliError> {
+        let raw = self.run(
+            ["pr", "view", pr_url, "--json", "mergeable,mergeStateStatus"],
+            None,
+        )?;
+        Self::parse_mergeability(&raw)
+    }
+
+    /// Mark a draft pull request ready for review, or with `draft` convert it back to a draft.
+    pub fn set_pr_draft(&self, pr_url: &str, draft: bool) -> Result<(), GhCliError> {
+        let mut args = vec!["pr", "ready", pr_url];
+        if draft {
+            args.push("--undo");
+        }
+        self.run(args, None)?;
+        Ok(())
+    }
+
+    /// Close a pull request without merging, optionally leaving a comment.
+    pub fn close_pr(&self, pr_url: &str, comment: Option<&str>) -> Result<(), GhCliError> {
+        let mut args = vec!["pr", "close", pr_url];
+        if let Some(comment) = comment {
+            args.extend(["--comment", comment]);
+        }
+        self.run(args, None)?;
+        Ok(())
+    }
+
+    /// Reopen a closed, unmerged pull request.
+    pub fn reopen_pr(&self, pr_url: &str) -> Result<(), GhCliError> {
+        self.run(["pr", "reopen", pr_url], None)?;
+        Ok(())
+    }
+
+    /// Merge a pull request with the given strategy.
+    pub fn merge_pr(&self, pr_url: &str, strategy: MergeStrategy) -> Result<(), GhCliError> {
+        self.run(["pr", "merge", pr_url, strategy.as_flag()], None)?;
+        Ok(())
+    }
+
+    /// Submit a review on a pull request.
+    pub fn submit_review(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+        review: &PrReviewSubmission,
+    ) -> Result<(), GhCliError> {
+        let mut args = vec![
+            "pr".to_string(),
+            "review".to_string(),
+            pr_number.to_string(),
+            "--repo".to_string(),
+            format!("{owner}/{repo}"),
+        ];
+        args.extend(review.cli_args());
+        self.run(args, None)?;
+        Ok(())
+    }
+
+    /// List pull requests for a branch (includes closed/merged), optionally only those
+    /// targeting `base`.
     pub fn list_prs_for_branch(
         &self,
         owner: &str,
         repo: &str,
         branch: &str,
+        base: Option<&str>,
+        state: PrState,
     ) -> Result<Vec<PullRequestInfo>, GhCliError> {
+        let mut args = vec![
+            "pr".to_string(),
+            "list".to_string(),
+            "--repo".to_string(),
+            format!("{owner}/{repo}"),
+            "--state".to_string(),
+            state.as_gh_state().to_string(),
+            "--limit".to_string(),
+            PR_LIST_LIMIT.to_string(),
+            "--head".to_string(),
+            branch.to_string(),
+            "--json".to_string(),
+            "number,url,state,mergedAt,mergeCommit,isDraft".to_string(),
+        ];
+        if let Some(base) = base {
+            args.extend(["--base".to_string(), base.to_string()]);
+        }
+        let raw = self.run(args, None)?;
+        Self::parse_pr_list(&raw)
+    }
+
+    /// Fetch the head commit SHA for a pull request.
+    pub fn get_pr_head_sha(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+    ) -> Result<String, GhCliError> {
+        let raw = self.run(
+            [
+                "pr",
+                "view",
+                &pr_number.to_string(),
+                "--repo",
+                &format!("{owner}/{repo}"),
+                "--json",
+                "headRefOid",
+            ],
+            None,
+        )?;
+        Self::parse_pr_head_sha(&raw)
+    }
+
+    /// List pull requests that contain the commit `sha`.
+    pub fn list_prs_for_commit(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Vec<PullRequestInfo>, GhCliError> {
+        let raw = self.run(
+            ["api", &format!("repos/{owner}/{repo}/commits/{sha}/pulls")],
+            None,
+        )?;
+        Self::parse_commit_pulls(&raw)
+    }
+
+    /// Fetch mergeability, base ref, head SHA and changed files for a pull request.
+    pub fn get_pr_merge_details(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+    ) -> Result<PrMergeDetails, GhCliError> {
         let raw = self.run(
             [
                 "pr",
+                "view",
+                &pr_number.to_string(),
+                "--repo",
+                &format!("{owner}/{repo}"),
+                "--json",
+                "mergeable,baseRefName,headRefOid,files",
+            ],
+            None,
+        )?;
+        serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse gh pr view merge details: {err}; raw: {raw}"
+            ))
+        })
+    }
+
+    /// List files changed on `head` since its merge base with `base`.
+    pub fn get_compare_files(
+        &self,
+        owner: &str,
+        repo: &str,
+        base: &str,
+        head: &str,
+    ) -> Result<Vec<String>, GhCliError> {
+        let raw = self.run(
+            [
+                "api",
+                &format!("repos/{owner}/{repo}/compare/{base}...{head}"),
+            ],
+            None,
+        )?;
+        Self::parse_compare_files(&raw)
+    }
+
+    /// Number of commits on `base` that `head` does not have yet.
+    pub fn get_compare_behind_by(
+        &self,
+        owner: &str,
+        repo: &str,
+        base: &str,
+        head: &str,
+    ) -> Result<u32, GhCliError> {
+        let raw = self.run(
+            [
+                "api",
+                &format!("repos/{owner}/{repo}/compare/{base}...{head}"),
+            ],
+            None,
+        )?;
+        Self::parse_compare_behind_by(&raw)
+    }
+
+    /// Unified diff of the changes on `head` since its merge base with `base`.
+    pub fn get_compare_diff(
+        &self,
+        owner: &str,
+        repo: &str,
+        base: &str,
+        head: &str,
+    ) -> Result<String, GhCliError> {
+        self.run(
+            [
+                "api",
+                "-H",
+                "Accept: application/vnd.github.diff",
+                &format!("repos/{owner}/{repo}/compare/{base}...{head}"),
+            ],
+            None,
+        )
+    }
+
+    /// Fetch a file from the repository at the given ref.
+    pub fn get_file_contents(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        git_ref: &str,
+    ) -> Result<RepoContent, GhCliError> {
+        let raw = self.run(
+            [
+                "api",
+                "-X",
+                "GET",
+                &format!(
+                    "repos/{owner}/{repo}/contents/{}",
+                    path.trim_start_matches('/')
+                ),
+                "-f",
+                &format!("ref={git_ref}"),
+            ],
+            None,
+        )?;
+        Self::parse_repo_content(&raw)
+    }
+
+    /// Create a commit status on `sha`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_commit_status(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        state: &str,
+        context: &str,
+        description: &str,
+        target_url: Option<&str>,
+    ) -> Result<(), GhCliError> {
+        let mut args = vec![
+            "api".to_string(),
+            "-X".to_string(),
+            "POST".to_string(),
+            format!("repos/{owner}/{repo}/statuses/{sha}"),
+            "-f".to_string(),
+            format!("state={state}"),
+            "-f".to_string(),
+            format!("context={context}"),
+            "-f".to_string(),
+            format!("description={description}"),
+        ];
+        if let Some(url) = target_url {
+            args.push("-f".to_string());
+            args.push(format!("target_url={url}"));
+        }
+        self.run(args, None)?;
+        Ok(())
+    }
+
+    /// Lock the conversation on an issue or pull request. Locking an already locked
+    /// conversation succeeds.
+    pub fn lock_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: i64,
+        lock_reason: Option<&str>,
+    ) -> Result<(), GhCliError> {
+        let mut args = vec![
+            "api".to_string(),
+            "-X".to_string(),
+            "PUT".to_string(),
+            format!("repos/{owner}/{repo}/issues/{number}/lock"),
+        ];
+        if let Some(reason) = lock_reason {
+            args.push("-f".to_string());
+            args.push(format!("lock_reason={reason}"));
+        }
+        match self.run(args, None) {
+            Ok(_) => Ok(()),
+            Err(GhCliError::CommandFailed(msg))
+                if msg.to_ascii_lowercase().contains("already locked") =>
+            {
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Unlock the conversation on an issue or pull request.
+    pub fn unlock_issue(&self, owner: &str, repo: &str, number: i64) -> Result<(), GhCliError> {
+        self.run(
+            [
+                "api",
+                "-X",
+                "DELETE",
+                &format!("repos/{owner}/{repo}/issues/{number}/lock"),
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    /// List login names of all members of an organization.
+    pub fn list_org_members(&self, org: &str) -> Result<Vec<String>, GhCliError> {
+        let raw = self.run(
+            [
+                "api",
+                "--paginate",
+                &format!("orgs/{org}/members"),
+                "--jq",
+                ".[].login",
+            ],
+            None,
+        )?;
+        Ok(raw
+            .lines()
+            .map(str::trim)
+            .filter(|login| !login.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Name of the repository's default branch.
+    pub fn get_default_branch(&self, owner: &str, repo: &str) -> Result<String, GhCliError> {
+        let raw = self.run(
+            [
+                "repo",
+                "view",
+                &format!("{owner}/{repo}"),
+                "--json",
+                "defaultBranchRef",
+                "--jq",
+                ".defaultBranchRef.name",
+            ],
+            None,
+        )?;
+        let branch = raw.trim();
+        if branch.is_empty() {
+            return Err(GhCliError::UnexpectedOutput(format!(
+                "{owner}/{repo} has no default branch"
+            )));
+        }
+        Ok(branch.to_string())
+    }
+
+    /// List every label defined on the repository.
+    pub fn list_labels(&self, owner: &str, repo: &str) -> Result<Vec<Label>, GhCliError> {
+        let raw = self.run(
+            [
+                "label",
                 "list",
                 "--repo",
                 &format!("{owner}/{repo}"),
-                "--state",
-                "all",
-                "--head",
-                branch,
+                "--limit",
+                LABEL_LIST_LIMIT,
                 "--json",
-                "number,url,state,mergedAt,mergeCommit",
+                "name,color,description",
             ],
             None,
         )?;
-        Self::parse_pr_list(&raw)
+        Self::parse_labels(&raw)
+    }
+
+    /// Names of the labels on a pull request.
+    pub fn get_pr_labels(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+    ) -> Result<Vec<String>, GhCliError> {
+        let raw = self.run(
+            [
+                "pr",
+                "view",
+                &pr_number.to_string(),
+                "--repo",
+                &format!("{owner}/{repo}"),
+                "--json",
+                "labels",
+            ],
+            None,
+        )?;
+        Self::parse_pr_labels(&raw)
+    }
+
+    /// Add and remove labels on a pull request in a single `gh pr edit`.
+    pub fn edit_pr_labels(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+        add: &[String],
+        remove: &[String],
+    ) -> Result<(), GhCliError> {
+        let mut args = vec![
+            "pr".to_string(),
+            "edit".to_string(),
+            pr_number.to_string(),
+            "--repo".to_string(),
+            format!("{owner}/{repo}"),
+        ];
+        for label in add {
+            args.push("--add-label".to_string());
+            args.push(label.clone());
+        }
+        for label in remove {
+            args.push("--remove-label".to_string());
+            args.push(label.clone());
+        }
+        self.run(args, None)?;
+        Ok(())
     }
 
     /// Fetch comments for a pull request.
@@ -249,7 +842,32 @@ impl GhCli {
         owner: &str,
         repo: &str,
         pr_number: i64,
+        since: Option<DateTime<Utc>>,
     ) -> Result<Vec<PrComment>, GhCliError> {
+        // Reshape the REST payload into the `gh pr view --json comments` form
+        let raw = self.run(
+            [
+                "api",
+                "--paginate",
+                &comments_endpoint(
+                    &format!("repos/{owner}/{repo}/issues/{pr_number}/comments"),
+                    since,
+                ),
+                "--jq",
+                PR_COMMENT_JQ,
+            ],
+            None,
+        )?;
+        Self::parse_pr_comments(&raw, since)
+    }
+
+    /// Fetch the author, submitted reviews and pending review requests of a pull request.
+    pub fn get_pr_people(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+    ) -> Result<PrPeople, GhCliError> {
         let raw = self.run(
             [
                 "pr",
@@ -258,11 +876,35 @@ impl GhCli {
                 "--repo",
                 &format!("{owner}/{repo}"),
                 "--json",
-                "comments",
+                "author,reviews,reviewRequests",
+            ],
+            None,
+        )?;
+        serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse gh pr view --json author,reviews,reviewRequests response: {err}; raw: {raw}"
+            ))
+        })
+    }
+
+    /// Fetch the submitted reviews of a pull request, oldest first.
+    pub fn get_pr_reviews(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+    ) -> Result<Vec<PrReview>, GhCliError> {
+        let raw = self.run(
+            [
+                "api",
+                "--paginate",
+                &format!("repos/{owner}/{repo}/pulls/{pr_number}/reviews?per_page=100"),
+                "--jq",
+                PR_REVIEW_JQ,
             ],
             None,
         )?;
-        Self::parse_pr_comments(&raw)
+        parse_json_lines(&raw, "PR review")
     }
 
     /// Fetch inline review comments for a pull request via API.
@@ -271,18 +913,90 @@ impl GhCli {
         owner: &str,
         repo: &str,
         pr_number: i64,
+        since: Option<DateTime<Utc>>,
     ) -> Result<Vec<PrReviewComment>, GhCliError> {
         let raw = self.run(
             [
                 "api",
-                &format!("repos/{owner}/{repo}/pulls/{pr_number}/comments"),
+                "--paginate",
+                &comments_endpoint(
+                    &format!("repos/{owner}/{repo}/pulls/{pr_number}/comments"),
+                    since,
+                ),
+                "--jq",
+                ".[]",
+            ],
+            None,
+        )?;
+        Self::parse_pr_review_comments(&raw, since)
+    }
+
+    /// Fetch a single inline review comment.
+    pub fn get_review_comment(
+        &self,
+        owner: &str,
+        repo: &str,
+        comment_id: i64,
+    ) -> Result<PrReviewComment, GhCliError> {
+        let raw = self.run(
+            [
+                "api",
+                &format!("repos/{owner}/{repo}/pulls/comments/{comment_id}"),
+            ],
+            None,
+        )?;
+        Self::parse_pr_review_comment(&raw)
+    }
+
+    /// Reply in the thread of an inline review comment.
+    pub fn reply_review_comment(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+        comment_id: i64,
+        body: &str,
+    ) -> Result<PrReviewComment, GhCliError> {
+        let raw = self.run(
+            [
+                "api",
+                "-X",
+                "POST",
+                &format!("repos/{owner}/{repo}/pulls/{pr_number}/comments/{comment_id}/replies"),
+                "-f",
+                &format!("body={body}"),
             ],
             None,
         )?;
-        Self::parse_pr_review_comments(&raw)
+        Self::parse_pr_review_comment(&raw)
+    }
+}
+
+/// Comment list endpoint with the largest page size. `since` narrows the listing to comments
+/// updated after it; callers still filter on creation time.
+pub(super) fn comments_endpoint(path: &str, since: Option<DateTime<Utc>>) -> String {
+    match since {
+        Some(since) => format!(
+            "{path}?per_page=100&since={}",
+            since.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        ),
+        None => format!("{path}?per_page=100"),
     }
 }
 
+/// Parse output with one JSON value per line, as produced by `gh api --paginate --jq`
+fn parse_json_lines<T: DeserializeOwned>(raw: &str, what: &str) -> Result<Vec<T>, GhCliError> {
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|err| {
+                GhCliError::UnexpectedOutput(format!("Failed to parse {what}: {err}; line: {line}"))
+            })
+        })
+        .collect()
+}
+
 impl GhCli {
     fn parse_pr_create_text(raw: &str) -> Result<PullRequestInfo, GhCliError> {
         let pr_url = raw
@@ -321,6 +1035,8 @@ impl GhCli {
             status: MergeStatus::Open,
             merged_at: None,
             merge_commit_sha: None,
+            is_draft: false,
+            provider: PrProvider::GitHub,
         })
     }
 
@@ -337,6 +1053,77 @@ impl GhCli {
         })
     }
 
+    fn parse_pr_checks(raw: &str) -> Result<Vec<CheckRun>, GhCliError> {
+        #[derive(Deserialize)]
+        struct Check {
+            name: String,
+            state: String,
+        }
+
+        let checks: Vec<Check> = serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse gh pr checks response: {err}; raw: {raw}"
+            ))
+        })?;
+        Ok(checks
+            .into_iter()
+            .map(|check| {
+                // `state` merges check run status/conclusion and commit status states
+                let (status, conclusion) = match check.state.as_str() {
+                    "QUEUED" | "PENDING" | "EXPECTED" | "WAITING" | "REQUESTED" => {
+                        (CheckStatus::Queued, None)
+                    }
+                    "IN_PROGRESS" => (CheckStatus::InProgress, None),
+                    state => (
+                        CheckStatus::Completed,
+                        match state {
+                            "SUCCESS" => Some(CheckConclusion::Success),
+                            "FAILURE" | "ERROR" | "STARTUP_FAILURE" => {
+                                Some(CheckConclusion::Failure)
+                            }
+                            "NEUTRAL" => Some(CheckConclusion::Neutral),
+                            "CANCELLED" => Some(CheckConclusion::Cancelled),
+                            "SKIPPED" => Some(CheckConclusion::Skipped),
+                            "TIMED_OUT" => Some(CheckConclusion::TimedOut),
+                            "ACTION_REQUIRED" => Some(CheckConclusion::ActionRequired),
+                            "STALE" => Some(CheckConclusion::Stale),
+                            _ => None,
+                        },
+                    ),
+                };
+                CheckRun {
+                    name: check.name,
+                    status,
+                    conclusion,
+                }
+            })
+            .collect())
+    }
+
+    fn parse_mergeability(raw: &str) -> Result<Mergeability, GhCliError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Response {
+            mergeable: String,
+            merge_state_status: String,
+        }
+
+        let resp: Response = serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse gh pr view --json mergeable response: {err}; raw: {raw}"
+            ))
+        })?;
+        Ok(
+            match (resp.mergeable.as_str(), resp.merge_state_status.as_str()) {
+                ("CONFLICTING", _) | (_, "DIRTY") => Mergeability::Conflicting,
+                ("UNKNOWN", _) | (_, "UNKNOWN") => Mergeability::Unknown,
+                (_, "BLOCKED" | "BEHIND" | "DRAFT") => Mergeability::Blocked,
+                ("MERGEABLE", _) => Mergeability::Clean,
+                _ => Mergeability::Unknown,
+            },
+        )
+    }
+
     fn parse_pr_list(raw: &str) -> Result<Vec<PullRequestInfo>, GhCliError> {
         let value: Value = serde_json::from_str(raw.trim()).map_err(|err| {
             GhCliError::UnexpectedOutput(format!(
@@ -357,36 +1144,187 @@ impl GhCli {
             .collect()
     }
 
-    fn parse_pr_comments(raw: &str) -> Result<Vec<PrComment>, GhCliError> {
+    fn parse_pr_head_sha(raw: &str) -> Result<String, GhCliError> {
+        let value: Value = serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse gh pr view --json headRefOid response: {err}; raw: {raw}"
+            ))
+        })?;
+        value
+            .get("headRefOid")
+            .and_then(Value::as_str)
+            .filter(|sha| !sha.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                GhCliError::UnexpectedOutput(format!(
+                    "gh pr view --json headRefOid response missing 'headRefOid': {value:#?}"
+                ))
+            })
+    }
+
+    /// Parse the REST `commits/{sha}/pulls` response, whose fields differ from `gh pr` JSON.
+    fn parse_commit_pulls(raw: &str) -> Result<Vec<PullRequestInfo>, GhCliError> {
+        let value: Value = serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse commit pulls API response: {err}; raw: {raw}"
+            ))
+        })?;
+        let arr = value.as_array().ok_or_else(|| {
+            GhCliError::UnexpectedOutput(format!(
+                "commit pulls API response is not an array: {value:#?}"
+            ))
+        })?;
+        arr.iter().map(Self::extract_rest_pr_info).collect()
+    }
+
+    /// Build a `PullRequestInfo` from a REST pull request object.
+    pub(super) fn extract_rest_pr_info(item: &Value) -> Result<PullRequestInfo, GhCliError> {
+        let number = item.get("number").and_then(Value::as_i64);
+        let url = item.get("html_url").and_then(Value::as_str);
+        let (Some(number), Some(url)) = (number, url) else {
+            return Err(GhCliError::UnexpectedOutput(format!(
+                "pull request API item missing required fields: {item:#?}"
+            )));
+        };
+        let merged_at = item
+            .get("merged_at")
+            .and_then(Value::as_str)
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+        let status = match item.get("state").and_then(Value::as_str) {
+            Some("open") => MergeStatus::Open,
+            Some("closed") if merged_at.is_some() => MergeStatus::Merged,
+            Some("closed") => MergeStatus::Closed,
+            _ => MergeStatus::Unknown,
+        };
+        Ok(PullRequestInfo {
+            number,
+            url: url.to_string(),
+            status,
+            merged_at,
+            merge_commit_sha: item
+                .get("merge_commit_sha")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            is_draft: item.get("draft").and_then(Value::as_bool).unwrap_or(false),
+            provider: PrProvider::GitHub,
+        })
+    }
+
+    fn parse_repo_content(raw: &str) -> Result<RepoContent, GhCliError> {
         let value: Value = serde_json::from_str(raw.trim()).map_err(|err| {
             GhCliError::UnexpectedOutput(format!(
-                "Failed to parse gh pr view --json comments response: {err}; raw: {raw}"
+                "Failed to parse contents API response: {err}; raw: {raw}"
             ))
         })?;
-        let comments_arr = value
-            .get("comments")
-            .and_then(|v| v.as_array())
+        if value.is_array() || value.get("type").and_then(Value::as_str) == Some("dir") {
+            return Ok(RepoContent::Directory);
+        }
+        let encoded = value
+            .get("content")
+            .and_then(Value::as_str)
             .ok_or_else(|| {
                 GhCliError::UnexpectedOutput(format!(
-                    "gh pr view --json comments response missing 'comments' array: {value:#?}"
+                    "contents API response missing 'content': {value:#?}"
                 ))
             })?;
-        comments_arr
-            .iter()
-            .map(|item| {
-                serde_json::from_value(item.clone()).map_err(|err| {
-                    GhCliError::UnexpectedOutput(format!(
-                        "Failed to parse PR comment: {err}; item: {item:#?}"
-                    ))
-                })
+        let encoded: String = encoded.chars().filter(|c| !c.is_whitespace()).collect();
+        let bytes = BASE64_STANDARD.decode(encoded).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!("Failed to decode file contents: {err}"))
+        })?;
+        String::from_utf8(bytes)
+            .map(RepoContent::File)
+            .map_err(|_| GhCliError::UnexpectedOutput("File contents are not UTF-8".to_string()))
+    }
+
+    fn parse_compare_files(raw: &str) -> Result<Vec<String>, GhCliError> {
+        let value: Value = serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse compare API response: {err}; raw: {raw}"
+            ))
+        })?;
+        Ok(value
+            .get("files")
+            .and_then(Value::as_array)
+            .map(|files| {
+                files
+                    .iter()
+                    .filter_map(|f| f.get("filename").and_then(Value::as_str))
+                    .map(str::to_string)
+                    .collect()
             })
-            .collect()
+            .unwrap_or_default())
+    }
+
+    fn parse_compare_behind_by(raw: &str) -> Result<u32, GhCliError> {
+        let value: Value = serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse compare API response: {err}; raw: {raw}"
+            ))
+        })?;
+        value
+            .get("behind_by")
+            .and_then(Value::as_u64)
+            .map(|behind| u32::try_from(behind).unwrap_or(u32::MAX))
+            .ok_or_else(|| {
+                GhCliError::UnexpectedOutput(format!(
+                    "Compare API response missing 'behind_by': {value:#?}"
+                ))
+            })
+    }
+
+    fn parse_labels(raw: &str) -> Result<Vec<Label>, GhCliError> {
+        serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse gh label list response: {err}; raw: {raw}"
+            ))
+        })
+    }
+
+    fn parse_pr_labels(raw: &str) -> Result<Vec<String>, GhCliError> {
+        #[derive(Deserialize)]
+        struct Response {
+            labels: Vec<LabelName>,
+        }
+        #[derive(Deserialize)]
+        struct LabelName {
+            name: String,
+        }
+
+        let resp: Response = serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse gh pr view --json labels response: {err}; raw: {raw}"
+            ))
+        })?;
+        Ok(resp.labels.into_iter().map(|l| l.name).collect())
     }
 
-    fn parse_pr_review_comments(raw: &str) -> Result<Vec<PrReviewComment>, GhCliError> {
+    fn parse_pr_comments(
+        raw: &str,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PrComment>, GhCliError> {
+        let comments: Vec<PrComment> = parse_json_lines(raw, "PR comment")?;
+        Ok(comments
+            .into_iter()
+            .filter(|c| since.is_none_or(|since| c.created_at > since))
+            .collect())
+    }
+
+    fn parse_pr_review_comments(
+        raw: &str,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PrReviewComment>, GhCliError> {
+        let comments: Vec<PrReviewComment> = parse_json_lines(raw, "review comment")?;
+        Ok(comments
+            .into_iter()
+            .filter(|c| since.is_none_or(|since| c.created_at > since))
+            .collect())
+    }
+
+    fn parse_pr_review_comment(raw: &str) -> Result<PrReviewComment, GhCliError> {
         serde_json::from_str(raw.trim()).map_err(|err| {
             GhCliError::UnexpectedOutput(format!(
-                "Failed to parse review comments API response: {err}; raw: {raw}"
+                "Failed to parse review comment API response: {err}; raw: {raw}"
             ))
         })
     }
@@ -420,6 +1358,276 @@ impl GhCli {
             },
             merged_at,
             merge_commit_sha,
+            is_draft: value
+                .get("isDraft")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            provider: PrProvider::GitHub,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pr_diff_args_add_name_only() {
+        let url = "https://github.com/o/r/pull/5";
+        assert_eq!(
+            GhCli::pr_diff_args(url, false),
+            ["pr", "diff", url, "--color", "never"]
+        );
+        assert_eq!(GhCli::pr_diff_args(url, true).last(), Some(&"--name-only"));
+    }
+
+    #[test]
+    fn pr_diff_by_number_leaves_the_host_to_gh() {
+        assert_eq!(
+            GhCli::pr_diff_by_number_args("acme", "widgets", 7),
+            [
+                "pr",
+                "diff",
+                "7",
+                "--repo",
+                "acme/widgets",
+                "--color",
+                "never"
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_pr_reviews() {
+        let raw = concat!(
+            r#"{"id":"PRR_1","author":{"login":"alice"},"authorAssociation":"MEMBER","state":"CHANGES_REQUESTED","body":"Needs tests","submittedAt":"2025-01-02T00:00:00Z","url":"https://github.com/acme/widgets/pull/7#pullrequestreview-1"}"#,
+            "\n",
+            r#"{"id":"PRR_2","author":{"login":"bob"},"authorAssociation":"NONE","state":"SOMETHING_NEW","body":"","submittedAt":null,"url":"https://github.com/acme/widgets/pull/7#pullrequestreview-2"}"#,
+        );
+        let reviews: Vec<PrReview> = parse_json_lines(raw, "PR review").unwrap();
+        assert_eq!(reviews.len(), 2);
+        assert_eq!(reviews[0].author.login, "alice");
+        assert_eq!(reviews[0].state, ReviewState::ChangesRequested);
+        assert_eq!(reviews[0].body, "Needs tests");
+        assert!(reviews[0].submitted_at.is_some());
+        assert_eq!(reviews[1].state, ReviewState::Unknown);
+        assert_eq!(reviews[1].body, "");
+        assert!(reviews[1].submitted_at.is_none());
+    }
+
+    #[test]
+    fn parses_pr_view_draft_state() {
+        let raw = r#"{"number": 5, "url": "https://github.com/o/r/pull/5", "state": "OPEN", "isDraft": true}"#;
+        let pr = GhCli::parse_pr_view(raw).unwrap();
+        assert_eq!(pr.number, 5);
+        assert!(pr.is_draft);
+
+        let raw = r#"{"number": 5, "url": "https://github.com/o/r/pull/5", "state": "OPEN"}"#;
+        assert!(!GhCli::parse_pr_view(raw).unwrap().is_draft);
+    }
+
+    #[test]
+    fn parses_pr_head_sha() {
+        let sha = GhCli::parse_pr_head_sha(r#"{"headRefOid":"abc123"}"#).unwrap();
+        assert_eq!(sha, "abc123");
+
+        assert!(GhCli::parse_pr_head_sha(r#"{"headRefOid":""}"#).is_err());
+        assert!(GhCli::parse_pr_head_sha("{}").is_err());
+    }
+
+    #[test]
+    fn parses_commit_pulls() {
+        let raw = r#"[
+            {"number":7,"html_url":"https://github.com/o/r/pull/7","state":"closed","merged_at":"2025-01-02T03:04:05Z","merge_commit_sha":"def"},
+            {"number":9,"html_url":"https://github.com/o/r/pull/9","state":"open","merged_at":null,"merge_commit_sha":null},
+            {"number":8,"html_url":"https://github.com/o/r/pull/8","state":"closed","merged_at":null}
+        ]"#;
+        let prs = GhCli::parse_commit_pulls(raw).unwrap();
+        assert_eq!(
+            prs.iter().map(|pr| pr.number).collect::<Vec<_>>(),
+            [7, 9, 8]
+        );
+        assert!(matches!(prs[0].status, MergeStatus::Merged));
+        assert!(matches!(prs[1].status, MergeStatus::Open));
+        assert!(matches!(prs[2].status, MergeStatus::Closed));
+        assert_eq!(prs[0].merge_commit_sha.as_deref(), Some("def"));
+        assert!(GhCli::parse_commit_pulls("[]").unwrap().is_empty());
+        assert!(GhCli::parse_commit_pulls(r#"[{"number":1}]"#).is_err());
+    }
+
+    #[test]
+    fn parses_repo_content() {
+        let file = r#"{"type":"file","encoding":"base64","content":"aGVsbG8g\nd29ybGQ=\n"}"#;
+        assert_eq!(
+            GhCli::parse_repo_content(file).unwrap(),
+            RepoContent::File("hello world".to_string())
+        );
+        assert_eq!(
+            GhCli::parse_repo_content(r#"[{"type":"file","name":"a.rs"}]"#).unwrap(),
+            RepoContent::Directory
+        );
+    }
+
+    #[test]
+    fn parses_compare_files() {
+        let raw = r#"{"files":[{"filename":"src/a.rs"},{"filename":"README.md"}]}"#;
+        assert_eq!(
+            GhCli::parse_compare_files(raw).unwrap(),
+            vec!["src/a.rs".to_string(), "README.md".to_string()]
+        );
+        assert!(GhCli::parse_compare_files("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parses_compare_behind_by() {
+        let raw = r#"{"status":"diverged","ahead_by":2,"behind_by":5}"#;
+        assert_eq!(GhCli::parse_compare_behind_by(raw).unwrap(), 5);
+        let identical = r#"{"status":"identical","ahead_by":0,"behind_by":0}"#;
+        assert_eq!(GhCli::parse_compare_behind_by(identical).unwrap(), 0);
+        assert!(GhCli::parse_compare_behind_by("{}").is_err());
+    }
+
+    #[test]
+    fn parses_pr_checks() {
+        let raw = r#"[
+            {"name": "build", "state": "SUCCESS"},
+            {"name": "lint", "state": "FAILURE"},
+            {"name": "e2e", "state": "IN_PROGRESS"},
+            {"name": "deploy", "state": "PENDING"}
+        ]"#;
+        let checks = GhCli::parse_pr_checks(raw).unwrap();
+        let summary: Vec<_> = checks
+            .iter()
+            .map(|c| (c.name.as_str(), c.status, c.conclusion))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "build",
+                    CheckStatus::Completed,
+                    Some(CheckConclusion::Success)
+                ),
+                (
+                    "lint",
+                    CheckStatus::Completed,
+                    Some(CheckConclusion::Failure)
+                ),
+                ("e2e", CheckStatus::InProgress, None),
+                ("deploy", CheckStatus::Queued, None),
+            ]
+        );
+        assert!(GhCli::parse_pr_checks("[]").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parses_mergeability() {
+        let parse = |mergeable: &str, state: &str| {
+            GhCli::parse_mergeability(&format!(
+                r#"{{"mergeable": "{mergeable}", "mergeStateStatus": "{state}"}}"#
+            ))
+            .unwrap()
+        };
+        assert_eq!(parse("MERGEABLE", "CLEAN"), Mergeability::Clean);
+        assert_eq!(parse("MERGEABLE", "UNSTABLE"), Mergeability::Clean);
+        assert_eq!(parse("CONFLICTING", "DIRTY"), Mergeability::Conflicting);
+        assert_eq!(parse("MERGEABLE", "BLOCKED"), Mergeability::Blocked);
+        assert_eq!(parse("MERGEABLE", "BEHIND"), Mergeability::Blocked);
+        assert_eq!(parse("UNKNOWN", "UNKNOWN"), Mergeability::Unknown);
+        assert!(GhCli::parse_mergeability(r#"{"mergeable": "MERGEABLE"}"#).is_err());
+    }
+
+    #[test]
+    fn parses_pr_labels() {
+        let raw = r#"{"labels": [{"id": "LA_1", "name": "bug", "color": "d73a4a"}, {"name": "ai-generated"}]}"#;
+        assert_eq!(
+            GhCli::parse_pr_labels(raw).unwrap(),
+            ["bug", "ai-generated"]
+        );
+        assert!(
+            GhCli::parse_pr_labels(r#"{"labels": []}"#)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn parses_labels() {
+        let raw = r#"[{"name":"bug","color":"d73a4a","description":"Something isn't working"},{"name":"chore","color":"ededed","description":""}]"#;
+        let labels = GhCli::parse_labels(raw).unwrap();
+        assert_eq!(
+            labels.iter().map(|l| l.name.as_str()).collect::<Vec<_>>(),
+            ["bug", "chore"]
+        );
+        assert_eq!(labels[0].color, "d73a4a");
+        assert_eq!(labels[1].description, "");
+        assert!(GhCli::parse_labels("[]").unwrap().is_empty());
+        assert!(GhCli::parse_labels("not json").is_err());
+    }
+
+    #[test]
+    fn parses_single_review_comment() {
+        let raw = r#"{
+            "id": 42,
+            "user": {"login": "octocat"},
+            "body": "Done, thanks",
+            "created_at": "2025-01-02T03:04:05Z",
+            "html_url": "https://github.com/acme/widgets/pull/7#discussion_r42",
+            "path": "src/lib.rs",
+            "line": null,
+            "side": "RIGHT",
+            "diff_hunk": "@@ -1,2 +1,2 @@",
+            "author_association": "MEMBER",
+            "in_reply_to_id": 41
+        }"#;
+        let comment = GhCli::parse_pr_review_comment(raw).unwrap();
+        assert_eq!(comment.id, 42);
+        assert_eq!(comment.user.login, "octocat");
+        assert_eq!(comment.path, "src/lib.rs");
+        assert_eq!(comment.line, None);
+        assert_eq!(comment.reactions, ReactionSummary::default());
+        assert!(GhCli::parse_pr_review_comment("[]").is_err());
+
+        let reacted = raw.replace(
+            r#""in_reply_to_id": 41"#,
+            r#""reactions": {"url": "https://api.github.com/x", "total_count": 3, "+1": 2, "-1": 0, "heart": 1}"#,
+        );
+        let reactions = GhCli::parse_pr_review_comment(&reacted).unwrap().reactions;
+        assert_eq!(
+            reactions,
+            ReactionSummary {
+                thumbs_up: 2,
+                heart: 1,
+                total_count: 3,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn parses_paginated_comments_after_since() {
+        let raw = concat!(
+            r#"{"id":"IC_1","author":{"login":"alice"},"authorAssociation":"OWNER","body":"first","createdAt":"2025-01-01T00:00:00Z","url":"https://github.com/acme/widgets/pull/7#issuecomment-1"}"#,
+            "\n",
+            r#"{"id":"IC_2","author":{"login":"bob"},"authorAssociation":"NONE","body":"second","createdAt":"2025-01-03T00:00:00Z","url":"https://github.com/acme/widgets/pull/7#issuecomment-2"}"#,
+            "\n"
+        );
+        let all = GhCli::parse_pr_comments(raw, None).unwrap();
+        assert_eq!(
+            all.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(),
+            ["IC_1", "IC_2"]
+        );
+
+        let since = "2025-01-02T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let recent = GhCli::parse_pr_comments(raw, Some(since)).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].author.login, "bob");
+
+        assert!(GhCli::parse_pr_comments("", None).unwrap().is_empty());
+        assert!(GhCli::parse_pr_comments("[1, 2]\n", None).is_err());
+        assert_eq!(
+            comments_endpoint("repos/a/b/pulls/1/comments", Some(since)),
+            "repos/a/b/pulls/1/comments?per_page=100&since=2025-01-02T00:00:00Z"
+        );
+    }
+}
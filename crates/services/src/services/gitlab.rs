@@ -0,0 +1,331 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use db::models::merge::PullRequestInfo;
+use thiserror::Error;
+use tokio::task;
+use tracing::info;
+
+mod cli;
+
+pub use cli::GlabCliError;
+use cli::{GlabCli, MrNote};
+
+use crate::services::{
+    github::{
+        AuthorAssociation, CreatePrRequest, GitHubRepoInfo, PrState, ReactionSummary,
+        UnifiedPrComment, select_prs,
+    },
+    vcs::{CliFailure, RetryableError, VcsProvider, classify_cli_failure, with_retry},
+};
+
+#[derive(Debug, Error)]
+pub enum GitLabServiceError {
+    #[error("Merge request error: {0}")]
+    MergeRequest(String),
+    #[error("GitLab authentication failed: {0}")]
+    AuthFailed(GlabCliError),
+    #[error("Insufficient permissions: {0}")]
+    InsufficientPermissions(GlabCliError),
+    #[error("GitLab project not found or no access: {0}")]
+    ProjectNotFoundOrNoAccess(GlabCliError),
+    #[error("Merge request is not mergeable: {0}")]
+    NotMergeable(GlabCliError),
+    #[error("Invalid merge request URL: {0}")]
+    InvalidMrUrl(String),
+    #[error(
+        "GitLab CLI is not installed or not available in PATH. Please install it from https://gitlab.com/gitlab-org/cli and authenticate with 'glab auth login'"
+    )]
+    GlabCliNotInstalled(GlabCliError),
+}
+
+impl From<GlabCliError> for GitLabServiceError {
+    fn from(error: GlabCliError) -> Self {
+        match &error {
+            GlabCliError::AuthFailed(_) => Self::AuthFailed(error),
+            GlabCliError::NotAvailable => Self::GlabCliNotInstalled(error),
+            GlabCliError::CommandFailed(msg) => match classify_cli_failure(msg) {
+                CliFailure::NotMergeable => Self::NotMergeable(error),
+                CliFailure::Forbidden => Self::InsufficientPermissions(error),
+                CliFailure::NotFound => Self::ProjectNotFoundOrNoAccess(error),
+                CliFailure::Other => Self::MergeRequest(msg.to_string()),
+            },
+            GlabCliError::UnexpectedOutput(msg) => Self::MergeRequest(msg.to_string()),
+        }
+    }
+}
+
+impl RetryableError for GitLabServiceError {
+    fn should_retry(&self) -> bool {
+        matches!(self, GitLabServiceError::MergeRequest(_))
+    }
+}
+
+/// Merge request operations for GitLab projects, backed by `glab`
+#[derive(Debug, Clone)]
+pub struct GitLabService {
+    glab_cli: GlabCli,
+}
+
+impl GitLabService {
+    pub fn new() -> Result<Self, GitLabServiceError> {
+        Ok(Self {
+            glab_cli: GlabCli::new(),
+        })
+    }
+
+    /// Run a blocking GitLab CLI call off the async runtime
+    async fn run_cli<T, F>(&self, action: &str, f: F) -> Result<T, GitLabServiceError>
+    where
+        F: FnOnce(GlabCli) -> Result<T, GlabCliError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let cli = self.glab_cli.clone();
+        task::spawn_blocking(move || f(cli))
+            .await
+            .map_err(|err| {
+                GitLabServiceError::MergeRequest(format!(
+                    "Failed to execute GitLab CLI for {action}: {err}"
+                ))
+            })?
+            .map_err(GitLabServiceError::from)
+    }
+}
+
+#[async_trait]
+impl VcsProvider for GitLabService {
+    type Error = GitLabServiceError;
+
+    async fn create_pr(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        request: &CreatePrRequest,
+    ) -> Result<PullRequestInfo, GitLabServiceError> {
+        let mr = with_retry(|| async {
+            let request = request.clone();
+            let repo = repo_info.clone();
+            self.run_cli("merge request creation", move |cli| {
+                cli.create_mr(&request, &repo)
+            })
+            .await
+        })
+        .await?;
+        info!(
+            "Created GitLab MR !{} for branch {} in {}/{}",
+            mr.number, request.head_branch, repo_info.owner, repo_info.repo_name
+        );
+        Ok(mr)
+    }
+
+    async fn update_pr_status(&self, pr_url: &str) -> Result<PullRequestInfo, GitLabServiceError> {
+        let (host, project_path, iid) = parse_mr_url(pr_url)?;
+        with_retry(|| async {
+            let host = host.clone();
+            let project_path = project_path.clone();
+            self.run_cli(&format!("viewing MR at {pr_url}"), move |cli| {
+                cli.view_mr(Some(&host), &project_path, iid)
+            })
+            .await
+        })
+        .await
+    }
+
+    async fn list_all_prs_for_branch(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        branch_name: &str,
+        states: &[PrState],
+    ) -> Result<Vec<PullRequestInfo>, GitLabServiceError> {
+        let mrs = with_retry(|| async {
+            let repo = repo_info.clone();
+            let branch = branch_name.to_string();
+            self.run_cli(
+                &format!("listing MRs on branch '{branch_name}'"),
+                move |cli| cli.list_mrs_for_branch(&repo, &branch),
+            )
+            .await
+        })
+        .await?;
+        Ok(select_prs(mrs, states))
+    }
+
+    async fn get_pr_comments(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<UnifiedPrComment>, GitLabServiceError> {
+        let notes = with_retry(|| async {
+            let repo = repo_info.clone();
+            self.run_cli(&format!("fetching MR !{pr_number} notes"), move |cli| {
+                cli.get_mr_notes(&repo, pr_number)
+            })
+            .await
+        })
+        .await?;
+        let mr_url = format!(
+            "{}/{}/{}/-/merge_requests/{pr_number}",
+            gitlab_base_url(),
+            repo_info.owner,
+            repo_info.repo_name
+        );
+        Ok(notes_to_comments(notes, &mr_url, since))
+    }
+}
+
+/// Web root of the GitLab instance `glab` talks to by default
+fn gitlab_base_url() -> String {
+    let host = std::env::var("GITLAB_HOST").unwrap_or_else(|_| "gitlab.com".to_string());
+    if host.starts_with("http://") || host.starts_with("https://") {
+        host.trim_end_matches('/').to_string()
+    } else {
+        format!("https://{host}")
+    }
+}
+
+/// Host, project path and IID of `https://host/group/project/-/merge_requests/N`
+fn parse_mr_url(url: &str) -> Result<(String, String, i64), GitLabServiceError> {
+    let invalid = || GitLabServiceError::InvalidMrUrl(url.to_string());
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or_else(invalid)?;
+    let (host, path) = rest.split_once('/').ok_or_else(invalid)?;
+    let (project_path, tail) = path.split_once("/-/merge_requests/").ok_or_else(invalid)?;
+    let iid = tail
+        .split(['/', '#', '?'])
+        .next()
+        .and_then(|n| n.parse().ok())
+        .ok_or_else(invalid)?;
+    if project_path.is_empty() {
+        return Err(invalid());
+    }
+    Ok((host.to_string(), project_path.to_string(), iid))
+}
+
+/// User notes as unified comments, oldest first. Diff notes become review comments; GitLab
+/// has no author association, so it is reported as `NONE`.
+fn notes_to_comments(
+    notes: Vec<MrNote>,
+    mr_url: &str,
+    since: Option<DateTime<Utc>>,
+) -> Vec<UnifiedPrComment> {
+    let mut comments: Vec<_> = notes
+        .into_iter()
+        .filter(|note| !note.system)
+        .filter(|note| since.is_none_or(|since| note.created_at > since))
+        .map(|note| {
+            let url = format!("{mr_url}#note_{}", note.id);
+            match note.position {
+                Some(position) => UnifiedPrComment::Review {
+                    id: note.id,
+                    author: note.author.username,
+                    author_association: AuthorAssociation::None,
+                    author_association_raw: "NONE".to_string(),
+                    body: note.body,
+                    created_at: note.created_at,
+                    url,
+                    path: position.new_path.or(position.old_path).unwrap_or_default(),
+                    line: position.new_line.or(position.old_line),
+                    diff_hunk: String::new(),
+                    reactions: ReactionSummary::default(),
+                    outdated: false,
+                },
+                None => UnifiedPrComment::General {
+                    id: note.id.to_string(),
+                    author: note.author.username,
+                    author_association: AuthorAssociation::None,
+                    author_association_raw: "NONE".to_string(),
+                    body: note.body,
+                    created_at: note.created_at,
+                    url,
+                    reactions: ReactionSummary::default(),
+                },
+            }
+        })
+        .collect();
+    comments.sort_by_key(UnifiedPrComment::created_at);
+    comments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_merge_request_urls() {
+        assert_eq!(
+            parse_mr_url("https://gitlab.example.com/group/sub/app/-/merge_requests/42#note_7")
+                .unwrap(),
+            (
+                "gitlab.example.com".to_string(),
+                "group/sub/app".to_string(),
+                42
+            )
+        );
+        for bad in [
+            "https://github.com/o/r/pull/1",
+            "gitlab.com/a/b/-/merge_requests/1",
+            "https://gitlab.com/a/b/-/merge_requests/x",
+        ] {
+            assert!(matches!(
+                parse_mr_url(bad),
+                Err(GitLabServiceError::InvalidMrUrl(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn maps_notes_to_unified_comments() {
+        let notes: Vec<MrNote> = serde_json::from_value(serde_json::json!([
+            {
+                "id": 3, "body": "Nit: rename", "author": {"username": "bob"},
+                "created_at": "2025-01-03T00:00:00Z", "system": false,
+                "position": {"new_path": "src/app.rs", "old_path": "src/app.rs", "new_line": 10, "old_line": null}
+            },
+            {
+                "id": 1, "body": "added 1 commit", "author": {"username": "alice"},
+                "created_at": "2025-01-01T00:00:00Z", "system": true
+            },
+            {
+                "id": 2, "body": "Looks good", "author": {"username": "carol"},
+                "created_at": "2025-01-02T00:00:00Z", "system": false, "position": null
+            }
+        ]))
+        .unwrap();
+        let url = "https://gitlab.com/a/b/-/merge_requests/5";
+
+        let comments = notes_to_comments(notes.clone(), url, None);
+        assert_eq!(comments.len(), 2);
+        let UnifiedPrComment::General { id, author, .. } = &comments[0] else {
+            panic!("expected a general comment first");
+        };
+        assert_eq!((id.as_str(), author.as_str()), ("2", "carol"));
+        let UnifiedPrComment::Review {
+            path, line, url, ..
+        } = &comments[1]
+        else {
+            panic!("expected a review comment second");
+        };
+        assert_eq!((path.as_str(), *line), ("src/app.rs", Some(10)));
+        assert_eq!(url, "https://gitlab.com/a/b/-/merge_requests/5#note_3");
+
+        let since = "2025-01-02T12:00:00Z".parse().unwrap();
+        assert_eq!(notes_to_comments(notes, url, Some(since)).len(), 1);
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        let err = GitLabServiceError::from(GlabCliError::CommandFailed(
+            "404 Project Not Found".to_string(),
+        ));
+        assert!(matches!(
+            err,
+            GitLabServiceError::ProjectNotFoundOrNoAccess(_)
+        ));
+        assert!(!err.should_retry());
+        assert!(
+            GitLabServiceError::from(GlabCliError::CommandFailed("timeout".to_string()))
+                .should_retry()
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Minimal helpers around the GitLab CLI (`glab`), mirroring `github/cli.rs`.
+//!
+//! Everything goes through `glab api` so responses are the documented REST JSON rather than
+//! the CLI's human-readable output.
+
+use std::{ffi::OsStr, process::Command};
+
+use chrono::{DateTime, Utc};
+use db::models::merge::{MergeStatus, PrProvider, PullRequestInfo};
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+use utils::shell::resolve_executable_path_blocking;
+
+use crate::services::github::{CreatePrRequest, GitHubRepoInfo};
+
+/// High-level errors originating from the GitLab CLI.
+#[derive(Debug, Error)]
+pub enum GlabCliError {
+    #[error("GitLab CLI (`glab`) executable not found or not runnable")]
+    NotAvailable,
+    #[error("GitLab CLI command failed: {0}")]
+    CommandFailed(String),
+    #[error("GitLab CLI authentication failed: {0}")]
+    AuthFailed(String),
+    #[error("GitLab CLI returned unexpected output: {0}")]
+    UnexpectedOutput(String),
+}
+
+/// A note (comment) on a merge request
+#[derive(Debug, Clone, Deserialize)]
+pub struct MrNote {
+    pub id: i64,
+    pub body: String,
+    pub author: MrNoteAuthor,
+    pub created_at: DateTime<Utc>,
+    /// Notes generated by GitLab itself, e.g. "added 1 commit"
+    #[serde(default)]
+    pub system: bool,
+    /// Set on diff notes anchored to a line of the change
+    #[serde(default)]
+    pub position: Option<MrNotePosition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MrNoteAuthor {
+    pub username: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MrNotePosition {
+    pub new_path: Option<String>,
+    pub old_path: Option<String>,
+    pub new_line: Option<i64>,
+    pub old_line: Option<i64>,
+}
+
+/// Newtype wrapper for invoking the `glab` command.
+#[derive(Debug, Clone, Default)]
+pub struct GlabCli;
+
+impl GlabCli {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn run<I, S>(&self, args: I) -> Result<String, GlabCliError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let glab = resolve_executable_path_blocking("glab").ok_or(GlabCliError::NotAvailable)?;
+        let output = Command::new(&glab)
+            .args(args)
+            .output()
+            .map_err(|err| GlabCliError::CommandFailed(err.to_string()))?;
+
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let lower = stderr.to_ascii_lowercase();
+        if lower.contains("401")
+            || lower.contains("unauthorized")
+            || lower.contains("glab auth login")
+        {
+            return Err(GlabCliError::AuthFailed(stderr));
+        }
+
+        Err(GlabCliError::CommandFailed(stderr))
+    }
+
+    /// Open a merge request from `request.head_branch` into `request.base_branch`.
+    pub fn create_mr(
+        &self,
+        request: &CreatePrRequest,
+        repo_info: &GitHubRepoInfo,
+    ) -> Result<PullRequestInfo, GlabCliError> {
+        // GitLab marks drafts by title prefix
+        let title = if request.draft.unwrap_or(false) {
+            format!("Draft: {}", request.title)
+        } else {
+            request.title.clone()
+        };
+        let raw = self.run([
+            "api".to_string(),
+            "-X".to_string(),
+            "POST".to_string(),
+            format!("projects/{}/merge_requests", project_id(repo_info)),
+            "-f".to_string(),
+            format!("source_branch={}", request.head_branch),
+            "-f".to_string(),
+            format!("target_branch={}", request.base_branch),
+            "-f".to_string(),
+            format!("title={title}"),
+            "-f".to_string(),
+            format!("description={}", request.body.as_deref().unwrap_or("")),
+        ])?;
+        Self::parse_mr(&raw)
+    }
+
+    /// Retrieve a merge request by project path and IID, on `host` when given.
+    pub fn view_mr(
+        &self,
+        host: Option<&str>,
+        project_path: &str,
+        iid: i64,
+    ) -> Result<PullRequestInfo, GlabCliError> {
+        let mut args = vec!["api".to_string()];
+        if let Some(host) = host {
+            args.push("--hostname".to_string());
+            args.push(host.to_string());
+        }
+        args.push(format!(
+            "projects/{}/merge_requests/{iid}",
+            encode_project_path(project_path)
+        ));
+        let raw = self.run(args)?;
+        Self::parse_mr(&raw)
+    }
+
+    /// List merge requests from a source branch, in any state.
+    pub fn list_mrs_for_branch(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        branch: &str,
+    ) -> Result<Vec<PullRequestInfo>, GlabCliError> {
+        let raw = self.run([
+            "api",
+            "--paginate",
+            &format!(
+                "projects/{}/merge_requests?source_branch={branch}&state=all&per_page=100",
+                project_id(repo_info)
+            ),
+        ])?;
+        Self::parse_json_pages(&raw)?
+            .iter()
+            .map(Self::extract_mr_info)
+            .collect()
+    }
+
+    /// Fetch every note on a merge request, oldest first.
+    pub fn get_mr_notes(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        iid: i64,
+    ) -> Result<Vec<MrNote>, GlabCliError> {
+        let raw = self.run([
+            "api",
+            "--paginate",
+            &format!(
+                "projects/{}/merge_requests/{iid}/notes?sort=asc&per_page=100",
+                project_id(repo_info)
+            ),
+        ])?;
+        Self::parse_json_pages(&raw)?
+            .into_iter()
+            .map(|item| {
+                serde_json::from_value(item.clone()).map_err(|err| {
+                    GlabCliError::UnexpectedOutput(format!(
+                        "Failed to parse MR note: {err}; item: {item:#?}"
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+/// URL-encoded `namespace/project` path, accepted by the API wherever a project ID is
+fn project_id(repo_info: &GitHubRepoInfo) -> String {
+    encode_project_path(&format!("{}/{}", repo_info.owner, repo_info.repo_name))
+}
+
+fn encode_project_path(path: &str) -> String {
+    path.trim_matches('/').replace('/', "%2F")
+}
+
+impl GlabCli {
+    fn parse_mr(raw: &str) -> Result<PullRequestInfo, GlabCliError> {
+        let value: Value = serde_json::from_str(raw.trim()).map_err(|err| {
+            GlabCliError::UnexpectedOutput(format!(
+                "Failed to parse merge request response: {err}; raw: {raw}"
+            ))
+        })?;
+        Self::extract_mr_info(&value)
+    }
+
+    /// Items of one or more JSON array pages; `--paginate` prints the pages back to back
+    fn parse_json_pages(raw: &str) -> Result<Vec<Value>, GlabCliError> {
+        let mut items = Vec::new();
+        for page in serde_json::Deserializer::from_str(raw).into_iter::<Vec<Value>>() {
+            let page = page.map_err(|err| {
+                GlabCliError::UnexpectedOutput(format!(
+                    "Failed to parse paginated response: {err}; raw: {raw}"
+                ))
+            })?;
+            items.extend(page);
+        }
+        Ok(items)
+    }
+
+    fn extract_mr_info(value: &Value) -> Result<PullRequestInfo, GlabCliError> {
+        let (Some(number), Some(url)) = (
+            value.get("iid").and_then(Value::as_i64),
+            value.get("web_url").and_then(Value::as_str),
+        ) else {
+            return Err(GlabCliError::UnexpectedOutput(format!(
+                "Merge request response missing iid or web_url: {value:#?}"
+            )));
+        };
+        let merged_at = value
+            .get("merged_at")
+            .and_then(Value::as_str)
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+        let merge_commit_sha = ["merge_commit_sha", "squash_commit_sha"]
+            .iter()
+            .find_map(|key| value.get(*key).and_then(Value::as_str))
+            .map(str::to_string);
+        Ok(PullRequestInfo {
+            number,
+            url: url.to_string(),
+            status: match value.get("state").and_then(Value::as_str) {
+                Some("opened") => MergeStatus::Open,
+                Some("merged") => MergeStatus::Merged,
+                Some("closed") | Some("locked") => MergeStatus::Closed,
+                _ => MergeStatus::Unknown,
+            },
+            merged_at,
+            merge_commit_sha,
+            is_draft: value.get("draft").and_then(Value::as_bool).unwrap_or(false),
+            provider: PrProvider::GitLab,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_merge_request() {
+        let raw = r#"{
+            "iid": 12,
+            "web_url": "https://gitlab.example.com/group/app/-/merge_requests/12",
+            "state": "merged",
+            "merged_at": "2025-03-04T05:06:07.000Z",
+            "merge_commit_sha": null,
+            "squash_commit_sha": "abc123"
+        }"#;
+        let mr = GlabCli::parse_mr(raw).unwrap();
+        assert_eq!(mr.number, 12);
+        assert!(matches!(mr.status, MergeStatus::Merged));
+        assert!(mr.merged_at.is_some());
+        assert_eq!(mr.merge_commit_sha.as_deref(), Some("abc123"));
+        assert_eq!(mr.provider, PrProvider::GitLab);
+
+        assert!(GlabCli::parse_mr(r#"{"state": "opened"}"#).is_err());
+    }
+
+    #[test]
+    fn parses_paginated_pages() {
+        let raw = r#"[{"iid": 1, "web_url": "https://gitlab.com/a/b/-/merge_requests/1", "state": "opened"}]
+[{"iid": 2, "web_url": "https://gitlab.com/a/b/-/merge_requests/2", "state": "closed"}][]"#;
+        let mrs: Vec<_> = GlabCli::parse_json_pages(raw)
+            .unwrap()
+            .iter()
+            .map(|v| GlabCli::extract_mr_info(v).unwrap().number)
+            .collect();
+        assert_eq!(mrs, [1, 2]);
+        assert!(GlabCli::parse_json_pages("").unwrap().is_empty());
+        assert!(GlabCli::parse_json_pages("{}").is_err());
+    }
+
+    #[test]
+    fn encodes_nested_project_paths() {
+        let repo = GitHubRepoInfo {
+            owner: "group/subgroup".to_string(),
+            repo_name: "app".to_string(),
+        };
+        assert_eq!(project_id(&repo), "group%2Fsubgroup%2Fapp");
+    }
+}
@@ -11,6 +11,7 @@ pub mod filesystem;
 pub mod filesystem_watcher;
 pub mod git;
 pub mod github;
+pub mod gitlab;
 pub mod image;
 pub mod notification;
 pub mod oauth_credentials;
@@ -20,5 +21,6 @@ pub mod queued_message;
 pub mod remote_client;
 pub mod repo;
 pub mod share;
+pub mod vcs;
 pub mod workspace_manager;
 pub mod worktree_manager;
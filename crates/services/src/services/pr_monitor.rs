@@ -3,7 +3,7 @@ use std::time::Duration;
 use db::{
     DBService,
     models::{
-        merge::{Merge, MergeStatus, PrMerge},
+        merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
         task::{Task, TaskStatus},
         workspace::{Workspace, WorkspaceError},
     },
@@ -82,8 +82,17 @@ impl PrMonitorService {
 
         info!("Checking {} open PRs", open_prs.len());
 
-        for pr_merge in open_prs {
-            if let Err(e) = self.check_pr_status(&pr_merge).await {
+        // GitHubService now uses gh CLI, no token needed
+        let github_service = GitHubService::new()?;
+        let urls: Vec<String> = open_prs.iter().map(|pr| pr.pr_info.url.clone()).collect();
+        let statuses = github_service.update_pr_statuses(&urls).await;
+
+        for (pr_merge, pr_status) in open_prs.iter().zip(statuses) {
+            let result = match pr_status {
+                Ok(pr_status) => self.apply_pr_status(pr_merge, pr_status).await,
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = result {
                 error!(
                     "Error checking PR #{} for workspace {}: {}",
                     pr_merge.pr_info.number, pr_merge.workspace_id, e
@@ -93,15 +102,12 @@ impl PrMonitorService {
         Ok(())
     }
 
-    /// Check the status of a specific PR
-    async fn check_pr_status(&self, pr_merge: &PrMerge) -> Result<(), PrMonitorError> {
-        // GitHubService now uses gh CLI, no token needed
-        let github_service = GitHubService::new()?;
-
-        let pr_status = github_service
-            .update_pr_status(&pr_merge.pr_info.url)
-            .await?;
-
+    /// Record the latest status of a specific PR
+    async fn apply_pr_status(
+        &self,
+        pr_merge: &PrMerge,
+        pr_status: PullRequestInfo,
+    ) -> Result<(), PrMonitorError> {
         debug!(
             "PR #{} status: {:?} (was open)",
             pr_merge.pr_info.number, pr_status.status
@@ -38,6 +38,8 @@ pub enum ProjectServiceError {
     PathNotDirectory(PathBuf),
     #[error("Path is not a git repository: {0}")]
     NotGitRepository(PathBuf),
+    #[error("Repository has no commits yet, commit something before adding it: {0}")]
+    RepoWithoutCommits(PathBuf),
     #[error("Duplicate git repository path")]
     DuplicateGitRepoPath,
     #[error("Duplicate repository name in project")]
@@ -58,6 +60,7 @@ impl From<RepoError> for ProjectServiceError {
             RepoError::PathNotFound(p) => Self::PathNotFound(p),
             RepoError::PathNotDirectory(p) => Self::PathNotDirectory(p),
             RepoError::NotGitRepository(p) => Self::NotGitRepository(p),
+            RepoError::NoCommits(p) => Self::RepoWithoutCommits(p),
             RepoError::Io(e) => Self::Io(e),
             RepoError::Database(e) => Self::Database(e),
             _ => Self::RepositoryNotFound,
@@ -132,6 +135,7 @@ impl ProjectService {
                     dev_script: None,
                     dev_script_working_dir: None,
                     default_agent_working_dir: Some(repo.name),
+                    disable_cleanup: None,
                 },
             )
             .await?;
@@ -210,7 +214,7 @@ impl ProjectService {
             .await?
             .len();
 
-        let repository = ProjectRepo::add_repo_to_project(
+        let (repository, warning) = ProjectRepo::add_repo_to_project(
             pool,
             project_id,
             &path.to_string_lossy(),
@@ -227,6 +231,15 @@ impl ProjectService {
             _ => ProjectServiceError::RepositoryNotFound,
         })?;
 
+        if let Some(warning) = warning {
+            tracing::warn!(
+                "Repository {} added to project {}: {}",
+                payload.git_repo_path,
+                project_id,
+                warning
+            );
+        }
+
         // If project just went from 1 to 2 repos, clear default_agent_working_dir
         if repo_count_before == 1 {
             Project::clear_default_agent_working_dir(pool, project_id).await?;
@@ -242,6 +255,59 @@ impl ProjectService {
         Ok(repository)
     }
 
+    /// Add several repositories at once. Every path is validated before anything is written,
+    /// so an invalid one, named in the error, leaves the project unchanged. Repositories
+    /// already in the project are skipped; the ones actually added are returned.
+    pub async fn add_repositories(
+        &self,
+        pool: &SqlitePool,
+        repo_service: &RepoService,
+        project_id: Uuid,
+        payload: &[CreateProjectRepo],
+    ) -> Result<Vec<Repo>> {
+        let mut seen_names = HashSet::new();
+        let mut normalized_repos = Vec::new();
+        for repo in payload {
+            let path = repo_service.normalize_path(&repo.git_repo_path)?;
+            repo_service.validate_git_repo_path(&path)?;
+            if !seen_names.insert(repo.display_name.clone()) {
+                return Err(ProjectServiceError::DuplicateRepositoryName);
+            }
+            normalized_repos.push(CreateProjectRepo {
+                display_name: repo.display_name.clone(),
+                git_repo_path: path.to_string_lossy().to_string(),
+            });
+        }
+
+        let repo_count_before = ProjectRepo::find_by_project_id(pool, project_id)
+            .await?
+            .len();
+
+        let added = ProjectRepo::add_repos_to_project(pool, project_id, &normalized_repos)
+            .await
+            .map_err(|e| match e {
+                db::models::project_repo::ProjectRepoError::Database(e) => {
+                    ProjectServiceError::Database(e)
+                }
+                _ => ProjectServiceError::RepositoryNotFound,
+            })?;
+
+        // Same as adding one at a time: a single-repo project that gains repos loses its
+        // default agent working dir
+        if repo_count_before == 1 && !added.is_empty() {
+            Project::clear_default_agent_working_dir(pool, project_id).await?;
+        }
+
+        tracing::info!(
+            "Added {} of {} repositories to project {}",
+            added.len(),
+            payload.len(),
+            project_id
+        );
+
+        Ok(added)
+    }
+
     pub async fn delete_repository(
         &self,
         pool: &SqlitePool,
@@ -20,6 +20,8 @@ pub enum RepoError {
     PathNotDirectory(PathBuf),
     #[error("Path is not a git repository: {0}")]
     NotGitRepository(PathBuf),
+    #[error("Repository has no commits yet, commit something before adding it: {0}")]
+    NoCommits(PathBuf),
     #[error("Repository not found")]
     NotFound,
     #[error("Directory already exists: {0}")]
@@ -40,6 +42,8 @@ impl RepoService {
         Self
     }
 
+    /// Check that `path` is a git repository with at least one commit, since worktrees can't
+    /// be created from an empty repository
     pub fn validate_git_repo_path(&self, path: &Path) -> Result<()> {
         if !path.exists() {
             return Err(RepoError::PathNotFound(path.to_path_buf()));
@@ -49,11 +53,11 @@ impl RepoService {
             return Err(RepoError::PathNotDirectory(path.to_path_buf()));
         }
 
-        if !path.join(".git").exists() {
-            return Err(RepoError::NotGitRepository(path.to_path_buf()));
+        match GitService::new().has_commits(path) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(RepoError::NoCommits(path.to_path_buf())),
+            Err(_) => Err(RepoError::NotGitRepository(path.to_path_buf())),
         }
-
-        Ok(())
     }
 
     pub fn normalize_path(&self, path: &str) -> std::io::Result<PathBuf> {
@@ -0,0 +1,154 @@
+//! Provider-agnostic pull/merge request operations shared by the GitHub and GitLab services.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use backon::{ExponentialBuilder, Retryable};
+use chrono::{DateTime, Utc};
+use db::models::merge::PullRequestInfo;
+
+use crate::services::github::{CreatePrRequest, GitHubRepoInfo, PrState, UnifiedPrComment};
+
+/// Pull request operations a hosting provider supports. GitLab merge requests are
+/// represented with the same types as GitHub pull requests; `repo_info.owner` holds the
+/// project namespace.
+#[async_trait]
+pub trait VcsProvider: Send + Sync {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    async fn create_pr(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        request: &CreatePrRequest,
+    ) -> Result<PullRequestInfo, Self::Error>;
+
+    async fn update_pr_status(&self, pr_url: &str) -> Result<PullRequestInfo, Self::Error>;
+
+    async fn list_all_prs_for_branch(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        branch_name: &str,
+        states: &[PrState],
+    ) -> Result<Vec<PullRequestInfo>, Self::Error>;
+
+    async fn get_pr_comments(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<UnifiedPrComment>, Self::Error>;
+}
+
+/// Errors that can tell whether repeating the call might succeed
+pub trait RetryableError: std::fmt::Display {
+    fn should_retry(&self) -> bool;
+
+    /// How long the provider asked us to wait before trying again, e.g. after a rate limit
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Longest provider-requested wait a retry will sit through; beyond it the error is returned
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+/// Retry a provider call with exponential backoff, skipping errors that won't resolve on retry
+pub async fn with_retry<T, E, F, Fut>(op: F) -> Result<T, E>
+where
+    E: RetryableError,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    op.retry(
+        &ExponentialBuilder::default()
+            .with_min_delay(Duration::from_secs(1))
+            .with_max_delay(Duration::from_secs(30))
+            .with_max_times(3)
+            .with_jitter(),
+    )
+    .when(|e: &E| e.should_retry())
+    .adjust(|e: &E, dur: Option<Duration>| match e.retry_after() {
+        Some(after) if after > MAX_RETRY_AFTER => None,
+        Some(after) => dur.map(|dur| dur.max(after)),
+        None => dur,
+    })
+    .notify(|err: &E, dur: Duration| {
+        tracing::warn!(
+            "VCS provider call failed, retrying after {:.2}s: {}",
+            dur.as_secs_f64(),
+            err
+        );
+    })
+    .await
+}
+
+/// What a failed CLI call's stderr says went wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CliFailure {
+    /// The PR or MR can't be merged as it stands (HTTP 405)
+    NotMergeable,
+    /// The token lacks access, or the action isn't allowed for this user (HTTP 403)
+    Forbidden,
+    /// The repository or resource doesn't exist or isn't visible (HTTP 404)
+    NotFound,
+    Other,
+}
+
+pub fn classify_cli_failure(stderr: &str) -> CliFailure {
+    let lower = stderr.to_ascii_lowercase();
+    // Status codes only count as `HTTP 40x` (also inside `(HTTP 40x)`) or next to their reason
+    // phrase, so that a PR or issue number like `#404` doesn't classify the failure
+    if lower.contains("http 405")
+        || lower.contains("method not allowed")
+        || lower.contains("not mergeable")
+    {
+        CliFailure::NotMergeable
+    } else if lower.contains("http 403")
+        || lower.contains("forbidden")
+        || lower.contains("approve your own")
+    {
+        CliFailure::Forbidden
+    } else if lower.contains("http 404") || lower.contains("not found") {
+        CliFailure::NotFound
+    } else {
+        CliFailure::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_cli_failures() {
+        assert_eq!(
+            classify_cli_failure("GraphQL: Pull Request is not mergeable"),
+            CliFailure::NotMergeable
+        );
+        assert_eq!(
+            classify_cli_failure("POST .../merge: 405 Method Not Allowed"),
+            CliFailure::NotMergeable
+        );
+        assert_eq!(
+            classify_cli_failure("HTTP 403: Resource not accessible"),
+            CliFailure::Forbidden
+        );
+        assert_eq!(
+            classify_cli_failure("404 Project Not Found"),
+            CliFailure::NotFound
+        );
+        assert_eq!(
+            classify_cli_failure("GraphQL: Could not resolve to a PullRequest (HTTP 404)"),
+            CliFailure::NotFound
+        );
+        assert_eq!(classify_cli_failure("timeout"), CliFailure::Other);
+        assert_eq!(
+            classify_cli_failure("failed to merge PR #405: merge conflict"),
+            CliFailure::Other
+        );
+        assert_eq!(
+            classify_cli_failure("checks failing on !403 (pipeline 404)"),
+            CliFailure::Other
+        );
+    }
+}
@@ -514,6 +514,58 @@ impl WorktreeManager {
         .map_err(|e| WorktreeError::TaskJoin(format!("{e}")))?
     }
 
+    /// Append `patterns` (one per line) to the `info/exclude` git reads for the worktree at
+    /// `worktree_path`, skipping lines already present. Linked worktrees share `info/exclude`
+    /// with their main repository, so the patterns apply there too; the tracked `.gitignore`
+    /// is never touched.
+    pub async fn write_worktree_excludes(
+        worktree_path: &Path,
+        patterns: &str,
+    ) -> Result<(), WorktreeError> {
+        let worktree_path = worktree_path.to_path_buf();
+        let patterns = patterns.to_string();
+        tokio::task::spawn_blocking(move || {
+            let repo = Repository::open(&worktree_path)?;
+            let exclude_path = repo.commondir().join("info").join("exclude");
+            let existing = match fs::read_to_string(&exclude_path) {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(e.into()),
+            };
+
+            let mut additions = String::new();
+            for line in patterns.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                let present = existing.lines().any(|l| l.trim() == line)
+                    || additions.lines().any(|l| l == line);
+                if !present {
+                    additions.push_str(line);
+                    additions.push('\n');
+                }
+            }
+            if additions.is_empty() {
+                return Ok(());
+            }
+
+            if let Some(parent) = exclude_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut contents = existing;
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                contents.push('\n');
+            }
+            contents.push_str(&additions);
+            fs::write(&exclude_path, contents)?;
+            debug!(
+                "Added worktree excludes for {} to {}",
+                worktree_path.display(),
+                exclude_path.display()
+            );
+            Ok(())
+        })
+        .await
+        .map_err(|e| WorktreeError::TaskJoin(format!("{e}")))?
+    }
+
     /// Get the base directory for vibe-kanban worktrees
     pub fn get_worktree_base_dir() -> std::path::PathBuf {
         utils::path::get_vibe_kanban_temp_dir().join("worktrees")
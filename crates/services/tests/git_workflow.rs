@@ -5,7 +5,10 @@ use std::{
 };
 
 use git2::{Repository, build::CheckoutBuilder};
-use services::services::git::{DiffTarget, GitCli, GitService};
+use services::services::{
+    git::{DiffTarget, GitCli, GitService},
+    worktree_manager::WorktreeManager,
+};
 use tempfile::TempDir;
 use utils::diff::DiffChangeKind;
 
@@ -228,6 +231,50 @@ fn initialize_and_default_branch_and_head_info() {
     assert!(!head.oid.is_empty());
 }
 
+#[test]
+fn has_commits_rejects_unborn_and_non_repos() {
+    let td = TempDir::new().unwrap();
+    let s = GitService::new();
+
+    let empty = td.path().join("empty");
+    Repository::init(&empty).unwrap();
+    assert!(!s.has_commits(&empty).unwrap());
+
+    let plain = td.path().join("plain");
+    fs::create_dir_all(&plain).unwrap();
+    assert!(s.has_commits(&plain).is_err());
+
+    let repo_path = init_repo_main(&td);
+    assert!(s.has_commits(&repo_path).unwrap());
+}
+
+#[test]
+fn default_branch_follows_remote_head() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+
+    // No remote: falls back to the current branch
+    assert_eq!(s.get_default_branch(&repo_path).unwrap(), "main");
+
+    let repo = Repository::open(&repo_path).unwrap();
+    repo.remote("origin", "https://example.com/repo.git")
+        .unwrap();
+    let head = repo.head().unwrap().target().unwrap();
+    repo.reference("refs/remotes/origin/develop", head, true, "test")
+        .unwrap();
+    repo.reference_symbolic(
+        "refs/remotes/origin/HEAD",
+        "refs/remotes/origin/develop",
+        true,
+        "test",
+    )
+    .unwrap();
+
+    // Still checked out on main, but the remote default wins
+    assert_eq!(s.get_default_branch(&repo_path).unwrap(), "develop");
+}
+
 #[test]
 fn commit_and_is_worktree_clean() {
     let td = TempDir::new().unwrap();
@@ -520,3 +567,49 @@ fn squash_merge_libgit2_sets_author_without_user() {
         assert_eq!(email.as_deref(), Some("noreply@vibekanban.com"));
     }
 }
+
+#[tokio::test]
+async fn worktree_excludes_hide_scratch_files() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let worktree_path = td.path().join("wt");
+    let git = GitCli::new();
+    git.git(
+        &repo_path,
+        [
+            "worktree",
+            "add",
+            "-b",
+            "agent",
+            worktree_path.to_str().unwrap(),
+        ],
+    )
+    .unwrap();
+
+    WorktreeManager::write_worktree_excludes(&worktree_path, "scratch/\n*.tmp\n")
+        .await
+        .unwrap();
+    // Writing again does not duplicate patterns
+    WorktreeManager::write_worktree_excludes(&worktree_path, "*.tmp\n")
+        .await
+        .unwrap();
+
+    let common_dir = Repository::open(&worktree_path)
+        .unwrap()
+        .commondir()
+        .to_path_buf();
+    let exclude = fs::read_to_string(common_dir.join("info").join("exclude")).unwrap();
+    assert_eq!(exclude.matches("*.tmp").count(), 1);
+    assert!(exclude.contains("scratch/"));
+
+    write_file(&worktree_path, "scratch/notes.md", "notes\n");
+    write_file(&worktree_path, "debug.tmp", "x\n");
+    let status = git
+        .git(
+            &worktree_path,
+            ["status", "--porcelain", "--untracked-files=all"],
+        )
+        .unwrap();
+    assert!(status.trim().is_empty(), "unexpected status: {status}");
+    assert!(!worktree_path.join(".gitignore").exists());
+}
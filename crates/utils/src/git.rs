@@ -10,6 +10,10 @@ pub fn is_valid_branch_prefix(prefix: &str) -> bool {
     git2::Branch::name_is_valid(&format!("{prefix}/x")).unwrap_or_default()
 }
 
+pub fn is_valid_branch_name(name: &str) -> bool {
+    git2::Branch::name_is_valid(name).unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,4 +48,13 @@ mod tests {
         assert!(!is_valid_branch_prefix("foo/"));
         assert!(!is_valid_branch_prefix(".foo"));
     }
+
+    #[test]
+    fn test_branch_names() {
+        assert!(is_valid_branch_name("main"));
+        assert!(is_valid_branch_name("release/1.2"));
+        assert!(!is_valid_branch_name("foo..bar"));
+        assert!(!is_valid_branch_name("foo bar"));
+        assert!(!is_valid_branch_name(""));
+    }
 }
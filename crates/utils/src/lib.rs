@@ -13,6 +13,7 @@ pub mod log_msg;
 pub mod msg_store;
 pub mod path;
 pub mod port_file;
+pub mod process;
 pub mod response;
 pub mod sentry;
 pub mod shell;
@@ -0,0 +1,56 @@
+#[cfg(unix)]
+use std::time::Duration;
+
+use command_group::AsyncGroupChild;
+#[cfg(unix)]
+use nix::{
+    sys::signal::{Signal, killpg},
+    unistd::{Pid, getpgid},
+};
+
+/// How long to wait for the group to exit after each signal before escalating
+#[cfg(unix)]
+const KILL_GRACE: Duration = Duration::from_secs(2);
+
+/// Stop `child` together with every process it started. On unix the whole process group gets
+/// SIGINT, SIGTERM and finally SIGKILL; on Windows the tree is killed with `taskkill /T`.
+pub async fn kill_process_group(child: &mut AsyncGroupChild) -> std::io::Result<()> {
+    // hit the whole process group, not just the leader
+    #[cfg(unix)]
+    {
+        if let Some(pid) = child.inner().id() {
+            let pgid = getpgid(Some(Pid::from_raw(pid as i32))).map_err(std::io::Error::other)?;
+
+            for sig in [Signal::SIGINT, Signal::SIGTERM, Signal::SIGKILL] {
+                if let Err(e) = killpg(pgid, sig) {
+                    tracing::warn!(
+                        "Failed to send signal {:?} to process group {}: {}",
+                        sig,
+                        pgid,
+                        e
+                    );
+                }
+                if tokio::time::timeout(KILL_GRACE, child.wait()).await.is_ok() {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        if let Some(pid) = child.inner().id() {
+            let status = tokio::process::Command::new("taskkill")
+                .args(["/T", "/F", "/PID", &pid.to_string()])
+                .status()
+                .await;
+            if let Err(e) = status {
+                tracing::warn!("Failed to run taskkill for process {pid}: {e}");
+            }
+        }
+    }
+
+    let _ = child.kill().await;
+    let _ = child.wait().await;
+    Ok(())
+}
@@ -1,13 +1,16 @@
 import { Check, AlertCircle, Loader2 } from 'lucide-react';
 import { useTranslation } from 'react-i18next';
+import { Button } from '@/components/ui/button';
 import type { AgentAvailabilityState } from '@/hooks/useAgentAvailability';
 
 interface AgentAvailabilityIndicatorProps {
   availability: AgentAvailabilityState;
+  onTestConnection?: () => void;
 }
 
 export function AgentAvailabilityIndicator({
   availability,
+  onTestConnection,
 }: AgentAvailabilityIndicatorProps) {
   const { t } = useTranslation('settings');
 
@@ -62,6 +65,29 @@ export function AgentAvailabilityIndicator({
           </p>
         </>
       )}
+      {availability.status === 'broken' && (
+        <>
+          <div className="flex items-center gap-2">
+            <AlertCircle className="h-4 w-4 text-destructive" />
+            <span className="text-destructive">
+              {t('settings.agents.availability.broken')}
+            </span>
+          </div>
+          <p className="text-xs text-muted-foreground pl-6 break-all">
+            {availability.reason}
+          </p>
+        </>
+      )}
+      {onTestConnection && availability.status !== 'checking' && (
+        <Button
+          variant="outline"
+          size="sm"
+          className="self-start"
+          onClick={onTestConnection}
+        >
+          {t('settings.agents.availability.testConnection')}
+        </Button>
+      )}
     </div>
   );
 }
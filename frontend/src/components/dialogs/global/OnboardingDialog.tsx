@@ -55,7 +55,9 @@ const OnboardingDialogImpl = NiceModal.create<NoProps>(() => {
   const [customCommand, setCustomCommand] = useState<string>('');
 
   const editorAvailability = useEditorAvailability(editorType);
-  const agentAvailability = useAgentAvailability(profile.executor);
+  const { availability: agentAvailability } = useAgentAvailability(
+    profile.executor
+  );
 
   const handleComplete = () => {
     modal.resolve({
@@ -78,6 +78,7 @@ const CreateAttemptDialogImpl = NiceModal.create<CreateAttemptDialogProps>(
       reset: resetBranchSelection,
     } = useRepoBranchSelection({
       repos: projectRepos,
+      projectId,
       initialBranch: parentAttempt?.branch,
       enabled: modal.visible && projectRepos.length > 0,
     });
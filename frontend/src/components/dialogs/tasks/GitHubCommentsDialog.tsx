@@ -27,9 +27,9 @@ export interface GitHubCommentsDialogResult {
 }
 
 function getCommentId(comment: UnifiedPrComment): string {
-  return comment.comment_type === 'general'
-    ? comment.id
-    : comment.id.toString();
+  return comment.comment_type === 'review'
+    ? comment.id.toString()
+    : comment.id;
 }
 
 const GitHubCommentsDialogImpl = NiceModal.create<GitHubCommentsDialogProps>(
@@ -165,7 +165,11 @@ const GitHubCommentsDialogImpl = NiceModal.create<GitHubCommentsDialogProps>(
                             body={comment.body}
                             createdAt={comment.created_at}
                             url={comment.url}
-                            commentType={comment.comment_type}
+                            commentType={
+                              comment.comment_type === 'review'
+                                ? 'review'
+                                : 'general'
+                            }
                             path={
                               comment.comment_type === 'review'
                                 ? comment.path
@@ -114,6 +114,7 @@ const TaskFormDialogImpl = NiceModal.create<TaskFormDialogProps>((props) => {
   const { configs: repoBranchConfigs, isLoading: branchesLoading } =
     useRepoBranchSelection({
       repos: projectRepos,
+      projectId,
       initialBranch,
       enabled: modal.visible && projectRepos.length > 0,
     });
@@ -543,10 +543,12 @@ export function TaskFollowUpSection({
       const markdownBlocks = result.comments.map((comment) => {
         const payload: NormalizedComment = {
           id:
-            comment.comment_type === 'general'
-              ? comment.id
-              : comment.id.toString(),
-          comment_type: comment.comment_type,
+            comment.comment_type === 'review'
+              ? comment.id.toString()
+              : comment.id,
+          // Review summaries carry no code location, so they read like general comments
+          comment_type:
+            comment.comment_type === 'review' ? 'review' : 'general',
           author: comment.author,
           body: comment.body,
           created_at: comment.created_at,
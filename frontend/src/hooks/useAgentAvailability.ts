@@ -1,5 +1,5 @@
-import { useEffect, useState } from 'react';
-import { BaseCodingAgent } from 'shared/types';
+import { useCallback, useEffect, useState } from 'react';
+import { AvailabilityInfo, BaseCodingAgent } from 'shared/types';
 import { configApi } from '../lib/api';
 
 export type AgentAvailabilityState =
@@ -7,11 +7,29 @@ export type AgentAvailabilityState =
   | { status: 'login_detected' }
   | { status: 'installation_found' }
   | { status: 'not_found' }
+  | { status: 'broken'; reason: string }
   | null;
 
+// Map backend enum to frontend state
+function toAvailabilityState(info: AvailabilityInfo): AgentAvailabilityState {
+  switch (info.type) {
+    case 'LOGIN_DETECTED':
+      return { status: 'login_detected' };
+    case 'INSTALLATION_FOUND':
+      return { status: 'installation_found' };
+    case 'NOT_FOUND':
+      return { status: 'not_found' };
+    case 'BROKEN':
+      return { status: 'broken', reason: info.reason };
+  }
+}
+
 export function useAgentAvailability(
   agent: BaseCodingAgent | null | undefined
-): AgentAvailabilityState {
+): {
+  availability: AgentAvailabilityState;
+  testConnection: () => Promise<void>;
+} {
   const [availability, setAvailability] =
     useState<AgentAvailabilityState>(null);
 
@@ -25,19 +43,7 @@ export function useAgentAvailability(
       setAvailability({ status: 'checking' });
       try {
         const info = await configApi.checkAgentAvailability(agent);
-
-        // Map backend enum to frontend state
-        switch (info.type) {
-          case 'LOGIN_DETECTED':
-            setAvailability({ status: 'login_detected' });
-            break;
-          case 'INSTALLATION_FOUND':
-            setAvailability({ status: 'installation_found' });
-            break;
-          case 'NOT_FOUND':
-            setAvailability({ status: 'not_found' });
-            break;
-        }
+        setAvailability(toAvailabilityState(info));
       } catch (error) {
         console.error('Failed to check agent availability:', error);
         setAvailability(null);
@@ -47,5 +53,18 @@ export function useAgentAvailability(
     checkAvailability();
   }, [agent]);
 
-  return availability;
+  // Slower check that actually starts the agent
+  const testConnection = useCallback(async () => {
+    if (!agent) return;
+    setAvailability({ status: 'checking' });
+    try {
+      const info = await configApi.probeAgentAvailability(agent);
+      setAvailability(toAvailabilityState(info));
+    } catch (error) {
+      console.error('Failed to probe agent availability:', error);
+      setAvailability(null);
+    }
+  }, [agent]);
+
+  return { availability, testConnection };
 }
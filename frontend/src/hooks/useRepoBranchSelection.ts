@@ -1,6 +1,6 @@
 import { useState, useMemo, useCallback } from 'react';
 import { useQueries } from '@tanstack/react-query';
-import { repoApi } from '@/lib/api';
+import { projectsApi, repoApi } from '@/lib/api';
 import { repoBranchKeys } from './useRepoBranches';
 import type { GitBranch, Repo } from 'shared/types';
 
@@ -13,6 +13,8 @@ export type RepoBranchConfig = {
 
 type UseRepoBranchSelectionOptions = {
   repos: Repo[];
+  /** When set, each repo's configured `base_branch` is the default pick */
+  projectId?: string;
   initialBranch?: string | null;
   enabled?: boolean;
 };
@@ -30,6 +32,7 @@ type UseRepoBranchSelectionReturn = {
 
 export function useRepoBranchSelection({
   repos,
+  projectId,
   initialBranch,
   enabled = true,
 }: UseRepoBranchSelectionOptions): UseRepoBranchSelectionReturn {
@@ -46,17 +49,30 @@ export function useRepoBranchSelection({
     })),
   });
 
-  const isLoadingBranches = queries.some((q) => q.isLoading);
+  const projectRepoQueries = useQueries({
+    queries: repos.map((repo) => ({
+      queryKey: ['projectRepository', projectId, repo.id],
+      queryFn: () => projectsApi.getRepository(projectId!, repo.id),
+      enabled: enabled && !!projectId,
+    })),
+  });
+
+  const isLoadingBranches =
+    queries.some((q) => q.isLoading) ||
+    projectRepoQueries.some((q) => q.isLoading);
 
   const configs = useMemo((): RepoBranchConfig[] => {
     return repos.map((repo, i) => {
       const branches = queries[i]?.data ?? [];
+      const baseBranch = projectRepoQueries[i]?.data?.base_branch;
 
       let targetBranch: string | null = userOverrides[repo.id] ?? null;
 
       if (targetBranch === null) {
         if (initialBranch && branches.some((b) => b.name === initialBranch)) {
           targetBranch = initialBranch;
+        } else if (baseBranch && branches.some((b) => b.name === baseBranch)) {
+          targetBranch = baseBranch;
         } else {
           const currentBranch = branches.find((b) => b.is_current);
           targetBranch = currentBranch?.name ?? branches[0]?.name ?? null;
@@ -70,7 +86,7 @@ export function useRepoBranchSelection({
         branches,
       };
     });
-  }, [repos, queries, userOverrides, initialBranch]);
+  }, [repos, queries, projectRepoQueries, userOverrides, initialBranch]);
 
   const setRepoBranch = useCallback((repoId: string, branch: string) => {
     setUserOverrides((prev) => ({
@@ -21,6 +21,8 @@ import {
   Repo,
   RepoWithTargetBranch,
   CreateProject,
+  AddProjectRepositoriesRequest,
+  ReorderProjectRepositoriesRequest,
   CreateProjectRepo,
   UpdateProjectRepo,
   SearchResult,
@@ -89,6 +91,7 @@ import {
   AbortConflictsRequest,
   Session,
   Workspace,
+  TurnResult,
 } from 'shared/types';
 import type { WorkspaceWithSession } from '@/types/attempt';
 import { createWorkspaceWithSession } from '@/types/attempt';
@@ -346,6 +349,34 @@ export const projectsApi = {
     return handleApiResponse<Repo>(response);
   },
 
+  addRepositories: async (
+    projectId: string,
+    data: AddProjectRepositoriesRequest
+  ): Promise<Repo[]> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/repositories/bulk`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<Repo[]>(response);
+  },
+
+  reorderRepositories: async (
+    projectId: string,
+    data: ReorderProjectRepositoriesRequest
+  ): Promise<Repo[]> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/repositories/order`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<Repo[]>(response);
+  },
+
   deleteRepository: async (
     projectId: string,
     repoId: string
@@ -746,10 +777,15 @@ export const attemptsApi = {
 
   getPrComments: async (
     attemptId: string,
-    repoId: string
+    repoId: string,
+    since?: string
   ): Promise<PrCommentsResponse> => {
+    const params = new URLSearchParams({ repo_id: repoId });
+    if (since) {
+      params.set('since', since);
+    }
     const response = await makeRequest(
-      `/api/task-attempts/${attemptId}/pr/comments?repo_id=${encodeURIComponent(repoId)}`
+      `/api/task-attempts/${attemptId}/pr/comments?${params.toString()}`
     );
     return handleApiResponse<PrCommentsResponse>(response);
   },
@@ -771,6 +807,13 @@ export const executionProcessesApi = {
     return handleApiResponse<ExecutionProcessRepoState[]>(response);
   },
 
+  getTurnResult: async (processId: string): Promise<TurnResult> => {
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/turn-result`
+    );
+    return handleApiResponse<TurnResult>(response);
+  },
+
   stopExecutionProcess: async (processId: string): Promise<void> => {
     const response = await makeRequest(
       `/api/execution-processes/${processId}/stop`,
@@ -860,6 +903,14 @@ export const configApi = {
     );
     return handleApiResponse<AvailabilityInfo>(response);
   },
+  probeAgentAvailability: async (
+    agent: BaseCodingAgent
+  ): Promise<AvailabilityInfo> => {
+    const response = await makeRequest(
+      `/api/agents/probe-availability?executor=${encodeURIComponent(agent)}`
+    );
+    return handleApiResponse<AvailabilityInfo>(response);
+  },
 };
 
 // Task Tags APIs (all tags are global)
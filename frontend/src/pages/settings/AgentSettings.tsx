@@ -82,7 +82,10 @@ export function AgentSettings() {
   const [executorError, setExecutorError] = useState<string | null>(null);
 
   // Check agent availability when draft executor changes
-  const agentAvailability = useAgentAvailability(executorDraft?.executor);
+  const {
+    availability: agentAvailability,
+    testConnection: testAgentConnection,
+  } = useAgentAvailability(executorDraft?.executor);
 
   // Sync server state to local state when not dirty
   useEffect(() => {
@@ -579,7 +582,10 @@ export function AgentSettings() {
                 return null;
               })()}
             </div>
-            <AgentAvailabilityIndicator availability={agentAvailability} />
+            <AgentAvailabilityIndicator
+              availability={agentAvailability}
+              onTestConnection={testAgentConnection}
+            />
             <p className="text-sm text-muted-foreground">
               {t('settings.general.taskExecution.executor.helper')}
             </p>
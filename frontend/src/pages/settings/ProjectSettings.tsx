@@ -29,7 +29,7 @@ import { useScriptPlaceholders } from '@/hooks/useScriptPlaceholders';
 import { CopyFilesField } from '@/components/projects/CopyFilesField';
 import { AutoExpandingTextarea } from '@/components/ui/auto-expanding-textarea';
 import { RepoPickerDialog } from '@/components/dialogs/shared/RepoPickerDialog';
-import { projectsApi } from '@/lib/api';
+import { ApiError, projectsApi } from '@/lib/api';
 import { repoBranchKeys } from '@/hooks/useRepoBranches';
 import type { Project, ProjectRepo, Repo, UpdateProject } from 'shared/types';
 
@@ -38,13 +38,24 @@ interface ProjectFormState {
   dev_script: string;
   dev_script_working_dir: string;
   default_agent_working_dir: string;
+  disable_cleanup: boolean;
 }
 
 interface RepoScriptsFormState {
   setup_script: string;
-  parallel_setup_script: boolean;
+  setup_concurrency: number;
   cleanup_script: string;
   copy_files: string;
+  base_branch: string;
+  context_prompt: string;
+  auto_open_pr: boolean;
+  setup_retries: string;
+  bootstrap_script: string;
+  env_file: string;
+  setup_paths: string;
+  max_worktrees: string;
+  worktree_gitignore: string;
+  setup_requires_network: boolean;
 }
 
 function projectToFormState(project: Project): ProjectFormState {
@@ -53,17 +64,50 @@ function projectToFormState(project: Project): ProjectFormState {
     dev_script: project.dev_script ?? '',
     dev_script_working_dir: project.dev_script_working_dir ?? '',
     default_agent_working_dir: project.default_agent_working_dir ?? '',
+    disable_cleanup: project.disable_cleanup,
   };
 }
 
+// setup_paths is stored as a JSON array but edited as one glob per line
+function setupPathsToLines(setupPaths: string | null | undefined): string {
+  if (!setupPaths) return '';
+  try {
+    const globs: unknown = JSON.parse(setupPaths);
+    return Array.isArray(globs) ? globs.join('\n') : setupPaths;
+  } catch {
+    return setupPaths;
+  }
+}
+
+function linesToSetupPaths(lines: string): string | null {
+  const globs = lines
+    .split('\n')
+    .map((line) => line.trim())
+    .filter(Boolean);
+  return globs.length > 0 ? JSON.stringify(globs) : null;
+}
+
 function projectRepoToScriptsFormState(
   projectRepo: ProjectRepo | null
 ): RepoScriptsFormState {
   return {
     setup_script: projectRepo?.setup_script ?? '',
-    parallel_setup_script: projectRepo?.parallel_setup_script ?? false,
+    setup_concurrency: projectRepo?.setup_concurrency ?? 1,
     cleanup_script: projectRepo?.cleanup_script ?? '',
     copy_files: projectRepo?.copy_files ?? '',
+    base_branch: projectRepo?.base_branch ?? '',
+    context_prompt: projectRepo?.context_prompt ?? '',
+    auto_open_pr: projectRepo?.auto_open_pr ?? true,
+    setup_retries: String(projectRepo?.setup_retries ?? 0),
+    bootstrap_script: projectRepo?.bootstrap_script ?? '',
+    env_file: projectRepo?.env_file ?? '',
+    setup_paths: setupPathsToLines(projectRepo?.setup_paths),
+    max_worktrees:
+      projectRepo?.max_worktrees != null
+        ? String(projectRepo.max_worktrees)
+        : '',
+    worktree_gitignore: projectRepo?.worktree_gitignore ?? '',
+    setup_requires_network: projectRepo?.setup_requires_network ?? false,
   };
 }
 
@@ -397,6 +441,7 @@ export function ProjectSettings() {
         dev_script_working_dir: draft.dev_script_working_dir.trim() || null,
         default_agent_working_dir:
           draft.default_agent_working_dir.trim() || null,
+        disable_cleanup: draft.disable_cleanup,
       };
 
       updateProject.mutate({
@@ -425,7 +470,22 @@ export function ProjectSettings() {
           setup_script: scriptsDraft.setup_script.trim() || null,
           cleanup_script: scriptsDraft.cleanup_script.trim() || null,
           copy_files: scriptsDraft.copy_files.trim() || null,
-          parallel_setup_script: scriptsDraft.parallel_setup_script,
+          setup_concurrency: scriptsDraft.setup_concurrency,
+          base_branch: scriptsDraft.base_branch.trim() || null,
+          context_prompt: scriptsDraft.context_prompt.trim() || null,
+          auto_open_pr: scriptsDraft.auto_open_pr,
+          setup_retries: Number(scriptsDraft.setup_retries) || 0,
+          version: selectedProjectRepo?.version ?? null,
+          bootstrap_script: scriptsDraft.bootstrap_script.trim() || null,
+          env_file: scriptsDraft.env_file.trim() || null,
+          setup_paths: linesToSetupPaths(scriptsDraft.setup_paths),
+          max_worktrees: Number(scriptsDraft.max_worktrees) || null,
+          worktree_gitignore: scriptsDraft.worktree_gitignore.trim() || null,
+          setup_requires_network: scriptsDraft.setup_requires_network,
+          env_vars: selectedProjectRepo?.env_vars ?? null,
+          setup_steps: selectedProjectRepo?.setup_steps ?? null,
+          setup_timeout_secs: selectedProjectRepo?.setup_timeout_secs ?? null,
+          dev_server_script: selectedProjectRepo?.dev_server_script ?? null,
         }
       );
       setSelectedProjectRepo(updatedRepo);
@@ -433,6 +493,13 @@ export function ProjectSettings() {
       setScriptsSuccess(true);
       setTimeout(() => setScriptsSuccess(false), 3000);
     } catch (err) {
+      if (err instanceof ApiError && err.status === 409) {
+        // Someone else saved first: pick up their version, keep our draft for review
+        const latest = await projectsApi
+          .getRepository(selectedProjectId, selectedScriptsRepoId)
+          .catch(() => null);
+        if (latest) setSelectedProjectRepo(latest);
+      }
       setScriptsError(
         err instanceof Error ? err.message : 'Failed to save scripts'
       );
@@ -630,6 +697,27 @@ export function ProjectSettings() {
                 </p>
               </div>
 
+              <div className="space-y-2">
+                <div className="flex items-center space-x-2">
+                  <Checkbox
+                    id="disable-cleanup"
+                    checked={draft.disable_cleanup}
+                    onCheckedChange={(checked) =>
+                      updateDraft({ disable_cleanup: checked === true })
+                    }
+                  />
+                  <Label
+                    htmlFor="disable-cleanup"
+                    className="text-sm font-normal cursor-pointer"
+                  >
+                    {t('settings.projects.general.disableCleanup.label')}
+                  </Label>
+                </div>
+                <p className="text-sm text-muted-foreground pl-6">
+                  {t('settings.projects.general.disableCleanup.helper')}
+                </p>
+              </div>
+
               {/* Save Button */}
               <div className="flex items-center justify-between pt-4 border-t">
                 {hasUnsavedProjectChanges ? (
@@ -834,10 +922,10 @@ export function ProjectSettings() {
                         <div className="flex items-center space-x-2 pt-2">
                           <Checkbox
                             id="parallel-setup-script"
-                            checked={scriptsDraft.parallel_setup_script}
+                            checked={scriptsDraft.setup_concurrency !== 1}
                             onCheckedChange={(checked) =>
                               updateScriptsDraft({
-                                parallel_setup_script: checked === true,
+                                setup_concurrency: checked === true ? 0 : 1,
                               })
                             }
                             disabled={!scriptsDraft.setup_script.trim()}
@@ -852,6 +940,74 @@ export function ProjectSettings() {
                         <p className="text-sm text-muted-foreground pl-6">
                           {t('settings.projects.scripts.setup.parallelHelper')}
                         </p>
+
+                        <div className="flex items-center space-x-2 pt-2">
+                          <Checkbox
+                            id="setup-requires-network"
+                            checked={scriptsDraft.setup_requires_network}
+                            onCheckedChange={(checked) =>
+                              updateScriptsDraft({
+                                setup_requires_network: checked === true,
+                              })
+                            }
+                            disabled={!scriptsDraft.setup_script.trim()}
+                          />
+                          <Label
+                            htmlFor="setup-requires-network"
+                            className="text-sm font-normal cursor-pointer"
+                          >
+                            {t('settings.projects.scripts.setupNetwork.label')}
+                          </Label>
+                        </div>
+                        <p className="text-sm text-muted-foreground pl-6">
+                          {t('settings.projects.scripts.setupNetwork.helper')}
+                        </p>
+
+                        <div className="space-y-2 pt-2">
+                          <Label htmlFor="setup-retries">
+                            {t('settings.projects.scripts.setupRetries.label')}
+                          </Label>
+                          <Input
+                            id="setup-retries"
+                            type="number"
+                            min={0}
+                            value={scriptsDraft.setup_retries}
+                            onChange={(e) =>
+                              updateScriptsDraft({
+                                setup_retries: e.target.value,
+                              })
+                            }
+                            disabled={!scriptsDraft.setup_script.trim()}
+                            className="w-24"
+                          />
+                          <p className="text-sm text-muted-foreground">
+                            {t('settings.projects.scripts.setupRetries.helper')}
+                          </p>
+                        </div>
+
+                        <div className="space-y-2 pt-2">
+                          <Label htmlFor="setup-paths">
+                            {t('settings.projects.scripts.setupPaths.label')}
+                          </Label>
+                          <AutoExpandingTextarea
+                            id="setup-paths"
+                            value={scriptsDraft.setup_paths}
+                            onChange={(e) =>
+                              updateScriptsDraft({
+                                setup_paths: e.target.value,
+                              })
+                            }
+                            disabled={!scriptsDraft.setup_script.trim()}
+                            placeholder={t(
+                              'settings.projects.scripts.setupPaths.placeholder'
+                            )}
+                            maxRows={8}
+                            className="w-full px-3 py-2 border border-input bg-background text-foreground rounded-md focus:outline-none focus:ring-2 focus:ring-ring font-mono"
+                          />
+                          <p className="text-sm text-muted-foreground">
+                            {t('settings.projects.scripts.setupPaths.helper')}
+                          </p>
+                        </div>
                       </div>
 
                       <div className="space-y-2">
@@ -875,6 +1031,102 @@ export function ProjectSettings() {
                         </p>
                       </div>
 
+                      <div className="space-y-2">
+                        <Label htmlFor="bootstrap-script">
+                          {t('settings.projects.scripts.bootstrap.label')}
+                        </Label>
+                        <AutoExpandingTextarea
+                          id="bootstrap-script"
+                          value={scriptsDraft.bootstrap_script}
+                          onChange={(e) =>
+                            updateScriptsDraft({
+                              bootstrap_script: e.target.value,
+                            })
+                          }
+                          placeholder={t(
+                            'settings.projects.scripts.bootstrap.placeholder'
+                          )}
+                          maxRows={12}
+                          className="w-full px-3 py-2 border border-input bg-background text-foreground rounded-md focus:outline-none focus:ring-2 focus:ring-ring font-mono"
+                        />
+                        <p className="text-sm text-muted-foreground">
+                          {t('settings.projects.scripts.bootstrap.helper')}
+                        </p>
+                      </div>
+
+                      <div className="space-y-2">
+                        <Label htmlFor="env-file">
+                          {t('settings.projects.scripts.envFile.label')}
+                        </Label>
+                        <Input
+                          id="env-file"
+                          value={scriptsDraft.env_file}
+                          onChange={(e) =>
+                            updateScriptsDraft({
+                              env_file: e.target.value,
+                            })
+                          }
+                          placeholder={t(
+                            'settings.projects.scripts.envFile.placeholder'
+                          )}
+                          className="font-mono"
+                        />
+                        <p className="text-sm text-muted-foreground">
+                          {t('settings.projects.scripts.envFile.helper')}
+                        </p>
+                      </div>
+
+                      <div className="space-y-2">
+                        <Label htmlFor="worktree-gitignore">
+                          {t(
+                            'settings.projects.scripts.worktreeGitignore.label'
+                          )}
+                        </Label>
+                        <AutoExpandingTextarea
+                          id="worktree-gitignore"
+                          value={scriptsDraft.worktree_gitignore}
+                          onChange={(e) =>
+                            updateScriptsDraft({
+                              worktree_gitignore: e.target.value,
+                            })
+                          }
+                          placeholder={t(
+                            'settings.projects.scripts.worktreeGitignore.placeholder'
+                          )}
+                          maxRows={8}
+                          className="w-full px-3 py-2 border border-input bg-background text-foreground rounded-md focus:outline-none focus:ring-2 focus:ring-ring font-mono"
+                        />
+                        <p className="text-sm text-muted-foreground">
+                          {t(
+                            'settings.projects.scripts.worktreeGitignore.helper'
+                          )}
+                        </p>
+                      </div>
+
+                      <div className="space-y-2">
+                        <Label htmlFor="max-worktrees">
+                          {t('settings.projects.scripts.maxWorktrees.label')}
+                        </Label>
+                        <Input
+                          id="max-worktrees"
+                          type="number"
+                          min={1}
+                          value={scriptsDraft.max_worktrees}
+                          onChange={(e) =>
+                            updateScriptsDraft({
+                              max_worktrees: e.target.value,
+                            })
+                          }
+                          placeholder={t(
+                            'settings.projects.scripts.maxWorktrees.placeholder'
+                          )}
+                          className="w-32"
+                        />
+                        <p className="text-sm text-muted-foreground">
+                          {t('settings.projects.scripts.maxWorktrees.helper')}
+                        </p>
+                      </div>
+
                       <div className="space-y-2">
                         <Label>
                           {t('settings.projects.scripts.copyFiles.label')}
@@ -891,6 +1143,71 @@ export function ProjectSettings() {
                         </p>
                       </div>
 
+                      <div className="space-y-2">
+                        <Label htmlFor="base-branch">
+                          {t('settings.projects.scripts.baseBranch.label')}
+                        </Label>
+                        <Input
+                          id="base-branch"
+                          value={scriptsDraft.base_branch}
+                          onChange={(e) =>
+                            updateScriptsDraft({ base_branch: e.target.value })
+                          }
+                          placeholder={t(
+                            'settings.projects.scripts.baseBranch.placeholder'
+                          )}
+                        />
+                        <p className="text-sm text-muted-foreground">
+                          {t('settings.projects.scripts.baseBranch.helper')}
+                        </p>
+                      </div>
+
+                      <div className="space-y-2">
+                        <Label htmlFor="context-prompt">
+                          {t('settings.projects.scripts.contextPrompt.label')}
+                        </Label>
+                        <AutoExpandingTextarea
+                          id="context-prompt"
+                          value={scriptsDraft.context_prompt}
+                          onChange={(e) =>
+                            updateScriptsDraft({
+                              context_prompt: e.target.value,
+                            })
+                          }
+                          placeholder={t(
+                            'settings.projects.scripts.contextPrompt.placeholder'
+                          )}
+                          maxRows={12}
+                          className="w-full px-3 py-2 border border-input bg-background text-foreground rounded-md focus:outline-none focus:ring-2 focus:ring-ring"
+                        />
+                        <p className="text-sm text-muted-foreground">
+                          {t('settings.projects.scripts.contextPrompt.helper')}
+                        </p>
+                      </div>
+
+                      <div className="space-y-2">
+                        <div className="flex items-center space-x-2">
+                          <Checkbox
+                            id="auto-open-pr"
+                            checked={scriptsDraft.auto_open_pr}
+                            onCheckedChange={(checked) =>
+                              updateScriptsDraft({
+                                auto_open_pr: checked === true,
+                              })
+                            }
+                          />
+                          <Label
+                            htmlFor="auto-open-pr"
+                            className="text-sm font-normal cursor-pointer"
+                          >
+                            {t('settings.projects.scripts.autoOpenPr.label')}
+                          </Label>
+                        </div>
+                        <p className="text-sm text-muted-foreground pl-6">
+                          {t('settings.projects.scripts.autoOpenPr.helper')}
+                        </p>
+                      </div>
+
                       {/* Scripts Save Buttons */}
                       <div className="flex items-center justify-between pt-4 border-t">
                         {hasUnsavedScriptsChanges ? (
@@ -12,11 +12,11 @@ export type SharedTask = { id: string, organization_id: string, project_id: stri
 
 export type UserData = { user_id: string, first_name: string | null, last_name: string | null, username: string | null, };
 
-export type Project = { id: string, name: string, dev_script: string | null, dev_script_working_dir: string | null, default_agent_working_dir: string | null, remote_project_id: string | null, created_at: Date, updated_at: Date, };
+export type Project = { id: string, name: string, dev_script: string | null, dev_script_working_dir: string | null, default_agent_working_dir: string | null, disable_cleanup: boolean, remote_project_id: string | null, created_at: Date, updated_at: Date, };
 
 export type CreateProject = { name: string, repositories: Array<CreateProjectRepo>, };
 
-export type UpdateProject = { name: string | null, dev_script: string | null, dev_script_working_dir: string | null, default_agent_working_dir: string | null, };
+export type UpdateProject = { name: string | null, dev_script: string | null, dev_script_working_dir: string | null, default_agent_working_dir: string | null, disable_cleanup: boolean | null, };
 
 export type SearchResult = { path: string, is_file: boolean, match_type: SearchMatchType, };
 
@@ -24,11 +24,69 @@ export type SearchMatchType = "FileName" | "DirectoryName" | "FullPath";
 
 export type Repo = { id: string, path: string, name: string, display_name: string, created_at: Date, updated_at: Date, };
 
-export type ProjectRepo = { id: string, project_id: string, repo_id: string, setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean, };
+export type ProjectRepo = { id: string, project_id: string, repo_id: string, setup_script: string | null, cleanup_script: string | null, copy_files: string | null, 
+/**
+ * How many setup scripts of the project's repos may run at once: 0 for no limit, 1 to
+ * run them one after another. The strictest limit among the repos being set up applies.
+ */
+setup_concurrency: number, base_branch: string | null, context_prompt: string | null, auto_open_pr: boolean, setup_retries: number | null, version: number, bootstrap_script: string | null, bootstrapped_at: string | null, 
+/**
+ * Repo-relative path of a `.env` file merged into the `ExecutionEnv` of this repo's
+ * setup and cleanup scripts only
+ */
+env_file: string | null, setup_paths: string | null, max_worktrees: number | null, setup_depends_on: string | null, worktree_gitignore: string | null, setup_requires_network: boolean, 
+/**
+ * JSON object of variables injected into the `ExecutionEnv` of this repo's setup and
+ * cleanup scripts only, overriding the env file
+ */
+env_vars: string | null, 
+/**
+ * JSON array of `{ name, command }` objects run in order; takes precedence over
+ * `setup_script`
+ */
+setup_steps: string | null, 
+/**
+ * Set when the repo is removed from the project; the row is kept so re-adding the repo
+ * restores its settings
+ */
+deleted_at: string | null, 
+/**
+ * Display and setup order within the project, see [`ProjectRepo::reorder`]
+ */
+position: number, 
+/**
+ * Seconds a setup script may run before its process group is killed, defaulting to
+ * [`DEFAULT_SETUP_TIMEOUT_SECS`]. The limit covers all retries. Parallel setups each
+ * run as their own process with their own timer, so one repo timing out never holds
+ * up the others; in a sequential chain a timeout fails the chain like any other error.
+ */
+setup_timeout_secs: number | null, 
+/**
+ * Long-running command such as `npm run dev`, started in the repo's worktree alongside
+ * the project's dev server and kept alive rather than expected to exit
+ */
+dev_server_script: string | null, };
 
 export type CreateProjectRepo = { display_name: string, git_repo_path: string, };
 
-export type UpdateProjectRepo = { setup_script: string | null, cleanup_script: string | null, copy_files: string | null, parallel_setup_script: boolean | null, };
+export type UpdateProjectRepo = { setup_script: string | null, cleanup_script: string | null, copy_files: string | null, 
+/**
+ * Also accepts the old `parallel_setup_script` flag: `true` means no limit, `false` serial
+ */
+setup_concurrency: number | null, base_branch: string | null, context_prompt: string | null, auto_open_pr: boolean | null, setup_retries: number | null, 
+/**
+ * Version the client loaded; the update is rejected if the row changed since
+ */
+version: number | null, bootstrap_script: string | null, env_file: string | null, setup_paths: string | null, max_worktrees: number | null, setup_depends_on: string | null, worktree_gitignore: string | null, setup_requires_network: boolean | null, env_vars: string | null, setup_steps: string | null, setup_timeout_secs: number | null, dev_server_script: string | null, };
+
+/**
+ * One command of a repo's setup, reported by name when it fails
+ */
+export type SetupStep = { 
+/**
+ * `None` for a plain `setup_script` run as a single step
+ */
+name: string | null, command: string, };
 
 export type WorkspaceRepo = { id: string, workspace_id: string, repo_id: string, target_branch: string, created_at: Date, updated_at: Date, };
 
@@ -96,7 +154,11 @@ export type PrMerge = { id: string, workspace_id: string, repo_id: string, creat
 
 export type MergeStatus = "open" | "merged" | "closed" | "unknown";
 
-export type PullRequestInfo = { number: bigint, url: string, status: MergeStatus, merged_at: string | null, merge_commit_sha: string | null, };
+export type PullRequestInfo = { number: bigint, url: string, status: MergeStatus, merged_at: string | null, merge_commit_sha: string | null, 
+/**
+ * Draft state as last reported by the host; not stored
+ */
+is_draft: boolean, };
 
 export type ApprovalStatus = { "status": "pending" } | { "status": "approved" } | { "status": "denied", reason?: string, } | { "status": "timed_out" };
 
@@ -174,6 +236,14 @@ export type ListProjectsResponse = { projects: Array<RemoteProject>, };
 
 export type RemoteProjectMembersResponse = { organization_id: string, members: Array<OrganizationMemberWithProfile>, };
 
+export type AddProjectRepositoriesRequest = { repositories: Array<CreateProjectRepo>, };
+
+export type ReorderProjectRepositoriesRequest = { 
+/**
+ * Every repository id of the project, in the new order
+ */
+repo_ids: Array<string>, };
+
 export type CreateRemoteProjectRequest = { organization_id: string, name: string, };
 
 export type LinkToExistingRequest = { remote_project_id: string, };
@@ -282,9 +352,71 @@ export type PrCommentsResponse = { comments: Array<UnifiedPrComment>, };
 
 export type GetPrCommentsError = { "type": "no_pr_attached" } | { "type": "github_cli_not_installed" } | { "type": "github_cli_not_logged_in" };
 
-export type GetPrCommentsQuery = { repo_id: string, };
+export type GetPrCommentsQuery = { repo_id: string, 
+/**
+ * Only return comments created after this time
+ */
+since: string | null, 
+/**
+ * Flag review comments whose code is no longer in the PR diff. Costs an extra diff fetch.
+ */
+detect_outdated: boolean | null, };
+
+export type UnifiedPrComment = { "comment_type": "general", id: string, author: string, author_association: AuthorAssociation, 
+/**
+ * GitHub's association string as received, for values newer than the enum
+ */
+author_association_raw: string, body: string, created_at: string, url: string, reactions: ReactionSummary, } | { "comment_type": "review", id: bigint, author: string, author_association: AuthorAssociation, 
+/**
+ * GitHub's association string as received, for values newer than the enum
+ */
+author_association_raw: string, body: string, created_at: string, url: string, path: string, line: bigint | null, diff_hunk: string, reactions: ReactionSummary, 
+/**
+ * The commented code is gone from the PR's current diff, e.g. after a force-push.
+ * Only computed when requested; `false` otherwise.
+ */
+outdated: boolean, } | { "comment_type": "review_summary", id: string, author: string, author_association: AuthorAssociation, 
+/**
+ * GitHub's association string as received, for values newer than the enum
+ */
+author_association_raw: string, state: ReviewState, body: string, created_at: string, url: string, };
+
+/**
+ * How a comment's author relates to the repository, from GitHub's `author_association`
+ */
+export type AuthorAssociation = "owner" | "member" | "collaborator" | "contributor" | "none" | { "other": string };
+
+/**
+ * Verdict of a PR review, in GitHub's spelling
+ */
+export type ReviewState = "APPROVED" | "CHANGES_REQUESTED" | "COMMENTED" | "DISMISSED" | "PENDING" | "UNKNOWN";
+
+/**
+ * Reaction counts on a comment, from the REST `reactions` object. Missing counts are zero.
+ */
+export type ReactionSummary = { thumbs_up: number, thumbs_down: number, laugh: number, hooray: number, confused: number, heart: number, rocket: number, eyes: number, total_count: number, };
+
+export type ParticipantRole = "author" | "reviewer" | "requested_reviewer" | "commenter";
+
+export type Participant = { login: string, roles: Array<ParticipantRole>, };
+
+export type Label = { name: string, 
+/**
+ * Hex color without the leading `#`
+ */
+color: string, description: string, };
+
+export type Mergeability = "clean" | "conflicting" | "unknown" | "blocked";
+
+export type CheckRun = { name: string, status: CheckStatus, 
+/**
+ * Set once the check has completed
+ */
+conclusion: CheckConclusion | null, };
+
+export type CheckStatus = "queued" | "in_progress" | "completed";
 
-export type UnifiedPrComment = { "comment_type": "general", id: string, author: string, author_association: string, body: string, created_at: string, url: string, } | { "comment_type": "review", id: bigint, author: string, author_association: string, body: string, created_at: string, url: string, path: string, line: bigint | null, diff_hunk: string, };
+export type CheckConclusion = "success" | "failure" | "neutral" | "cancelled" | "skipped" | "timed_out" | "action_required" | "stale";
 
 export type RepoBranchStatus = { repo_id: string, repo_name: string, commits_behind: number | null, commits_ahead: number | null, has_uncommitted_changes: boolean | null, head_oid: string | null, uncommitted_count: number | null, untracked_count: number | null, target_branch_name: string, remote_commits_behind: number | null, remote_commits_ahead: number | null, merges: Array<Merge>, 
 /**
@@ -359,15 +491,19 @@ export type ScriptRequest = { script: string, language: ScriptRequestLanguage, c
  * Optional relative path to execute the script in (relative to container_ref).
  * If None, uses the container_ref directory directly.
  */
-working_dir: string | null, };
+working_dir: string | null, 
+/**
+ * Kill the script's process group if it is still running after this many seconds
+ */
+timeout_secs: number | null, };
 
 export type ScriptRequestLanguage = "Bash";
 
-export enum BaseCodingAgent { CLAUDE_CODE = "CLAUDE_CODE", AMP = "AMP", GEMINI = "GEMINI", CODEX = "CODEX", OPENCODE = "OPENCODE", CURSOR_AGENT = "CURSOR_AGENT", QWEN_CODE = "QWEN_CODE", COPILOT = "COPILOT", DROID = "DROID" }
+export enum BaseCodingAgent { CLAUDE_CODE = "CLAUDE_CODE", AMP = "AMP", GEMINI = "GEMINI", CODEX = "CODEX", OPENCODE = "OPENCODE", CURSOR_AGENT = "CURSOR_AGENT", QWEN_CODE = "QWEN_CODE", COPILOT = "COPILOT", DROID = "DROID", AIDER = "AIDER", SHELL = "SHELL" }
 
-export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "DROID": Droid };
+export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "DROID": Droid } | { "AIDER": Aider } | { "SHELL": ShellExecutor };
 
-export type AvailabilityInfo = { "type": "LOGIN_DETECTED", last_auth_timestamp: bigint, } | { "type": "INSTALLATION_FOUND" } | { "type": "NOT_FOUND" };
+export type AvailabilityInfo = { "type": "LOGIN_DETECTED", last_auth_timestamp: bigint, } | { "type": "INSTALLATION_FOUND" } | { "type": "NOT_FOUND" } | { "type": "BROKEN", reason: string, };
 
 export type CommandBuilder = { 
 /**
@@ -389,7 +525,7 @@ executor: BaseCodingAgent,
  */
 variant: string | null, };
 
-export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "DROID": Droid } };
+export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "DROID": Droid } | { "AIDER": Aider } | { "SHELL": ShellExecutor } };
 
 export type ExecutorConfigs = { executors: { [key in BaseCodingAgent]?: ExecutorConfig }, };
 
@@ -417,11 +553,149 @@ export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null,
 
 export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
-export type Opencode = { append_prompt: AppendPrompt, model?: string | null, mode?: string | null, 
+/**
+ * How opencode treats a tool call
+ */
+export type PermissionLevel = "allow" | "ask" | "deny";
+
+/**
+ * Per-tool approval levels; tools left out default to `ask`
+ */
+export type OpencodePermissions = { edit: PermissionLevel, bash: PermissionLevel, webfetch: PermissionLevel, doom_loop: PermissionLevel, external_directory: PermissionLevel, };
+
+/**
+ * Decision applied to a tool approval request nobody answered in time
+ */
+export type ApprovalTimeoutAction = "deny" | "approve";
+
+/**
+ * Outcome of an [`ApprovalPolicy`] for a tool call
+ */
+export type PolicyDecision = "allow" | "ask" | "deny";
+
+/**
+ * One rule of an [`ApprovalPolicy`]; every condition that is set must hold for it to match
+ */
+export type ApprovalRule = { 
+/**
+ * Case-insensitive glob matched against the tool name, e.g. `read` or `{edit,write}`;
+ * unset matches every tool
+ */
+tool?: string | null, 
+/**
+ * Glob every path the call touches must match, e.g. `src/**`. Paths inside the worktree
+ * are matched relative to it, others as absolute paths. Calls touching no path never match.
+ */
+path?: string | null, 
+/**
+ * `true` matches calls touching any path outside the worktree, `false` calls whose paths
+ * all lie inside it
+ */
+outside_worktree?: boolean | null, decision: PolicyDecision, };
+
+/**
+ * Declarative approval rules evaluated in order; the first matching rule decides and calls
+ * no rule matches get `default`
+ */
+export type ApprovalPolicy = { rules?: Array<ApprovalRule>, default: PolicyDecision, };
+
+/**
+ * How opencode is started
+ */
+export type OpencodeLauncher = { "type": "npx" } | { "type": "bunx" } | { "type": "global_binary", path: string, };
+
+export type Opencode = { append_prompt: AppendPrompt, 
+/**
+ * System-level instruction sent with every new and follow-up session, ahead of the
+ * conversation. `append_prompt` is still appended to each user message.
+ */
+system_prompt?: string | null, model?: string | null, mode?: string | null, 
+/**
+ * `opencode-ai` npm version to run, e.g. `1.2.0` or `latest`; defaults to the pinned version
+ */
+version?: string | null, 
+/**
+ * How opencode is started; defaults to `npx`
+ */
+launcher: OpencodeLauncher, 
 /**
  * Auto-approve agent actions
  */
-auto_approve: boolean, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+auto_approve: boolean, 
+/**
+ * Sampling seed for reproducible runs. Determinism depends on the provider honoring it.
+ */
+seed?: bigint | null, 
+/**
+ * Sampling temperature between 0.0 and 2.0
+ */
+temperature?: number | null, 
+/**
+ * Maximum tokens the model may generate per response
+ */
+max_tokens?: number | null, 
+/**
+ * Extra HTTP headers sent to the model provider, e.g. for a corporate gateway
+ */
+provider_headers?: Array<[string, string]> | null, 
+/**
+ * Per-tool approval levels exported as `OPENCODE_PERMISSION`. Without them every tool asks
+ * unless `auto_approve` is on. An `OPENCODE_PERMISSION` already set in `env` takes precedence.
+ */
+permissions?: OpencodePermissions | null, 
+/**
+ * Bash command prefixes (e.g. `npm test`) that run without asking when approvals are on
+ */
+bash_allowlist?: Array<string>, 
+/**
+ * Seconds to wait for each approval decision before applying `approval_timeout_action`;
+ * unset waits indefinitely
+ */
+approval_timeout_secs?: bigint | null, 
+/**
+ * Decision recorded when an approval request times out
+ */
+approval_timeout_action: ApprovalTimeoutAction, 
+/**
+ * Rules answering approval requests before they reach a human; calls the policy leaves at
+ * `ask` go to the approval service. Ignored when `auto_approve` is on.
+ */
+approval_policy?: ApprovalPolicy | null, 
+/**
+ * Proxy URL for outbound traffic, exported as `HTTP_PROXY`/`HTTPS_PROXY` for opencode and npx
+ */
+http_proxy?: string | null, 
+/**
+ * Comma-separated hosts that bypass `http_proxy`, exported as `NO_PROXY`
+ */
+no_proxy?: string | null, 
+/**
+ * Glob patterns (e.g. `OPENCODE_*`, `PATH`) of inherited environment variables opencode
+ * may see; an empty list admits every variable that is not denied. Variables vibe-kanban
+ * sets for the run, including opencode's permissions and MCP config, are always kept.
+ */
+env_allowlist?: Array<string>, 
+/**
+ * Glob patterns (e.g. `AWS_*`) of environment variables withheld from opencode, even if
+ * allowlisted
+ */
+env_denylist?: Array<string>, 
+/**
+ * Prepend a `.gitignore`-aware overview of the worktree to the initial prompt
+ */
+include_file_tree: boolean, 
+/**
+ * Hard ceiling on total tokens for a run; the turn is cancelled once usage exceeds it
+ */
+token_budget?: number | null, 
+/**
+ * Shell command run in the worktree after each completed turn, e.g. `cargo fmt`
+ */
+post_turn_command?: string | null, 
+/**
+ * Fail the turn when `post_turn_command` exits non-zero instead of only warning
+ */
+post_turn_fail_on_error: boolean, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
 export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
@@ -431,6 +705,18 @@ export type Autonomy = "normal" | "low" | "medium" | "high" | "skip-permissions-
 
 export type DroidReasoningEffort = "none" | "dynamic" | "off" | "low" | "medium" | "high";
 
+export type Aider = { append_prompt: AppendPrompt, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+
+/**
+ * Runs a fixed shell command instead of a coding agent, for deterministic steps that should
+ * still go through the executor log pipeline. The prompt is exported as `VK_PROMPT`.
+ */
+export type ShellExecutor = { 
+/**
+ * Command to run in the worktree, e.g. `cargo test --workspace`
+ */
+command: string, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+
 export type AppendPrompt = string | null;
 
 export type CodingAgentInitialRequest = { prompt: string, 
@@ -461,7 +747,12 @@ export type CommandRunResult = { exit_status: CommandExitStatus | null, output:
 
 export type NormalizedEntry = { timestamp: string | null, entry_type: NormalizedEntryType, content: string, };
 
-export type NormalizedEntryType = { "type": "user_message" } | { "type": "user_feedback", denied_tool: string, } | { "type": "assistant_message" } | { "type": "tool_use", tool_name: string, action_type: ActionType, status: ToolStatus, } | { "type": "system_message" } | { "type": "error_message", error_type: NormalizedEntryError, } | { "type": "thinking" } | { "type": "loading" } | { "type": "next_action", failed: boolean, execution_processes: number, needs_setup: boolean, };
+export type NormalizedEntryType = { "type": "user_message" } | { "type": "user_feedback", denied_tool: string, } | { "type": "assistant_message" } | { "type": "tool_use", tool_name: string, action_type: ActionType, status: ToolStatus, } | { "type": "system_message" } | { "type": "error_message", error_type: NormalizedEntryError, } | { "type": "thinking" } | { "type": "loading" } | { "type": "next_action", failed: boolean, execution_processes: number, needs_setup: boolean, } | { "type": "file_edit", path: string, old_range: LineRange, new_range: LineRange, hunk: string, };
+
+/**
+ * `count` lines of a file starting at line `start`, numbered as in unified diff hunk headers
+ */
+export type LineRange = { start: number, count: number, };
 
 export type FileChange = { "action": "write", content: string, } | { "action": "delete" } | { "action": "rename", new_path: string, } | { "action": "edit", 
 /**
@@ -491,6 +782,39 @@ export type ToolStatus = { "status": "created" } | { "status": "success" } | { "
 
 export type PatchType = { "type": "NORMALIZED_ENTRY", "content": NormalizedEntry } | { "type": "STDOUT", "content": string } | { "type": "STDERR", "content": string } | { "type": "DIFF", "content": Diff };
 
+export type TurnResult = { 
+/**
+ * The agent's closing message, trimmed
+ */
+final_message: string | null, 
+/**
+ * Paths edited during the turn, in order of first edit
+ */
+files_edited: Array<string>, tool_calls: number, 
+/**
+ * Token totals, for agents that report usage
+ */
+usage: TurnUsage | null, success: boolean, 
+/**
+ * The last error reported when the turn did not succeed
+ */
+error: string | null, };
+
+export type TurnUsage = { input_tokens: number, output_tokens: number, };
+
+/**
+ * Token usage of a run as reported by the agent, with the model that served it
+ */
+export type UsageStats = { prompt_tokens: number, completion_tokens: number, model: string | null, };
+
+export type SideBySideDiff = { file: string, 
+/**
+ * The patch changed a binary file; no hunks are available
+ */
+binary: boolean, hunks: Array<Hunk>, };
+
+export type Hunk = { old_lines: Array<string | null>, new_lines: Array<string | null>, };
+
 export type JsonValue = number | string | boolean | Array<JsonValue> | { [key in string]?: JsonValue } | null;
 
 export const DEFAULT_PR_DESCRIPTION_PROMPT = `Update the GitHub PR that was just created with a better title and description.